@@ -0,0 +1,735 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{sequence_index_conflict_error, StorageBackend};
+use crate::{app::fetch::Range, ipc::BootstrapConfig, Error, Result};
+use async_trait::async_trait;
+use hex::encode;
+use log::debug;
+use rand::rngs::OsRng;
+use sn_client::Client;
+use sn_data_types::{
+    register::{Address, Entry, EntryHash},
+    Keypair, MapSeqValue, MapValue, PublicKey, Token,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+};
+use tiny_keccak::{Hasher, Sha3};
+use xor_name::XorName;
+
+type SequenceKey = (XorName, u64, bool);
+
+// Entries of a Register, each of them along with the hashes of its parents
+type RegisterEntries = BTreeMap<EntryHash, (Entry, BTreeSet<EntryHash>)>;
+
+#[derive(Default)]
+struct InMemoryState {
+    blobs: HashMap<XorName, Vec<u8>>,
+    maps: HashMap<(XorName, u64), BTreeMap<Vec<u8>, MapSeqValue>>,
+    sequences: HashMap<SequenceKey, Vec<Vec<u8>>>,
+    registers: HashMap<Address, RegisterEntries>,
+    balances: HashMap<PublicKey, u64>,
+    transfer_counters: HashMap<PublicKey, u64>,
+}
+
+/// Storage backend which keeps all data in memory, without connecting to the network.
+///
+/// All clones of an `InMemoryBackend` share the same data, just like all clients
+/// connected to the same network would, which makes it suitable for testing
+/// applications built on top of the API in a deterministic way.
+#[derive(Clone)]
+pub struct InMemoryBackend {
+    keypair: Keypair,
+    state: Arc<Mutex<InMemoryState>>,
+}
+
+impl Default for InMemoryBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InMemoryBackend {
+    /// Create a new and empty in-memory backend, using a random keypair
+    pub fn new() -> Self {
+        let mut rng = OsRng;
+        Self {
+            keypair: Keypair::new_ed25519(&mut rng),
+            state: Arc::new(Mutex::new(InMemoryState::default())),
+        }
+    }
+
+    // Private helper to obtain the lock on the shared state
+    fn state(&self) -> Result<MutexGuard<InMemoryState>> {
+        self.state
+            .lock()
+            .map_err(|err| Error::NetDataError(format!("In-memory storage is unusable: {}", err)))
+    }
+
+    // Private helper to get the entries of a Sequence data
+    fn sequence_entries(
+        state: &InMemoryState,
+        name: XorName,
+        tag: u64,
+        private: bool,
+    ) -> Result<&Vec<Vec<u8>>> {
+        state.sequences.get(&(name, tag, private)).ok_or_else(|| {
+            Error::ContentNotFound(format!(
+                "Sequence not found at Xor name: {} (type tag: {})",
+                encode(&name),
+                tag
+            ))
+        })
+    }
+}
+
+#[async_trait]
+impl StorageBackend for InMemoryBackend {
+    fn clone_box(&self) -> Box<dyn StorageBackend> {
+        Box::new(self.clone())
+    }
+
+    async fn connect(
+        &mut self,
+        app_keypair: Option<Keypair>,
+        _config_path: Option<&Path>,
+        _bootstrap_config: Option<BootstrapConfig>,
+    ) -> Result<()> {
+        if let Some(keypair) = app_keypair {
+            self.keypair = keypair;
+        }
+
+        Ok(())
+    }
+
+    fn keypair(&self) -> Result<Keypair> {
+        Ok(self.keypair.clone())
+    }
+
+    // === Token operations ===
+    async fn read_balance_from_keypair(&self, id: Keypair) -> Result<Token> {
+        let state = self.state()?;
+        let balance = state
+            .balances
+            .get(&id.public_key())
+            .copied()
+            .unwrap_or_default();
+
+        Ok(Token::from_nano(balance))
+    }
+
+    #[cfg(feature = "simulated-payouts")]
+    async fn trigger_simulated_farming_payout(
+        &self,
+        amount: Token,
+        id: Option<Keypair>,
+    ) -> Result<()> {
+        let pk = id.unwrap_or_else(|| self.keypair.clone()).public_key();
+        let mut state = self.state()?;
+        let balance = state.balances.entry(pk).or_default();
+        *balance = balance.checked_add(amount.as_nano()).ok_or_else(|| {
+            Error::InvalidAmount(format!(
+                "The amount '{}' specified for the payout is invalid",
+                amount
+            ))
+        })?;
+
+        Ok(())
+    }
+
+    async fn safecoin_transfer_to_xorname(
+        &self,
+        from_id: Option<Keypair>,
+        to_xorname: XorName,
+        amount: Token,
+    ) -> Result<u64> {
+        let to_pk = ed25519_dalek::PublicKey::from_bytes(to_xorname.as_ref()).map_err(|err| {
+            Error::NetDataError(format!(
+                "Failed to derive Ed25519 PublicKey from Xorname '{}': {:?}",
+                to_xorname, err
+            ))
+        })?;
+
+        self.safecoin_transfer_to_pk(from_id, to_pk.into(), amount)
+            .await
+    }
+
+    async fn safecoin_transfer_to_pk(
+        &self,
+        from_id: Option<Keypair>,
+        to_pk: PublicKey,
+        amount: Token,
+    ) -> Result<u64> {
+        let nanos = amount.as_nano();
+        if nanos == 0 {
+            return Err(Error::InvalidAmount(
+                "Cannot send zero-value transfers".to_string(),
+            ));
+        }
+
+        let from_pk = from_id.unwrap_or_else(|| self.keypair.clone()).public_key();
+        let mut state = self.state()?;
+        let from_balance = state.balances.get(&from_pk).copied().unwrap_or_default();
+        if from_balance < nanos {
+            return Err(Error::NotEnoughBalance(format!(
+                "Not enough balance at 'source' for the operation: {}",
+                amount
+            )));
+        }
+
+        // the source is debited first, so a transfer to oneself leaves the balance as it was
+        let to_balance = if to_pk == from_pk {
+            from_balance - nanos
+        } else {
+            state.balances.get(&to_pk).copied().unwrap_or_default()
+        };
+        let new_to_balance = to_balance.checked_add(nanos).ok_or_else(|| {
+            Error::InvalidAmount(format!(
+                "The amount '{}' specified for the transfer is invalid",
+                amount
+            ))
+        })?;
+
+        let _ = state.balances.insert(from_pk, from_balance - nanos);
+        let _ = state.balances.insert(to_pk, new_to_balance);
+
+        let counter = state.transfer_counters.entry(from_pk).or_default();
+        *counter += 1;
+
+        Ok(*counter)
+    }
+
+    // === Blob operations ===
+    async fn store_public_blob(&self, data: &[u8], dry_run: bool) -> Result<XorName> {
+        // We use the same data map the network client generates so the
+        // XOR-URLs are the same as if the content was stored on the network
+        let (_, address) = Client::blob_data_map(data.to_vec(), None).await?;
+        let xorname = *address.name();
+        if !dry_run {
+            let _ = self.state()?.blobs.insert(xorname, data.to_vec());
+        }
+
+        Ok(xorname)
+    }
+
+    async fn get_public_blob(&self, xorname: XorName, range: Range) -> Result<Vec<u8>> {
+        debug!("Fetching immutable data from memory: {:?}", &xorname);
+
+        let state = self.state()?;
+        let data = state.blobs.get(&xorname).ok_or_else(|| {
+            Error::ContentNotFound(format!("Public Blob not found at Xor name: {}", xorname))
+        })?;
+
+        let data = match range {
+            Some((start, end)) => {
+                let len = data.len() as u64;
+                let start = start.unwrap_or(0).min(len) as usize;
+                let end = end.unwrap_or(len).min(len).max(start as u64) as usize;
+                data[start..end].to_vec()
+            }
+            None => data.clone(),
+        };
+
+        Ok(data)
+    }
+
+    // === Map operations ===
+    async fn store_map(
+        &self,
+        name: Option<XorName>,
+        tag: u64,
+        _data: Option<String>,
+        _permissions: Option<String>,
+    ) -> Result<XorName> {
+        let xorname = name.unwrap_or_else(rand::random);
+        let mut state = self.state()?;
+        if state.maps.contains_key(&(xorname, tag)) {
+            return Err(Error::NetDataError(format!(
+                "Failed to store SeqMap: data already exists at Xor name: {} (type tag: {})",
+                encode(&xorname),
+                tag
+            )));
+        }
+
+        let _ = state.maps.insert((xorname, tag), BTreeMap::new());
+        Ok(xorname)
+    }
+
+    async fn map_insert(&self, name: XorName, tag: u64, key: &[u8], value: &[u8]) -> Result<()> {
+        let mut state = self.state()?;
+        let entries = state.maps.get_mut(&(name, tag)).ok_or_else(|| {
+            Error::ContentNotFound(format!(
+                "Sequenced Map not found at Xor name: {} (type tag: {})",
+                encode(&name),
+                tag
+            ))
+        })?;
+
+        if entries.contains_key(key) {
+            return Err(Error::EntryExists(format!(
+                "Failed to insert to SeqMap: entry with key {:?} already exists",
+                key
+            )));
+        }
+
+        let _ = entries.insert(
+            key.to_vec(),
+            MapSeqValue {
+                data: value.to_vec(),
+                version: 0,
+            },
+        );
+
+        Ok(())
+    }
+
+    async fn map_get_value(&self, name: XorName, tag: u64, key: &[u8]) -> Result<MapValue> {
+        let entries = self.list_map_entries(name, tag).await?;
+        entries
+            .get(key)
+            .map(|value| MapValue::Seq(value.clone()))
+            .ok_or_else(|| {
+                Error::EntryNotFound(format!(
+                    "Entry not found in Sequenced Map found at Xor name: {}",
+                    encode(&name)
+                ))
+            })
+    }
+
+    async fn list_map_entries(
+        &self,
+        name: XorName,
+        tag: u64,
+    ) -> Result<BTreeMap<Vec<u8>, MapSeqValue>> {
+        let state = self.state()?;
+        state.maps.get(&(name, tag)).cloned().ok_or_else(|| {
+            Error::ContentNotFound(format!(
+                "Sequenced Map not found at Xor name: {} (type tag: {})",
+                encode(&name),
+                tag
+            ))
+        })
+    }
+
+    async fn update_map(
+        &self,
+        name: XorName,
+        tag: u64,
+        key: &[u8],
+        value: &[u8],
+        version: u64,
+    ) -> Result<()> {
+        let mut state = self.state()?;
+        let current = state
+            .maps
+            .get_mut(&(name, tag))
+            .and_then(|entries| entries.get_mut(key))
+            .ok_or_else(|| {
+                Error::EntryNotFound(format!(
+                    "Entry not found in Sequenced Map found at Xor name: {}",
+                    encode(&name)
+                ))
+            })?;
+
+        if version != current.version + 1 {
+            return Err(Error::NetDataError(format!(
+                "Failed to update SeqMD: invalid version {} for entry currently at version {}",
+                version, current.version
+            )));
+        }
+
+        *current = MapSeqValue {
+            data: value.to_vec(),
+            version,
+        };
+
+        Ok(())
+    }
+
+    // === Sequence data operations ===
+    async fn store_sequence(
+        &self,
+        data: &[u8],
+        name: Option<XorName>,
+        tag: u64,
+        _permissions: Option<String>,
+        private: bool,
+    ) -> Result<XorName> {
+        let xorname = name.unwrap_or_else(rand::random);
+        let mut state = self.state()?;
+        if state.sequences.contains_key(&(xorname, tag, private)) {
+            return Err(Error::NetDataError(format!(
+                "Failed to store Sequence data: data already exists at Xor name: {} (type tag: {})",
+                encode(&xorname),
+                tag
+            )));
+        }
+
+        let _ = state
+            .sequences
+            .insert((xorname, tag, private), vec![data.to_vec()]);
+
+        Ok(xorname)
+    }
+
+    async fn sequence_get_last_entry(
+        &self,
+        name: XorName,
+        tag: u64,
+        private: bool,
+    ) -> Result<(u64, Vec<u8>)> {
+        let state = self.state()?;
+        let entries = Self::sequence_entries(&state, name, tag, private)?;
+        match entries.last() {
+            Some(entry) => Ok((entries.len() as u64 - 1, entry.clone())),
+            None => Err(Error::EmptyContent(format!(
+                "Empty Sequence found at XoR name {}",
+                name
+            ))),
+        }
+    }
+
+    async fn sequence_get_entry(
+        &self,
+        name: XorName,
+        tag: u64,
+        index: u64,
+        private: bool,
+    ) -> Result<Vec<u8>> {
+        let state = self.state()?;
+        let entries = Self::sequence_entries(&state, name, tag, private)?;
+        entries.get(index as usize).cloned().ok_or_else(|| {
+            Error::VersionNotFound(format!(
+                "Invalid version ({}) for Sequence found at XoR name {}",
+                index, name
+            ))
+        })
+    }
+
+    async fn append_to_sequence(
+        &self,
+        data: &[u8],
+        name: XorName,
+        tag: u64,
+        private: bool,
+        expected_index: Option<u64>,
+    ) -> Result<()> {
+        // The state is locked while checking the index, thus appending is atomic
+        let mut state = self.state()?;
+        let entries = state
+            .sequences
+            .get_mut(&(name, tag, private))
+            .ok_or_else(|| {
+                Error::NetDataError(format!(
+                    "Failed to append to Sequence: no Sequence found at XoR name {}",
+                    name
+                ))
+            })?;
+
+        if let Some(expected_index) = expected_index {
+            if entries.len() as u64 != expected_index {
+                return Err(sequence_index_conflict_error(
+                    expected_index,
+                    entries.len() as u64,
+                ));
+            }
+        }
+
+        entries.push(data.to_vec());
+        Ok(())
+    }
+
+    // === Register data operations ===
+    async fn store_register(
+        &self,
+        name: Option<XorName>,
+        tag: u64,
+        _permissions: Option<String>,
+        private: bool,
+    ) -> Result<XorName> {
+        let xorname = name.unwrap_or_else(rand::random);
+        let address = if private {
+            Address::Private { name: xorname, tag }
+        } else {
+            Address::Public { name: xorname, tag }
+        };
+
+        let mut state = self.state()?;
+        if state.registers.contains_key(&address) {
+            return Err(Error::NetDataError(format!(
+                "Failed to store Register data: data already exists at {:?}",
+                address
+            )));
+        }
+
+        let _ = state.registers.insert(address, RegisterEntries::new());
+        Ok(xorname)
+    }
+
+    async fn read_register(&self, address: Address) -> Result<BTreeSet<(EntryHash, Entry)>> {
+        let state = self.state()?;
+        let entries = state
+            .registers
+            .get(&address)
+            .ok_or_else(|| Error::ContentNotFound(format!("No Register found at {:?}", address)))?;
+
+        // The current value of the Register are all the entries which are not a parent of any other
+        let parents: BTreeSet<&EntryHash> = entries
+            .values()
+            .flat_map(|(_, parents)| parents.iter())
+            .collect();
+
+        Ok(entries
+            .iter()
+            .filter(|(hash, _)| !parents.contains(hash))
+            .map(|(hash, (entry, _))| (*hash, entry.clone()))
+            .collect())
+    }
+
+    async fn get_register_entry(&self, address: Address, hash: EntryHash) -> Result<Entry> {
+        let state = self.state()?;
+        let entries = state
+            .registers
+            .get(&address)
+            .ok_or_else(|| Error::ContentNotFound(format!("No Register found at {:?}", address)))?;
+
+        entries
+            .get(&hash)
+            .map(|(entry, _)| entry.clone())
+            .ok_or(Error::HashNotFound(hash))
+    }
+
+    async fn write_to_register(
+        &self,
+        address: Address,
+        data: Vec<u8>,
+        parents: BTreeSet<EntryHash>,
+    ) -> Result<EntryHash> {
+        let mut state = self.state()?;
+        let entries = state.registers.get_mut(&address).ok_or_else(|| {
+            Error::NetDataError(format!(
+                "Failed to write to Register: no Register found at {:?}",
+                address
+            ))
+        })?;
+
+        if let Some(parent) = parents.iter().find(|parent| !entries.contains_key(*parent)) {
+            return Err(Error::HashNotFound(*parent));
+        }
+
+        // The hash of the entry is calculated over its parents and its content
+        let mut hasher = Sha3::v256();
+        parents.iter().for_each(|parent| hasher.update(parent));
+        hasher.update(&data);
+        let mut hash = EntryHash::default();
+        hasher.finalize(&mut hash);
+
+        let _ = entries.insert(hash, (data, parents));
+        Ok(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{
+        fetch::SafeData,
+        test_helpers::{new_in_memory_safe_instance, random_nrs_name},
+    };
+    use anyhow::Result;
+
+    #[tokio::test]
+    async fn test_in_memory_sequence() -> Result<()> {
+        let backend = InMemoryBackend::new();
+        let xorname = backend
+            .store_sequence(b"first", None, 20_000, None, false)
+            .await?;
+        backend
+            .append_to_sequence(b"second", xorname, 20_000, false, None)
+            .await?;
+
+        let (version, entry) = backend
+            .sequence_get_last_entry(xorname, 20_000, false)
+            .await?;
+        assert_eq!(version, 1);
+        assert_eq!(entry, b"second");
+
+        let first = backend
+            .sequence_get_entry(xorname, 20_000, 0, false)
+            .await?;
+        assert_eq!(first, b"first");
+
+        // private Sequences live in a different namespace
+        assert!(matches!(
+            backend.sequence_get_last_entry(xorname, 20_000, true).await,
+            Err(Error::ContentNotFound(_))
+        ));
+        assert!(matches!(
+            backend.sequence_get_entry(xorname, 20_000, 2, false).await,
+            Err(Error::VersionNotFound(_))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_sequence_expected_index() -> Result<()> {
+        let backend = InMemoryBackend::new();
+        let xorname = backend
+            .store_sequence(b"first", None, 20_000, None, false)
+            .await?;
+        backend
+            .append_to_sequence(b"second", xorname, 20_000, false, Some(1))
+            .await?;
+
+        // another writer appended the entry at index 1 first
+        assert!(matches!(
+            backend
+                .append_to_sequence(b"other second", xorname, 20_000, false, Some(1))
+                .await,
+            Err(Error::VersionConflict(_))
+        ));
+        assert!(matches!(
+            backend
+                .append_to_sequence(b"fourth", xorname, 20_000, false, Some(3))
+                .await,
+            Err(Error::VersionConflict(_))
+        ));
+
+        let (version, entry) = backend
+            .sequence_get_last_entry(xorname, 20_000, false)
+            .await?;
+        assert_eq!(version, 1);
+        assert_eq!(entry, b"second");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_register() -> Result<()> {
+        let backend = InMemoryBackend::new();
+        let name = backend.store_register(None, 25_000, None, false).await?;
+        let address = Address::Public { name, tag: 25_000 };
+        assert!(backend.read_register(address).await?.is_empty());
+
+        let first = backend
+            .write_to_register(address, b"first".to_vec(), BTreeSet::new())
+            .await?;
+        let mut parents = BTreeSet::new();
+        let _ = parents.insert(first);
+        let second = backend
+            .write_to_register(address, b"second".to_vec(), parents)
+            .await?;
+
+        let current = backend.read_register(address).await?;
+        assert_eq!(current.len(), 1);
+        assert!(current.contains(&(second, b"second".to_vec())));
+        assert_eq!(
+            backend.get_register_entry(address, first).await?,
+            b"first".to_vec()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_blob_range() -> Result<()> {
+        let backend = InMemoryBackend::new();
+        let xorname = backend.store_public_blob(b"0123456789", false).await?;
+
+        let data = backend.get_public_blob(xorname, None).await?;
+        assert_eq!(data, b"0123456789");
+        let data = backend
+            .get_public_blob(xorname, Some((Some(2), Some(5))))
+            .await?;
+        assert_eq!(data, b"234");
+
+        // a dry run generates the same address without storing the content
+        let other = backend.store_public_blob(b"not stored", true).await?;
+        assert!(backend.get_public_blob(other, None).await.is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "simulated-payouts")]
+    #[tokio::test]
+    async fn test_in_memory_transfers() -> Result<()> {
+        let backend = InMemoryBackend::new();
+        let mut rng = OsRng;
+        let to_keypair = Keypair::new_ed25519(&mut rng);
+
+        backend
+            .trigger_simulated_farming_payout(Token::from_nano(10), None)
+            .await?;
+        let _ = backend
+            .safecoin_transfer_to_pk(None, to_keypair.public_key(), Token::from_nano(4))
+            .await?;
+
+        let balance = backend
+            .read_balance_from_keypair(backend.keypair()?)
+            .await?;
+        assert_eq!(balance, Token::from_nano(6));
+        let balance = backend.read_balance_from_keypair(to_keypair).await?;
+        assert_eq!(balance, Token::from_nano(4));
+
+        // a transfer to oneself doesn't change the balance
+        let my_pk = backend.keypair()?.public_key();
+        let _ = backend
+            .safecoin_transfer_to_pk(None, my_pk, Token::from_nano(5))
+            .await?;
+        let balance = backend
+            .read_balance_from_keypair(backend.keypair()?)
+            .await?;
+        assert_eq!(balance, Token::from_nano(6));
+
+        assert!(matches!(
+            backend
+                .safecoin_transfer_to_pk(None, my_pk, Token::from_nano(7))
+                .await,
+            Err(Error::NotEnoughBalance(_))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_safe_files_container_and_nrs() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let (xorurl, _, files_map) = safe
+            .files_container_create(None, None, false, false, false)
+            .await?;
+        assert!(files_map.is_empty());
+
+        let (version, _, files_map) = safe
+            .files_container_add_from_raw(
+                b"hello in-memory",
+                &format!("{}/test.md", xorurl),
+                false,
+                false,
+                false,
+            )
+            .await?;
+        assert_eq!(version, 1);
+        assert_eq!(files_map.len(), 1);
+
+        let nrs_name = random_nrs_name();
+        let (nrs_xorurl, _, _) = safe
+            .nrs_map_container_create(&nrs_name, &format!("{}?v=1", xorurl), true, false, false)
+            .await?;
+        let (nrs_version, _) = safe.nrs_map_container_get(&nrs_xorurl).await?;
+        assert_eq!(nrs_version, 0);
+
+        match safe
+            .fetch(&format!("safe://{}/test.md", nrs_name), None)
+            .await?
+        {
+            SafeData::PublicBlob { data, .. } => assert_eq!(data, b"hello in-memory"),
+            other => panic!("Unexpected content fetched: {:?}", other),
+        }
+        Ok(())
+    }
+}
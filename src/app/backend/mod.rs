@@ -0,0 +1,201 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+mod in_memory;
+
+pub use in_memory::InMemoryBackend;
+
+use super::fetch::Range;
+use crate::{ipc::BootstrapConfig, Error, Result};
+use async_trait::async_trait;
+use sn_data_types::{
+    register::{Address, Entry, EntryHash},
+    Keypair, MapSeqValue, MapValue, PublicKey, Token,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+use xor_name::XorName;
+
+/// Storage backend used by a `Safe` instance to store and retrieve all the data
+/// types the API is built upon, i.e. Blobs, Maps, Sequences, Registers, and Token transfers.
+///
+/// The default backend is the one connecting to the SAFE Network, an in-memory
+/// implementation is also available as `InMemoryBackend`, which can be used
+/// to exercise the API without a network connection.
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// Clone this backend into a boxed trait object
+    fn clone_box(&self) -> Box<dyn StorageBackend>;
+
+    /// Connect the backend using the keypair if provided
+    async fn connect(
+        &mut self,
+        app_keypair: Option<Keypair>,
+        config_path: Option<&Path>,
+        bootstrap_config: Option<BootstrapConfig>,
+    ) -> Result<()>;
+
+    /// Keypair used by the backend to sign each outgoing message
+    fn keypair(&self) -> Result<Keypair>;
+
+    // === Token operations ===
+
+    /// Read the balance of the SafeKey corresponding to the keypair provided
+    async fn read_balance_from_keypair(&self, id: Keypair) -> Result<Token>;
+
+    /// Credit the amount of tokens to the keypair provided, or to our own keypair
+    #[cfg(feature = "simulated-payouts")]
+    async fn trigger_simulated_farming_payout(
+        &self,
+        amount: Token,
+        id: Option<Keypair>,
+    ) -> Result<()>;
+
+    /// Transfer tokens to a SafeKey given its XorName
+    async fn safecoin_transfer_to_xorname(
+        &self,
+        from_id: Option<Keypair>,
+        to_xorname: XorName,
+        amount: Token,
+    ) -> Result<u64>;
+
+    /// Transfer tokens to a SafeKey given its public key
+    async fn safecoin_transfer_to_pk(
+        &self,
+        from_id: Option<Keypair>,
+        to_pk: PublicKey,
+        amount: Token,
+    ) -> Result<u64>;
+
+    // === Blob operations ===
+
+    /// Store a Public Blob, or only calculate its XorName if `dry_run` is set
+    async fn store_public_blob(&self, data: &[u8], dry_run: bool) -> Result<XorName>;
+
+    /// Retrieve the content of a Public Blob, optionally just a range of it
+    async fn get_public_blob(&self, xorname: XorName, range: Range) -> Result<Vec<u8>>;
+
+    // === Map operations ===
+
+    /// Store a new empty Sequenced Map
+    async fn store_map(
+        &self,
+        name: Option<XorName>,
+        tag: u64,
+        data: Option<String>,
+        permissions: Option<String>,
+    ) -> Result<XorName>;
+
+    /// Insert a new entry into a Sequenced Map
+    async fn map_insert(&self, name: XorName, tag: u64, key: &[u8], value: &[u8]) -> Result<()>;
+
+    /// Retrieve the value of an entry from a Sequenced Map
+    async fn map_get_value(&self, name: XorName, tag: u64, key: &[u8]) -> Result<MapValue>;
+
+    /// List all the entries of a Sequenced Map
+    async fn list_map_entries(
+        &self,
+        name: XorName,
+        tag: u64,
+    ) -> Result<BTreeMap<Vec<u8>, MapSeqValue>>;
+
+    /// Update an existing entry of a Sequenced Map
+    async fn update_map(
+        &self,
+        name: XorName,
+        tag: u64,
+        key: &[u8],
+        value: &[u8],
+        version: u64,
+    ) -> Result<()>;
+
+    // === Sequence data operations ===
+
+    /// Store a new Sequence with the data provided as its first entry
+    async fn store_sequence(
+        &self,
+        data: &[u8],
+        name: Option<XorName>,
+        tag: u64,
+        permissions: Option<String>,
+        private: bool,
+    ) -> Result<XorName>;
+
+    /// Retrieve the last entry of a Sequence along with its index
+    async fn sequence_get_last_entry(
+        &self,
+        name: XorName,
+        tag: u64,
+        private: bool,
+    ) -> Result<(u64, Vec<u8>)>;
+
+    /// Retrieve the entry at the given index of a Sequence
+    async fn sequence_get_entry(
+        &self,
+        name: XorName,
+        tag: u64,
+        index: u64,
+        private: bool,
+    ) -> Result<Vec<u8>>;
+
+    /// Append a new entry to a Sequence. If an expected index is provided the entry is only
+    /// appended at that index, i.e. if no other entry was appended since the Sequence was read,
+    /// otherwise a `VersionConflict` error is returned. Backends which cannot append
+    /// atomically, like the Network, check the index before appending and verify it after,
+    /// thus the entry may have been appended at a later index when the error is returned.
+    async fn append_to_sequence(
+        &self,
+        data: &[u8],
+        name: XorName,
+        tag: u64,
+        private: bool,
+        expected_index: Option<u64>,
+    ) -> Result<()>;
+
+    // === Register data operations ===
+
+    /// Store a new empty Register
+    async fn store_register(
+        &self,
+        name: Option<XorName>,
+        tag: u64,
+        permissions: Option<String>,
+        private: bool,
+    ) -> Result<XorName>;
+
+    /// Read the current entries of a Register, i.e. those which have no children
+    async fn read_register(&self, address: Address) -> Result<BTreeSet<(EntryHash, Entry)>>;
+
+    /// Retrieve an entry from a Register given its hash
+    async fn get_register_entry(&self, address: Address, hash: EntryHash) -> Result<Entry>;
+
+    /// Write a new entry to a Register, as a child of the `parents` entries
+    async fn write_to_register(
+        &self,
+        address: Address,
+        data: Vec<u8>,
+        parents: BTreeSet<EntryHash>,
+    ) -> Result<EntryHash>;
+}
+
+impl Clone for Box<dyn StorageBackend> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+// Error returned when an entry couldn't be appended to a Sequence at the expected index
+pub(crate) fn sequence_index_conflict_error(expected_index: u64, next_index: u64) -> Error {
+    Error::VersionConflict(format!(
+        "Failed to append to Sequence: the entry was expected at index '{}' but it'd be at index '{}'",
+        expected_index, next_index
+    ))
+}
@@ -0,0 +1,64 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{Error, Result};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Token to cooperatively cancel a long-running operation.
+///
+/// The token can be cloned and shared with another task, which can then cancel the operation
+/// by calling `cancel`. The operation stops as soon as it finds the token cancelled at one of
+/// its checkpoints, e.g. before uploading each file, and it never leaves a partially committed
+/// change behind, i.e. either a new version of the content is appended or none is.
+///
+/// ## Example
+///
+/// ```rust
+/// # use sn_api::CancellationToken;
+/// let token = CancellationToken::new();
+/// let other_task_token = token.clone();
+/// other_task_token.cancel();
+/// assert!(token.is_cancelled());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a token which is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the operations this token, or any of its clones, was provided to
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Check if the token was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+// Check if the token, when provided, was cancelled
+pub(crate) fn is_cancelled(token: Option<&CancellationToken>) -> bool {
+    token.map_or(false, CancellationToken::is_cancelled)
+}
+
+// Return an error if the token, when provided, was cancelled
+pub(crate) fn check_cancelled(token: Option<&CancellationToken>) -> Result<()> {
+    if is_cancelled(token) {
+        Err(Error::Cancelled("The operation was cancelled".to_string()))
+    } else {
+        Ok(())
+    }
+}
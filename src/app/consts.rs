@@ -26,6 +26,7 @@ pub const PREDICATE_ORIGINAL_MODIFIED: &str = "o_modified";
 pub const PREDICATE_ORIGINAL_CREATED: &str = "o_created";
 pub const PREDICATE_READONLY: &str = "readonly";
 pub const PREDICATE_MODE_BITS: &str = "mode_bits";
+pub const PREDICATE_CHECKSUM: &str = "checksum";
 
 // see: https://stackoverflow.com/questions/18869772/mime-type-for-a-directory
 // We will use the FreeDesktop standard for directories and symlinks.
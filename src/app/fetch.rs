@@ -8,7 +8,7 @@
 // Software.
 
 use super::{
-    files::{FileItem, FileMeta, FilesMap, RealPath},
+    files::{FileItem, FileMeta, FilesMap},
     multimap::MultimapKeyValues,
     nrs::NrsMap,
     register::{Entry, EntryHash},
@@ -174,7 +174,9 @@ impl Safe {
     /// # });
     /// ```
     pub async fn fetch(&self, url: &str, range: Range) -> Result<SafeData> {
-        let mut resolution_chain = self.retrieve_from_url(url, true, range, true).await?;
+        let mut resolution_chain = self
+            .retrieve_from_url(url, true, range, true, false)
+            .await?;
         // Construct return data using the last and first items from the resolution chain
         resolution_chain
             .pop()
@@ -222,18 +224,21 @@ impl Safe {
     /// # });
     /// ```
     pub async fn inspect(&mut self, url: &str) -> Result<Vec<SafeData>> {
-        self.retrieve_from_url(url, false, None, true).await
+        self.retrieve_from_url(url, false, None, true, true).await
     }
 
     // Retrieves all pieces of data that resulted from resolving the given URL.
     // An optional 'while_is' argment can be set as a filter to stop reslution process
     // upon the first non-matching content type.
+    // Unless 'keep_chain' is set only the last piece of data is meant to be used, thus the
+    // FilesMaps of the FilesContainers resolved through are not entirely loaded.
     pub(crate) async fn retrieve_from_url(
         &self,
         url: &str,
         retrieve_data: bool,
         range: Range,
         resolve_path: bool,
+        keep_chain: bool,
     ) -> Result<Vec<SafeData>> {
         let current_safe_url = Safe::parse_url(url)?;
         info!("URL parsed successfully, fetching: {}", current_safe_url);
@@ -260,6 +265,7 @@ impl Safe {
                     retrieve_data,
                     range,
                     resolve_path,
+                    keep_chain,
                 )
                 .await?;
 
@@ -278,6 +284,7 @@ impl Safe {
         retrieve_data: bool,
         range: Range,
         resolve_path: bool,
+        keep_chain: bool,
     ) -> Result<(SafeData, Option<NextStepInfo>)> {
         let url = the_xor.to_string();
         let xorurl = the_xor.to_xorurl_string();
@@ -293,32 +300,41 @@ impl Safe {
                     return Err(Error::InvalidXorUrl(msg));
                 }
 
-                let (version, files_map) = self.fetch_files_container(&the_xor).await?;
+                let (version, mut partial_files_map) =
+                    self.fetch_files_container_partial(&the_xor).await?;
                 debug!(
-                    "Files container found with v:{}, on data type: {}, containing: {:?}",
+                    "Files container found with v:{}, on data type: {}",
                     version,
                     the_xor.data_type(),
-                    files_map
                 );
 
+                // Only the shards of the FilesMap needed to resolve the path are loaded, along
+                // with those of the subtree of a directory, the rest are not even fetched
                 let path = the_xor.path_decoded()?;
                 let (files_map, next) = if resolve_path && path != "/" && !path.is_empty() {
                     // TODO: Move this logic (path resolver) to the FilesMap struct
-                    let realpath = files_map.realpath(&path)?;
-                    match &files_map.get(&realpath) {
+                    let realpath = partial_files_map.resolve_realpath(self, &path).await?;
+                    match partial_files_map.get(&realpath).cloned() {
                         Some(file_item) => match file_item.get("type") {
                             Some(file_type) => {
                                 if FileMeta::filetype_is_file(&file_type) {
                                     match file_item.get("link") {
                                         Some(link) => {
                                             let new_target_xorurl = SafeUrl::from_url(link)?;
-                                            let mut metadata = (*file_item).clone();
+                                            let mut metadata = file_item.clone();
                                             Path::new(&path).file_name().map(|name| {
                                                 name.to_str().map(|str| {
                                                     metadata
                                                         .insert("name".to_string(), str.to_string())
                                                 })
                                             });
+                                            // The FilesMap is entirely loaded only if this step
+                                            // is kept, otherwise only the file's is of use
+                                            let files_map = if keep_chain {
+                                                partial_files_map.load_all(self).await?
+                                            } else {
+                                                partial_files_map.into_files_map()
+                                            };
                                             (files_map, Some((new_target_xorurl, Some(metadata))))
                                         }
                                         None => {
@@ -334,7 +350,15 @@ impl Safe {
                                     return Err(Error::ContentError(msg));
                                 } else {
                                     // Must be a directory.
-                                    (gen_filtered_filesmap(&realpath, &files_map, &xorurl)?, None)
+                                    partial_files_map.load_subtree(self, &realpath).await?;
+                                    (
+                                        gen_filtered_filesmap(
+                                            &realpath,
+                                            partial_files_map.files_map(),
+                                            &xorurl,
+                                        )?,
+                                        None,
+                                    )
                                 }
                             }
                             None => {
@@ -342,10 +366,20 @@ impl Safe {
                                 return Err(Error::ContentError(msg));
                             }
                         },
-                        None => (gen_filtered_filesmap(&realpath, &files_map, &xorurl)?, None),
+                        None => {
+                            partial_files_map.load_subtree(self, &realpath).await?;
+                            (
+                                gen_filtered_filesmap(
+                                    &realpath,
+                                    partial_files_map.files_map(),
+                                    &xorurl,
+                                )?,
+                                None,
+                            )
+                        }
                     }
                 } else {
-                    (files_map, None)
+                    (partial_files_map.load_all(self).await?, None)
                 };
 
                 // We don't want the path just the FilesContainer XOR-URL and version
@@ -604,9 +638,7 @@ impl Safe {
         };
 
         let data = if retrieve_data {
-            self.safe_client
-                .get_public_blob(the_xor.xorname(), range)
-                .await?
+            self.fetch_public_blob(the_xor, range).await?
         } else {
             vec![]
         };
@@ -0,0 +1,171 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{streaming::blob_checksum, FileEntry, FilesMap};
+use crate::{Error, Result, Safe, SafeUrl};
+use futures::stream::{self, StreamExt};
+use std::collections::BTreeMap;
+use tiny_keccak::{Hasher, Sha3};
+
+// Algorithm the checksums are calculated with, which prefixes them when stored in a FileItem
+const CHECKSUM_ALGORITHM: &str = "sha3-256";
+
+// Maximum number of Blobs fetched at a time when verifying a FilesContainer
+const VERIFY_CONCURRENCY: usize = 8;
+
+/// Outcome of verifying the content a file of a FilesContainer links to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileIntegrity {
+    /// The content matches the size and checksum recorded for it
+    Valid,
+    /// The content matches the size recorded, if any, but there is no checksum to check it against
+    Unverified,
+    /// The content couldn't be fetched
+    Missing(String),
+    /// The content fetched doesn't match the size or checksum recorded for it
+    Corrupted(String),
+}
+
+/// Outcome of verifying the content of each file of a FilesContainer, keyed by their paths
+pub type FilesIntegrity = BTreeMap<String, FileIntegrity>;
+
+// Incremental calculation of the checksum of some content
+pub(crate) struct ChecksumHasher(Sha3);
+
+impl ChecksumHasher {
+    pub(crate) fn new() -> Self {
+        Self(Sha3::v256())
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    pub(crate) fn finalize(self) -> String {
+        let mut hash = [0; 32];
+        self.0.finalize(&mut hash);
+        format!("{}:{}", CHECKSUM_ALGORITHM, hex::encode(hash))
+    }
+}
+
+// Checksum of some content held in memory
+pub(crate) fn data_checksum(data: &[u8]) -> String {
+    let mut hasher = ChecksumHasher::new();
+    hasher.update(data);
+    hasher.finalize()
+}
+
+// Fetch the content each file of the FilesMap links to, checking its size and checksum.
+// Only the paths found at, or within, `base_path` are verified. Entries which cannot
+// be parsed are reported as corrupted, without fetching anything for them.
+pub(crate) async fn files_map_verify(
+    safe: &Safe,
+    files_map: &FilesMap,
+    base_path: &str,
+) -> Result<FilesIntegrity> {
+    let base_path = base_path.trim_end_matches('/');
+    let mut files = vec![];
+    let mut malformed = FilesIntegrity::new();
+    for (path, file_item) in files_map.iter() {
+        if path != base_path && !path.starts_with(&format!("{}/", base_path)) {
+            continue;
+        }
+        match FileEntry::from_file_item(file_item) {
+            Ok(file_entry) if file_entry.link().is_some() => files.push((path, file_entry)),
+            Ok(_) => {}
+            Err(err) => {
+                let _ =
+                    malformed.insert(path.to_string(), FileIntegrity::Corrupted(err.to_string()));
+            }
+        }
+    }
+
+    if files.is_empty()
+        && malformed.is_empty()
+        && !base_path.is_empty()
+        && !files_map.contains_key(base_path)
+    {
+        return Err(Error::ContentNotFound(format!(
+            "No content found matching the \"{}\" path on the target FilesContainer",
+            base_path
+        )));
+    }
+
+    let mut integrity: FilesIntegrity = stream::iter(files)
+        .map(|(path, file_entry)| async move {
+            (path.to_string(), verify_file_entry(safe, &file_entry).await)
+        })
+        .buffered(VERIFY_CONCURRENCY)
+        .collect()
+        .await;
+    integrity.append(&mut malformed);
+
+    Ok(integrity)
+}
+
+async fn verify_file_entry(safe: &Safe, file_entry: &FileEntry) -> FileIntegrity {
+    let safe_url = match SafeUrl::from_url(file_entry.link().unwrap_or_default()) {
+        Ok(safe_url) => safe_url,
+        Err(err) => return FileIntegrity::Corrupted(format!("Invalid link: {}", err)),
+    };
+
+    let (size, checksum) = match blob_checksum(safe, &safe_url).await {
+        Ok(result) => result,
+        Err(Error::ContentError(msg)) => return FileIntegrity::Corrupted(msg),
+        Err(err) => return FileIntegrity::Missing(err.to_string()),
+    };
+
+    match (file_entry.size, &file_entry.checksum) {
+        (Some(expected), _) if expected != size => FileIntegrity::Corrupted(format!(
+            "Size of the content is {} bytes rather than the {} bytes expected",
+            size, expected
+        )),
+        (_, Some(expected)) if *expected != checksum => FileIntegrity::Corrupted(format!(
+            "Checksum of the content is {} rather than the {} expected",
+            checksum, expected
+        )),
+        (_, Some(_)) => FileIntegrity::Valid,
+        (_, None) => FileIntegrity::Unverified,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{
+        files::{file_system::upload_file_to_net, streaming::BLOB_PIECE_SIZE},
+        test_helpers::{new_in_memory_safe_instance, new_temp_dir},
+    };
+    use anyhow::{anyhow, Result};
+
+    #[tokio::test]
+    async fn test_uploaded_file_checksum() -> Result<()> {
+        let safe = new_in_memory_safe_instance();
+        let dir = new_temp_dir()?;
+        let data: Vec<u8> = (0..BLOB_PIECE_SIZE * 2 + 10)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        std::fs::write(dir.join("data"), &data)?;
+
+        // The checksum is calculated from the pieces uploaded
+        let uploaded = upload_file_to_net(&safe, &dir.join("data"), None, None, false).await?;
+        let checksum = uploaded
+            .checksum
+            .ok_or_else(|| anyhow!("No checksum was calculated"))?;
+        assert_eq!(checksum, data_checksum(&data));
+        assert!(checksum.starts_with("sha3-256:"));
+        assert_eq!(checksum.len(), "sha3-256:".len() + 64);
+        assert_ne!(checksum, data_checksum(&data[1..]));
+
+        // Nothing is hashed on a dry run
+        let uploaded = upload_file_to_net(&safe, &dir.join("data"), None, None, true).await?;
+        assert_eq!(uploaded.checksum, None);
+        Ok(())
+    }
+}
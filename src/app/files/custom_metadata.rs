@@ -0,0 +1,205 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    files_map::SYMLINK_TARGET, relocate::resolve_parents, sync::is_same_blob, FileItem, FilesMap,
+    ProcessedFiles,
+};
+use crate::{app::consts::*, Error, Result};
+use std::collections::BTreeMap;
+
+// Prefix of the FileItem keys holding custom metadata. None of the
+// reserved keys contain a colon, thus they cannot clash with them.
+pub(crate) const CUSTOM_METADATA_PREFIX: &str = "custom:";
+
+// Custom metadata of a FileItem, keyed by their names without the namespace prefix
+pub(crate) fn custom_metadata(file_item: &FileItem) -> BTreeMap<String, String> {
+    file_item
+        .iter()
+        .filter_map(|(key, value)| {
+            key.strip_prefix(CUSTOM_METADATA_PREFIX)
+                .map(|name| (name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+// Custom metadata of the FileItem found at a path of a FilesMap
+pub(crate) fn files_map_get_metadata(
+    files_map: &FilesMap,
+    path: &str,
+) -> Result<BTreeMap<String, String>> {
+    let path = metadata_path(files_map, path)?;
+    files_map
+        .get(&path)
+        .map(custom_metadata)
+        .ok_or_else(|| content_not_found_error(&path))
+}
+
+// Set, or remove if no value is provided, a custom metadata entry of the FileItem
+// found at a path of a FilesMap. The number of changes made is returned.
+pub(crate) fn files_map_set_metadata(
+    path: &str,
+    mut files_map: FilesMap,
+    name: &str,
+    value: Option<&str>,
+) -> Result<(ProcessedFiles, FilesMap, u64)> {
+    validate_name(name)?;
+    let path = metadata_path(&files_map, path)?;
+    let file_item = files_map
+        .get_mut(&path)
+        .ok_or_else(|| content_not_found_error(&path))?;
+
+    let key = format!("{}{}", CUSTOM_METADATA_PREFIX, name);
+    let changed = match value {
+        Some(value) => file_item.insert(key, value.to_string()).as_deref() != Some(value),
+        None => file_item.remove(&key).is_some(),
+    };
+
+    let mut processed_files = ProcessedFiles::new();
+    if changed {
+        let link = file_item.get(PREDICATE_LINK).cloned().unwrap_or_default();
+        processed_files.insert(path, (CONTENT_UPDATED_SIGN.to_string(), link));
+    }
+
+    Ok((processed_files, files_map, u64::from(changed)))
+}
+
+// Copy the custom metadata of a FileItem onto the one replacing it, as long as
+// both have the same content, so it's not lost when the entry is re-uploaded
+pub(crate) fn preserve_custom_metadata(file_item: &FileItem, new_file_item: &mut FileItem) {
+    let is_same_content = file_item.get(PREDICATE_TYPE) == new_file_item.get(PREDICATE_TYPE)
+        && file_item.get(SYMLINK_TARGET) == new_file_item.get(SYMLINK_TARGET)
+        && match (
+            file_item.get(PREDICATE_LINK),
+            new_file_item.get(PREDICATE_LINK),
+        ) {
+            (Some(link), Some(new_link)) => is_same_blob(link, new_link),
+            (link, new_link) => link == new_link,
+        };
+
+    if is_same_content {
+        for (key, value) in file_item.iter() {
+            if key.starts_with(CUSTOM_METADATA_PREFIX) && !new_file_item.contains_key(key) {
+                new_file_item.insert(key.to_string(), value.to_string());
+            }
+        }
+    }
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.chars().any(|c| c.is_control() || c.is_whitespace()) {
+        Err(Error::InvalidInput(format!(
+            "Invalid custom metadata name \"{}\", it cannot be empty nor contain whitespaces",
+            name
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+// Path of the entry whose custom metadata is read or written, resolving the symlinks found in
+// its parents, but not a symlink in its last component which holds its own custom metadata
+fn metadata_path(files_map: &FilesMap, path: &str) -> Result<String> {
+    resolve_parents(files_map, &format!("/{}", path.trim_matches('/')))
+}
+
+fn content_not_found_error(path: &str) -> Error {
+    Error::ContentNotFound(format!(
+        "No content found matching the \"{}\" path on the target FilesContainer",
+        path
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_helpers::{self, FileItemBuilder};
+
+    fn file_item(link: &str) -> FileItem {
+        FileItemBuilder::file("text/plain", link).build()
+    }
+
+    #[test]
+    fn test_files_map_set_metadata() -> Result<()> {
+        let mut files_map = FilesMap::new();
+        files_map.insert("/a.txt".to_string(), file_item("safe://a"));
+
+        let (processed_files, files_map, count) =
+            files_map_set_metadata("/a.txt", files_map, "author", Some("maidsafe"))?;
+        assert_eq!(count, 1);
+        assert_eq!(processed_files["/a.txt"].0, CONTENT_UPDATED_SIGN);
+        assert_eq!(files_map["/a.txt"]["custom:author"], "maidsafe");
+
+        // setting the same value is not a change
+        let (_, files_map, count) =
+            files_map_set_metadata("a.txt", files_map, "author", Some("maidsafe"))?;
+        assert_eq!(count, 0);
+
+        // custom metadata can use the names of reserved keys without clashing
+        let (_, files_map, _) = files_map_set_metadata("/a.txt", files_map, "link", Some("x"))?;
+        assert_eq!(files_map["/a.txt"][PREDICATE_LINK], "safe://a");
+
+        let metadata = custom_metadata(&files_map["/a.txt"]);
+        assert_eq!(metadata.len(), 2);
+        assert_eq!(metadata["author"], "maidsafe");
+        assert_eq!(metadata["link"], "x");
+
+        let (_, files_map, count) = files_map_set_metadata("/a.txt", files_map, "author", None)?;
+        assert_eq!(count, 1);
+        assert!(!files_map["/a.txt"].contains_key("custom:author"));
+
+        assert!(files_map_set_metadata("/a.txt", files_map.clone(), "a b", Some("c")).is_err());
+        assert!(files_map_set_metadata("/b.txt", files_map, "author", Some("c")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_map_metadata_through_symlinks() -> Result<()> {
+        let files_map = test_helpers::files_map(vec![
+            ("/dir", FileItemBuilder::dir().build()),
+            ("/dir/a.txt", file_item("safe://a")),
+            (
+                "/link",
+                FileItemBuilder::new(MIMETYPE_FILESYSTEM_SYMLINK)
+                    .property(SYMLINK_TARGET, "dir")
+                    .build(),
+            ),
+        ]);
+
+        let (processed_files, files_map, _) =
+            files_map_set_metadata("/link/a.txt", files_map, "author", Some("maidsafe"))?;
+        assert!(processed_files.contains_key("/dir/a.txt"));
+        assert_eq!(files_map["/dir/a.txt"]["custom:author"], "maidsafe");
+        assert_eq!(
+            files_map_get_metadata(&files_map, "/link/a.txt")?["author"],
+            "maidsafe"
+        );
+
+        // the symlink itself has its own custom metadata
+        let (_, files_map, _) = files_map_set_metadata("/link", files_map, "author", Some("x"))?;
+        assert_eq!(files_map_get_metadata(&files_map, "/link")?["author"], "x");
+        assert_eq!(files_map_get_metadata(&files_map, "/dir")?.len(), 0);
+        assert!(files_map_get_metadata(&files_map, "/link/b.txt").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_preserve_custom_metadata() {
+        let mut item = file_item("safe://a");
+        item.insert("custom:tags".to_string(), "docs".to_string());
+
+        let mut same_content = file_item("safe://a");
+        preserve_custom_metadata(&item, &mut same_content);
+        assert_eq!(same_content["custom:tags"], "docs");
+
+        let mut new_content = file_item("safe://b");
+        preserve_custom_metadata(&item, &mut new_content);
+        assert!(!new_content.contains_key("custom:tags"));
+    }
+}
@@ -0,0 +1,275 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{files_map::SYMLINK_TARGET, FileItem, FileMeta, FilesMap, ProcessedFiles};
+use crate::app::consts::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The kind of change a path went through between two versions of a FilesMap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileDiffKind {
+    /// The path only exists in the newer version
+    Added,
+    /// The path only exists in the older version
+    Removed,
+    /// The content changed, i.e. the file's link or the symlink's target is different
+    LinkChanged,
+    /// Only the metadata changed, e.g. size, timestamps or permissions
+    MetadataChanged,
+    /// The path changed its type, e.g. from a file to a directory or a symlink
+    TypeChanged,
+}
+
+impl FileDiffKind {
+    /// Sign used to represent this kind of change, as used in `ProcessedFiles`
+    pub fn sign(&self) -> &'static str {
+        match self {
+            Self::Added => CONTENT_ADDED_SIGN,
+            Self::Removed => CONTENT_DELETED_SIGN,
+            Self::LinkChanged | Self::MetadataChanged | Self::TypeChanged => CONTENT_UPDATED_SIGN,
+        }
+    }
+}
+
+/// A change found for a path between two versions of a FilesMap,
+/// along with the FileItem the path had in each of them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDiff {
+    pub kind: FileDiffKind,
+    pub old: Option<FileItem>,
+    pub new: Option<FileItem>,
+}
+
+impl FileDiff {
+    /// Sign used to represent this change, as used in `ProcessedFiles`
+    pub fn sign(&self) -> &'static str {
+        self.kind.sign()
+    }
+
+    /// Name of the metadata keys which changed, only relevant for updated paths
+    pub fn changed_keys(&self) -> BTreeSet<String> {
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => old
+                .keys()
+                .chain(new.keys())
+                .filter(|key| old.get(*key) != new.get(*key))
+                .cloned()
+                .collect(),
+            _ => BTreeSet::default(),
+        }
+    }
+}
+
+/// Changes found between two versions of a FilesMap, keyed by path.
+/// Paths which are identical in both versions are not included.
+pub type FilesMapDiff = BTreeMap<String, FileDiff>;
+
+// Compare two FilesMaps and return the changes needed to go from the old to the new one
+pub(crate) fn files_map_diff(old_files_map: &FilesMap, new_files_map: &FilesMap) -> FilesMapDiff {
+    let mut diff = FilesMapDiff::new();
+
+    for (path, old_item) in old_files_map.iter() {
+        let kind = match new_files_map.get(path) {
+            None => FileDiffKind::Removed,
+            Some(new_item) => match file_item_diff_kind(old_item, new_item) {
+                Some(kind) => kind,
+                None => continue,
+            },
+        };
+        diff.insert(
+            path.to_string(),
+            FileDiff {
+                kind,
+                old: Some(old_item.clone()),
+                new: new_files_map.get(path).cloned(),
+            },
+        );
+    }
+
+    for (path, new_item) in new_files_map.iter() {
+        if !old_files_map.contains_key(path) {
+            diff.insert(
+                path.to_string(),
+                FileDiff {
+                    kind: FileDiffKind::Added,
+                    old: None,
+                    new: Some(new_item.clone()),
+                },
+            );
+        }
+    }
+
+    diff
+}
+
+// Summarise a FilesMapDiff using the same format as the ProcessedFiles returned by the
+// operations which update a FilesContainer, i.e. the change sign and the content's link
+pub(crate) fn files_map_diff_to_processed_files(diff: &FilesMapDiff) -> ProcessedFiles {
+    diff.iter()
+        .map(|(path, file_diff)| {
+            let link = file_diff
+                .new
+                .as_ref()
+                .or_else(|| file_diff.old.as_ref())
+                .and_then(|item| item.get(PREDICATE_LINK))
+                .cloned()
+                .unwrap_or_default();
+            (path.to_string(), (file_diff.sign().to_string(), link))
+        })
+        .collect()
+}
+
+// Find out what kind of change, if any, there is between two FileItems of the same path
+fn file_item_diff_kind(old_item: &FileItem, new_item: &FileItem) -> Option<FileDiffKind> {
+    if old_item == new_item {
+        None
+    } else if item_category(old_item) != item_category(new_item) {
+        Some(FileDiffKind::TypeChanged)
+    } else if old_item.get(PREDICATE_LINK) != new_item.get(PREDICATE_LINK)
+        || old_item.get(SYMLINK_TARGET) != new_item.get(SYMLINK_TARGET)
+    {
+        Some(FileDiffKind::LinkChanged)
+    } else {
+        Some(FileDiffKind::MetadataChanged)
+    }
+}
+
+// The type of a FileItem is a media type for files, so we only
+// care about whether it's a file, a directory or a symlink
+fn item_category(item: &FileItem) -> &'static str {
+    let file_type = item.get(PREDICATE_TYPE).map(String::as_str).unwrap_or("");
+    if FileMeta::filetype_is_dir(file_type) {
+        "dir"
+    } else if FileMeta::filetype_is_symlink(file_type) {
+        "symlink"
+    } else {
+        "file"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_helpers::{new_in_memory_safe_instance, FileItemBuilder};
+    use anyhow::Result;
+
+    fn file_item(file_type: &str, link: &str, size: &str) -> FileItem {
+        FileItemBuilder::file(file_type, link).size(size).build()
+    }
+
+    #[test]
+    fn test_files_map_diff() {
+        let mut old = FilesMap::new();
+        old.insert(
+            "/same.md".to_string(),
+            file_item("text/markdown", "safe://a", "1"),
+        );
+        old.insert(
+            "/removed.md".to_string(),
+            file_item("text/plain", "safe://b", "1"),
+        );
+        old.insert(
+            "/relinked.md".to_string(),
+            file_item("text/plain", "safe://c", "1"),
+        );
+        old.insert(
+            "/meta.md".to_string(),
+            file_item("text/plain", "safe://d", "1"),
+        );
+        old.insert(
+            "/retyped".to_string(),
+            file_item("text/plain", "safe://e", "1"),
+        );
+
+        let mut new = old.clone();
+        new.remove("/removed.md");
+        new.insert(
+            "/added.md".to_string(),
+            file_item("text/plain", "safe://f", "1"),
+        );
+        new.insert(
+            "/relinked.md".to_string(),
+            file_item("text/plain", "safe://g", "2"),
+        );
+        new.insert(
+            "/meta.md".to_string(),
+            file_item("text/markdown", "safe://d", "1"),
+        );
+        new.insert(
+            "/retyped".to_string(),
+            FileItemBuilder::dir().size("0").build(),
+        );
+
+        let diff = files_map_diff(&old, &new);
+        assert_eq!(diff.len(), 5);
+        assert!(!diff.contains_key("/same.md"));
+        assert_eq!(diff["/added.md"].kind, FileDiffKind::Added);
+        assert_eq!(diff["/removed.md"].kind, FileDiffKind::Removed);
+        assert_eq!(diff["/relinked.md"].kind, FileDiffKind::LinkChanged);
+        assert_eq!(diff["/meta.md"].kind, FileDiffKind::MetadataChanged);
+        assert_eq!(diff["/retyped"].kind, FileDiffKind::TypeChanged);
+
+        let changed_keys: Vec<String> = diff["/meta.md"].changed_keys().into_iter().collect();
+        assert_eq!(changed_keys, vec![PREDICATE_TYPE.to_string()]);
+
+        assert!(files_map_diff(&new, &new).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_files_container_diff() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let (xorurl, _, _) = safe
+            .files_container_create(None, None, true, false, false)
+            .await?;
+        let _ = safe
+            .files_container_add_from_raw(
+                b"first",
+                &format!("{}/first.txt", xorurl),
+                false,
+                false,
+                false,
+            )
+            .await?;
+        let _ = safe
+            .files_container_add_from_raw(
+                b"second",
+                &format!("{}/second.txt", xorurl),
+                false,
+                false,
+                false,
+            )
+            .await?;
+        let _ = safe
+            .files_container_add_from_raw(
+                b"updated",
+                &format!("{}/first.txt", xorurl),
+                true,
+                false,
+                false,
+            )
+            .await?;
+
+        let (from_version, to_version, diff) =
+            safe.files_container_diff(&xorurl, Some(1), None).await?;
+        assert_eq!(from_version, 1);
+        assert_eq!(to_version, 3);
+        assert_eq!(diff.len(), 2);
+        assert_eq!(diff["/first.txt"].kind, FileDiffKind::LinkChanged);
+        assert_eq!(diff["/first.txt"].sign(), CONTENT_UPDATED_SIGN);
+        assert_eq!(diff["/second.txt"].kind, FileDiffKind::Added);
+
+        let (_, _, diff) = safe.files_container_diff(&xorurl, None, Some(0)).await?;
+        assert_eq!(diff.len(), 2);
+        assert!(diff
+            .values()
+            .all(|change| change.kind == FileDiffKind::Removed));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,556 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    files_map::{FileItem, FilesMap, SYMLINK_TARGET, SYMLINK_TARGET_TYPE},
+    metadata::FileMeta,
+    streaming::write_blob,
+    ProcessedFiles,
+};
+use crate::{app::consts::*, Error, Result, Safe, SafeUrl};
+use chrono::DateTime;
+use filetime::FileTime;
+use log::{debug, info, warn};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+use tokio::fs::File;
+
+// Write onto the local filesystem, under the `dest` folder, the content found at `src_path`
+// in the FilesMap, recreating directories, files and symlinks, and restoring their metadata
+// as much as the platform allows. If `src_path` is a folder, its content is written onto
+// the `dest` folder, if it's a file or symlink then it's written into the `dest` folder.
+pub(crate) async fn files_map_download(
+    safe: &Safe,
+    files_map: &FilesMap,
+    src_path: &str,
+    dest: &Path,
+    overwrite: bool,
+    dry_run: bool,
+) -> Result<ProcessedFiles> {
+    let entries = entries_to_download(files_map, src_path)?;
+    info!(
+        "Downloading {} entries from \"{}\" into {}",
+        entries.len(),
+        src_path,
+        dest.display()
+    );
+
+    if !dry_run {
+        fs::create_dir_all(dest).map_err(|err| {
+            Error::FileSystemError(format!(
+                "Couldn't create destination folder '{}': {}",
+                dest.display(),
+                err
+            ))
+        })?;
+    }
+
+    let mut local_dest = LocalDest::new(dest);
+    let mut processed_files = ProcessedFiles::default();
+    let mut dirs_created = Vec::<(PathBuf, &FileItem)>::new();
+    for (relative_path, file_item) in entries {
+        let local_path_str = dest.join(relative_path).display().to_string();
+        let link = file_item.get(PREDICATE_LINK).cloned().unwrap_or_default();
+
+        let result = match local_dest.local_path(relative_path) {
+            Ok(local_path) => download_file_item(safe, &local_path, file_item, overwrite, dry_run)
+                .await
+                .map(|change| (local_path, change)),
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok((_, None)) => {
+                debug!("Skipping existing folder {}", local_path_str);
+            }
+            Ok((local_path, Some(change))) => {
+                let file_type = file_type(file_item)?;
+                if FileMeta::filetype_is_symlink(file_type) {
+                    local_dest.symlink_created(local_path);
+                } else if !dry_run && FileMeta::filetype_is_dir(file_type) {
+                    // Dirs' metadata is restored once all their content has been written
+                    dirs_created.push((local_path, file_item));
+                }
+                processed_files.insert(local_path_str, (change.to_string(), link));
+            }
+            Err(err) => {
+                info!("Skipping file \"{}\": {}", local_path_str, err);
+                processed_files.insert(
+                    local_path_str,
+                    (CONTENT_ERROR_SIGN.to_string(), format!("<{}>", err)),
+                );
+            }
+        }
+    }
+
+    // Restore the dirs' metadata, starting from the innermost ones
+    for (dir_path, file_item) in dirs_created.iter().rev() {
+        restore_metadata(dir_path, file_item);
+    }
+
+    Ok(processed_files)
+}
+
+// Folder on the local filesystem which content from a FilesMap is written into. The paths
+// found in a FilesMap are not to be trusted, thus it makes sure nothing is written or
+// removed outside of it, neither through `..` components nor through symlinks.
+pub(crate) struct LocalDest {
+    root: PathBuf,
+    // Canonical path of the root, or None if it doesn't exist yet, e.g. on a dry run
+    canonical_root: Option<PathBuf>,
+    // Symlinks created within the root so far, which are never written through
+    symlinks_created: HashSet<PathBuf>,
+}
+
+impl LocalDest {
+    pub(crate) fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            canonical_root: root.canonicalize().ok(),
+            symlinks_created: HashSet::new(),
+        }
+    }
+
+    pub(crate) fn root(&self) -> &Path {
+        &self.root
+    }
+
+    // Local path of a path relative to the root, making sure that
+    // writing or removing it doesn't reach anything outside of the root
+    pub(crate) fn local_path(&self, relative_path: &str) -> Result<PathBuf> {
+        let relative = Path::new(relative_path.trim_start_matches('/'));
+        let mut components = relative.components().peekable();
+        if components.peek().is_none()
+            || components.any(|component| !matches!(component, Component::Normal(_)))
+        {
+            return Err(Error::ContentError(format!(
+                "Path '{}' is not allowed since it's not relative to the destination folder",
+                relative_path
+            )));
+        }
+
+        let local_path = self.root.join(relative);
+        let parents = local_path
+            .ancestors()
+            .skip(1)
+            .take_while(|parent| *parent != self.root);
+        let mut existing_parent = None;
+        for parent in parents {
+            if self.symlinks_created.contains(parent) {
+                return Err(Error::FileSystemError(format!(
+                    "Cannot write '{}' through the symlink '{}' created from the FilesContainer",
+                    local_path.display(),
+                    parent.display()
+                )));
+            }
+            if existing_parent.is_none() && fs::symlink_metadata(parent).is_ok() {
+                existing_parent = Some(parent);
+            }
+        }
+
+        // The innermost parent which already exists may be, or be within, a symlink
+        // found in the destination folder beforehand, which must not lead out of it
+        if let (Some(canonical_root), Some(parent)) = (&self.canonical_root, existing_parent) {
+            let within_root = parent
+                .canonicalize()
+                .map_or(false, |canonical| canonical.starts_with(canonical_root));
+            if !within_root {
+                return Err(Error::FileSystemError(format!(
+                    "Cannot write '{}' since its parent '{}' is outside of the destination folder",
+                    local_path.display(),
+                    parent.display()
+                )));
+            }
+        }
+
+        Ok(local_path)
+    }
+
+    // Keep track of a symlink created at the local path
+    pub(crate) fn symlink_created(&mut self, local_path: PathBuf) {
+        let _ = self.symlinks_created.insert(local_path);
+    }
+}
+
+// Obtain the list of entries from the FilesMap, along with their path relative to `src_path`
+fn entries_to_download<'a>(
+    files_map: &'a FilesMap,
+    src_path: &str,
+) -> Result<Vec<(&'a str, &'a FileItem)>> {
+    let src_path = src_path.trim_end_matches('/');
+    if src_path.is_empty() {
+        return Ok(files_map
+            .iter()
+            .map(|(path, file_item)| (path.trim_start_matches('/'), file_item))
+            .collect());
+    }
+
+    match files_map.get_key_value(src_path) {
+        Some((path, file_item)) if !FileMeta::filetype_is_dir(file_type(file_item)?) => {
+            let name = path.rsplit('/').next().unwrap_or(path.as_str());
+            Ok(vec![(name, file_item)])
+        }
+        explicit_dir => {
+            let folder_path = format!("{}/", src_path);
+            let entries: Vec<(&str, &FileItem)> = files_map
+                .iter()
+                .filter_map(|(path, file_item)| {
+                    path.strip_prefix(&folder_path)
+                        .map(|relative_path| (relative_path, file_item))
+                })
+                .collect();
+
+            if entries.is_empty() && explicit_dir.is_none() {
+                Err(Error::ContentNotFound(format!(
+                    "No content found matching the \"{}\" path on the FilesContainer",
+                    src_path
+                )))
+            } else {
+                Ok(entries)
+            }
+        }
+    }
+}
+
+// Write a single FileItem onto the local path, returning the type of
+// change made, or None if it was a folder which already existed.
+pub(crate) async fn download_file_item(
+    safe: &Safe,
+    local_path: &Path,
+    file_item: &FileItem,
+    overwrite: bool,
+    dry_run: bool,
+) -> Result<Option<&'static str>> {
+    let file_type = file_type(file_item)?;
+    let existing = fs::symlink_metadata(local_path).ok();
+
+    if FileMeta::filetype_is_dir(file_type) {
+        return match existing {
+            Some(meta) if meta.is_dir() => Ok(None),
+            Some(_) => Err(Error::FileSystemError(format!(
+                "Cannot create folder '{}' since a file with same name already exists",
+                local_path.display()
+            ))),
+            None => {
+                if !dry_run {
+                    fs::create_dir_all(local_path).map_err(|err| {
+                        Error::FileSystemError(format!("Couldn't create folder: {}", err))
+                    })?;
+                }
+                Ok(Some(CONTENT_ADDED_SIGN))
+            }
+        };
+    }
+
+    let change = match existing {
+        Some(meta) if meta.is_dir() => {
+            return Err(Error::FileSystemError(format!(
+                "Cannot write file '{}' since a folder with same name already exists",
+                local_path.display()
+            )))
+        }
+        Some(_) if !overwrite => {
+            return Err(Error::FileSystemError(format!(
+                "File '{}' already exists. Use the 'overwrite' flag to replace it",
+                local_path.display()
+            )))
+        }
+        Some(_) => CONTENT_UPDATED_SIGN,
+        None => CONTENT_ADDED_SIGN,
+    };
+
+    if dry_run {
+        return Ok(Some(change));
+    }
+
+    if let Some(parent) = local_path.parent() {
+        fs::create_dir_all(parent).map_err(|err| {
+            Error::FileSystemError(format!("Couldn't create parent folder: {}", err))
+        })?;
+    }
+
+    if change == CONTENT_UPDATED_SIGN {
+        fs::remove_file(local_path).map_err(|err| {
+            Error::FileSystemError(format!("Couldn't replace existing file: {}", err))
+        })?;
+    }
+
+    if FileMeta::filetype_is_symlink(file_type) {
+        create_symlink(local_path, file_item)?;
+    } else {
+        let link = file_item.get(PREDICATE_LINK).ok_or_else(|| {
+            Error::ContentError("FileItem is corrupt. It is missing a \"link\" property".into())
+        })?;
+        let safe_url = SafeUrl::from_url(link)?;
+
+        let mut file = File::create(local_path)
+            .await
+            .map_err(|err| Error::FileSystemError(format!("Couldn't create file: {}", err)))?;
+        let _ = write_blob(safe, &safe_url, None, &mut file).await?;
+        restore_metadata(local_path, file_item);
+    }
+
+    Ok(Some(change))
+}
+
+fn file_type(file_item: &FileItem) -> Result<&str> {
+    file_item
+        .get(PREDICATE_TYPE)
+        .map(String::as_str)
+        .ok_or_else(|| {
+            Error::ContentError("FileItem is corrupt. It is missing a \"type\" property".into())
+        })
+}
+
+#[cfg(unix)]
+fn create_symlink(local_path: &Path, file_item: &FileItem) -> Result<()> {
+    let target = symlink_target(file_item)?;
+    std::os::unix::fs::symlink(target, local_path)
+        .map_err(|err| Error::FileSystemError(format!("Couldn't create symlink: {}", err)))
+}
+
+#[cfg(windows)]
+fn create_symlink(local_path: &Path, file_item: &FileItem) -> Result<()> {
+    let target = symlink_target(file_item)?;
+    let result = match file_item.get(SYMLINK_TARGET_TYPE).map(String::as_str) {
+        Some("dir") => std::os::windows::fs::symlink_dir(target, local_path),
+        _ => std::os::windows::fs::symlink_file(target, local_path),
+    };
+    result.map_err(|err| Error::FileSystemError(format!("Couldn't create symlink: {}", err)))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn create_symlink(local_path: &Path, _file_item: &FileItem) -> Result<()> {
+    Err(Error::FileSystemError(format!(
+        "Symlinks are not supported on this platform, cannot create '{}'",
+        local_path.display()
+    )))
+}
+
+fn symlink_target(file_item: &FileItem) -> Result<&str> {
+    match file_item.get(SYMLINK_TARGET) {
+        Some(target) if !target.is_empty() => Ok(target),
+        _ => Err(Error::ContentError(
+            "Invalid/corrupted symlink, missing target".to_string(),
+        )),
+    }
+}
+
+// Restore the permissions and original modification time of a file or folder.
+// This is done on a best-effort basis, failing to do so is not considered an error.
+fn restore_metadata(local_path: &Path, file_item: &FileItem) {
+    restore_permissions(local_path, file_item);
+
+    if let Some(modified) = file_item.get(PREDICATE_ORIGINAL_MODIFIED) {
+        match DateTime::parse_from_rfc3339(modified) {
+            Ok(datetime) => {
+                let mtime = FileTime::from_unix_time(
+                    datetime.timestamp(),
+                    datetime.timestamp_subsec_nanos(),
+                );
+                if let Err(err) = filetime::set_file_mtime(local_path, mtime) {
+                    warn!(
+                        "Couldn't set modification time of {}: {}",
+                        local_path.display(),
+                        err
+                    );
+                }
+            }
+            Err(err) => warn!("Invalid modification time '{}' found: {}", modified, err),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn restore_permissions(local_path: &Path, file_item: &FileItem) {
+    use std::os::unix::fs::PermissionsExt;
+
+    if let Some(mode_bits) = file_item.get(PREDICATE_MODE_BITS) {
+        match mode_bits.parse::<u32>() {
+            Ok(mode) => {
+                let permissions = fs::Permissions::from_mode(mode & 0o7777);
+                if let Err(err) = fs::set_permissions(local_path, permissions) {
+                    warn!(
+                        "Couldn't set permissions of {}: {}",
+                        local_path.display(),
+                        err
+                    );
+                }
+            }
+            Err(err) => warn!("Invalid mode bits '{}' found: {}", mode_bits, err),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn restore_permissions(local_path: &Path, file_item: &FileItem) {
+    if file_item.get(PREDICATE_READONLY).map(String::as_str) == Some("true") {
+        let result = fs::metadata(local_path).and_then(|meta| {
+            let mut permissions = meta.permissions();
+            permissions.set_readonly(true);
+            fs::set_permissions(local_path, permissions)
+        });
+        if let Err(err) = result {
+            warn!("Couldn't set {} as readonly: {}", local_path.display(), err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_helpers::{new_in_memory_safe_instance, new_temp_dir, FileItemBuilder};
+    use anyhow::Result;
+    use std::time::UNIX_EPOCH;
+
+    #[tokio::test]
+    async fn test_files_container_download() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let src = new_temp_dir()?;
+        fs::write(src.join("test.md"), "hello tests!")?;
+        fs::create_dir_all(src.join("subfolder").join("empty"))?;
+        fs::write(src.join("subfolder").join("sub.md"), "hello from subfolder")?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::os::unix::fs::symlink("test.md", src.join("link.md"))?;
+            fs::set_permissions(src.join("test.md"), fs::Permissions::from_mode(0o600))?;
+        }
+
+        let (xorurl, _, _) = safe
+            .files_container_create(
+                Some(&format!("{}/", src.display())),
+                None,
+                true,
+                false,
+                false,
+            )
+            .await?;
+
+        let dest = new_temp_dir()?;
+        let dest_str = dest.display().to_string();
+        let (version, processed_files) = safe
+            .files_container_download(&xorurl, &dest_str, false, false)
+            .await?;
+        assert_eq!(version, 0);
+        assert!(processed_files
+            .values()
+            .all(|(change, _)| change == CONTENT_ADDED_SIGN));
+
+        assert_eq!(fs::read_to_string(dest.join("test.md"))?, "hello tests!");
+        assert_eq!(
+            fs::read_to_string(dest.join("subfolder").join("sub.md"))?,
+            "hello from subfolder"
+        );
+        assert!(dest.join("subfolder").join("empty").is_dir());
+        // the original modification time is only kept with a precision of seconds
+        let modified_secs = |path: &Path| -> Result<u64> {
+            Ok(fs::metadata(path)?
+                .modified()?
+                .duration_since(UNIX_EPOCH)?
+                .as_secs())
+        };
+        assert_eq!(
+            modified_secs(&dest.join("test.md"))?,
+            modified_secs(&src.join("test.md"))?
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let link_target = fs::read_link(dest.join("link.md"))?;
+            assert_eq!(link_target.display().to_string(), "test.md");
+            let mode = fs::metadata(dest.join("test.md"))?.permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // existing files are not replaced unless 'overwrite' is set
+        let (_, processed_files) = safe
+            .files_container_download(&xorurl, &dest_str, false, false)
+            .await?;
+        let test_md = dest.join("test.md").display().to_string();
+        assert_eq!(processed_files[&test_md].0, CONTENT_ERROR_SIGN);
+
+        let (_, processed_files) = safe
+            .files_container_download(&xorurl, &dest_str, true, false)
+            .await?;
+        assert_eq!(processed_files[&test_md].0, CONTENT_UPDATED_SIGN);
+
+        // download only a sub-folder
+        let sub_dest = new_temp_dir()?;
+        let _ = safe
+            .files_container_download(
+                &format!("{}/subfolder", xorurl),
+                &sub_dest.display().to_string(),
+                false,
+                false,
+            )
+            .await?;
+        assert_eq!(
+            fs::read_to_string(sub_dest.join("sub.md"))?,
+            "hello from subfolder"
+        );
+        assert!(!sub_dest.join("test.md").exists());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_map_download_paths_outside_dest() -> Result<()> {
+        let safe = new_in_memory_safe_instance();
+        let link = safe
+            .files_store_public_blob(b"malicious", Some("text/plain"), false)
+            .await?;
+        let file_item = FileItemBuilder::file("text/plain", &link).size("9").build();
+
+        let outside = new_temp_dir()?;
+        let outside_name = outside
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        let dest = new_temp_dir()?;
+
+        let mut files_map = FilesMap::new();
+        let _ = files_map.insert(
+            format!("/../{}/escaped.txt", outside_name),
+            file_item.clone(),
+        );
+        let _ = files_map.insert(
+            format!("/sub/../../{}/escaped.txt", outside_name),
+            file_item.clone(),
+        );
+        let _ = files_map.insert("/legit.txt".to_string(), file_item.clone());
+        #[cfg(unix)]
+        {
+            // a symlink created by the download itself...
+            let symlink_item = FileItemBuilder::new(MIMETYPE_FILESYSTEM_SYMLINK)
+                .property(SYMLINK_TARGET, &outside.display().to_string())
+                .build();
+            let _ = files_map.insert("/link".to_string(), symlink_item);
+            let _ = files_map.insert("/link/escaped.txt".to_string(), file_item.clone());
+
+            // ...and one found in the destination folder beforehand
+            std::os::unix::fs::symlink(&*outside, dest.join("existing"))?;
+            let _ = files_map.insert("/existing/escaped.txt".to_string(), file_item.clone());
+        }
+
+        let processed_files =
+            files_map_download(&safe, &files_map, "", &dest, false, false).await?;
+        assert_eq!(fs::read_to_string(dest.join("legit.txt"))?, "malicious");
+        assert!(!outside.join("escaped.txt").exists());
+        assert_eq!(fs::read_dir(&*outside)?.count(), 0);
+        assert!(processed_files
+            .iter()
+            .filter(|(path, _)| path.ends_with("escaped.txt"))
+            .all(|(_, (change, _))| change == CONTENT_ERROR_SIGN));
+
+        Ok(())
+    }
+}
@@ -7,39 +7,119 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use super::{metadata::get_metadata, ProcessedFiles};
-use crate::{app::consts::*, Error, Result, Safe, XorUrl};
+use super::{
+    checksum::ChecksumHasher,
+    filter::PathFilter,
+    metadata::get_metadata,
+    progress::{notify_progress, FilesProgress, ProgressObserver},
+    ProcessedFiles, SyncOptions,
+};
+use crate::{
+    app::{
+        cancellation::{check_cancelled, CancellationToken},
+        consts::*,
+    },
+    Error, Result, Safe, SafeUrl, XorUrl,
+};
+use futures::stream::{self, StreamExt};
 use log::info;
 use std::{collections::BTreeMap, fs, path::Path};
+use tokio::fs::File;
 use walkdir::{DirEntry, WalkDir};
 
 const MAX_RECURSIVE_DEPTH: usize = 10_000;
 
-// Upload a files to the Network as a Public Blob
+// Checksums of the local files uploaded, keyed by their local paths
+pub(crate) type UploadedChecksums = BTreeMap<String, String>;
+
+// A local file uploaded to the Network
+#[derive(Debug, Clone)]
+pub(crate) struct UploadedFile {
+    pub(crate) xorurl: XorUrl,
+    // Checksum of the content uploaded, which is not calculated on a dry run
+    pub(crate) checksum: Option<String>,
+}
+
+// Upload a files to the Network as a Public Blob, streaming its content from the local
+// file so it's never entirely loaded in memory. The observer, if any, is notified of each
+// piece of the file uploaded, and the upload is aborted between pieces if it's cancelled.
+// The checksum of the content is calculated from the very same pieces which are uploaded.
 pub(crate) async fn upload_file_to_net(
-    safe: &mut Safe,
+    safe: &Safe,
     path: &Path,
+    progress: Option<&ProgressObserver>,
+    cancel: Option<&CancellationToken>,
     dry_run: bool,
-) -> Result<XorUrl> {
-    let data = fs::read(path).map_err(|err| {
+) -> Result<UploadedFile> {
+    let file = File::open(path).await.map_err(|err| {
         Error::InvalidInput(format!("Failed to read file from local location: {}", err))
     })?;
 
+    // If the media-type is not supported we simply upload it as raw content
     let mime_type = mime_guess::from_path(&path);
-    match safe
-        .files_store_public_blob(&data, mime_type.first_raw(), dry_run)
-        .await
-    {
-        Ok(xorurl) => Ok(xorurl),
-        Err(err) => {
-            // Let's then upload it and set media-type to be simply raw content
-            if let Error::InvalidMediaType(_) = err {
-                safe.files_store_public_blob(&data, None, dry_run).await
-            } else {
-                Err(err)
-            }
+    let media_type = mime_type
+        .first_raw()
+        .filter(|media_type| SafeUrl::is_media_type_supported(media_type));
+
+    let mut hasher = if dry_run {
+        None
+    } else {
+        Some(ChecksumHasher::new())
+    };
+    let on_piece = |piece: &[u8]| {
+        if let Some(hasher) = hasher.as_mut() {
+            hasher.update(piece);
         }
+        notify_progress(progress, || FilesProgress::BytesUploaded {
+            path: path.display().to_string(),
+            bytes: piece.len() as u64,
+        });
+        check_cancelled(cancel)
+    };
+    let xorurl = safe
+        .store_public_blob_from_reader(file, media_type, dry_run, on_piece)
+        .await?;
+
+    Ok(UploadedFile {
+        xorurl,
+        checksum: hasher.map(ChecksumHasher::finalize),
+    })
+}
+
+// Upload a local file to the Network, reporting the outcome to the progress observer if any.
+// The file is not uploaded if the operation was cancelled.
+pub(crate) async fn upload_local_file(
+    safe: &Safe,
+    local_file_name: &str,
+    options: &SyncOptions,
+    dry_run: bool,
+) -> Result<UploadedFile> {
+    check_cancelled(options.cancel.as_ref())?;
+    let path = Path::new(local_file_name);
+    let result = upload_file_to_net(
+        safe,
+        path,
+        options.progress.as_ref(),
+        options.cancel.as_ref(),
+        dry_run,
+    )
+    .await;
+    // A cancelled upload is not reported as an error of the file
+    if let Err(Error::Cancelled(_)) = result {
+        return result;
     }
+    notify_progress(options.progress.as_ref(), || match &result {
+        Ok(uploaded) => FilesProgress::FileUploaded {
+            path: local_file_name.to_string(),
+            size: fs::metadata(path).map_or(0, |metadata| metadata.len()),
+            xorurl: uploaded.xorurl.clone(),
+        },
+        Err(err) => FilesProgress::FileErrored {
+            path: local_file_name.to_string(),
+            error: err.to_string(),
+        },
+    });
+    result
 }
 
 // Simply change Windows style path separator into `/`
@@ -49,14 +129,60 @@ pub(crate) fn normalise_path_separator(from: &str) -> String {
 
 // Walk the local filesystem starting from `location`, creating a list of files paths,
 // and if not requested as a `dry_run` upload the files to the network filling up
-// the list of files with their corresponding XOR-URLs
+// the list of files with their corresponding XOR-URLs. Paths ignored by the filter are
+// skipped, and up to `options.concurrency` files are uploaded at a time. The checksums
+// of the files uploaded are returned along with the list.
 pub(crate) async fn file_system_dir_walk(
-    safe: &mut Safe,
+    safe: &Safe,
     location: &str,
     recursive: bool,
     follow_links: bool,
+    path_filter: &mut PathFilter,
+    options: &SyncOptions,
     dry_run: bool,
-) -> Result<ProcessedFiles> {
+) -> Result<(ProcessedFiles, UploadedChecksums)> {
+    let (mut processed_files, files) = file_system_dir_list(
+        location,
+        recursive,
+        follow_links,
+        path_filter,
+        options.progress.as_ref(),
+    )?;
+
+    let mut checksums = UploadedChecksums::new();
+    let results = upload_files_to_net(safe, &files, options, dry_run).await;
+    for (normalised_path, result) in files.into_iter().zip(results) {
+        match result {
+            Ok(UploadedFile { xorurl, checksum }) => {
+                if let Some(checksum) = checksum {
+                    let _ = checksums.insert(normalised_path.clone(), checksum);
+                }
+                processed_files.insert(normalised_path, (CONTENT_ADDED_SIGN.to_string(), xorurl));
+            }
+            Err(err) => {
+                info!("Skipping file \"{}\". {}", normalised_path, err);
+                processed_files.insert(
+                    normalised_path,
+                    (CONTENT_ERROR_SIGN.to_string(), format!("<{}>", err)),
+                );
+            }
+        }
+    }
+
+    Ok((processed_files, checksums))
+}
+
+// Walk the local filesystem starting from `location`, creating a list of files paths without
+// uploading nor reading any file. Apart from the list, it returns the paths which are files,
+// in the order they were found, so the caller can upload them if needed.
+// Each file found is reported to the progress observer, if any.
+pub(crate) fn file_system_dir_list(
+    location: &str,
+    recursive: bool,
+    follow_links: bool,
+    path_filter: &mut PathFilter,
+    progress: Option<&ProgressObserver>,
+) -> Result<(ProcessedFiles, Vec<String>)> {
     let file_path = Path::new(location);
     info!("Reading files from {}", file_path.display());
     let (metadata, _) = get_metadata(&file_path, follow_links)?;
@@ -65,10 +191,11 @@ pub(crate) async fn file_system_dir_walk(
         // We now compare both FilesMaps to upload the missing files
         let max_depth = if recursive { MAX_RECURSIVE_DEPTH } else { 1 };
         let mut processed_files = BTreeMap::new();
+        let mut files = vec![];
         let children_to_process = WalkDir::new(file_path)
             .follow_links(follow_links)
             .into_iter()
-            .filter_entry(|e| valid_depth(e, max_depth))
+            .filter_entry(|e| valid_depth(e, max_depth) && !path_filter.skip_entry(e))
             .filter_map(|v| v.ok());
 
         for (idx, child) in children_to_process.enumerate() {
@@ -110,21 +237,15 @@ pub(crate) async fn file_system_dir_walk(
                         );
                     }
                     if metadata.file_type().is_file() {
-                        match upload_file_to_net(safe, &current_file_path, dry_run).await {
-                            Ok(xorurl) => {
-                                processed_files.insert(
-                                    normalised_path,
-                                    (CONTENT_ADDED_SIGN.to_string(), xorurl),
-                                );
-                            }
-                            Err(err) => {
-                                processed_files.insert(
-                                    normalised_path.clone(),
-                                    (CONTENT_ERROR_SIGN.to_string(), format!("<{}>", err)),
-                                );
-                                info!("Skipping file \"{}\". {}", normalised_path, err);
-                            }
-                        }
+                        processed_files.insert(
+                            normalised_path.clone(),
+                            (CONTENT_ADDED_SIGN.to_string(), String::default()),
+                        );
+                        notify_progress(progress, || FilesProgress::FileDiscovered {
+                            path: normalised_path.clone(),
+                            size: metadata.len(),
+                        });
+                        files.push(normalised_path);
                     }
                 }
                 Err(err) => {
@@ -139,7 +260,7 @@ pub(crate) async fn file_system_dir_walk(
             }
         }
 
-        Ok(processed_files)
+        Ok((processed_files, files))
     } else {
         // Recursive only works on a dir path. Let's error as the user may be making a mistake
         // so it's better for the user to double check and either provide the correct path
@@ -151,6 +272,21 @@ pub(crate) async fn file_system_dir_walk(
     }
 }
 
+// Upload local files to the Network, up to `options.concurrency` of them at a time,
+// returning the result for each of them in the same order they were provided
+pub(crate) async fn upload_files_to_net(
+    safe: &Safe,
+    paths: &[String],
+    options: &SyncOptions,
+    dry_run: bool,
+) -> Vec<Result<UploadedFile>> {
+    stream::iter(paths.iter())
+        .map(|path| upload_local_file(safe, path, options, dry_run))
+        .buffered(options.concurrency.max(1))
+        .collect()
+        .await
+}
+
 // Checks if the depth in the dir hierarchy is under a threshold
 fn valid_depth(entry: &DirEntry, max_depth: usize) -> bool {
     entry
@@ -162,10 +298,12 @@ fn valid_depth(entry: &DirEntry, max_depth: usize) -> bool {
 
 // Read the local filesystem at `location`, creating a list of one single file's path,
 // and if not as a `dry_run` upload the file to the network and putting
-// the obtained XOR-URL in the single file list returned
+// the obtained XOR-URL in the single file list returned. The file is reported to the
+// progress observer, if any, as discovered, and as errored if it couldn't be read.
 pub(crate) async fn file_system_single_file(
-    safe: &mut Safe,
+    safe: &Safe,
     location: &str,
+    progress: Option<&ProgressObserver>,
     dry_run: bool,
 ) -> Result<ProcessedFiles> {
     let file_path = Path::new(location);
@@ -181,11 +319,19 @@ pub(crate) async fn file_system_single_file(
             location
         )))
     } else {
-        match upload_file_to_net(safe, &file_path, dry_run).await {
-            Ok(xorurl) => {
+        notify_progress(progress, || FilesProgress::FileDiscovered {
+            path: normalised_path.clone(),
+            size: metadata.len(),
+        });
+        match upload_file_to_net(safe, &file_path, None, None, dry_run).await {
+            Ok(UploadedFile { xorurl, .. }) => {
                 processed_files.insert(normalised_path, (CONTENT_ADDED_SIGN.to_string(), xorurl));
             }
             Err(err) => {
+                notify_progress(progress, || FilesProgress::FileErrored {
+                    path: normalised_path.clone(),
+                    error: err.to_string(),
+                });
                 processed_files.insert(
                     normalised_path.clone(),
                     (CONTENT_ERROR_SIGN.to_string(), format!("<{}>", err)),
@@ -8,13 +8,15 @@
 // Software.
 
 use super::{
+    custom_metadata::{custom_metadata, CUSTOM_METADATA_PREFIX},
     file_system::{normalise_path_separator, upload_file_to_net},
     metadata::FileMeta,
     ProcessedFiles,
 };
-use crate::{app::consts::*, Error, Result, Safe};
+use crate::{app::consts::*, Error, Result, Safe, XorUrl};
+use chrono::{DateTime, SecondsFormat, Utc};
 use log::{debug, info};
-use std::{collections::BTreeMap, fs, path::Path};
+use std::{collections::BTreeMap, fs, path::Path, str::FromStr};
 
 // To use for mapping files names (with path in a flattened hierarchy) to FileItems
 pub type FilesMap = BTreeMap<String, FileItem>;
@@ -38,6 +40,353 @@ impl GetAttr for FileItem {
     }
 }
 
+// Keys of a FileItem holding the target of a symlink and its type
+pub(crate) const SYMLINK_TARGET: &str = "symlink_target";
+pub(crate) const SYMLINK_TARGET_TYPE: &str = "symlink_target_type";
+
+/// Typed version of a `FileItem`, with its properties parsed.
+///
+/// It can be converted from and to a `FileItem` without losing any information. Properties
+/// it doesn't know about are kept as they are in `other`, and timestamps are written back
+/// as they were found unless they are changed, in which case they are written in the
+/// RFC 3339 format used when storing them.
+///
+/// ## Example
+///
+/// ```rust
+/// # use sn_api::{files::{FileEntry, FileKind}, Safe};
+/// # let mut safe = Safe::default();
+/// # async_std::task::block_on(async {
+/// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+///     let (_, _, files_map) = safe.files_container_create(Some("../testdata/"), None, true, false, false).await.unwrap();
+///     let file_entry = FileEntry::from_file_item(&files_map["/test.md"]).unwrap();
+///     assert!(matches!(file_entry.kind, FileKind::File { .. }));
+///     assert_eq!(file_entry.to_file_item(), files_map["/test.md"]);
+/// # });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEntry {
+    /// The kind of entry, along with the properties specific to it
+    pub kind: FileKind,
+    /// Size in bytes, which is 0 for directories and symlinks, and unknown for
+    /// files added by linking to an existing Blob
+    pub size: Option<u64>,
+    /// When the entry was added to the FilesContainer
+    pub created: DateTime<Utc>,
+    /// When the entry was last updated on the FilesContainer
+    pub modified: DateTime<Utc>,
+    /// Creation time of the file on the filesystem it was uploaded from, if available
+    pub original_created: Option<DateTime<Utc>>,
+    /// Modification time of the file on the filesystem it was uploaded from, if available
+    pub original_modified: Option<DateTime<Utc>>,
+    /// Whether the file was read-only on the filesystem it was uploaded from, if known
+    pub readonly: Option<bool>,
+    /// Unix mode bits of the file on the filesystem it was uploaded from, if known
+    pub mode_bits: Option<u32>,
+    /// Checksum of the content of a file, prefixed with the algorithm it was calculated
+    /// with, e.g. `sha3-256:<hex digest>`. Files uploaded by older versions don't have it.
+    pub checksum: Option<String>,
+    /// Custom metadata set on the entry, see `Safe::files_container_set_metadata`
+    pub custom: BTreeMap<String, String>,
+    /// Any other property of the FileItem
+    pub other: BTreeMap<String, String>,
+    // Timestamps as they were found in the FileItem, keyed by their property, since they
+    // can be in any of the RFC 3339 forms, e.g. with a "+00:00" offset or a ".000" fraction
+    raw_timestamps: BTreeMap<String, String>,
+}
+
+/// Kind of a `FileEntry`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileKind {
+    /// A file, with its media type and the XOR-URL of the Blob storing its content
+    File {
+        /// Media type of the file
+        media_type: String,
+        /// XOR-URL of the Blob storing the content of the file
+        link: XorUrl,
+    },
+    /// A directory
+    Dir,
+    /// A symbolic link
+    Symlink(SymlinkInfo),
+}
+
+impl FileKind {
+    /// Media type of the kind of entry, which for directories and symlinks is
+    /// `inode/directory` and `inode/symlink` respectively
+    pub fn media_type(&self) -> &str {
+        match self {
+            Self::File { media_type, .. } => media_type,
+            Self::Dir => MIMETYPE_FILESYSTEM_DIR,
+            Self::Symlink(_) => MIMETYPE_FILESYSTEM_SYMLINK,
+        }
+    }
+}
+
+/// Target of a symbolic link stored on a FilesContainer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymlinkInfo {
+    /// Path the symlink points to, either absolute or relative to the symlink
+    pub target: String,
+    /// Type of the target when the symlink was uploaded, if known
+    pub target_type: Option<SymlinkTargetType>,
+}
+
+/// Type of the target of a symbolic link, which some platforms need to create it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymlinkTargetType {
+    /// The target is a file
+    File,
+    /// The target is a directory
+    Dir,
+    /// The target couldn't be found when uploading the symlink
+    Unknown,
+}
+
+impl SymlinkTargetType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::File => "file",
+            Self::Dir => "dir",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+impl FileEntry {
+    /// Parse a `FileItem`, failing if any of its properties is missing or invalid
+    pub fn from_file_item(file_item: &FileItem) -> Result<Self> {
+        let mut other = file_item.clone();
+        let mut take = |key: &str| other.remove(key);
+
+        let media_type = required(PREDICATE_TYPE, take(PREDICATE_TYPE))?;
+        let kind = match media_type.as_str() {
+            MIMETYPE_FILESYSTEM_DIR => FileKind::Dir,
+            MIMETYPE_FILESYSTEM_SYMLINK => FileKind::Symlink(SymlinkInfo {
+                target: required(SYMLINK_TARGET, take(SYMLINK_TARGET))?,
+                target_type: parse_optional(
+                    SYMLINK_TARGET_TYPE,
+                    take(SYMLINK_TARGET_TYPE),
+                    |value| match value {
+                        "file" => Some(SymlinkTargetType::File),
+                        "dir" => Some(SymlinkTargetType::Dir),
+                        "unknown" => Some(SymlinkTargetType::Unknown),
+                        _ => None,
+                    },
+                )?,
+            }),
+            _ => FileKind::File {
+                link: required(PREDICATE_LINK, take(PREDICATE_LINK))?,
+                media_type,
+            },
+        };
+
+        // Files added by linking to an existing Blob have an empty size since it's unknown
+        let size = required(PREDICATE_SIZE, take(PREDICATE_SIZE)).and_then(|size| {
+            parse_value(PREDICATE_SIZE, &size, |v| match v {
+                "" => Some(None),
+                v => u64::from_str(v).ok().map(Some),
+            })
+        })?;
+        let created = required(PREDICATE_CREATED, take(PREDICATE_CREATED))
+            .and_then(|time| parse_value(PREDICATE_CREATED, &time, parse_timestamp))?;
+        let modified = required(PREDICATE_MODIFIED, take(PREDICATE_MODIFIED))
+            .and_then(|time| parse_value(PREDICATE_MODIFIED, &time, parse_timestamp))?;
+        let original_created = parse_optional(
+            PREDICATE_ORIGINAL_CREATED,
+            take(PREDICATE_ORIGINAL_CREATED),
+            parse_timestamp,
+        )?;
+        let original_modified = parse_optional(
+            PREDICATE_ORIGINAL_MODIFIED,
+            take(PREDICATE_ORIGINAL_MODIFIED),
+            parse_timestamp,
+        )?;
+        let readonly = parse_optional(PREDICATE_READONLY, take(PREDICATE_READONLY), |v| {
+            bool::from_str(v).ok()
+        })?;
+        let mode_bits = parse_optional(PREDICATE_MODE_BITS, take(PREDICATE_MODE_BITS), |v| {
+            u32::from_str(v).ok()
+        })?;
+        let checksum = take(PREDICATE_CHECKSUM);
+        let custom = custom_metadata(file_item);
+        other.retain(|key, _| !key.starts_with(CUSTOM_METADATA_PREFIX));
+        let raw_timestamps = TIMESTAMP_PROPERTIES
+            .iter()
+            .filter_map(|key| Some((key.to_string(), file_item.get(*key)?.clone())))
+            .collect();
+
+        Ok(Self {
+            kind,
+            size,
+            created,
+            modified,
+            original_created,
+            original_modified,
+            readonly,
+            mode_bits,
+            checksum,
+            custom,
+            other,
+            raw_timestamps,
+        })
+    }
+
+    /// Convert it into a `FileItem`, the format it's stored with on a FilesContainer
+    pub fn to_file_item(&self) -> FileItem {
+        let mut file_item = self.other.clone();
+        let mut insert = |key: &str, value: String| {
+            file_item.insert(key.to_string(), value);
+        };
+        for (name, value) in self.custom.iter() {
+            insert(
+                &format!("{}{}", CUSTOM_METADATA_PREFIX, name),
+                value.clone(),
+            );
+        }
+
+        match &self.kind {
+            FileKind::File { media_type, link } => {
+                insert(PREDICATE_TYPE, media_type.clone());
+                insert(PREDICATE_LINK, link.clone());
+            }
+            FileKind::Dir => insert(PREDICATE_TYPE, MIMETYPE_FILESYSTEM_DIR.to_string()),
+            FileKind::Symlink(symlink) => {
+                insert(PREDICATE_TYPE, MIMETYPE_FILESYSTEM_SYMLINK.to_string());
+                insert(SYMLINK_TARGET, symlink.target.clone());
+                if let Some(target_type) = symlink.target_type {
+                    insert(SYMLINK_TARGET_TYPE, target_type.as_str().to_string());
+                }
+            }
+        }
+
+        insert(
+            PREDICATE_SIZE,
+            self.size.map(|size| size.to_string()).unwrap_or_default(),
+        );
+        insert(
+            PREDICATE_CREATED,
+            self.timestamp_value(PREDICATE_CREATED, &self.created),
+        );
+        insert(
+            PREDICATE_MODIFIED,
+            self.timestamp_value(PREDICATE_MODIFIED, &self.modified),
+        );
+        if let Some(time) = &self.original_created {
+            insert(
+                PREDICATE_ORIGINAL_CREATED,
+                self.timestamp_value(PREDICATE_ORIGINAL_CREATED, time),
+            );
+        }
+        if let Some(time) = &self.original_modified {
+            insert(
+                PREDICATE_ORIGINAL_MODIFIED,
+                self.timestamp_value(PREDICATE_ORIGINAL_MODIFIED, time),
+            );
+        }
+        if let Some(readonly) = self.readonly {
+            insert(PREDICATE_READONLY, readonly.to_string());
+        }
+        if let Some(mode_bits) = self.mode_bits {
+            insert(PREDICATE_MODE_BITS, mode_bits.to_string());
+        }
+        if let Some(checksum) = &self.checksum {
+            insert(PREDICATE_CHECKSUM, checksum.clone());
+        }
+
+        file_item
+    }
+
+    /// Returns true if it's a directory
+    pub fn is_dir(&self) -> bool {
+        self.kind == FileKind::Dir
+    }
+
+    /// Returns true if it's a symbolic link
+    pub fn is_symlink(&self) -> bool {
+        matches!(self.kind, FileKind::Symlink(_))
+    }
+
+    /// Media type of the entry, which for directories and symlinks is
+    /// `inode/directory` and `inode/symlink` respectively
+    pub fn media_type(&self) -> &str {
+        self.kind.media_type()
+    }
+
+    /// XOR-URL of the Blob storing the content, only available for files
+    pub fn link(&self) -> Option<&str> {
+        match &self.kind {
+            FileKind::File { link, .. } => Some(link.as_str()),
+            _ => None,
+        }
+    }
+
+    // The timestamp as it was found in the FileItem, unless it was changed since
+    fn timestamp_value(&self, key: &str, time: &DateTime<Utc>) -> String {
+        match self.raw_timestamps.get(key) {
+            Some(raw) if parse_timestamp(raw).as_ref() == Some(time) => raw.clone(),
+            _ => format_timestamp(time),
+        }
+    }
+}
+
+// Properties of a FileItem holding timestamps
+const TIMESTAMP_PROPERTIES: [&str; 4] = [
+    PREDICATE_CREATED,
+    PREDICATE_MODIFIED,
+    PREDICATE_ORIGINAL_CREATED,
+    PREDICATE_ORIGINAL_MODIFIED,
+];
+
+// Check every FileItem of a FilesMap can be parsed, so the rest of the
+// code can rely on the properties they must have being present
+pub(crate) fn validate_files_map(files_map: &FilesMap) -> Result<()> {
+    for (path, file_item) in files_map.iter() {
+        FileEntry::from_file_item(file_item).map_err(|err| {
+            Error::ContentError(format!(
+                "The FilesMap has an invalid FileItem at path \"{}\": {}",
+                path, err
+            ))
+        })?;
+    }
+    Ok(())
+}
+
+fn required(key: &str, value: Option<String>) -> Result<String> {
+    value.ok_or_else(|| Error::ContentError(format!("FileItem is missing a \"{}\" property", key)))
+}
+
+fn parse_value<T, F>(key: &str, value: &str, parse: F) -> Result<T>
+where
+    F: Fn(&str) -> Option<T>,
+{
+    parse(value).ok_or_else(|| {
+        Error::ContentError(format!(
+            "FileItem has an invalid \"{}\" property: \"{}\"",
+            key, value
+        ))
+    })
+}
+
+fn parse_optional<T, F>(key: &str, value: Option<String>, parse: F) -> Result<Option<T>>
+where
+    F: Fn(&str) -> Option<T>,
+{
+    value
+        .map(|value| parse_value(key, &value, parse))
+        .transpose()
+}
+
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+fn format_timestamp(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
 // Helper function to add or update a FileItem in a FilesMap
 #[allow(clippy::too_many_arguments)]
 pub(crate) async fn add_or_update_file_item(
@@ -47,13 +396,14 @@ pub(crate) async fn add_or_update_file_item(
     file_path: &Path,
     file_meta: &FileMeta,
     file_link: Option<&str>,
+    checksum: Option<&str>,
     name_exists: bool,
     dry_run: bool,
     files_map: &mut FilesMap,
     processed_files: &mut ProcessedFiles,
 ) -> bool {
     // We need to add a new FileItem, let's generate the FileItem first
-    match gen_new_file_item(safe, file_path, file_meta, file_link, dry_run).await {
+    match gen_new_file_item(safe, file_path, file_meta, file_link, checksum, dry_run).await {
         Ok(new_file_item) => {
             let content_added_sign = if name_exists {
                 CONTENT_UPDATED_SIGN.to_string()
@@ -99,15 +449,23 @@ async fn gen_new_file_item(
     file_path: &Path,
     file_meta: &FileMeta,
     link: Option<&str>, // must be symlink target or None if FileMeta::is_symlink() is true.
+    checksum: Option<&str>, // checksum of the content the link was uploaded from, if known.
     dry_run: bool,
 ) -> Result<FileItem> {
     let mut file_item = file_meta.to_file_item();
     if file_meta.is_file() {
-        let xorurl = match link {
-            None => upload_file_to_net(safe, file_path, dry_run).await?,
-            Some(link) => link.to_string(),
+        let (xorurl, checksum) = match link {
+            None => {
+                let uploaded = upload_file_to_net(safe, file_path, None, None, dry_run).await?;
+                (uploaded.xorurl, uploaded.checksum)
+            }
+            Some(link) => (link.to_string(), checksum.map(str::to_string)),
         };
         file_item.insert(PREDICATE_LINK.to_string(), xorurl);
+        // The checksum is unknown on dry runs, and when linking to existing content
+        if let Some(checksum) = checksum {
+            file_item.insert(PREDICATE_CHECKSUM.to_string(), checksum);
+        }
     } else if file_meta.is_symlink() {
         // get metadata, with any symlinks resolved.
         let result = fs::metadata(&file_path);
@@ -135,14 +493,139 @@ async fn gen_new_file_item(
                 normalise_path_separator(&target_path.display().to_string())
             }
         };
-        file_item.insert("symlink_target".to_string(), target_path);
+        file_item.insert(SYMLINK_TARGET.to_string(), target_path);
         // This is a hint for windows-platform clients to be able to call
         //   symlink_dir() or symlink_file().  on unix, there's no need.
         file_item.insert(
-            "symlink_target_type".to_string(),
+            SYMLINK_TARGET_TYPE.to_string(),
             symlink_target_type.to_string(),
         );
     }
 
     Ok(file_item)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_helpers::FileItemBuilder;
+    use anyhow::{anyhow, Result};
+
+    #[test]
+    fn test_file_entry_from_file_item() -> Result<()> {
+        let item = FileItemBuilder::file(
+            "text/plain",
+            "safe://hnyynyie8kccparz3pcxj9uisdc4gyzcpem9dfhehhjd6hpzwf8se5w1zobnc",
+        )
+        .size("23")
+        .property(PREDICATE_CREATED, "2021-03-01T10:00:00Z")
+        .property(PREDICATE_MODIFIED, "2021-03-02T10:00:00Z")
+        .property(PREDICATE_ORIGINAL_MODIFIED, "2021-02-01T10:00:00Z")
+        .property(PREDICATE_READONLY, "false")
+        .property(PREDICATE_MODE_BITS, "33188")
+        .property(PREDICATE_CHECKSUM, "sha3-256:0123")
+        .property("custom", "value")
+        .property("custom:author", "maidsafe")
+        .build();
+
+        let entry = FileEntry::from_file_item(&item)?;
+        assert_eq!(
+            entry.link(),
+            Some("safe://hnyynyie8kccparz3pcxj9uisdc4gyzcpem9dfhehhjd6hpzwf8se5w1zobnc")
+        );
+        assert_eq!(entry.size, Some(23));
+        assert_eq!(entry.modified.to_rfc3339(), "2021-03-02T10:00:00+00:00");
+        assert_eq!(entry.original_created, None);
+        assert_eq!(entry.readonly, Some(false));
+        assert_eq!(entry.mode_bits, Some(33188));
+        assert_eq!(entry.other.get("custom").map(String::as_str), Some("value"));
+        assert_eq!(
+            entry.custom.get("author").map(String::as_str),
+            Some("maidsafe")
+        );
+        assert_eq!(entry.to_file_item(), item);
+
+        let symlink = FileItemBuilder::new(MIMETYPE_FILESYSTEM_SYMLINK)
+            .property(SYMLINK_TARGET, "../dir")
+            .property(SYMLINK_TARGET_TYPE, "dir")
+            .size("0")
+            .timestamps("2021-03-01T10:00:00Z")
+            .build();
+        let entry = FileEntry::from_file_item(&symlink)?;
+        match &entry.kind {
+            FileKind::Symlink(info) => {
+                assert_eq!(info.target, "../dir");
+                assert_eq!(info.target_type, Some(SymlinkTargetType::Dir));
+            }
+            other => return Err(anyhow!("Unexpected kind: {:?}", other)),
+        }
+        assert_eq!(entry.to_file_item(), symlink);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_entry_non_canonical_timestamps() -> Result<()> {
+        let item = FileItemBuilder::dir()
+            .size("0")
+            .property(PREDICATE_CREATED, "2021-03-01T10:00:00.000Z")
+            .property(PREDICATE_MODIFIED, "2021-03-01T12:00:00+02:00")
+            .property(PREDICATE_ORIGINAL_MODIFIED, "2021-02-01T10:00:00+00:00")
+            .build();
+        let mut entry = FileEntry::from_file_item(&item)?;
+        assert_eq!(entry.created.to_rfc3339(), "2021-03-01T10:00:00+00:00");
+        assert_eq!(entry.modified, entry.created);
+        assert_eq!(entry.to_file_item(), item);
+
+        // only the timestamps changed are written in the format used when storing them
+        entry.modified = entry.modified + chrono::Duration::seconds(1);
+        let file_item = entry.to_file_item();
+        assert_eq!(file_item[PREDICATE_MODIFIED], "2021-03-01T10:00:01Z");
+        assert_eq!(file_item[PREDICATE_CREATED], "2021-03-01T10:00:00.000Z");
+        assert_eq!(
+            file_item[PREDICATE_ORIGINAL_MODIFIED],
+            "2021-02-01T10:00:00+00:00"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_entry_from_malformed_file_item() {
+        let dir = FileItemBuilder::dir()
+            .size("0")
+            .timestamps("2021-03-01T10:00:00Z")
+            .build();
+        assert!(FileEntry::from_file_item(&dir).is_ok());
+
+        for key in &[
+            PREDICATE_TYPE,
+            PREDICATE_SIZE,
+            PREDICATE_CREATED,
+            PREDICATE_MODIFIED,
+        ] {
+            let mut item = dir.clone();
+            item.remove(*key);
+            assert!(matches!(
+                FileEntry::from_file_item(&item),
+                Err(Error::ContentError(_))
+            ));
+        }
+
+        let mut item = dir.clone();
+        item.insert(PREDICATE_MODIFIED.to_string(), "yesterday".to_string());
+        assert!(FileEntry::from_file_item(&item).is_err());
+
+        // a file must link to its content
+        let mut item = dir.clone();
+        item.insert(PREDICATE_TYPE.to_string(), "text/plain".to_string());
+        assert!(FileEntry::from_file_item(&item).is_err());
+
+        let mut files_map = FilesMap::new();
+        files_map.insert("/dir".to_string(), dir);
+        assert!(validate_files_map(&files_map).is_ok());
+        files_map.insert("/file.txt".to_string(), item);
+        match validate_files_map(&files_map) {
+            Err(Error::ContentError(msg)) => assert!(msg.contains("/file.txt")),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+}
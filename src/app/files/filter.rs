@@ -0,0 +1,301 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{Error, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::{
+    gitignore::{Gitignore, GitignoreBuilder},
+    Match,
+};
+use log::{debug, warn};
+use std::path::{Path, PathBuf};
+use walkdir::DirEntry;
+
+/// Name of the file, using gitignore syntax, which lists the local paths to be ignored
+/// when uploading a folder. It's honoured in the folder being uploaded and any of its sub-folders.
+pub const SAFE_IGNORE_FILENAME: &str = ".safeignore";
+
+/// Filter to decide which local files are uploaded when creating or syncing a FilesContainer.
+///
+/// Paths are matched relative to the local folder being uploaded. Patterns not containing
+/// a `/` are matched against any path component, like in a `.gitignore` file.
+/// Excluded folders are skipped along with all their content, whilst include patterns are
+/// only applied to files, i.e. if any include pattern is set only matching files are uploaded.
+/// By default any `.safeignore` file found in the uploaded tree is honoured.
+///
+/// ## Example
+///
+/// ```rust
+/// # use sn_api::files::FilesFilter;
+/// let filter = FilesFilter::default()
+///     .exclude("node_modules")
+///     .exclude(".git")
+///     .include("*.html")
+///     .include("assets/**");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilesFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+    ignore_files: bool,
+}
+
+impl Default for FilesFilter {
+    fn default() -> Self {
+        Self {
+            include: vec![],
+            exclude: vec![],
+            ignore_files: true,
+        }
+    }
+}
+
+impl FilesFilter {
+    /// Only upload the files matching this glob pattern (or any other include pattern)
+    pub fn include(mut self, pattern: &str) -> Self {
+        self.include.push(pattern.to_string());
+        self
+    }
+
+    /// Don't upload the files or folders matching this glob pattern
+    pub fn exclude(mut self, pattern: &str) -> Self {
+        self.exclude.push(pattern.to_string());
+        self
+    }
+
+    /// Whether the `.safeignore` files found in the uploaded tree shall be honoured
+    pub fn ignore_files(mut self, honour: bool) -> Self {
+        self.ignore_files = honour;
+        self
+    }
+}
+
+// Matcher built from a FilesFilter for a specific local folder, which
+// loads the ignore files it finds in the tree as it's being walked
+pub(crate) struct PathFilter {
+    root: PathBuf,
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    honour_ignore_files: bool,
+    // Ignore files matchers, those from deeper folders come after their ancestors'
+    ignore_files: Vec<Gitignore>,
+}
+
+impl PathFilter {
+    pub(crate) fn new(filter: &FilesFilter, root: &Path) -> Result<Self> {
+        let include = if filter.include.is_empty() {
+            None
+        } else {
+            Some(build_glob_set(&filter.include)?)
+        };
+
+        let mut path_filter = Self {
+            root: root.to_path_buf(),
+            include,
+            exclude: build_glob_set(&filter.exclude)?,
+            honour_ignore_files: filter.ignore_files,
+            ignore_files: vec![],
+        };
+
+        if root.is_dir() {
+            path_filter.load_ignore_file(root);
+        }
+
+        Ok(path_filter)
+    }
+
+    // Check if a path, expected to be under the root folder, shall be ignored
+    pub(crate) fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let relative_path = match path.strip_prefix(&self.root) {
+            Ok(relative_path) if !relative_path.as_os_str().is_empty() => relative_path,
+            _ => return false,
+        };
+
+        if relative_path
+            .ancestors()
+            .filter(|ancestor| !ancestor.as_os_str().is_empty())
+            .any(|ancestor| self.exclude.is_match(ancestor))
+        {
+            return true;
+        }
+
+        // Patterns from deeper ignore files take precedence
+        for ignore_file in self.ignore_files.iter().rev() {
+            if !path.starts_with(ignore_file.path()) {
+                continue;
+            }
+            match ignore_file.matched_path_or_any_parents(path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => break,
+                Match::None => {}
+            }
+        }
+
+        match &self.include {
+            Some(include) if !is_dir => !include.is_match(relative_path),
+            _ => false,
+        }
+    }
+
+    // Check if a single file, rather than one found whilst walking through a folder,
+    // shall be ignored, honouring the ignore file of the folder it's in
+    pub(crate) fn is_file_ignored(filter: &FilesFilter, file_path: &Path) -> Result<bool> {
+        let (parent, name) = match (file_path.parent(), file_path.file_name()) {
+            (Some(parent), Some(name)) => (parent, name),
+            _ => return Ok(false),
+        };
+        let root = if parent.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            parent
+        };
+
+        let path_filter = Self::new(filter, root)?;
+        Ok(path_filter.is_ignored(&root.join(name), false))
+    }
+
+    // To be used as a WalkDir entries filter, so ignored folders are not even walked
+    // through. The ignore file of each folder not ignored is loaded before its content is
+    // walked, therefore any path in the tree can be checked with this filter afterwards.
+    pub(crate) fn skip_entry(&mut self, entry: &DirEntry) -> bool {
+        if entry.depth() == 0 {
+            return false;
+        }
+
+        let is_dir = entry.file_type().is_dir();
+        let ignored = self.is_ignored(entry.path(), is_dir);
+        if ignored {
+            debug!("Ignoring local path {}", entry.path().display());
+        } else if is_dir {
+            self.load_ignore_file(entry.path());
+        }
+
+        ignored
+    }
+
+    fn load_ignore_file(&mut self, dir: &Path) {
+        if !self.honour_ignore_files {
+            return;
+        }
+
+        let ignore_file_path = dir.join(SAFE_IGNORE_FILENAME);
+        if !ignore_file_path.is_file() {
+            return;
+        }
+
+        let mut builder = GitignoreBuilder::new(dir);
+        if let Some(err) = builder.add(&ignore_file_path) {
+            warn!(
+                "Some patterns of \"{}\" are invalid and were ignored: {}",
+                ignore_file_path.display(),
+                err
+            );
+        }
+        match builder.build() {
+            Ok(ignore_file) => self.ignore_files.push(ignore_file),
+            Err(err) => warn!(
+                "Failed to read \"{}\", it won't be honoured: {}",
+                ignore_file_path.display(),
+                err
+            ),
+        }
+    }
+}
+
+// Patterns without a '/' match any path component, others are relative to the root folder
+fn build_glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns.iter() {
+        let normalised = if pattern.contains('/') {
+            pattern.trim_start_matches('/').to_string()
+        } else {
+            format!("**/{}", pattern)
+        };
+        let glob = GlobBuilder::new(&normalised)
+            .literal_separator(true)
+            .build()
+            .map_err(|err| {
+                Error::InvalidInput(format!("Invalid glob pattern '{}': {}", pattern, err))
+            })?;
+        builder.add(glob);
+    }
+
+    builder
+        .build()
+        .map_err(|err| Error::InvalidInput(format!("Invalid glob patterns: {}", err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_helpers::new_temp_dir;
+    use anyhow::Result;
+    use std::fs;
+
+    #[test]
+    fn test_path_filter_globs() -> Result<()> {
+        let root = Path::new("root");
+        let filter = FilesFilter::default()
+            .exclude("node_modules")
+            .exclude("/build/*.o")
+            .include("*.md");
+        let path_filter = PathFilter::new(&filter, root)?;
+
+        assert!(!path_filter.is_ignored(root, true));
+        assert!(!path_filter.is_ignored(&root.join("docs"), true));
+        assert!(!path_filter.is_ignored(&root.join("docs").join("readme.md"), false));
+        assert!(path_filter.is_ignored(&root.join("docs").join("readme.txt"), false));
+        assert!(path_filter.is_ignored(&root.join("node_modules"), true));
+        assert!(path_filter.is_ignored(&root.join("a").join("node_modules").join("b.md"), false));
+        assert!(path_filter.is_ignored(&root.join("build").join("x.o"), false));
+        assert!(!path_filter.is_ignored(&root.join("build").join("x.md"), false));
+        assert!(!path_filter.is_ignored(&root.join("sub").join("build").join("x.md"), false));
+
+        assert!(PathFilter::new(&FilesFilter::default().exclude("a{"), root).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_path_filter_ignore_files() -> Result<()> {
+        let root = new_temp_dir()?;
+        fs::write(root.join(SAFE_IGNORE_FILENAME), "*.log\ntarget/\n")?;
+        fs::create_dir_all(root.join("sub"))?;
+        fs::write(root.join("sub").join(SAFE_IGNORE_FILENAME), "!keep.log\n")?;
+
+        let mut path_filter = PathFilter::new(&FilesFilter::default(), &root)?;
+        assert!(path_filter.is_ignored(&root.join("a.log"), false));
+        assert!(path_filter.is_ignored(&root.join("target"), true));
+        assert!(path_filter.is_ignored(&root.join("target").join("a.txt"), false));
+        assert!(!path_filter.is_ignored(&root.join("a.txt"), false));
+        assert!(path_filter.is_ignored(&root.join("sub").join("keep.log"), false));
+
+        // the ignore file of a sub-folder is only honoured once it's been walked through
+        for entry in walkdir::WalkDir::new(&root)
+            .into_iter()
+            .filter_entry(|entry| !path_filter.skip_entry(entry))
+        {
+            let _ = entry?;
+        }
+        assert!(!path_filter.is_ignored(&root.join("sub").join("keep.log"), false));
+        assert!(path_filter.is_ignored(&root.join("sub").join("other.log"), false));
+
+        let path_filter = PathFilter::new(&FilesFilter::default().ignore_files(false), &root)?;
+        assert!(!path_filter.is_ignored(&root.join("a.log"), false));
+
+        let filter = FilesFilter::default();
+        assert!(PathFilter::is_file_ignored(&filter, &root.join("a.log"))?);
+        assert!(!PathFilter::is_file_ignored(&filter, &root.join("a.txt"))?);
+        assert!(PathFilter::is_file_ignored(
+            &filter.exclude("*.txt"),
+            &root.join("a.txt")
+        )?);
+
+        Ok(())
+    }
+}
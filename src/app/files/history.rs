@@ -0,0 +1,114 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{FileMeta, FilesMap};
+use crate::{app::consts::*, XorUrl};
+use chrono::DateTime;
+
+/// Summary of a version of a FilesContainer, as listed by `files_container_history`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilesContainerVersion {
+    /// Version of the FilesContainer
+    pub version: u64,
+    /// XOR-URL of the FilesMap this version links to
+    pub files_map_xorurl: XorUrl,
+    /// Number of entries (files, directories and symlinks) in the FilesMap
+    pub entries: usize,
+    /// Total size in bytes of all the files in the FilesMap
+    pub total_size: u64,
+    /// The newest `modified` timestamp among all the entries, if any
+    pub last_modified: Option<String>,
+}
+
+impl FilesContainerVersion {
+    // Summarise the FilesMap a version of a FilesContainer links to
+    pub(crate) fn new(version: u64, files_map_xorurl: XorUrl, files_map: &FilesMap) -> Self {
+        let total_size = files_map
+            .values()
+            .filter(|item| {
+                item.get(PREDICATE_TYPE)
+                    .map_or(false, |file_type| FileMeta::filetype_is_file(file_type))
+            })
+            .filter_map(|item| item.get(PREDICATE_SIZE))
+            .filter_map(|size| size.parse::<u64>().ok())
+            .sum();
+
+        // Timestamps which cannot be parsed are ignored
+        let last_modified = files_map
+            .values()
+            .filter_map(|item| item.get(PREDICATE_MODIFIED))
+            .filter_map(|modified| {
+                DateTime::parse_from_rfc3339(modified)
+                    .ok()
+                    .map(|time| (time, modified))
+            })
+            .max_by_key(|(time, _)| *time)
+            .map(|(_, modified)| modified.to_string());
+
+        Self {
+            version,
+            files_map_xorurl,
+            entries: files_map.len(),
+            total_size,
+            last_modified,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_helpers::new_in_memory_safe_instance;
+    use anyhow::Result;
+
+    #[tokio::test]
+    async fn test_files_container_history() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let (xorurl, _, _) = safe
+            .files_container_create(None, None, true, false, false)
+            .await?;
+        let _ = safe
+            .files_container_add_from_raw(
+                b"12345",
+                &format!("{}/a.txt", xorurl),
+                false,
+                false,
+                false,
+            )
+            .await?;
+        let _ = safe
+            .files_container_add_from_raw(b"123", &format!("{}/b.txt", xorurl), false, false, false)
+            .await?;
+
+        let (latest_version, history) = safe.files_container_history(&xorurl, 0, None).await?;
+        assert_eq!(latest_version, 2);
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].version, 0);
+        assert_eq!(history[0].entries, 0);
+        assert_eq!(history[0].total_size, 0);
+        assert_eq!(history[0].last_modified, None);
+        assert_eq!(history[2].entries, 2);
+        assert_eq!(history[2].total_size, 8);
+        assert!(history[2].last_modified.is_some());
+
+        let (_, files_map) = safe.files_container_get(&xorurl).await?;
+        assert_eq!(
+            history[2].last_modified.as_deref(),
+            Some(files_map["/b.txt"][PREDICATE_MODIFIED].as_str())
+        );
+
+        let (_, page) = safe.files_container_history(&xorurl, 1, Some(1)).await?;
+        assert_eq!(page, vec![history[1].clone()]);
+
+        let (_, page) = safe.files_container_history(&xorurl, 3, Some(10)).await?;
+        assert!(page.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{files_map::validate_files_map, FilesMap};
+use crate::{
+    app::{
+        consts::*,
+        rdf::{
+            self, decode_iri_chars, encode_iri_chars, map_from_properties, properties_from_map,
+            vocab_iri, RdfFormat, RdfNode,
+        },
+    },
+    Error, Result,
+};
+
+// Classes of the resources each entry of a FilesMap is described as
+const FILE_CLASS: &str = "File";
+const DIRECTORY_CLASS: &str = "Directory";
+const SYMLINK_CLASS: &str = "Symlink";
+
+/// # Export a FilesMap as linked data.
+/// Each entry is described as a resource identified by its path, relative to `base_url`,
+/// which is expected to be the URL of the FilesContainer the FilesMap belongs to.
+/// The properties of the entries are described with the vocabulary found at `SAFE_VOCAB_IRI`.
+///
+/// ## Example
+///
+/// ```rust
+/// # use sn_api::{files::files_map_to_rdf, rdf::RdfFormat, Safe};
+/// # let mut safe = Safe::default();
+/// # async_std::task::block_on(async {
+/// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+///     let (xorurl, _processed_files, files_map) = safe.files_container_create(Some("../testdata/"), None, true, true, false).await.unwrap();
+///     let turtle = files_map_to_rdf(&files_map, &xorurl, RdfFormat::Turtle).unwrap();
+///     println!("FilesContainer as Turtle: {}", turtle);
+/// # });
+/// ```
+pub fn files_map_to_rdf(files_map: &FilesMap, base_url: &str, format: RdfFormat) -> Result<String> {
+    let nodes: Vec<RdfNode> = files_map
+        .iter()
+        .map(|(path, file_item)| {
+            let class = match file_item.get(PREDICATE_TYPE).map(String::as_str) {
+                Some(MIMETYPE_FILESYSTEM_DIR) => DIRECTORY_CLASS,
+                Some(MIMETYPE_FILESYSTEM_SYMLINK) => SYMLINK_CLASS,
+                _ => FILE_CLASS,
+            };
+            RdfNode {
+                id: Some(encode_iri_chars(path)),
+                types: vec![vocab_iri(class)],
+                properties: properties_from_map(file_item),
+            }
+        })
+        .collect();
+
+    rdf::serialise(&nodes, base_url, format)
+}
+
+/// # Import a FilesMap from linked data.
+/// Every resource described as a file, directory or symlink is an entry of the FilesMap,
+/// as exported by `files_map_to_rdf`. Any other resource or property is ignored.
+///
+/// ## Example
+///
+/// ```rust
+/// # use sn_api::{files::{files_map_from_rdf, files_map_to_rdf}, rdf::RdfFormat, Safe};
+/// # let mut safe = Safe::default();
+/// # async_std::task::block_on(async {
+/// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+///     let (xorurl, _processed_files, files_map) = safe.files_container_create(Some("../testdata/"), None, true, true, false).await.unwrap();
+///     let json_ld = files_map_to_rdf(&files_map, &xorurl, RdfFormat::JsonLd).unwrap();
+///     let imported_files_map = files_map_from_rdf(&json_ld, RdfFormat::JsonLd).unwrap();
+///     assert_eq!(imported_files_map, files_map);
+/// # });
+/// ```
+pub fn files_map_from_rdf(data: &str, format: RdfFormat) -> Result<FilesMap> {
+    let mut files_map = FilesMap::new();
+    for node in rdf::parse(data, format)? {
+        let is_entry = [FILE_CLASS, DIRECTORY_CLASS, SYMLINK_CLASS]
+            .iter()
+            .any(|class| node.has_type(class));
+        if !is_entry {
+            continue;
+        }
+
+        let id = node.id.as_ref().ok_or_else(|| {
+            Error::InvalidInput(
+                "Entries of a FilesMap must be identified by an IRI with their path".to_string(),
+            )
+        })?;
+        let path = path_from_iri(id)?;
+        let file_item = map_from_properties(&node.properties)?;
+        if files_map.insert(path.clone(), file_item).is_some() {
+            return Err(Error::InvalidInput(format!(
+                "Entry \"{}\" is described more than once",
+                path
+            )));
+        }
+    }
+
+    validate_files_map(&files_map)?;
+    Ok(files_map)
+}
+
+// Path of the entry identified by an IRI, either absolute or relative to the FilesContainer
+fn path_from_iri(iri: &str) -> Result<String> {
+    let path = match iri.find("://") {
+        Some(index) => {
+            let authority_and_path = &iri[index + 3..];
+            authority_and_path
+                .find('/')
+                .map_or("", |index| &authority_and_path[index..])
+        }
+        None => iri,
+    };
+    // query and fragment are not part of the path, those characters are otherwise encoded
+    let path = path
+        .split(|c| c == '?' || c == '#')
+        .next()
+        .unwrap_or_default();
+
+    let path = decode_iri_chars(path)?;
+    Ok(format!("/{}", path.trim_start_matches('/')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_helpers::{self, FileItemBuilder};
+
+    fn files_map() -> FilesMap {
+        test_helpers::files_map(vec![
+            (
+                "/docs/read me #1.txt",
+                FileItemBuilder::file("text/plain", "safe://hbyyyyx")
+                    .size("12")
+                    .timestamps("2021-03-10T10:00:00Z")
+                    .property("custom:author", "maidsafe")
+                    .build(),
+            ),
+            (
+                "/docs",
+                FileItemBuilder::dir()
+                    .size("0")
+                    .timestamps("2021-03-10T10:00:00Z")
+                    .build(),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_files_map_rdf_roundtrip() -> Result<()> {
+        let files_map = files_map();
+        for format in &[RdfFormat::JsonLd, RdfFormat::Turtle] {
+            let data = files_map_to_rdf(&files_map, "safe://hnyynyss", *format)?;
+            assert!(data.contains("/docs/read%20me%20%231.txt"));
+            assert_eq!(files_map_from_rdf(&data, *format)?, files_map);
+        }
+
+        let turtle = files_map_to_rdf(&files_map, "safe://hnyynyss", RdfFormat::Turtle)?;
+        assert!(turtle.contains("</docs> a safe:Directory ;"));
+        assert!(turtle.contains("safe:link <safe://hbyyyyx>"));
+        assert!(turtle.contains("safe:size \"12\"^^xsd:integer"));
+        assert!(turtle.contains("<https://safenetwork.tech/vocab/v1/keys#custom:author>"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_map_from_rdf_absolute_iris() -> Result<()> {
+        let turtle = format!(
+            "@prefix safe: <{}> .\n\
+             <safe://hnyynyss/a%20b.txt?v=2> a safe:File ;\n\
+                 safe:mediaType \"text/plain\" ;\n\
+                 safe:link <safe://hbyyyyx> ;\n\
+                 safe:created \"2021-03-10T10:00:00Z\" ;\n\
+                 safe:modified \"2021-03-10T10:00:00Z\" ;\n\
+                 safe:size 3 .\n\
+             <safe://hnyynyss> a safe:Container .",
+            rdf::SAFE_VOCAB_IRI
+        );
+        let files_map = files_map_from_rdf(&turtle, RdfFormat::Turtle)?;
+        assert_eq!(files_map.len(), 1);
+        assert_eq!(files_map["/a b.txt"][PREDICATE_SIZE], "3");
+
+        // entries are validated
+        let invalid = turtle.replace("safe:link <safe://hbyyyyx> ;", "");
+        assert!(files_map_from_rdf(&invalid, RdfFormat::Turtle).is_err());
+        Ok(())
+    }
+}
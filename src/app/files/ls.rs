@@ -0,0 +1,388 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{FileEntry, FileKind, FilesMap, RealPath};
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use std::{cmp::Ordering, collections::BTreeMap};
+
+/// Key the entries listed with `Safe::files_container_ls` are sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LsSortKey {
+    /// Name of the entry
+    Name,
+    /// Size of the entry, where unknown sizes come first
+    Size,
+    /// When the entry was added to the FilesContainer
+    Created,
+    /// When the entry was last updated on the FilesContainer
+    Modified,
+    /// Media type of the entry
+    MediaType,
+}
+
+impl Default for LsSortKey {
+    fn default() -> Self {
+        Self::Name
+    }
+}
+
+/// Options to sort, filter and paginate the entries listed with `Safe::files_container_ls`
+#[derive(Debug, Clone, Default)]
+pub struct LsOptions {
+    /// Key the entries are sorted by, entries with the same value are sorted by name
+    pub sort_by: LsSortKey,
+    /// Sort the entries in descending order
+    pub descending: bool,
+    /// Only list the entries with any of these media types, e.g. `text/html`. A type can end
+    /// with `/*` to match any of its subtypes, e.g. `image/*`. All entries are listed if empty.
+    pub media_types: Vec<String>,
+    /// Maximum number of entries to return, all of them are returned if not set
+    pub limit: Option<usize>,
+    /// Cursor returned with a previous page, to continue listing from where it ended
+    pub cursor: Option<String>,
+}
+
+/// An entry found in a directory of a FilesContainer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LsEntry {
+    /// Name of the entry
+    pub name: String,
+    /// Path of the entry in the FilesContainer
+    pub path: String,
+    /// The kind of entry, along with the properties specific to it
+    pub kind: FileKind,
+    /// Size in bytes, if known
+    pub size: Option<u64>,
+    /// When the entry was added to the FilesContainer, not available for directories
+    /// only implied by the paths of their content
+    pub created: Option<DateTime<Utc>>,
+    /// When the entry was last updated on the FilesContainer, not available for
+    /// directories only implied by the paths of their content
+    pub modified: Option<DateTime<Utc>>,
+}
+
+impl LsEntry {
+    /// Media type of the entry, which for directories and symlinks is
+    /// `inode/directory` and `inode/symlink` respectively
+    pub fn media_type(&self) -> &str {
+        self.kind.media_type()
+    }
+
+    fn from_file_entry(name: &str, path: String, file_entry: FileEntry) -> Self {
+        Self {
+            name: name.to_string(),
+            path,
+            kind: file_entry.kind,
+            size: file_entry.size,
+            created: Some(file_entry.created),
+            modified: Some(file_entry.modified),
+        }
+    }
+
+    fn implied_dir(name: &str, path: String) -> Self {
+        Self {
+            name: name.to_string(),
+            path,
+            kind: FileKind::Dir,
+            size: None,
+            created: None,
+            modified: None,
+        }
+    }
+}
+
+/// A page of the entries listed with `Safe::files_container_ls`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LsResult {
+    /// Version of the FilesContainer listed, which all the pages are taken from
+    pub version: u64,
+    /// Entries of the page
+    pub entries: Vec<LsEntry>,
+    /// Cursor to get the next page with, or None if this is the last one
+    pub next_cursor: Option<String>,
+}
+
+// Version of the FilesContainer a cursor was generated for
+pub(crate) fn cursor_version(cursor: &str) -> Result<u64> {
+    parse_cursor(cursor).map(|(version, _)| version)
+}
+
+// List the direct children of a path of a FilesMap, once any symlink in it is resolved.
+// If the path is a file, or a symlink to one, that's the only entry listed.
+pub(crate) fn files_map_ls(
+    files_map: &FilesMap,
+    version: u64,
+    path: &str,
+    options: &LsOptions,
+) -> Result<LsResult> {
+    if options.limit == Some(0) {
+        return Err(Error::InvalidInput(
+            "The maximum number of entries to list must be greater than zero".to_string(),
+        ));
+    }
+
+    let path = path.trim_end_matches('/');
+    let dir_path = if path.is_empty() {
+        String::default()
+    } else {
+        files_map.realpath(path)?.trim_end_matches('/').to_string()
+    };
+
+    let mut entries = match files_map.get(&dir_path) {
+        Some(file_item) => {
+            let file_entry = FileEntry::from_file_item(file_item)?;
+            if file_entry.is_dir() {
+                list_children(files_map, &dir_path)?
+            } else {
+                let name = dir_path.rsplit('/').next().unwrap_or_default();
+                vec![LsEntry::from_file_entry(name, dir_path.clone(), file_entry)]
+            }
+        }
+        None => {
+            let children = list_children(files_map, &dir_path)?;
+            if children.is_empty() && !dir_path.is_empty() {
+                return Err(Error::ContentNotFound(format!(
+                    "No content found matching the \"{}\" path on the target FilesContainer",
+                    path
+                )));
+            }
+            children
+        }
+    };
+
+    entries.retain(|entry| matches_media_types(entry.media_type(), &options.media_types));
+    entries.sort_by(|a, b| {
+        let ordering = compare_entries(a, b, options.sort_by);
+        if options.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+
+    if let Some(cursor) = &options.cursor {
+        let (cursor_version, last_name) = parse_cursor(cursor)?;
+        if cursor_version != version {
+            return Err(Error::InvalidInput(format!(
+                "The cursor provided is for version {} of the FilesContainer rather than {}",
+                cursor_version, version
+            )));
+        }
+        let position = entries
+            .iter()
+            .position(|entry| entry.name == last_name)
+            .ok_or_else(|| {
+                Error::InvalidInput(
+                    "The cursor provided doesn't match any of the entries listed".to_string(),
+                )
+            })?;
+        entries.drain(..=position);
+    }
+
+    let next_cursor = match options.limit {
+        Some(limit) if entries.len() > limit => {
+            entries.truncate(limit);
+            entries
+                .last()
+                .map(|entry| format!("{}:{}", version, entry.name))
+        }
+        _ => None,
+    };
+
+    Ok(LsResult {
+        version,
+        entries,
+        next_cursor,
+    })
+}
+
+// Direct children of a directory, including the directories only implied by their content
+fn list_children(files_map: &FilesMap, dir_path: &str) -> Result<Vec<LsEntry>> {
+    let prefix = format!("{}/", dir_path);
+    let mut children = BTreeMap::new();
+    for (path, file_item) in files_map.range(prefix.clone()..) {
+        let relative_path = match path.strip_prefix(&prefix) {
+            Some(relative_path) => relative_path,
+            None => break,
+        };
+
+        match relative_path.find('/') {
+            None if !relative_path.is_empty() => {
+                let file_entry = FileEntry::from_file_item(file_item)?;
+                let entry = LsEntry::from_file_entry(relative_path, path.clone(), file_entry);
+                let _ = children.insert(relative_path.to_string(), entry);
+            }
+            Some(idx) if !children.contains_key(&relative_path[..idx]) => {
+                let name = &relative_path[..idx];
+                let entry = LsEntry::implied_dir(name, format!("{}{}", prefix, name));
+                let _ = children.insert(name.to_string(), entry);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(children.into_iter().map(|(_, entry)| entry).collect())
+}
+
+pub(crate) fn matches_media_types(media_type: &str, media_types: &[String]) -> bool {
+    media_types.is_empty()
+        || media_types
+            .iter()
+            .any(|filter| match filter.strip_suffix("/*") {
+                Some(top_level) => media_type
+                    .split('/')
+                    .next()
+                    .map_or(false, |media_top_level| media_top_level == top_level),
+                None => filter == media_type,
+            })
+}
+
+fn compare_entries(a: &LsEntry, b: &LsEntry, sort_by: LsSortKey) -> Ordering {
+    let ordering = match sort_by {
+        LsSortKey::Name => Ordering::Equal,
+        LsSortKey::Size => a.size.cmp(&b.size),
+        LsSortKey::Created => a.created.cmp(&b.created),
+        LsSortKey::Modified => a.modified.cmp(&b.modified),
+        LsSortKey::MediaType => a.media_type().cmp(b.media_type()),
+    };
+    ordering.then_with(|| a.name.cmp(&b.name))
+}
+
+fn parse_cursor(cursor: &str) -> Result<(u64, &str)> {
+    let mut parts = cursor.splitn(2, ':');
+    match (parts.next().map(str::parse), parts.next()) {
+        (Some(Ok(version)), Some(name)) => Ok((version, name)),
+        _ => Err(Error::InvalidInput(format!("Invalid cursor: {}", cursor))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{
+        files::FileItem,
+        test_helpers::{self, FileItemBuilder},
+    };
+
+    fn file(file_type: &str, size: &str, modified: &str) -> FileItem {
+        FileItemBuilder::file(file_type, "safe://link")
+            .size(size)
+            .timestamps(modified)
+            .build()
+    }
+
+    fn files_map() -> FilesMap {
+        test_helpers::files_map(vec![
+            ("/b.html", file("text/html", "30", "2021-03-01T10:00:00Z")),
+            ("/a.png", file("image/png", "20", "2021-03-03T10:00:00Z")),
+            ("/c.jpg", file("image/jpeg", "10", "2021-03-02T10:00:00Z")),
+            (
+                "/dir",
+                FileItemBuilder::dir()
+                    .size("0")
+                    .timestamps("2021-03-01T10:00:00Z")
+                    .build(),
+            ),
+            (
+                "/dir/d.txt",
+                file("text/plain", "1", "2021-03-01T10:00:00Z"),
+            ),
+            (
+                "/implied/e.txt",
+                file("text/plain", "1", "2021-03-01T10:00:00Z"),
+            ),
+        ])
+    }
+
+    fn names(result: &LsResult) -> Vec<&str> {
+        result
+            .entries
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_files_map_ls() -> Result<()> {
+        let files_map = files_map();
+
+        let result = files_map_ls(&files_map, 1, "/", &LsOptions::default())?;
+        assert_eq!(
+            names(&result),
+            ["a.png", "b.html", "c.jpg", "dir", "implied"]
+        );
+        assert_eq!(result.entries[0].path, "/a.png");
+        assert_eq!(result.entries[0].size, Some(20));
+        assert_eq!(result.entries[3].kind, FileKind::Dir);
+        assert_eq!(result.entries[4].kind, FileKind::Dir);
+        assert_eq!(result.entries[4].modified, None);
+        assert_eq!(result.next_cursor, None);
+
+        let result = files_map_ls(&files_map, 1, "/dir/", &LsOptions::default())?;
+        assert_eq!(names(&result), ["d.txt"]);
+        assert_eq!(result.entries[0].path, "/dir/d.txt");
+
+        let result = files_map_ls(&files_map, 1, "/b.html", &LsOptions::default())?;
+        assert_eq!(names(&result), ["b.html"]);
+
+        assert!(files_map_ls(&files_map, 1, "/missing", &LsOptions::default()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_map_ls_sort_and_filter() -> Result<()> {
+        let files_map = files_map();
+
+        let options = LsOptions {
+            sort_by: LsSortKey::Size,
+            descending: true,
+            media_types: vec!["image/*".to_string(), "text/html".to_string()],
+            ..LsOptions::default()
+        };
+        let result = files_map_ls(&files_map, 1, "", &options)?;
+        assert_eq!(names(&result), ["b.html", "a.png", "c.jpg"]);
+
+        let options = LsOptions {
+            sort_by: LsSortKey::Modified,
+            media_types: vec!["image/*".to_string()],
+            ..LsOptions::default()
+        };
+        let result = files_map_ls(&files_map, 1, "", &options)?;
+        assert_eq!(names(&result), ["c.jpg", "a.png"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_map_ls_pagination() -> Result<()> {
+        let files_map = files_map();
+
+        let mut options = LsOptions {
+            limit: Some(2),
+            ..LsOptions::default()
+        };
+        let mut pages = vec![];
+        loop {
+            let result = files_map_ls(&files_map, 3, "/", &options)?;
+            pages.push(names(&result).join(","));
+            match result.next_cursor {
+                Some(cursor) => options.cursor = Some(cursor),
+                None => break,
+            }
+        }
+        assert_eq!(pages, ["a.png,b.html", "c.jpg,dir", "implied"]);
+
+        // cursors are only valid for the version they were generated for
+        options.cursor = Some("2:b.html".to_string());
+        assert!(files_map_ls(&files_map, 3, "/", &options).is_err());
+        assert_eq!(cursor_version("2:b.html")?, 2);
+        assert!(cursor_version("b.html").is_err());
+        Ok(())
+    }
+}
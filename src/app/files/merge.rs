@@ -0,0 +1,141 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{diff::files_map_diff, FilesMap, FilesMapDiff};
+use crate::{Error, Result};
+
+// Three-way merge of the changes made to a base FilesMap by two different writers. It fails
+// if both changed the same path differently, or if one of them removed a directory whose
+// content was changed by the other one.
+pub(crate) fn files_map_merge(
+    base_files_map: &FilesMap,
+    our_files_map: &FilesMap,
+    their_files_map: &FilesMap,
+) -> Result<FilesMap> {
+    let our_changes = files_map_diff(base_files_map, our_files_map);
+    let their_changes = files_map_diff(base_files_map, their_files_map);
+
+    let mut conflicts: Vec<&str> = our_changes
+        .iter()
+        .filter(|(path, our_change)| match their_changes.get(*path) {
+            Some(their_change) => our_change.new != their_change.new,
+            None => is_removed_ancestor(path, &our_changes, &their_changes),
+        })
+        .map(|(path, _)| path.as_str())
+        .collect();
+    conflicts.extend(
+        their_changes
+            .keys()
+            .filter(|path| !our_changes.contains_key(*path))
+            .filter(|path| is_removed_ancestor(path, &their_changes, &our_changes))
+            .map(|path| path.as_str()),
+    );
+
+    if !conflicts.is_empty() {
+        return Err(Error::VersionConflict(format!(
+            "The FilesContainer was updated concurrently and the changes cannot be merged since these paths conflict: {}",
+            conflicts.join(", ")
+        )));
+    }
+
+    let mut merged_files_map = their_files_map.clone();
+    for (path, our_change) in our_changes.into_iter() {
+        match our_change.new {
+            Some(file_item) => {
+                merged_files_map.insert(path, file_item);
+            }
+            None => {
+                merged_files_map.remove(&path);
+            }
+        }
+    }
+
+    Ok(merged_files_map)
+}
+
+// Check if a path was removed by one of the writers whilst the other one changed its content
+fn is_removed_ancestor(path: &str, changes: &FilesMapDiff, other_changes: &FilesMapDiff) -> bool {
+    let is_removed = changes
+        .get(path)
+        .map_or(false, |change| change.new.is_none());
+    let prefix = format!("{}/", path.trim_end_matches('/'));
+    is_removed && other_changes.keys().any(|other| other.starts_with(&prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{
+        consts::*,
+        files::FileItem,
+        test_helpers::{self, FileItemBuilder},
+    };
+
+    fn file_item(link: &str) -> FileItem {
+        FileItemBuilder::file("text/plain", link).build()
+    }
+
+    fn base_files_map() -> FilesMap {
+        test_helpers::files_map(vec![
+            ("/a.txt", file_item("safe://a")),
+            ("/b.txt", file_item("safe://b")),
+            ("/dir", FileItemBuilder::dir().build()),
+            ("/dir/c.txt", file_item("safe://c")),
+        ])
+    }
+
+    #[test]
+    fn test_files_map_merge() -> Result<()> {
+        let base = base_files_map();
+
+        let mut ours = base.clone();
+        ours.insert("/a.txt".to_string(), file_item("safe://a2"));
+        ours.insert("/new.txt".to_string(), file_item("safe://new"));
+        ours.remove("/b.txt");
+
+        let mut theirs = base.clone();
+        theirs.insert("/dir/d.txt".to_string(), file_item("safe://d"));
+        theirs.remove("/b.txt");
+
+        let merged = files_map_merge(&base, &ours, &theirs)?;
+        assert_eq!(merged.len(), 5);
+        assert_eq!(merged["/a.txt"][PREDICATE_LINK], "safe://a2");
+        assert_eq!(merged["/new.txt"][PREDICATE_LINK], "safe://new");
+        assert_eq!(merged["/dir/d.txt"][PREDICATE_LINK], "safe://d");
+        assert!(!merged.contains_key("/b.txt"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_map_merge_conflicts() {
+        let base = base_files_map();
+
+        // the same path changed differently
+        let mut ours = base.clone();
+        ours.insert("/a.txt".to_string(), file_item("safe://ours"));
+        let mut theirs = base.clone();
+        theirs.insert("/a.txt".to_string(), file_item("safe://theirs"));
+        match files_map_merge(&base, &ours, &theirs) {
+            Err(Error::VersionConflict(msg)) => assert!(msg.contains("/a.txt")),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        // a directory removed whilst its content changed
+        let mut ours = base.clone();
+        ours.remove("/dir");
+        ours.remove("/dir/c.txt");
+        let mut theirs = base.clone();
+        theirs.insert("/dir/d.txt".to_string(), file_item("safe://d"));
+        match files_map_merge(&base, &ours, &theirs) {
+            Err(Error::VersionConflict(msg)) => assert!(msg.contains("/dir")),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        assert!(files_map_merge(&base, &theirs, &ours).is_err());
+    }
+}
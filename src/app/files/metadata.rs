@@ -7,7 +7,7 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use super::files_map::FileItem;
+use super::files_map::{FileItem, GetAttr};
 use crate::{
     app::{
         consts::*,
@@ -79,15 +79,13 @@ impl FileMeta {
         Ok(s)
     }
 
-    // Instantiates FileMeta from a FileItem
-    pub(crate) fn from_file_item(file_item: &FileItem) -> Self {
-        // The first 4 must be present, else a crash.
-        // lots of other code relies on this, so big refactor
-        // would be needed to change it.
-        let created = file_item[PREDICATE_CREATED].to_string();
-        let modified = file_item[PREDICATE_MODIFIED].to_string();
-        let file_size = file_item[PREDICATE_SIZE].to_string();
-        let file_type = file_item[PREDICATE_TYPE].to_string();
+    // Instantiates FileMeta from a FileItem, failing if it's missing any of the
+    // created, modified, size and type properties.
+    pub(crate) fn from_file_item(file_item: &FileItem) -> Result<Self> {
+        let created = file_item.getattr(PREDICATE_CREATED)?.to_string();
+        let modified = file_item.getattr(PREDICATE_MODIFIED)?.to_string();
+        let file_size = file_item.getattr(PREDICATE_SIZE)?.to_string();
+        let file_type = file_item.getattr(PREDICATE_TYPE)?.to_string();
 
         // These are all Option<String>
         let original_created = file_item
@@ -99,7 +97,7 @@ impl FileMeta {
         let readonly = file_item.get(PREDICATE_READONLY).map(ToOwned::to_owned);
         let mode_bits = file_item.get(PREDICATE_MODE_BITS).map(ToOwned::to_owned);
 
-        Self {
+        Ok(Self {
             created,
             modified,
             file_size,
@@ -108,7 +106,7 @@ impl FileMeta {
             mode_bits,
             original_created,
             original_modified,
-        }
+        })
     }
 
     // Instantiates FileMeta from just type and size properties.
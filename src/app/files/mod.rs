@@ -7,25 +7,72 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
+mod checksum;
+mod custom_metadata;
+mod diff;
+mod download;
 mod file_system;
 mod files_map;
+mod filter;
+mod history;
+mod linked_data;
+mod ls;
+mod merge;
 mod metadata;
+mod progress;
 mod realpath;
+mod relocate;
+mod remove;
+mod search;
+mod shards;
+mod streaming;
+mod sync;
+mod two_way;
 
 use crate::{
-    app::consts::*, fetch::Range, Error, Result, Safe, SafeContentType, SafeDataType, SafeUrl,
-    XorUrl,
+    app::{cancellation::is_cancelled, consts::*},
+    fetch::{Range, SafeData},
+    Error, Result, Safe, SafeContentType, SafeDataType, SafeUrl, XorUrl,
+};
+use checksum::{data_checksum, files_map_verify};
+use custom_metadata::{files_map_get_metadata, files_map_set_metadata, preserve_custom_metadata};
+use file_system::{
+    file_system_dir_list, file_system_dir_walk, file_system_single_file, normalise_path_separator,
+    UploadedChecksums,
 };
-use file_system::{file_system_dir_walk, file_system_single_file, normalise_path_separator};
 use files_map::add_or_update_file_item;
+use filter::PathFilter;
+use futures::stream::{self, StreamExt};
 use log::{debug, info, warn};
+use ls::{cursor_version, files_map_ls};
+use merge::files_map_merge;
+use progress::notify_progress;
 use relative_path::RelativePath;
-use std::{collections::BTreeMap, fs, path::Path};
-
+use remove::files_map_remove_paths;
+use search::files_map_search;
+use std::{collections::BTreeMap, path::Path};
+use sync::{prepare_local_file, PreparedFile, SyncState};
+use tokio::io::{AsyncRead, AsyncWrite};
+use two_way::files_map_two_way_sync;
+
+pub(crate) use files_map::{SYMLINK_TARGET, SYMLINK_TARGET_TYPE};
 pub(crate) use metadata::FileMeta;
 pub(crate) use realpath::RealPath;
+pub(crate) use shards::PartialFilesMap;
 
-pub use files_map::{FileItem, FilesMap, GetAttr};
+pub use checksum::{FileIntegrity, FilesIntegrity};
+pub use diff::{FileDiff, FileDiffKind, FilesMapDiff};
+pub use files_map::{
+    FileEntry, FileItem, FileKind, FilesMap, GetAttr, SymlinkInfo, SymlinkTargetType,
+};
+pub use filter::{FilesFilter, SAFE_IGNORE_FILENAME};
+pub use history::FilesContainerVersion;
+pub use linked_data::{files_map_from_rdf, files_map_to_rdf};
+pub use ls::{LsEntry, LsOptions, LsResult, LsSortKey};
+pub use progress::{FilesProgress, ProgressObserver};
+pub use search::FilesQuery;
+pub use sync::{ChangeDetection, SyncOptions};
+pub use two_way::{SyncConflictResolution, TwoWaySyncResult};
 
 // List of files uploaded with details if they were added, updated or deleted from FilesContainer
 pub type ProcessedFiles = BTreeMap<String, (String, String)>;
@@ -37,6 +84,7 @@ const ERROR_MSG_NO_FILES_CONTAINER_FOUND: &str = "No FilesContainer found at thi
 
 impl Safe {
     /// # Create a FilesContainer.
+    /// Any `.safeignore` file found in the location is honoured, see `FilesFilter`.
     ///
     /// ## Example
     ///
@@ -57,14 +105,87 @@ impl Safe {
         follow_links: bool,
         dry_run: bool,
     ) -> Result<(XorUrl, ProcessedFiles, FilesMap)> {
+        self.files_container_create_with_options(
+            location,
+            dest,
+            recursive,
+            follow_links,
+            &SyncOptions::default(),
+            dry_run,
+        )
+        .await
+    }
+
+    /// # Create a FilesContainer using the options provided.
+    /// Only the local files allowed by the filter are uploaded, up to `concurrency` files at a
+    /// time. If a sync state cache is set, the XOR-URLs of the uploaded files are cached so
+    /// subsequent syncs of the location don't need to hash them again.
+    /// If the operation is cancelled no FilesContainer is created, an empty XOR-URL is returned
+    /// along with the list of the files uploaded until then.
+    /// The change detection, expected version and merge options don't apply to this operation,
+    /// and setting any of them is an error.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::{files::{FilesFilter, SyncOptions}, Safe};
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let options = SyncOptions {
+    ///         filter: FilesFilter::default().exclude("subfolder"),
+    ///         concurrency: 4,
+    ///         ..SyncOptions::default()
+    ///     };
+    ///     let (xorurl, _processed_files, files_map) = safe.files_container_create_with_options(Some("../testdata/"), None, true, true, &options, false).await.unwrap();
+    ///     assert!(files_map.keys().all(|path| !path.starts_with("/subfolder")));
+    /// # });
+    /// ```
+    pub async fn files_container_create_with_options(
+        &mut self,
+        location: Option<&str>,
+        dest: Option<&str>,
+        recursive: bool,
+        follow_links: bool,
+        options: &SyncOptions,
+        dry_run: bool,
+    ) -> Result<(XorUrl, ProcessedFiles, FilesMap)> {
+        options.check_supported(
+            "creating a FilesContainer",
+            &["filter", "sync_state", "concurrency", "progress", "cancel"],
+        )?;
+
         // TODO: Enable source for funds / ownership
         // Warn about ownership?
 
         // Let's upload the files and generate the list of local files paths
         let (processed_files, files_map) = match location {
             Some(path) => {
-                let mut processed_files =
-                    file_system_dir_walk(self, path, recursive, follow_links, dry_run).await?;
+                let mut path_filter = PathFilter::new(&options.filter, Path::new(path))?;
+                let (mut processed_files, checksums) = file_system_dir_walk(
+                    self,
+                    path,
+                    recursive,
+                    follow_links,
+                    &mut path_filter,
+                    options,
+                    dry_run,
+                )
+                .await?;
+
+                if let (Some(sync_state_path), false) = (&options.sync_state, dry_run) {
+                    let sync_state = SyncState::load(sync_state_path);
+                    sync_state.record_uploaded(&processed_files);
+                    sync_state.save(sync_state_path)?;
+                }
+
+                if is_cancelled(options.cancel.as_ref()) {
+                    return Ok((
+                        String::default(),
+                        uploaded_files(processed_files),
+                        FilesMap::default(),
+                    ));
+                }
 
                 // The FilesContainer is stored on a Sequence
                 // and the link to the serialised FilesMap as the entry's value
@@ -72,6 +193,7 @@ impl Safe {
                 let files_map = files_map_create(
                     self,
                     &mut processed_files,
+                    &checksums,
                     path,
                     dest,
                     follow_links,
@@ -87,7 +209,7 @@ impl Safe {
             "".to_string()
         } else {
             // Store the serialised FilesMap in a Public Blob
-            let files_map_xorurl = self.store_files_map(&files_map).await?;
+            let files_map_xorurl = self.store_files_map(&files_map, None).await?;
 
             // Store the FilesContainer in a Public Sequence, putting the
             // serialised FilesMap XOR-URL as the first entry value
@@ -111,6 +233,15 @@ impl Safe {
             )?
         };
 
+        if !dry_run {
+            notify_progress(options.progress.as_ref(), || {
+                FilesProgress::VersionCommitted {
+                    version: 0,
+                    url: xorurl.clone(),
+                }
+            });
+        }
+
         Ok((xorurl, processed_files, files_map))
     }
 
@@ -136,11 +267,262 @@ impl Safe {
         self.fetch_files_container(&safe_url).await
     }
 
+    /// # List the entries of a directory of a FilesContainer.
+    /// Only the direct children of the path in the URL are listed, after resolving any symlink
+    /// in it, or just the file itself if it's not a directory. The entries can be sorted,
+    /// filtered by their media type, and paginated, see `LsOptions`.
+    ///
+    /// All the pages are taken from the same version of the FilesContainer, which is the
+    /// version in the URL if any, or the latest one when the first page is listed.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::{files::LsOptions, Safe};
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, true, true, false).await.unwrap();
+    ///     let mut options = LsOptions { limit: Some(10), ..LsOptions::default() };
+    ///     loop {
+    ///         let result = safe.files_container_ls(&xorurl, &options).await.unwrap();
+    ///         for entry in result.entries.iter() {
+    ///             println!("{} ({}) {:?}", entry.name, entry.media_type(), entry.size);
+    ///         }
+    ///         match result.next_cursor {
+    ///             Some(cursor) => options.cursor = Some(cursor),
+    ///             None => break,
+    ///         }
+    ///     }
+    /// # });
+    /// ```
+    pub async fn files_container_ls(&mut self, url: &str, options: &LsOptions) -> Result<LsResult> {
+        let (mut safe_url, _) = self.parse_and_resolve_url(url).await?;
+        if let Some(cursor) = &options.cursor {
+            let version = cursor_version(cursor)?;
+            match safe_url.content_version() {
+                Some(url_version) if url_version != version => {
+                    return Err(Error::InvalidInput(format!(
+                        "The cursor provided is for version {} of the FilesContainer rather than the version {} in the URL",
+                        version, url_version
+                    )))
+                }
+                _ => safe_url.set_content_version(Some(version)),
+            }
+        }
+
+        // Only the shards of the FilesMap the path resolves through, and those of the
+        // subtree listed, are loaded
+        let path = safe_url.path_decoded()?;
+        let (version, mut partial_files_map) =
+            self.fetch_files_container_partial(&safe_url).await?;
+        let dir_path = match path.trim_end_matches('/') {
+            "" => String::default(),
+            path => partial_files_map.resolve_realpath(self, path).await?,
+        };
+        partial_files_map.load_subtree(self, &dir_path).await?;
+        files_map_ls(partial_files_map.files_map(), version, &path, options)
+    }
+
+    /// # Search the entries of a FilesContainer matching a query.
+    /// If the URL has a path only the entries found at, or within, such path are searched.
+    /// The URL can target a specific version of the FilesContainer, which is returned along
+    /// with the matching entries keyed by their paths.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::{files::FilesQuery, Safe};
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, true, true, false).await.unwrap();
+    ///     let query = FilesQuery::All(vec![
+    ///         FilesQuery::PathGlob("*.md".to_string()),
+    ///         FilesQuery::SizeAtLeast(10),
+    ///     ]);
+    ///     let (version, found) = safe.files_container_search(&xorurl, &query).await.unwrap();
+    ///     for (path, entry) in found.iter() {
+    ///         println!("Found \"{}\" ({:?} bytes) in version {}", path, entry.size, version);
+    ///     }
+    /// # });
+    /// ```
+    pub async fn files_container_search(
+        &mut self,
+        url: &str,
+        query: &FilesQuery,
+    ) -> Result<(u64, BTreeMap<String, FileEntry>)> {
+        let (safe_url, _) = self.parse_and_resolve_url(url).await?;
+        let path = safe_url.path_decoded()?;
+        let (version, mut partial_files_map) =
+            self.fetch_files_container_partial(&safe_url).await?;
+        partial_files_map.load_subtree(self, &path).await?;
+
+        let found = files_map_search(partial_files_map.files_map(), &path, query)?;
+        Ok((version, found))
+    }
+
+    /// # Compare two versions of a FilesContainer.
+    /// Each version can be either a specific version number or `None` to use the latest one.
+    /// It returns the versions which were compared along with the changes found for each path
+    /// when going from the first to the second version.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, true, false, false).await.unwrap();
+    ///     let (from_version, to_version, diff) = safe.files_container_diff(&xorurl, Some(0), None).await.unwrap();
+    ///     for (path, change) in diff.iter() {
+    ///         println!("{} {} ({:?})", change.sign(), path, change.kind);
+    ///     }
+    /// # });
+    /// ```
+    pub async fn files_container_diff(
+        &self,
+        url: &str,
+        from_version: Option<u64>,
+        to_version: Option<u64>,
+    ) -> Result<(u64, u64, FilesMapDiff)> {
+        let (mut safe_url, _) = self.parse_and_resolve_url(url).await?;
+        if safe_url.content_type() != SafeContentType::FilesContainer {
+            return Err(Error::InvalidInput(format!(
+                "The URL provided doesn't target a FilesContainer but a '{}'",
+                safe_url.content_type()
+            )));
+        }
+
+        safe_url.set_content_version(from_version);
+        let (from_version, from_files_map) = self.fetch_files_container(&safe_url).await?;
+
+        safe_url.set_content_version(to_version);
+        let (to_version, to_files_map) = self.fetch_files_container(&safe_url).await?;
+
+        let diff = diff::files_map_diff(&from_files_map, &to_files_map);
+
+        Ok((from_version, to_version, diff))
+    }
+
+    /// # List the versions of a FilesContainer.
+    /// Versions are listed in ascending order starting from `start_version`, and up to `limit`
+    /// of them are returned if a limit is provided. It also returns the latest version of the
+    /// FilesContainer so the caller can tell if there are more versions to be listed.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, true, false, false).await.unwrap();
+    ///     let (latest_version, history) = safe.files_container_history(&xorurl, 0, Some(10)).await.unwrap();
+    ///     println!("FilesContainer is at version: {}", latest_version);
+    ///     for v in history.iter() {
+    ///         println!("v{}: {} entries, {} bytes", v.version, v.entries, v.total_size);
+    ///     }
+    /// # });
+    /// ```
+    pub async fn files_container_history(
+        &self,
+        url: &str,
+        start_version: u64,
+        limit: Option<u64>,
+    ) -> Result<(u64, Vec<FilesContainerVersion>)> {
+        let (mut safe_url, _) = self.parse_and_resolve_url(url).await?;
+        if safe_url.content_type() != SafeContentType::FilesContainer {
+            return Err(Error::InvalidInput(format!(
+                "The URL provided doesn't target a FilesContainer but a '{}'",
+                safe_url.content_type()
+            )));
+        }
+
+        // We list the versions regardless of the version the URL may be targeting
+        safe_url.set_content_version(None);
+        let (latest_version, latest_xorurl, latest_files_map) =
+            self.fetch_files_container_version(&safe_url).await?;
+
+        let latest_xorurl = match latest_xorurl {
+            Some(xorurl) => xorurl,
+            None => return Ok((latest_version, vec![])),
+        };
+
+        let end_version = match limit {
+            Some(limit) => std::cmp::min(start_version.saturating_add(limit), latest_version + 1),
+            None => latest_version + 1,
+        };
+
+        let mut history = vec![];
+        for version in start_version..end_version {
+            let summary = if version == latest_version {
+                FilesContainerVersion::new(version, latest_xorurl.clone(), &latest_files_map)
+            } else {
+                safe_url.set_content_version(Some(version));
+                let (_, files_map_xorurl, files_map) =
+                    self.fetch_files_container_version(&safe_url).await?;
+                FilesContainerVersion::new(
+                    version,
+                    files_map_xorurl.unwrap_or_default(),
+                    &files_map,
+                )
+            };
+            history.push(summary);
+        }
+
+        Ok((latest_version, history))
+    }
+
     /// Fetch a FilesContainer from a SafeUrl without performing any type of URL resolution
     pub(crate) async fn fetch_files_container(
         &self,
         safe_url: &SafeUrl,
     ) -> Result<(u64, FilesMap)> {
+        self.fetch_files_container_version(safe_url)
+            .await
+            .map(|(version, _, files_map)| (version, files_map))
+    }
+
+    /// Fetch a FilesContainer from a SafeUrl without performing any type of URL resolution,
+    /// loading only the root shard of the FilesMap, the rest are loaded as they are needed
+    pub(crate) async fn fetch_files_container_partial(
+        &self,
+        safe_url: &SafeUrl,
+    ) -> Result<(u64, PartialFilesMap)> {
+        match self.fetch_files_map_link(safe_url).await? {
+            (version, Some(files_map_xorurl)) => {
+                let partial_files_map = PartialFilesMap::fetch(self, &files_map_xorurl).await?;
+                Ok((version, partial_files_map))
+            }
+            (version, None) => Ok((version, PartialFilesMap::default())),
+        }
+    }
+
+    // Fetch a version of a FilesContainer along with the XOR-URL of the FilesMap it links to,
+    // the XOR-URL is None only if the FilesContainer is empty.
+    async fn fetch_files_container_version(
+        &self,
+        safe_url: &SafeUrl,
+    ) -> Result<(u64, Option<XorUrl>, FilesMap)> {
+        match self.fetch_files_map_link(safe_url).await? {
+            (version, Some(files_map_xorurl)) => {
+                // Using the FilesMap XOR-URL we can now fetch the FilesMap, and all its shards
+                let files_map = PartialFilesMap::fetch(self, &files_map_xorurl)
+                    .await?
+                    .load_all(self)
+                    .await?;
+
+                Ok((version, Some(files_map_xorurl), files_map))
+            }
+            (version, None) => Ok((version, None, FilesMap::default())),
+        }
+    }
+
+    // Fetch the XOR-URL of the FilesMap a version of a FilesContainer links to,
+    // which is None only if the FilesContainer is empty.
+    async fn fetch_files_map_link(&self, safe_url: &SafeUrl) -> Result<(u64, Option<XorUrl>)> {
         // Check if the URL specifies a specific version of the content or simply the latest available
         match self.fetch_sequence(safe_url).await {
             Ok((version, serialised_files_map)) => {
@@ -156,21 +538,11 @@ impl Safe {
                     })?,
                 )?;
 
-                // Using the FilesMap XOR-URL we can now fetch the FilesMap and deserialise it
-                let serialised_files_map = self.fetch_public_blob(&files_map_xorurl, None).await?;
-                let files_map =
-                    serde_json::from_slice(serialised_files_map.as_slice()).map_err(|err| {
-                        Error::ContentError(format!(
-                            "Couldn't deserialise the FilesMap stored in the FilesContainer: {:?}",
-                            err
-                        ))
-                    })?;
-
-                Ok((version, files_map))
+                Ok((version, Some(files_map_xorurl.to_string())))
             }
             Err(Error::EmptyContent(_)) => {
                 warn!("FilesContainer found at \"{:?}\" was empty", safe_url);
-                Ok((0, FilesMap::default()))
+                Ok((0, None))
             }
             Err(Error::ContentNotFound(_)) => Err(Error::ContentNotFound(
                 ERROR_MSG_NO_FILES_CONTAINER_FOUND.to_string(),
@@ -187,7 +559,66 @@ impl Safe {
         }
     }
 
+    /// # Download the content of a FilesContainer onto a local folder.
+    /// The URL can target a specific version of the FilesContainer, as well as a path within it.
+    /// If the path is a folder, its content is written onto the `dest` folder, whilst if it's a
+    /// file or symlink it's written into the `dest` folder. Directories, files and symlinks are
+    /// recreated, restoring their permissions and original modification time where the
+    /// platform allows.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, true, false, false).await.unwrap();
+    ///     let (version, processed_files) = safe.files_container_download(&xorurl, "../testdata_download", false, false).await.unwrap();
+    ///     println!("FilesContainer downloaded was at version: {}", version);
+    ///     println!("The local files that were written are: {:?}", processed_files);
+    /// # });
+    /// ```
+    pub async fn files_container_download(
+        &self,
+        url: &str,
+        dest: &str,
+        overwrite: bool,
+        dry_run: bool,
+    ) -> Result<(u64, ProcessedFiles)> {
+        let (safe_url, _) = self.parse_and_resolve_url(url).await?;
+        if safe_url.content_type() != SafeContentType::FilesContainer {
+            return Err(Error::InvalidInput(format!(
+                "The URL provided doesn't target a FilesContainer but a '{}'",
+                safe_url.content_type()
+            )));
+        }
+
+        let (version, files_map) = self.fetch_files_container(&safe_url).await?;
+
+        let path = safe_url.path_decoded()?;
+        let src_path = if path.is_empty() || path == "/" {
+            String::default()
+        } else {
+            files_map.realpath(&path)?
+        };
+
+        let processed_files = download::files_map_download(
+            self,
+            &files_map,
+            &src_path,
+            Path::new(dest),
+            overwrite,
+            dry_run,
+        )
+        .await?;
+
+        Ok((version, processed_files))
+    }
+
     /// # Sync up local folder with the content on a FilesContainer.
+    /// The default `SyncOptions` are used, thus any `.safeignore` file found in the
+    /// location is honoured, and the content of all local files is compared.
     ///
     /// ## Example
     ///
@@ -213,6 +644,63 @@ impl Safe {
         delete: bool,
         update_nrs: bool,
         dry_run: bool,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        self.files_container_sync_with_options(
+            location,
+            url,
+            recursive,
+            follow_links,
+            delete,
+            update_nrs,
+            &SyncOptions::default(),
+            dry_run,
+        )
+        .await
+    }
+
+    /// # Sync up local folder with the content on a FilesContainer using the options provided.
+    /// Only the local files allowed by the filter are synced, and when `delete` is set, files in
+    /// the FilesContainer which are ignored by the filter are kept rather than deleted.
+    /// The cost of detecting changes in large folders can be reduced by comparing the local
+    /// files' metadata first, and by caching the XOR-URLs of the local files between syncs.
+    /// If the operation is cancelled no new version is appended to the FilesContainer, nor to the
+    /// NRS Map Container, and the current version is returned along with the current FilesMap
+    /// and the list of the files uploaded until then.
+    /// An expected version can be set so the changes of concurrent writers are never overwritten,
+    /// in which case the operation either fails with a `VersionConflict` error, or the changes are
+    /// merged with theirs if the merge mode is enabled and they don't change the same paths.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::{files::{ChangeDetection, FilesFilter, SyncOptions}, Safe};
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, true, false, false).await.unwrap();
+    ///     let options = SyncOptions {
+    ///         filter: FilesFilter::default().include("*.md"),
+    ///         change_detection: ChangeDetection::Metadata,
+    ///         sync_state: Some(std::env::temp_dir().join("testdata.syncstate")),
+    ///         ..SyncOptions::default()
+    ///     };
+    ///     let (version, new_processed_files, new_files_map) = safe.files_container_sync_with_options("../testdata", &xorurl, true, true, true, false, &options, false).await.unwrap();
+    ///     println!("FilesContainer synced up is at version: {}", version);
+    ///     println!("The local files that were synced up are: {:?}", new_processed_files);
+    ///     println!("The FilesMap of the updated FilesContainer now is: {:?}", new_files_map);
+    /// # });
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub async fn files_container_sync_with_options(
+        &mut self,
+        location: &str,
+        url: &str,
+        recursive: bool,
+        follow_links: bool,
+        delete: bool,
+        update_nrs: bool,
+        options: &SyncOptions,
+        dry_run: bool,
     ) -> Result<(u64, ProcessedFiles, FilesMap)> {
         if delete && !recursive {
             return Err(Error::InvalidInput(
@@ -220,51 +708,68 @@ impl Safe {
             ));
         }
 
-        let safe_url = Safe::parse_url(url)?;
-        if safe_url.content_version().is_some() {
-            return Err(Error::InvalidInput(format!(
-                "The target URL cannot contain a version: {}",
-                url
-            )));
-        };
-
-        // If NRS name shall be updated then the URL has to be an NRS-URL
-        if update_nrs && safe_url.content_type() != SafeContentType::NrsMapContainer {
-            return Err(Error::InvalidInput(
-                "'update-nrs' is not allowed since the URL provided is not an NRS URL".to_string(),
-            ));
-        }
-
-        let (mut safe_url, _) = self.parse_and_resolve_url(url).await?;
-
-        // If the FilesContainer URL was resolved from an NRS name we need to remove
-        // the version from it so we can fetch latest version of it for sync-ing
-        safe_url.set_content_version(None);
+        let (_, safe_url) = self.resolve_files_container_target(url, update_nrs).await?;
 
         let (current_version, current_files_map): (u64, FilesMap) =
-            self.fetch_files_container(&safe_url).await?;
+            self.fetch_files_container_base(&safe_url, options).await?;
 
         // Let's generate the list of local files paths, without uploading any new file yet
-        let processed_files =
-            file_system_dir_walk(self, location, recursive, follow_links, true).await?;
+        let mut path_filter = PathFilter::new(&options.filter, Path::new(location))?;
+        let (processed_files, _) = file_system_dir_list(
+            location,
+            recursive,
+            follow_links,
+            &mut path_filter,
+            options.progress.as_ref(),
+        )?;
 
         let dest_path = Some(safe_url.path());
 
+        let sync_state = options.sync_state.as_deref().map(SyncState::load);
+
         let (processed_files, new_files_map, success_count): (ProcessedFiles, FilesMap, u64) =
             files_map_sync(
                 self,
-                current_files_map,
+                current_files_map.clone(),
                 location,
                 processed_files,
                 dest_path,
                 delete,
+                Some(&path_filter),
                 dry_run,
                 false,
                 true,
+                options,
+                sync_state.as_ref(),
                 follow_links,
             )
             .await?;
 
+        // The hashes cached are valid even if the operation was cancelled
+        if let (Some(sync_state), Some(path)) = (sync_state, &options.sync_state) {
+            if !dry_run {
+                sync_state.save(path)?;
+            }
+        }
+
+        if is_cancelled(options.cancel.as_ref()) {
+            return Ok((
+                current_version,
+                uploaded_files(processed_files),
+                current_files_map,
+            ));
+        }
+
+        let (current_version, new_files_map) = self
+            .reconcile_files_map(
+                &safe_url,
+                current_version,
+                &current_files_map,
+                new_files_map,
+                options,
+            )
+            .await?;
+
         let version = self
             .append_version_to_files_container(
                 success_count,
@@ -274,66 +779,306 @@ impl Safe {
                 safe_url,
                 dry_run,
                 update_nrs,
+                options.checks_version(),
             )
             .await?;
 
+        if success_count > 0 && !dry_run {
+            notify_progress(options.progress.as_ref(), || {
+                FilesProgress::VersionCommitted {
+                    version,
+                    url: url.to_string(),
+                }
+            });
+        }
+
         Ok((version, processed_files, new_files_map))
     }
 
-    /// # Add a file, either a local path or an already uploaded file, on an existing FilesContainer.
+    /// # Sync a local folder and a FilesContainer both ways.
+    /// The content of the local folder is reconciled with the content found at the path of the
+    /// FilesContainer the URL targets, using the version of the FilesContainer they were last
+    /// synced at as the common base: the changes only made remotely since then are written
+    /// onto the local folder, and the changes only made locally are pushed as a new version of
+    /// the FilesContainer. The paths changed on both ends are reported as conflicts, and
+    /// resolved as requested. The version returned is to be used as the base of the next sync.
+    /// The base version takes the place of the expected version option, thus setting the
+    /// latter is an error.
     ///
     /// ## Example
     ///
     /// ```rust
-    /// # use sn_api::Safe;
+    /// # use sn_api::{files::{SyncConflictResolution, SyncOptions}, Safe};
     /// # let mut safe = Safe::default();
     /// # async_std::task::block_on(async {
     /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
-    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, true, true, false).await.unwrap();
-    ///     let new_file_name = format!("{}/new_name_test.md", xorurl);
-    ///     let (version, new_processed_files, new_files_map) = safe.files_container_add("../testdata/test.md", &new_file_name, false, false, true, false).await.unwrap();
-    ///     println!("FilesContainer is now at version: {}", version);
-    ///     println!("The local files that were synced up are: {:?}", new_processed_files);
-    ///     println!("The FilesMap of the updated FilesContainer now is: {:?}", new_files_map);
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, true, false, false).await.unwrap();
+    ///     let result = safe.files_container_sync_two_way("../testdata", &xorurl, 0, false, SyncConflictResolution::KeepBoth, &SyncOptions::default(), false).await.unwrap();
+    ///     println!("FilesContainer synced up is at version: {}", result.version);
+    ///     println!("The local files updated are: {:?}", result.pulled);
+    ///     println!("The local changes pushed are: {:?}", result.pushed);
+    ///     println!("The paths in conflict are: {:?}", result.conflicts);
     /// # });
     /// ```
-    pub async fn files_container_add(
+    #[allow(clippy::too_many_arguments)]
+    pub async fn files_container_sync_two_way(
         &mut self,
-        source_file: &str,
+        location: &str,
         url: &str,
-        force: bool,
+        base_version: u64,
         update_nrs: bool,
-        follow_links: bool,
+        conflict_resolution: SyncConflictResolution,
+        options: &SyncOptions,
         dry_run: bool,
-    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+    ) -> Result<TwoWaySyncResult> {
+        options.check_supported(
+            "syncing both ways",
+            &[
+                "filter",
+                "change_detection",
+                "sync_state",
+                "concurrency",
+                "progress",
+                "cancel",
+                "merge",
+            ],
+        )?;
+
+        let (_, safe_url) = self.resolve_files_container_target(url, update_nrs).await?;
+
+        let local_path = Path::new(location);
+        if !local_path.is_dir() {
+            return Err(Error::InvalidInput(format!(
+                "'{}' is not a directory. Only folders can be synced both ways.",
+                location
+            )));
+        }
+
+        let (current_version, current_files_map) = self.fetch_files_container(&safe_url).await?;
+        if base_version > current_version {
+            return Err(Error::VersionNotFound(format!(
+                "Base version '{}' is invalid, the latest version of the FilesContainer is '{}'",
+                base_version, current_version
+            )));
+        }
+
+        let mut base_url = safe_url.clone();
+        base_url.set_content_version(Some(base_version));
+        let (_, base_files_map) = self.fetch_files_container(&base_url).await?;
+
+        let sync_state = options.sync_state.as_deref().map(SyncState::load);
+        let (mut result, success_count) = files_map_two_way_sync(
+            self,
+            local_path,
+            safe_url.path(),
+            &base_files_map,
+            current_version,
+            current_files_map.clone(),
+            conflict_resolution,
+            options,
+            sync_state.as_ref(),
+            dry_run,
+        )
+        .await?;
+
+        if let (Some(sync_state), Some(path)) = (sync_state, &options.sync_state) {
+            if !dry_run {
+                sync_state.save(path)?;
+            }
+        }
+
+        if is_cancelled(options.cancel.as_ref()) {
+            result.pushed = uploaded_files(result.pushed);
+            result.files_map = current_files_map;
+            return Ok(result);
+        }
+
+        // Make sure no changes made remotely during the sync are overwritten
+        let reconcile_options = SyncOptions {
+            expected_version: Some(current_version),
+            ..options.clone()
+        };
+        let (current_version, new_files_map) = self
+            .reconcile_files_map(
+                &safe_url,
+                current_version,
+                &current_files_map,
+                result.files_map,
+                &reconcile_options,
+            )
+            .await?;
+
+        result.version = self
+            .append_version_to_files_container(
+                success_count,
+                current_version,
+                &new_files_map,
+                url,
+                safe_url,
+                dry_run,
+                update_nrs,
+                reconcile_options.checks_version(),
+            )
+            .await?;
+        result.files_map = new_files_map;
+
+        if success_count > 0 && !dry_run {
+            notify_progress(options.progress.as_ref(), || {
+                FilesProgress::VersionCommitted {
+                    version: result.version,
+                    url: url.to_string(),
+                }
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// # Add a file, either a local path or an already uploaded file, on an existing FilesContainer.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, true, true, false).await.unwrap();
+    ///     let new_file_name = format!("{}/new_name_test.md", xorurl);
+    ///     let (version, new_processed_files, new_files_map) = safe.files_container_add("../testdata/test.md", &new_file_name, false, false, true, false).await.unwrap();
+    ///     println!("FilesContainer is now at version: {}", version);
+    ///     println!("The local files that were synced up are: {:?}", new_processed_files);
+    ///     println!("The FilesMap of the updated FilesContainer now is: {:?}", new_files_map);
+    /// # });
+    /// ```
+    pub async fn files_container_add(
+        &mut self,
+        source_file: &str,
+        url: &str,
+        force: bool,
+        update_nrs: bool,
+        follow_links: bool,
+        dry_run: bool,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        self.files_container_add_with_options(
+            source_file,
+            url,
+            force,
+            update_nrs,
+            follow_links,
+            &SyncOptions::default(),
+            dry_run,
+        )
+        .await
+    }
+
+    /// # Add a file, either a local path or an already uploaded file, on an existing FilesContainer
+    /// using the options provided. Only the filter, the progress observer, the cancellation token,
+    /// the expected version and the merge mode of the options apply to this operation, setting
+    /// any other option is an error. A local file ignored by the filter, or by the `.safeignore`
+    /// file of the folder it's in, is rejected.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::{files::{FilesProgress, ProgressObserver, SyncOptions}, Safe};
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata"), None, true, true, false).await.unwrap();
+    ///     let options = SyncOptions {
+    ///         progress: Some(ProgressObserver::new(|event: FilesProgress| println!("{:?}", event))),
+    ///         ..SyncOptions::default()
+    ///     };
+    ///     let new_file_name = format!("{}/new_name_test.md", xorurl);
+    ///     let (version, _new_processed_files, _new_files_map) = safe.files_container_add_with_options("../testdata/test.md", &new_file_name, false, false, true, &options, false).await.unwrap();
+    ///     println!("FilesContainer is now at version: {}", version);
+    /// # });
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub async fn files_container_add_with_options(
+        &mut self,
+        source_file: &str,
+        url: &str,
+        force: bool,
+        update_nrs: bool,
+        follow_links: bool,
+        options: &SyncOptions,
+        dry_run: bool,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        options.check_supported(
+            "adding a file",
+            &["filter", "progress", "cancel", "expected_version", "merge"],
+        )?;
         let (safe_url, current_version, current_files_map) =
-            validate_files_add_params(self, source_file, url, update_nrs).await?;
+            validate_files_add_params(self, source_file, url, update_nrs, options).await?;
 
         let dest_path = safe_url.path();
+        let progress = options.progress.as_ref();
 
         // Let's act according to if it's a local file path or a safe:// location
         let (processed_files, new_files_map, success_count) = if source_file.starts_with("safe://")
         {
-            files_map_add_link(self, current_files_map, source_file, dest_path, force).await?
+            files_map_add_link(
+                self,
+                current_files_map.clone(),
+                source_file,
+                dest_path,
+                force,
+            )
+            .await?
         } else {
+            if PathFilter::is_file_ignored(&options.filter, Path::new(source_file))? {
+                return Err(Error::InvalidInput(format!(
+                    "'{}' is ignored by the filter or a '{}' file, thus it cannot be added",
+                    source_file, SAFE_IGNORE_FILENAME
+                )));
+            }
+
             // Let's generate the list of local files paths, without uploading any new file yet
-            let processed_files = file_system_single_file(self, source_file, true).await?;
+            let processed_files =
+                file_system_single_file(self, source_file, progress, true).await?;
 
             files_map_sync(
                 self,
-                current_files_map,
+                current_files_map.clone(),
                 source_file,
                 processed_files,
                 Some(dest_path),
                 false,
+                None,
                 dry_run,
                 force,
                 false,
+                &SyncOptions {
+                    change_detection: ChangeDetection::Content,
+                    concurrency: 1,
+                    ..options.clone()
+                },
+                None,
                 follow_links,
             )
             .await?
         };
 
+        if is_cancelled(options.cancel.as_ref()) {
+            return Ok((
+                current_version,
+                uploaded_files(processed_files),
+                current_files_map,
+            ));
+        }
+
+        let (current_version, new_files_map) = self
+            .reconcile_files_map(
+                &safe_url,
+                current_version,
+                &current_files_map,
+                new_files_map,
+                options,
+            )
+            .await?;
+
         let version = self
             .append_version_to_files_container(
                 success_count,
@@ -343,9 +1088,17 @@ impl Safe {
                 safe_url,
                 dry_run,
                 update_nrs,
+                options.checks_version(),
             )
             .await?;
 
+        if success_count > 0 && !dry_run {
+            notify_progress(progress, || FilesProgress::VersionCommitted {
+                version,
+                url: url.to_string(),
+            });
+        }
+
         Ok((version, processed_files, new_files_map))
     }
 
@@ -375,14 +1128,23 @@ impl Safe {
         dry_run: bool,
     ) -> Result<(u64, ProcessedFiles, FilesMap)> {
         let (safe_url, current_version, current_files_map) =
-            validate_files_add_params(self, "", url, update_nrs).await?;
+            validate_files_add_params(self, "", url, update_nrs, &SyncOptions::default()).await?;
 
         let dest_path = safe_url.path();
         let new_file_xorurl = self.files_store_public_blob(data, None, false).await?;
 
         // Let's act according to if it's a local file path or a safe:// location
-        let (processed_files, new_files_map, success_count) =
+        let (processed_files, mut new_files_map, success_count) =
             files_map_add_link(self, current_files_map, &new_file_xorurl, dest_path, force).await?;
+
+        // Unlike when linking to existing content, the size and checksum are known
+        if let Some(file_item) = new_files_map.get_mut(dest_path) {
+            if file_item.get(PREDICATE_LINK) == Some(&new_file_xorurl) {
+                file_item.insert(PREDICATE_SIZE.to_string(), data.len().to_string());
+                file_item.insert(PREDICATE_CHECKSUM.to_string(), data_checksum(data));
+            }
+        }
+
         let version = self
             .append_version_to_files_container(
                 success_count,
@@ -392,6 +1154,7 @@ impl Safe {
                 safe_url,
                 dry_run,
                 update_nrs,
+                false,
             )
             .await?;
 
@@ -422,39 +1185,163 @@ impl Safe {
         update_nrs: bool,
         dry_run: bool,
     ) -> Result<(u64, ProcessedFiles, FilesMap)> {
-        let safe_url = Safe::parse_url(url)?;
-        if safe_url.content_version().is_some() {
-            return Err(Error::InvalidInput(format!(
-                "The target URL cannot contain a version: {}",
-                url
-            )));
-        };
+        self.files_container_remove_path_with_options(
+            url,
+            recursive,
+            update_nrs,
+            &SyncOptions::default(),
+            dry_run,
+        )
+        .await
+    }
 
-        let dest_path = safe_url.path();
-        if dest_path.is_empty() {
+    /// # Remove a file from an existing FilesContainer using the options provided.
+    /// Only the expected version and the merge mode of the options apply to this operation,
+    /// setting any other option is an error.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::{files::SyncOptions, Safe};
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let (xorurl, processed_files, files_map) = safe.files_container_create(Some("../testdata/"), None, true, true, false).await.unwrap();
+    ///     let options = SyncOptions {
+    ///         expected_version: Some(0),
+    ///         ..SyncOptions::default()
+    ///     };
+    ///     let remote_file_path = format!("{}/test.md", xorurl);
+    ///     let (version, _new_processed_files, _new_files_map) = safe.files_container_remove_path_with_options(&remote_file_path, false, false, &options, false).await.unwrap();
+    ///     assert_eq!(version, 1);
+    /// # });
+    /// ```
+    pub async fn files_container_remove_path_with_options(
+        &mut self,
+        url: &str,
+        recursive: bool,
+        update_nrs: bool,
+        options: &SyncOptions,
+        dry_run: bool,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        let safe_url = Safe::parse_url(url)?;
+        if safe_url.path().is_empty() {
             return Err(Error::InvalidInput(
                 "The destination URL should include a target file path".to_string(),
             ));
         }
 
-        // If NRS name shall be updated then the URL has to be an NRS-URL
-        if update_nrs && safe_url.content_type() != SafeContentType::NrsMapContainer {
+        // An empty path refers to the path of the URL itself
+        self.files_container_remove_paths_with_options(
+            url,
+            &[""],
+            recursive,
+            update_nrs,
+            options,
+            dry_run,
+        )
+        .await
+    }
+
+    /// # Remove several paths from an existing FilesContainer in a single new version.
+    /// The paths are relative to the path of the URL, if any, which is always taken literally,
+    /// and they can be glob patterns,
+    /// e.g. `/logs/**/*.tmp`, which remove every entry matching them. Directories matched by a
+    /// pattern are only removed, along with all their content, if `recursive` is set. Unlike
+    /// paths, it's not an error if a pattern doesn't match any entry. Characters with a special
+    /// meaning in patterns, like `*`, `?`, `[` or `{`, can be escaped with a `\`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, true, true, false).await.unwrap();
+    ///     let paths = ["/test.md", "/subfolder/*.txt"];
+    ///     let (version, processed_files, files_map) = safe.files_container_remove_paths(&xorurl, &paths, false, false, false).await.unwrap();
+    ///     println!("FilesContainer is now at version: {}", version);
+    ///     println!("The files that were removed: {:?}", processed_files);
+    ///     println!("The FilesMap of the updated FilesContainer now is: {:?}", files_map);
+    /// # });
+    /// ```
+    pub async fn files_container_remove_paths(
+        &mut self,
+        url: &str,
+        paths: &[&str],
+        recursive: bool,
+        update_nrs: bool,
+        dry_run: bool,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        self.files_container_remove_paths_with_options(
+            url,
+            paths,
+            recursive,
+            update_nrs,
+            &SyncOptions::default(),
+            dry_run,
+        )
+        .await
+    }
+
+    /// # Remove several paths from an existing FilesContainer using the options provided.
+    /// Only the expected version and the merge mode of the options apply to this operation,
+    /// setting any other option is an error.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::{files::SyncOptions, Safe};
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, true, true, false).await.unwrap();
+    ///     let options = SyncOptions {
+    ///         expected_version: Some(0),
+    ///         ..SyncOptions::default()
+    ///     };
+    ///     let paths = ["/test.md", "/subfolder/**"];
+    ///     let (version, _processed_files, _files_map) = safe.files_container_remove_paths_with_options(&xorurl, &paths, true, false, &options, false).await.unwrap();
+    ///     assert_eq!(version, 1);
+    /// # });
+    /// ```
+    pub async fn files_container_remove_paths_with_options(
+        &mut self,
+        url: &str,
+        paths: &[&str],
+        recursive: bool,
+        update_nrs: bool,
+        options: &SyncOptions,
+        dry_run: bool,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        options.check_supported("removing paths", &["expected_version", "merge"])?;
+
+        let (target_url, safe_url) = self.resolve_files_container_target(url, update_nrs).await?;
+
+        if paths.is_empty() {
             return Err(Error::InvalidInput(
-                "'update-nrs' is not allowed since the URL provided is not an NRS URL".to_string(),
+                "At least one path to remove shall be provided".to_string(),
             ));
         }
 
-        let (mut safe_url, _) = self.parse_and_resolve_url(url).await?;
-
-        // If the FilesContainer URL was resolved from an NRS name we need to remove
-        // the version from it so we can fetch latest version of it
-        safe_url.set_content_version(None);
+        let base_path = target_url.path_decoded()?;
 
         let (current_version, files_map): (u64, FilesMap) =
-            self.fetch_files_container(&safe_url).await?;
+            self.fetch_files_container_base(&safe_url, options).await?;
 
         let (processed_files, new_files_map, success_count) =
-            files_map_remove_path(dest_path, files_map, recursive)?;
+            files_map_remove_paths(&base_path, paths, files_map.clone(), recursive)?;
+
+        let (current_version, new_files_map) = self
+            .reconcile_files_map(
+                &safe_url,
+                current_version,
+                &files_map,
+                new_files_map,
+                options,
+            )
+            .await?;
 
         let version = self
             .append_version_to_files_container(
@@ -465,1262 +1352,2747 @@ impl Safe {
                 safe_url,
                 dry_run,
                 update_nrs,
+                options.checks_version(),
             )
             .await?;
 
         Ok((version, processed_files, new_files_map))
     }
 
-    // Private helper function to append new version of the FilesMap to the Files Container
-    // It flagged with `update_nrs`, it will also update the link in the corresponding NRS Map Container
-    #[allow(clippy::too_many_arguments)]
-    async fn append_version_to_files_container(
-        &mut self,
-        success_count: u64,
-        current_version: u64,
-        new_files_map: &FilesMap,
-        url: &str,
-        mut safe_url: SafeUrl,
-        dry_run: bool,
-        update_nrs: bool,
-    ) -> Result<u64> {
-        let version = if success_count == 0 {
-            current_version
-        } else if dry_run {
-            current_version + 1
-        } else {
-            // The FilesContainer is updated by adding an entry containing the link to
-            // the Blob with the serialised new version of the FilesMap.
-            let files_map_xorurl = self.store_files_map(new_files_map).await?;
-
-            let xorname = safe_url.xorname();
-            let type_tag = safe_url.type_tag();
-            self.safe_client
-                .append_to_sequence(files_map_xorurl.as_bytes(), xorname, type_tag, false)
-                .await?;
-
-            let new_version = current_version + 1;
-
-            if update_nrs {
-                // We need to update the link in the NRS container as well,
-                // to link it to the new new_version of the FilesContainer we just generated
-                safe_url.set_content_version(Some(new_version));
-                let new_link_for_nrs = safe_url.to_string();
-                let _ = self
-                    .nrs_map_container_add(url, &new_link_for_nrs, false, true, false)
-                    .await?;
-            }
-
-            new_version
-        };
-
-        Ok(version)
-    }
-
-    /// # Put a Public Blob
-    /// Put data blobs onto the network.
+    /// # Create a directory in a FilesContainer.
+    /// If `parents` is set any missing parent directory is created as well, and it's not an
+    /// error if the directory already exists, just like `mkdir -p` does.
     ///
     /// ## Example
-    /// ```
+    ///
+    /// ```rust
     /// # use sn_api::Safe;
     /// # let mut safe = Safe::default();
     /// # async_std::task::block_on(async {
     /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
-    ///     let data = b"Something super good";
-    ///     let xorurl = safe.files_store_public_blob(data, Some("text/plain"), false).await.unwrap();
-    ///     let received_data = safe.files_get_public_blob(&xorurl, None).await.unwrap();
-    ///     assert_eq!(received_data, data);
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(None, None, true, true, false).await.unwrap();
+    ///     let remote_dir_path = format!("{}/new/empty/folder", xorurl);
+    ///     let (version, processed_files, files_map) = safe.files_container_mkdir(&remote_dir_path, true, false, false).await.unwrap();
+    ///     println!("FilesContainer is now at version: {}", version);
+    ///     println!("The directories that were created: {:?}", processed_files);
+    ///     println!("The FilesMap of the updated FilesContainer now is: {:?}", files_map);
     /// # });
     /// ```
-    pub async fn files_store_public_blob(
-        &self,
-        data: &[u8],
-        media_type: Option<&str>,
+    pub async fn files_container_mkdir(
+        &mut self,
+        url: &str,
+        parents: bool,
+        update_nrs: bool,
         dry_run: bool,
-    ) -> Result<XorUrl> {
-        let content_type = media_type.map_or_else(
-            || Ok(SafeContentType::Raw),
-            |media_type_str| {
-                if SafeUrl::is_media_type_supported(media_type_str) {
-                    Ok(SafeContentType::MediaType(media_type_str.to_string()))
-                } else {
-                    Err(Error::InvalidMediaType(format!(
-                        "Media-type '{}' not supported. You can pass 'None' as the 'media_type' for this content to be treated as raw",
-                        media_type_str
-                    )))
-                }
-            },
-        )?;
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        let (target_url, safe_url) = self.resolve_files_container_target(url, update_nrs).await?;
 
-        // TODO: do we want ownership from other PKs yet?
-        let xorname = self.safe_client.store_public_blob(&data, dry_run).await?;
+        let dir_path = target_url.path_decoded()?;
+        if dir_path.is_empty() {
+            return Err(Error::InvalidInput(
+                "The destination URL should include the path of the directory to create"
+                    .to_string(),
+            ));
+        }
 
-        let xorurl = SafeUrl::encode_blob(xorname, content_type, self.xorurl_base)?;
+        let (current_version, files_map): (u64, FilesMap) =
+            self.fetch_files_container(&safe_url).await?;
 
-        Ok(xorurl)
+        let (processed_files, new_files_map, success_count) =
+            files_map_mkdir(&dir_path, files_map, parents)?;
+
+        let version = self
+            .append_version_to_files_container(
+                success_count,
+                current_version,
+                &new_files_map,
+                url,
+                safe_url,
+                dry_run,
+                update_nrs,
+                false,
+            )
+            .await?;
+
+        Ok((version, processed_files, new_files_map))
     }
 
-    /// # Get a Public Blob
-    /// Get blob from the network.
+    /// # Move a file, symlink or directory to a different path within a FilesContainer.
+    /// Only the FilesMap is updated, no content is re-uploaded. If the destination path
+    /// is an existing directory the source is moved into it. Symlinks found in the parents of
+    /// both paths are followed. If any of the entries cannot be moved, e.g. since it'd overwrite
+    /// an existing file without the `force` flag, nothing is moved and no version is created.
     ///
     /// ## Example
-    /// ```
+    ///
+    /// ```rust
     /// # use sn_api::Safe;
     /// # let mut safe = Safe::default();
     /// # async_std::task::block_on(async {
     /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
-    ///     let data = b"Something super good";
-    ///     let xorurl = safe.files_store_public_blob(data, None, false).await.unwrap();
-    ///     let received_data = safe.files_get_public_blob(&xorurl, None).await.unwrap();
-    ///     assert_eq!(received_data, data);
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, true, true, false).await.unwrap();
+    ///     let remote_file_path = format!("{}/test.md", xorurl);
+    ///     let (version, processed_files, files_map) = safe.files_container_move(&remote_file_path, "/renamed.md", false, false, false).await.unwrap();
+    ///     println!("FilesContainer is now at version: {}", version);
+    ///     println!("The files that were moved: {:?}", processed_files);
+    ///     println!("The FilesMap of the updated FilesContainer now is: {:?}", files_map);
     /// # });
     /// ```
-    pub async fn files_get_public_blob(&mut self, url: &str, range: Range) -> Result<Vec<u8>> {
-        // TODO: do we want ownership from other PKs yet?
-        let (safe_url, _) = self.parse_and_resolve_url(url).await?;
-        self.fetch_public_blob(&safe_url, range).await
+    pub async fn files_container_move(
+        &mut self,
+        url: &str,
+        dest_path: &str,
+        force: bool,
+        update_nrs: bool,
+        dry_run: bool,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        self.files_container_relocate(url, dest_path, force, false, update_nrs, dry_run)
+            .await
     }
 
-    /// Fetch an Blob from a SafeUrl without performing any type of URL resolution
-    pub(crate) async fn fetch_public_blob(
-        &self,
-        safe_url: &SafeUrl,
-        range: Range,
-    ) -> Result<Vec<u8>> {
-        self.safe_client
-            .get_public_blob(safe_url.xorname(), range)
+    /// # Copy a file, symlink or directory to a different path within a FilesContainer.
+    /// Only the FilesMap is updated, no content is re-uploaded. If the destination path
+    /// is an existing directory the source is copied into it.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, true, true, false).await.unwrap();
+    ///     let remote_folder_path = format!("{}/subfolder", xorurl);
+    ///     let (version, processed_files, files_map) = safe.files_container_copy(&remote_folder_path, "/backup", false, false, false).await.unwrap();
+    ///     println!("FilesContainer is now at version: {}", version);
+    ///     println!("The files that were copied: {:?}", processed_files);
+    ///     println!("The FilesMap of the updated FilesContainer now is: {:?}", files_map);
+    /// # });
+    /// ```
+    pub async fn files_container_copy(
+        &mut self,
+        url: &str,
+        dest_path: &str,
+        force: bool,
+        update_nrs: bool,
+        dry_run: bool,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        self.files_container_relocate(url, dest_path, force, true, update_nrs, dry_run)
             .await
     }
 
-    // Private helper to serialise a FilesMap and store it in a Public Blob
-    async fn store_files_map(&mut self, files_map: &FilesMap) -> Result<String> {
-        // The FilesMapContainer is a Sequence where each NRS Map version is
-        // an entry containing the XOR-URL of the Blob that contains the serialised NrsMap.
-        // TODO: use RDF format
-        let serialised_files_map = serde_json::to_string(&files_map).map_err(|err| {
-            Error::Serialisation(format!(
-                "Couldn't serialise the FilesMap generated: {:?}",
-                err
-            ))
-        })?;
-        let files_map_xorurl = self
-            .files_store_public_blob(serialised_files_map.as_bytes(), None, false)
+    // Private helper function to move or copy a path within a FilesContainer
+    async fn files_container_relocate(
+        &mut self,
+        url: &str,
+        dest_path: &str,
+        force: bool,
+        keep_source: bool,
+        update_nrs: bool,
+        dry_run: bool,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        let (target_url, safe_url) = self.resolve_files_container_target(url, update_nrs).await?;
+
+        let src_path = target_url.path_decoded()?;
+        if src_path.is_empty() {
+            return Err(Error::InvalidInput(
+                "The source URL should include a target file path".to_string(),
+            ));
+        }
+
+        let (current_version, files_map): (u64, FilesMap) =
+            self.fetch_files_container(&safe_url).await?;
+
+        let (processed_files, new_files_map, success_count) =
+            relocate::files_map_relocate(&src_path, dest_path, files_map, force, keep_source)?;
+
+        let version = self
+            .append_version_to_files_container(
+                success_count,
+                current_version,
+                &new_files_map,
+                url,
+                safe_url,
+                dry_run,
+                update_nrs,
+                false,
+            )
             .await?;
 
-        Ok(files_map_xorurl)
+        Ok((version, processed_files, new_files_map))
     }
-}
-
-// Helper functions
 
-// Make sure the input params are valid for a files_container_add operation
-async fn validate_files_add_params(
-    safe: &mut Safe,
-    source_file: &str,
-    url: &str,
-    update_nrs: bool,
-) -> Result<(SafeUrl, u64, FilesMap)> {
-    let safe_url = Safe::parse_url(url)?;
-    if safe_url.content_version().is_some() {
-        return Err(Error::InvalidInput(format!(
-            "The target URL cannot contain a version: {}",
-            url
-        )));
-    };
+    /// # Verify the content of the files of a FilesContainer.
+    /// The content each file links to is fetched, checking it matches the size and checksum
+    /// recorded when it was uploaded. If the URL has a path only the files found at, or within,
+    /// such path are verified. The version verified is returned along with the outcome for each
+    /// file, which reports any content missing or corrupted.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::{files::FileIntegrity, Safe};
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, true, true, false).await.unwrap();
+    ///     let (version, files_integrity) = safe.files_container_verify(&xorurl).await.unwrap();
+    ///     println!("FilesContainer verified is at version: {}", version);
+    ///     for (path, integrity) in files_integrity.iter() {
+    ///         if *integrity != FileIntegrity::Valid {
+    ///             println!("Content of \"{}\" couldn't be verified: {:?}", path, integrity);
+    ///         }
+    ///     }
+    /// # });
+    /// ```
+    pub async fn files_container_verify(&mut self, url: &str) -> Result<(u64, FilesIntegrity)> {
+        let (safe_url, _) = self.parse_and_resolve_url(url).await?;
+        let path = safe_url.path_decoded()?;
+        let (version, mut partial_files_map) =
+            self.fetch_files_container_partial(&safe_url).await?;
+        partial_files_map.load_subtree(self, &path).await?;
 
-    // If NRS name shall be updated then the URL has to be an NRS-URL
-    if update_nrs && safe_url.content_type() != SafeContentType::NrsMapContainer {
-        return Err(Error::InvalidInput(
-            "'update-nrs' is not allowed since the URL provided is not an NRS URL".to_string(),
-        ));
+        let files_integrity = files_map_verify(self, partial_files_map.files_map(), &path).await?;
+        Ok((version, files_integrity))
     }
 
-    let (mut safe_url, _) = safe.parse_and_resolve_url(url).await?;
+    /// # Set a custom metadata entry on a file, symlink or directory of a FilesContainer.
+    /// Custom metadata is kept in its own namespace within the FileItem, thus any name can be
+    /// used without clashing with the properties set by the API. It's preserved when syncing
+    /// the FilesContainer as long as the content of the entry doesn't change.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, true, true, false).await.unwrap();
+    ///     let remote_file_path = format!("{}/test.md", xorurl);
+    ///     let (version, _processed_files, _files_map) = safe.files_container_set_metadata(&remote_file_path, "content-language", "en", false, false).await.unwrap();
+    ///     println!("FilesContainer is now at version: {}", version);
+    ///     let metadata = safe.files_container_get_metadata(&remote_file_path).await.unwrap();
+    ///     assert_eq!(metadata["content-language"], "en");
+    /// # });
+    /// ```
+    pub async fn files_container_set_metadata(
+        &mut self,
+        url: &str,
+        name: &str,
+        value: &str,
+        update_nrs: bool,
+        dry_run: bool,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        self.files_container_update_metadata(url, name, Some(value), update_nrs, dry_run)
+            .await
+    }
 
-    // If the FilesContainer URL was resolved from an NRS name we need to remove
-    // the version from it so we can fetch latest version of it for sync-ing
-    safe_url.set_content_version(None);
+    /// # Remove a custom metadata entry from a file, symlink or directory of a FilesContainer.
+    /// No new version is created if the entry doesn't have such custom metadata.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, true, true, false).await.unwrap();
+    ///     let remote_file_path = format!("{}/test.md", xorurl);
+    ///     let _ = safe.files_container_set_metadata(&remote_file_path, "author", "maidsafe", false, false).await.unwrap();
+    ///     let (version, _processed_files, _files_map) = safe.files_container_remove_metadata(&remote_file_path, "author", false, false).await.unwrap();
+    ///     assert_eq!(version, 2);
+    /// # });
+    /// ```
+    pub async fn files_container_remove_metadata(
+        &mut self,
+        url: &str,
+        name: &str,
+        update_nrs: bool,
+        dry_run: bool,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        self.files_container_update_metadata(url, name, None, update_nrs, dry_run)
+            .await
+    }
 
-    let (current_version, current_files_map): (u64, FilesMap) =
-        safe.fetch_files_container(&safe_url).await?;
+    /// # Get the custom metadata of a file, symlink or directory of a FilesContainer.
+    /// The URL can target a specific version of the FilesContainer.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, true, true, false).await.unwrap();
+    ///     let remote_file_path = format!("{}/test.md", xorurl);
+    ///     let _ = safe.files_container_set_metadata(&remote_file_path, "tags", "docs,test", false, false).await.unwrap();
+    ///     let metadata = safe.files_container_get_metadata(&remote_file_path).await.unwrap();
+    ///     println!("Custom metadata of the file: {:?}", metadata);
+    /// # });
+    /// ```
+    pub async fn files_container_get_metadata(
+        &mut self,
+        url: &str,
+    ) -> Result<BTreeMap<String, String>> {
+        let (safe_url, _) = self.parse_and_resolve_url(url).await?;
+        let path = safe_url.path_decoded()?;
+        let (_, files_map) = self.fetch_files_container(&safe_url).await?;
 
-    let dest_path = safe_url.path().to_string();
+        files_map_get_metadata(&files_map, &path)
+    }
 
-    // Let's act according to if it's a local file path or a safe:// location
-    if source_file.starts_with("safe://") {
-        let source_safe_url = Safe::parse_url(source_file)?;
-        if source_safe_url.data_type() != SafeDataType::PublicBlob {
-            return Err(Error::InvalidInput(format!(
-                "The source URL should target a file ('{}'), but the URL provided targets a '{}'",
-                SafeDataType::PublicBlob,
-                source_safe_url.content_type()
-            )));
-        }
+    // Private helper function to set or remove a custom metadata entry
+    async fn files_container_update_metadata(
+        &mut self,
+        url: &str,
+        name: &str,
+        value: Option<&str>,
+        update_nrs: bool,
+        dry_run: bool,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        let (target_url, safe_url) = self.resolve_files_container_target(url, update_nrs).await?;
 
-        if dest_path.is_empty() {
+        let path = target_url.path_decoded()?;
+        if path.is_empty() {
             return Err(Error::InvalidInput(
-                "The destination URL should include a target file path since we are adding a link"
-                    .to_string(),
+                "The URL should include the path of the entry to update".to_string(),
             ));
         }
-    }
-    Ok((safe_url, current_version, current_files_map))
-}
 
-// From the location path and the destination path chosen by the user, calculate
-// the destination path considering ending '/' in both the  location and dest path
-fn get_base_paths(location: &str, dest_path: Option<&str>) -> (String, String) {
-    // Let's normalise the path to use '/' (instead of '\' as on Windows)
-    let location_base_path = if location == "." {
-        "./".to_string()
-    } else {
-        normalise_path_separator(location)
-    };
+        let (current_version, files_map): (u64, FilesMap) =
+            self.fetch_files_container(&safe_url).await?;
 
-    let new_dest_path = match dest_path {
-        Some(path) => {
-            if path.is_empty() {
-                "/".to_string()
-            } else {
-                path.to_string()
-            }
-        }
-        None => "/".to_string(),
-    };
+        let (processed_files, new_files_map, success_count) =
+            files_map_set_metadata(&path, files_map, name, value)?;
 
-    // Let's first check if it ends with '/'
-    let dest_base_path = if new_dest_path.ends_with('/') {
-        if location_base_path.ends_with('/') {
-            new_dest_path
-        } else {
-            // Location is a folder, then append it to dest path
-            let parts_vec: Vec<&str> = location_base_path.split('/').collect();
-            let dir_name = parts_vec[parts_vec.len() - 1];
-            format!("{}{}", new_dest_path, dir_name)
-        }
-    } else {
-        // Then just append an ending '/'
-        format!("{}/", new_dest_path)
-    };
+        let version = self
+            .append_version_to_files_container(
+                success_count,
+                current_version,
+                &new_files_map,
+                url,
+                safe_url,
+                dry_run,
+                update_nrs,
+                false,
+            )
+            .await?;
 
-    (location_base_path, dest_base_path)
-}
+        Ok((version, processed_files, new_files_map))
+    }
 
-// From the provided list of local files paths, find the local changes made in comparison with the
-// target FilesContainer, uploading new files as necessary, and creating a new FilesMap with file's
-// metadata and their corresponding links, as well as generating the report of processed files
-#[allow(clippy::too_many_arguments)]
-async fn files_map_sync(
-    safe: &mut Safe,
-    mut current_files_map: FilesMap,
-    location: &str,
-    new_content: ProcessedFiles,
-    dest_path: Option<&str>,
-    delete: bool,
-    dry_run: bool,
-    force: bool,
-    compare_file_content: bool,
-    follow_links: bool,
-) -> Result<(ProcessedFiles, FilesMap, u64)> {
-    let (location_base_path, dest_base_path) = get_base_paths(location, dest_path);
-    let mut updated_files_map = FilesMap::new();
-    let mut processed_files = ProcessedFiles::new();
-    let mut success_count = 0;
-
-    for (local_file_name, _) in new_content
-        .iter()
-        .filter(|(_, (change, _))| change != CONTENT_ERROR_SIGN)
-    {
-        let file_path = Path::new(&local_file_name);
+    /// # Revert a FilesContainer to a previous version.
+    /// A new version is appended to the FilesContainer which links to the same FilesMap
+    /// the target version links to, thus no content is re-uploaded. The list of changes
+    /// returned are those made when going from the current to the new version.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create(Some("../testdata/"), None, true, true, false).await.unwrap();
+    ///     let remote_file_path = format!("{}/test.md", xorurl);
+    ///     let _ = safe.files_container_remove_path(&remote_file_path, false, false, false).await.unwrap();
+    ///     let (version, processed_files, files_map) = safe.files_container_revert(&xorurl, 0, false, false).await.unwrap();
+    ///     println!("FilesContainer is now at version: {}", version);
+    ///     println!("The files that were restored: {:?}", processed_files);
+    ///     println!("The FilesMap of the reverted FilesContainer now is: {:?}", files_map);
+    /// # });
+    /// ```
+    pub async fn files_container_revert(
+        &mut self,
+        url: &str,
+        target_version: u64,
+        update_nrs: bool,
+        dry_run: bool,
+    ) -> Result<(u64, ProcessedFiles, FilesMap)> {
+        let (_, mut safe_url) = self.resolve_files_container_target(url, update_nrs).await?;
+        if safe_url.content_type() != SafeContentType::FilesContainer {
+            return Err(Error::InvalidInput(format!(
+                "The URL provided doesn't target a FilesContainer but a '{}'",
+                safe_url.content_type()
+            )));
+        }
 
-        let file_name = RelativePath::new(
-            &local_file_name
-                .to_string()
-                .replace(&location_base_path, &dest_base_path),
-        )
-        .normalize();
-        // Above normalize removes initial slash, and uses '\' if it's on Windows
-        // here, we trim any trailing '/', as it could be a filename.
-        let mut normalised_file_name = format!("/{}", normalise_path_separator(file_name.as_str()))
-            .trim_end_matches('/')
-            .to_string();
+        let (current_version, current_files_map) = self.fetch_files_container(&safe_url).await?;
 
-        if normalised_file_name.is_empty() {
-            normalised_file_name = "/".to_string();
-        }
+        safe_url.set_content_version(Some(target_version));
+        let (_, files_map_xorurl, target_files_map) =
+            self.fetch_files_container_version(&safe_url).await?;
+        safe_url.set_content_version(None);
 
-        // Let's update FileItem if there is a change or it doesn't exist in current_files_map
-        match current_files_map.get(&normalised_file_name) {
-            None => {
-                // We need to add a new FileItem
-                if add_or_update_file_item(
-                    safe,
-                    &local_file_name,
-                    &normalised_file_name,
-                    &file_path,
-                    &FileMeta::from_path(&local_file_name, follow_links)?,
-                    None, // no xorurl link
+        let diff = diff::files_map_diff(&current_files_map, &target_files_map);
+        let processed_files = diff::files_map_diff_to_processed_files(&diff);
+
+        let version = match files_map_xorurl {
+            Some(files_map_xorurl) if !diff.is_empty() && !dry_run => {
+                self.append_files_map_link_to_files_container(
+                    &files_map_xorurl,
+                    current_version,
+                    url,
+                    safe_url,
+                    update_nrs,
                     false,
-                    dry_run,
-                    &mut updated_files_map,
-                    &mut processed_files,
                 )
-                .await
-                {
-                    success_count += 1;
-
-                    // We remove self and any parent directories
-                    // from the current list so we know it has been processed
-                    let mut trail = Vec::<&str>::new();
-                    for part in normalised_file_name.split('/') {
-                        trail.push(part);
-                        let ancestor = if trail.len() > 1 {
-                            trail.join("/")
-                        } else {
-                            "/".to_string()
-                        };
-                        if ancestor != normalised_file_name {
-                            if let Some(fi) = current_files_map.get(&ancestor) {
-                                updated_files_map.insert(ancestor.clone(), fi.clone());
-                                current_files_map.remove(&ancestor);
-                            }
-                        }
-                    }
-                }
+                .await?
             }
-            Some(file_item) => {
-                let is_modified =
-                    is_file_item_modified(safe, &Path::new(local_file_name), file_item).await;
-                if force || (compare_file_content && is_modified) {
-                    // We need to update the current FileItem
-                    if add_or_update_file_item(
-                        safe,
-                        &local_file_name,
-                        &normalised_file_name,
-                        &file_path,
-                        &FileMeta::from_path(&local_file_name, follow_links)?,
-                        None, // no xorurl link
-                        true,
-                        dry_run,
-                        &mut updated_files_map,
-                        &mut processed_files,
-                    )
-                    .await
-                    {
-                        success_count += 1;
-                    }
-                } else {
-                    // No need to update FileItem just copy the existing one
-                    updated_files_map.insert(normalised_file_name.to_string(), file_item.clone());
-
-                    if !force && !compare_file_content {
-                        let comp_str = if is_modified { "different" } else { "same" };
-                        processed_files.insert(
-                            local_file_name.to_string(),
-                            (
-                                CONTENT_ERROR_SIGN.to_string(),
-                                format!(
-                                    "File named \"{}\" with {} content already exists on target. Use the 'force' flag to replace it",
-                                    normalised_file_name, comp_str
-                                ),
-                            ),
-                        );
-                        info!("Skipping file \"{}\" since a file named \"{}\" with {} content already exists on target. You can use the 'force' flag to replace the existing file with the new one", local_file_name, normalised_file_name, comp_str);
-                    }
-                }
+            Some(_) if !diff.is_empty() => current_version + 1,
+            _ => current_version,
+        };
 
-                // let's now remove it from the current list so we now it has been processed
-                current_files_map.remove(&normalised_file_name);
+        Ok((version, processed_files, target_files_map))
+    }
 
-                // We also remove any parent directories
-                // from the current list, so they will not be deleted.
-                let mut trail = Vec::<&str>::new();
-                for part in normalised_file_name.split('/') {
-                    trail.push(part);
-                    let ancestor = if trail.len() > 1 {
-                        trail.join("/")
-                    } else {
-                        "/".to_string()
-                    };
-                    if ancestor != normalised_file_name {
-                        if let Some(fi) = current_files_map.get(&ancestor) {
-                            updated_files_map.insert(ancestor.clone(), fi.clone());
-                            current_files_map.remove(&ancestor);
-                        }
-                    }
-                }
+    // Fetch the version of a FilesContainer the changes are to be made on, which is the latest
+    // one, unless a different one is expected and the changes are allowed to be merged
+    async fn fetch_files_container_base(
+        &self,
+        safe_url: &SafeUrl,
+        options: &SyncOptions,
+    ) -> Result<(u64, FilesMap)> {
+        let (current_version, current_files_map) = self.fetch_files_container(safe_url).await?;
+        match options.expected_version {
+            None => Ok((current_version, current_files_map)),
+            Some(expected) if expected == current_version => {
+                Ok((current_version, current_files_map))
+            }
+            Some(expected) if expected > current_version => Err(Error::VersionNotFound(format!(
+                "Version '{}' was expected but the latest version of the FilesContainer is '{}'",
+                expected, current_version
+            ))),
+            Some(expected) if options.merge => {
+                let mut base_url = safe_url.clone();
+                base_url.set_content_version(Some(expected));
+                self.fetch_files_container(&base_url).await
             }
+            Some(expected) => Err(version_conflict_error(expected, current_version)),
         }
     }
 
-    // Finally, unless 'delete' was set keep the files that are currently
-    // in FilesContainer but not in source location
-    current_files_map.iter().for_each(|(file_name, file_item)| {
-        if !delete {
-            updated_files_map.insert(file_name.to_string(), file_item.clone());
-        } else {
-            processed_files.insert(
-                file_name.to_string(),
-                (
-                    CONTENT_DELETED_SIGN.to_string(),
-                    // note: files have link property,
-                    //       dirs and symlinks do not
-                    file_item
-                        .get(PREDICATE_LINK)
-                        .unwrap_or(&String::default())
-                        .to_string(),
-                ),
-            );
-            success_count += 1;
+    // Parse and resolve the URL of a FilesContainer to be updated. The URL cannot contain a
+    // version since the latest one is always updated, and it has to be an NRS-URL if the
+    // NRS name shall be updated too. Returns the URL as parsed, along with the URL of the
+    // FilesContainer it resolves to, without a version even if resolved from an NRS name.
+    async fn resolve_files_container_target(
+        &self,
+        url: &str,
+        update_nrs: bool,
+    ) -> Result<(SafeUrl, SafeUrl)> {
+        let target_url = Safe::parse_url(url)?;
+        if target_url.content_version().is_some() {
+            return Err(Error::InvalidInput(format!(
+                "The target URL cannot contain a version: {}",
+                url
+            )));
+        };
+
+        // If NRS name shall be updated then the URL has to be an NRS-URL
+        if update_nrs && target_url.content_type() != SafeContentType::NrsMapContainer {
+            return Err(Error::InvalidInput(
+                "'update-nrs' is not allowed since the URL provided is not an NRS URL".to_string(),
+            ));
         }
-    });
 
-    Ok((processed_files, updated_files_map, success_count))
-}
+        let (mut safe_url, _) = self.parse_and_resolve_url(url).await?;
 
-async fn is_file_item_modified(
-    safe: &mut Safe,
-    local_filename: &Path,
-    file_item: &FileItem,
-) -> bool {
-    if FileMeta::filetype_is_file(&file_item[PREDICATE_TYPE]) {
-        match upload_file_to_net(safe, local_filename, true /* dry-run */).await {
-            Ok(local_xorurl) => file_item[PREDICATE_LINK] != local_xorurl,
-            Err(_err) => false,
-        }
-    } else {
-        // for now, we just return false if a symlink or directory.
-        // In the future, should check if symlink has been modified.
-        // Also, could check if ctime or mtime is different, though that
-        // could apply to files as well, and some use-cases would not want to
-        // sync remotely if actual content has not changed.  So it should
-        // probably be a user flag to enable.
-        false
+        // If the FilesContainer URL was resolved from an NRS name we need to remove
+        // the version from it so we can fetch latest version of it
+        safe_url.set_content_version(None);
+
+        Ok((target_url, safe_url))
     }
-}
 
-async fn files_map_add_link(
-    safe: &mut Safe,
-    mut files_map: FilesMap,
-    file_link: &str,
-    file_name: &str,
-    force: bool,
-) -> Result<(ProcessedFiles, FilesMap, u64)> {
-    let mut processed_files = ProcessedFiles::new();
-    let mut success_count = 0;
-    match SafeUrl::from_url(file_link) {
-        Err(err) => {
-            processed_files.insert(
-                file_link.to_string(),
-                (CONTENT_ERROR_SIGN.to_string(), format!("<{}>", err)),
+    // Make sure the FilesContainer hasn't moved on from the version the new FilesMap is based on
+    // when requested, merging the changes made since then if allowed. Returns the version the
+    // new FilesMap shall be appended after, along with the FilesMap, merged if needed.
+    // Another writer could still append a version after this check, which is why the new
+    // version is then appended with a compare-and-append on the returned version.
+    async fn reconcile_files_map(
+        &self,
+        safe_url: &SafeUrl,
+        base_version: u64,
+        base_files_map: &FilesMap,
+        new_files_map: FilesMap,
+        options: &SyncOptions,
+    ) -> Result<(u64, FilesMap)> {
+        if !options.checks_version() {
+            return Ok((base_version, new_files_map));
+        }
+
+        let (latest_version, latest_files_map) = self.fetch_files_container(safe_url).await?;
+        if latest_version == base_version {
+            Ok((base_version, new_files_map))
+        } else if options.merge {
+            debug!(
+                "Merging changes based on version {} of the FilesContainer onto version {}",
+                base_version, latest_version
             );
-            info!("Skipping file \"{}\". {}", file_link, err);
-            Ok((processed_files, files_map, success_count))
+            let merged_files_map =
+                files_map_merge(base_files_map, &new_files_map, &latest_files_map)?;
+            Ok((latest_version, merged_files_map))
+        } else {
+            Err(version_conflict_error(base_version, latest_version))
         }
-        Ok(safe_url) => {
-            let file_path = Path::new("");
-            let file_type = match safe_url.content_type() {
-                SafeContentType::MediaType(media_type) => media_type,
-                other => format!("{}", other),
-            };
-            let file_size = ""; // unknown
+    }
 
-            // Let's update FileItem if the link is different or it doesn't exist in the files_map
-            match files_map.get(file_name) {
-                Some(current_file_item) => {
-                    let mut file_meta = FileMeta::from_file_item(&current_file_item);
-                    file_meta.file_type = file_type;
-                    file_meta.file_size = file_size.to_string();
+    // Private helper function to append new version of the FilesMap to the Files Container
+    // It flagged with `update_nrs`, it will also update the link in the corresponding NRS Map Container.
+    // If flagged with `check_version`, it's only appended if the FilesContainer is still at
+    // `current_version`, failing with a `VersionConflict` error otherwise.
+    #[allow(clippy::too_many_arguments)]
+    async fn append_version_to_files_container(
+        &mut self,
+        success_count: u64,
+        current_version: u64,
+        new_files_map: &FilesMap,
+        url: &str,
+        safe_url: SafeUrl,
+        dry_run: bool,
+        update_nrs: bool,
+        check_version: bool,
+    ) -> Result<u64> {
+        let version = if success_count == 0 {
+            current_version
+        } else if dry_run {
+            current_version + 1
+        } else {
+            // The FilesContainer is updated by adding an entry containing the link to
+            // the Blob with the serialised new version of the FilesMap. The shards of
+            // the latest version which didn't change are not stored again.
+            let mut latest_url = safe_url.clone();
+            latest_url.set_content_version(None);
+            let (_, latest_files_map_xorurl) = self.fetch_files_map_link(&latest_url).await?;
+            let files_map_xorurl = self
+                .store_files_map(new_files_map, latest_files_map_xorurl)
+                .await?;
 
-                    let is_modified = if file_meta.is_file() {
-                        current_file_item[PREDICATE_LINK] != file_link
-                    } else {
-                        // directory: nothing to check.
-                        // symlink: TODO: check if sym-link path has changed.
-                        false
-                    };
+            self.append_files_map_link_to_files_container(
+                &files_map_xorurl,
+                current_version,
+                url,
+                safe_url,
+                update_nrs,
+                check_version,
+            )
+            .await?
+        };
 
-                    if is_modified {
-                        if force {
-                            if add_or_update_file_item(
-                                safe,
-                                file_name,
-                                file_name,
-                                &file_path,
-                                &file_meta,
-                                Some(file_link),
-                                true,
-                                true,
-                                &mut files_map,
-                                &mut processed_files,
-                            )
-                            .await
-                            {
-                                success_count += 1;
-                            }
-                        } else {
-                            processed_files.insert(file_name.to_string(), (CONTENT_ERROR_SIGN.to_string(), format!("File named \"{}\" already exists on target. Use the 'force' flag to replace it", file_name)));
-                            info!("Skipping file \"{}\" since a file with name \"{}\" already exists on target. You can use the 'force' flag to replace the existing file with the new one", file_link, file_name);
-                        }
-                    } else {
-                        processed_files.insert(
-                            file_link.to_string(),
-                            (
-                                CONTENT_ERROR_SIGN.to_string(),
-                                format!(
-                                    "File named \"{}\" already exists on target with same link",
-                                    file_name
-                                ),
-                            ),
-                        );
-                        info!("Skipping file \"{}\" since a file with name \"{}\" already exists on target with the same link", file_link, file_name);
-                    }
-                }
-                None => {
-                    if add_or_update_file_item(
-                        safe,
-                        file_name,
-                        file_name,
-                        &file_path,
-                        &FileMeta::from_type_and_size(&file_type, &file_size),
-                        Some(file_link),
-                        false,
-                        true,
-                        &mut files_map,
-                        &mut processed_files,
-                    )
-                    .await
-                    {
-                        success_count += 1;
-                    }
-                }
-            };
+        Ok(version)
+    }
 
-            Ok((processed_files, files_map, success_count))
+    // Private helper function to append a new version to the Files Container which links
+    // to a FilesMap already stored on the network. If flagged with `update_nrs`, it will
+    // also update the link in the corresponding NRS Map Container. If flagged with
+    // `check_version`, the new version is atomically appended only if the FilesContainer
+    // is still at `current_version` where the backend supports it, see `StorageBackend`.
+    async fn append_files_map_link_to_files_container(
+        &mut self,
+        files_map_xorurl: &str,
+        current_version: u64,
+        url: &str,
+        mut safe_url: SafeUrl,
+        update_nrs: bool,
+        check_version: bool,
+    ) -> Result<u64> {
+        let xorname = safe_url.xorname();
+        let type_tag = safe_url.type_tag();
+        let new_version = current_version + 1;
+        let expected_index = if check_version {
+            Some(new_version)
+        } else {
+            None
+        };
+        self.safe_client
+            .append_to_sequence(
+                files_map_xorurl.as_bytes(),
+                xorname,
+                type_tag,
+                false,
+                expected_index,
+            )
+            .await
+            .map_err(|err| match err {
+                Error::VersionConflict(msg) => Error::VersionConflict(format!(
+                    "The FilesContainer was expected to be at version '{}' but it was updated concurrently. {}",
+                    current_version, msg
+                )),
+                other => other,
+            })?;
+
+        if update_nrs {
+            // We need to update the link in the NRS container as well,
+            // to link it to the new new_version of the FilesContainer we just generated
+            safe_url.set_content_version(Some(new_version));
+            let new_link_for_nrs = safe_url.to_string();
+            let _ = self
+                .nrs_map_container_add(url, &new_link_for_nrs, false, true, false)
+                .await?;
+        }
+
+        Ok(new_version)
+    }
+
+    /// # Put a Public Blob
+    /// Put data blobs onto the network.
+    ///
+    /// ## Example
+    /// ```
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let data = b"Something super good";
+    ///     let xorurl = safe.files_store_public_blob(data, Some("text/plain"), false).await.unwrap();
+    ///     let received_data = safe.files_get_public_blob(&xorurl, None).await.unwrap();
+    ///     assert_eq!(received_data, data);
+    /// # });
+    /// ```
+    pub async fn files_store_public_blob(
+        &self,
+        data: &[u8],
+        media_type: Option<&str>,
+        dry_run: bool,
+    ) -> Result<XorUrl> {
+        let content_type = gen_content_type(media_type)?;
+
+        // TODO: do we want ownership from other PKs yet?
+        let xorname = self.safe_client.store_public_blob(&data, dry_run).await?;
+
+        let xorurl = SafeUrl::encode_blob(xorname, content_type, self.xorurl_base)?;
+
+        Ok(xorurl)
+    }
+
+    /// # Put a Public Blob reading its content from a stream
+    /// The content is read and uploaded in pieces of bounded size, thus it's never
+    /// entirely held in memory. Content which fits in a single piece gets the same
+    /// XOR-URL it would get if uploaded with `files_store_public_blob`, otherwise the
+    /// XOR-URL returned links to the manifest of the pieces and is marked as such by its type tag.
+    ///
+    /// ## Example
+    /// ```
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let file = std::io::Cursor::new(std::fs::read("../testdata/test.md").unwrap());
+    ///     let xorurl = safe.files_store_public_blob_from_reader(file, Some("text/markdown"), false).await.unwrap();
+    ///     let received_data = safe.files_get_public_blob(&xorurl, None).await.unwrap();
+    ///     assert!(received_data.starts_with(b"hello tests!"));
+    /// # });
+    /// ```
+    pub async fn files_store_public_blob_from_reader<R>(
+        &self,
+        reader: R,
+        media_type: Option<&str>,
+        dry_run: bool,
+    ) -> Result<XorUrl>
+    where
+        R: AsyncRead + Unpin + Send,
+    {
+        self.store_public_blob_from_reader(reader, media_type, dry_run, |_| Ok(()))
+            .await
+    }
+
+    // Put a Public Blob reading its content from a stream, like `files_store_public_blob_from_reader`
+    // does, calling `on_piece` with each piece of the content right after it's been uploaded.
+    // The upload is aborted if `on_piece` returns an error.
+    pub(crate) async fn store_public_blob_from_reader<R, F>(
+        &self,
+        reader: R,
+        media_type: Option<&str>,
+        dry_run: bool,
+        on_piece: F,
+    ) -> Result<XorUrl>
+    where
+        R: AsyncRead + Unpin + Send,
+        F: FnMut(&[u8]) -> Result<()> + Send,
+    {
+        let content_type = gen_content_type(media_type)?;
+        streaming::store_blob_from_reader(self, reader, content_type, dry_run, on_piece).await
+    }
+
+    /// # Get a Public Blob
+    /// Get blob from the network.
+    ///
+    /// ## Example
+    /// ```
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let data = b"Something super good";
+    ///     let xorurl = safe.files_store_public_blob(data, None, false).await.unwrap();
+    ///     let received_data = safe.files_get_public_blob(&xorurl, None).await.unwrap();
+    ///     assert_eq!(received_data, data);
+    /// # });
+    /// ```
+    pub async fn files_get_public_blob(&mut self, url: &str, range: Range) -> Result<Vec<u8>> {
+        // TODO: do we want ownership from other PKs yet?
+        let (safe_url, _) = self.parse_and_resolve_url(url).await?;
+        self.fetch_public_blob(&safe_url, range).await
+    }
+
+    /// # Get a Public Blob writing its content into a stream
+    /// The URL is resolved as with `fetch`, i.e. it can be an NRS-URL and/or a path within a
+    /// FilesContainer, as long as it finally targets a file. The content is written into the
+    /// writer one piece at a time, without holding it entirely in memory.
+    /// It returns the total number of bytes written.
+    ///
+    /// ## Example
+    /// ```
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let data = b"Something super good";
+    ///     let xorurl = safe.files_store_public_blob(data, None, false).await.unwrap();
+    ///     let mut received_data = vec![];
+    ///     let size = safe.files_get_public_blob_to_writer(&xorurl, None, &mut received_data).await.unwrap();
+    ///     assert_eq!(size, data.len() as u64);
+    ///     assert_eq!(received_data, data);
+    /// # });
+    /// ```
+    pub async fn files_get_public_blob_to_writer<W>(
+        &self,
+        url: &str,
+        range: Range,
+        writer: &mut W,
+    ) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin + Send,
+    {
+        let mut resolution_chain = self
+            .retrieve_from_url(url, false, None, true, false)
+            .await?;
+        match resolution_chain.pop() {
+            Some(SafeData::PublicBlob { xorurl, .. }) => {
+                let safe_url = SafeUrl::from_url(&xorurl)?;
+                streaming::write_blob(self, &safe_url, range, writer).await
+            }
+            Some(other) => Err(Error::ContentError(format!(
+                "The URL provided doesn't target a file but a '{}'",
+                Safe::parse_url(&other.xorurl())?.content_type()
+            ))),
+            None => Err(Error::ContentNotFound(format!("Failed to resolve {}", url))),
         }
     }
+
+    /// Fetch an Blob from a SafeUrl without performing any type of URL resolution
+    pub(crate) async fn fetch_public_blob(
+        &self,
+        safe_url: &SafeUrl,
+        range: Range,
+    ) -> Result<Vec<u8>> {
+        streaming::fetch_blob(self, safe_url, range).await
+    }
+
+    // Private helper to serialise a FilesMap and store it in Public Blobs, one per shard
+    // if it's large enough to be sharded, reusing those of the previous FilesMap if any
+    async fn store_files_map(
+        &mut self,
+        files_map: &FilesMap,
+        previous_files_map_xorurl: Option<XorUrl>,
+    ) -> Result<String> {
+        // The FilesMapContainer is a Sequence where each NRS Map version is
+        // an entry containing the XOR-URL of the Blob that contains the serialised NrsMap.
+        shards::store_files_map(self, files_map, previous_files_map_xorurl).await
+    }
 }
 
-// Remove a path from the FilesMap provided
-fn files_map_remove_path(
-    dest_path: &str,
-    mut files_map: FilesMap,
-    recursive: bool,
-) -> Result<(ProcessedFiles, FilesMap, u64)> {
-    let mut processed_files = ProcessedFiles::default();
-    let (success_count, new_files_map) = if recursive {
-        let mut success_count = 0;
-        let mut new_files_map = FilesMap::default();
-        let folder_path = if !dest_path.ends_with('/') {
-            format!("{}/", dest_path)
-        } else {
-            dest_path.to_string()
-        };
+// Helper functions
 
-        files_map.iter().for_each(|(file_path, file_item)| {
-            // if the current file_path is a subfolder we remove it
-            if file_path.starts_with(&folder_path) {
-                processed_files.insert(
-                    file_path.to_string(),
-                    (
-                        CONTENT_DELETED_SIGN.to_string(),
-                        // note: files have link property,
-                        //       dirs and symlinks do not
-                        file_item
-                            .get(PREDICATE_LINK)
-                            .unwrap_or(&String::default())
-                            .to_string(),
-                    ),
-                );
-                success_count += 1;
+// Generate the content type for a Blob from the media type provided
+fn gen_content_type(media_type: Option<&str>) -> Result<SafeContentType> {
+    media_type.map_or_else(
+        || Ok(SafeContentType::Raw),
+        |media_type_str| {
+            if SafeUrl::is_media_type_supported(media_type_str) {
+                Ok(SafeContentType::MediaType(media_type_str.to_string()))
             } else {
-                new_files_map.insert(file_path.to_string(), file_item.clone());
+                Err(Error::InvalidMediaType(format!(
+                    "Media-type '{}' not supported. You can pass 'None' as the 'media_type' for this content to be treated as raw",
+                    media_type_str
+                )))
             }
-        });
-        (success_count, new_files_map)
-    } else {
-        let file_item = files_map
-            .remove(dest_path)
-            .ok_or_else(|| Error::ContentError(format!(
-                "No content found matching the \"{}\" path on the target FilesContainer. If you are trying to remove a folder rather than a file, you need to pass the 'recursive' flag",
-                dest_path
-            )))?;
-        processed_files.insert(
-            dest_path.to_string(),
-            (
-                CONTENT_DELETED_SIGN.to_string(),
-                // note: files have link property,
-                //       dirs and symlinks do not
-                file_item
-                    .get(PREDICATE_LINK)
-                    .unwrap_or(&String::default())
-                    .to_string(),
-            ),
+        },
+    )
+}
+
+// Make sure the input params are valid for a files_container_add operation
+async fn validate_files_add_params(
+    safe: &mut Safe,
+    source_file: &str,
+    url: &str,
+    update_nrs: bool,
+    options: &SyncOptions,
+) -> Result<(SafeUrl, u64, FilesMap)> {
+    let (_, safe_url) = safe.resolve_files_container_target(url, update_nrs).await?;
+
+    let (current_version, current_files_map): (u64, FilesMap) =
+        safe.fetch_files_container_base(&safe_url, options).await?;
+
+    let dest_path = safe_url.path().to_string();
+
+    // Let's act according to if it's a local file path or a safe:// location
+    if source_file.starts_with("safe://") {
+        let source_safe_url = Safe::parse_url(source_file)?;
+        if source_safe_url.data_type() != SafeDataType::PublicBlob {
+            return Err(Error::InvalidInput(format!(
+                "The source URL should target a file ('{}'), but the URL provided targets a '{}'",
+                SafeDataType::PublicBlob,
+                source_safe_url.content_type()
+            )));
+        }
+
+        if dest_path.is_empty() {
+            return Err(Error::InvalidInput(
+                "The destination URL should include a target file path since we are adding a link"
+                    .to_string(),
+            ));
+        }
+    }
+    Ok((safe_url, current_version, current_files_map))
+}
+
+// From the location path and the destination path chosen by the user, calculate
+// the destination path considering ending '/' in both the  location and dest path
+fn get_base_paths(location: &str, dest_path: Option<&str>) -> (String, String) {
+    // Let's normalise the path to use '/' (instead of '\' as on Windows)
+    let location_base_path = if location == "." {
+        "./".to_string()
+    } else {
+        normalise_path_separator(location)
+    };
+
+    let new_dest_path = match dest_path {
+        Some(path) => {
+            if path.is_empty() {
+                "/".to_string()
+            } else {
+                path.to_string()
+            }
+        }
+        None => "/".to_string(),
+    };
+
+    // Let's first check if it ends with '/'
+    let dest_base_path = if new_dest_path.ends_with('/') {
+        if location_base_path.ends_with('/') {
+            new_dest_path
+        } else {
+            // Location is a folder, then append it to dest path
+            let parts_vec: Vec<&str> = location_base_path.split('/').collect();
+            let dir_name = parts_vec[parts_vec.len() - 1];
+            format!("{}{}", new_dest_path, dir_name)
+        }
+    } else {
+        // Then just append an ending '/'
+        format!("{}/", new_dest_path)
+    };
+
+    (location_base_path, dest_base_path)
+}
+
+// From the provided list of local files paths, find the local changes made in comparison with the
+// target FilesContainer, uploading new files as necessary, and creating a new FilesMap with file's
+// metadata and their corresponding links, as well as generating the report of processed files.
+// Up to `concurrency` local files are checked and uploaded at a time.
+#[allow(clippy::too_many_arguments)]
+async fn files_map_sync(
+    safe: &mut Safe,
+    mut current_files_map: FilesMap,
+    location: &str,
+    new_content: ProcessedFiles,
+    dest_path: Option<&str>,
+    delete: bool,
+    path_filter: Option<&PathFilter>,
+    dry_run: bool,
+    force: bool,
+    compare_file_content: bool,
+    options: &SyncOptions,
+    sync_state: Option<&SyncState>,
+    follow_links: bool,
+) -> Result<(ProcessedFiles, FilesMap, u64)> {
+    let progress = options.progress.as_ref();
+    let (location_base_path, dest_base_path) = get_base_paths(location, dest_path);
+    let mut updated_files_map = FilesMap::new();
+    let mut processed_files = ProcessedFiles::new();
+    let mut success_count = 0;
+
+    let local_files: Vec<(&String, String)> = new_content
+        .iter()
+        .filter(|(_, (change, _))| change != CONTENT_ERROR_SIGN)
+        .map(|(local_file_name, _)| {
+            let file_name = RelativePath::new(
+                &local_file_name
+                    .to_string()
+                    .replace(&location_base_path, &dest_base_path),
+            )
+            .normalize();
+            // Above normalize removes initial slash, and uses '\' if it's on Windows
+            // here, we trim any trailing '/', as it could be a filename.
+            let mut normalised_file_name =
+                format!("/{}", normalise_path_separator(file_name.as_str()))
+                    .trim_end_matches('/')
+                    .to_string();
+
+            if normalised_file_name.is_empty() {
+                normalised_file_name = "/".to_string();
+            }
+
+            (local_file_name, normalised_file_name)
+        })
+        .collect();
+
+    // Let's first check and upload the local files which need it, concurrently
+    let prepared_files: Vec<PreparedFile> = {
+        let safe: &Safe = safe;
+        let current_files_map = &current_files_map;
+        stream::iter(local_files.iter())
+            .map(|(local_file_name, normalised_file_name)| {
+                prepare_local_file(
+                    safe,
+                    local_file_name,
+                    current_files_map.get(normalised_file_name),
+                    force,
+                    compare_file_content,
+                    options,
+                    sync_state,
+                    follow_links,
+                    dry_run,
+                )
+            })
+            .buffered(options.concurrency.max(1))
+            .collect()
+            .await
+    };
+
+    for ((local_file_name, normalised_file_name), prepared_file) in
+        local_files.into_iter().zip(prepared_files)
+    {
+        let file_path = Path::new(&local_file_name);
+
+        // Files which failed to be uploaded are reported and skipped
+        let uploaded = match prepared_file.upload {
+            Some(Err(err)) => {
+                processed_files.insert(
+                    local_file_name.to_string(),
+                    (CONTENT_ERROR_SIGN.to_string(), format!("<{}>", err)),
+                );
+                info!("Skipping file \"{}\": {:?}", local_file_name, err);
+                None
+            }
+            Some(Ok(uploaded)) => Some(uploaded),
+            None => None,
+        };
+        let file_link = uploaded.as_ref().map(|uploaded| uploaded.xorurl.as_str());
+        let checksum = uploaded
+            .as_ref()
+            .and_then(|uploaded| uploaded.checksum.as_deref());
+        let upload_failed = processed_files
+            .get(local_file_name)
+            .map_or(false, |(change, _)| change == CONTENT_ERROR_SIGN);
+
+        // Let's update FileItem if there is a change or it doesn't exist in current_files_map
+        match current_files_map.get(&normalised_file_name) {
+            None => {
+                // We need to add a new FileItem
+                if !upload_failed
+                    && add_or_update_file_item(
+                        safe,
+                        &local_file_name,
+                        &normalised_file_name,
+                        &file_path,
+                        &FileMeta::from_path(&local_file_name, follow_links)?,
+                        file_link,
+                        checksum,
+                        false,
+                        dry_run,
+                        &mut updated_files_map,
+                        &mut processed_files,
+                    )
+                    .await
+                {
+                    success_count += 1;
+                    if let Some(sync_state) = sync_state {
+                        sync_state.record(file_path, &updated_files_map[&normalised_file_name]);
+                    }
+
+                    // We remove self and any parent directories
+                    // from the current list so we know it has been processed
+                    let mut trail = Vec::<&str>::new();
+                    for part in normalised_file_name.split('/') {
+                        trail.push(part);
+                        let ancestor = if trail.len() > 1 {
+                            trail.join("/")
+                        } else {
+                            "/".to_string()
+                        };
+                        if ancestor != normalised_file_name {
+                            if let Some(fi) = current_files_map.get(&ancestor) {
+                                updated_files_map.insert(ancestor.clone(), fi.clone());
+                                current_files_map.remove(&ancestor);
+                            }
+                        }
+                    }
+                } else if !upload_failed {
+                    notify_add_error(progress, local_file_name, &processed_files);
+                }
+            }
+            Some(file_item) => {
+                let is_modified = prepared_file.is_modified;
+                if force || (compare_file_content && is_modified) {
+                    // We need to update the current FileItem
+                    if !upload_failed
+                        && add_or_update_file_item(
+                            safe,
+                            &local_file_name,
+                            &normalised_file_name,
+                            &file_path,
+                            &FileMeta::from_path(&local_file_name, follow_links)?,
+                            file_link,
+                            checksum,
+                            true,
+                            dry_run,
+                            &mut updated_files_map,
+                            &mut processed_files,
+                        )
+                        .await
+                    {
+                        success_count += 1;
+                        if let Some(new_file_item) =
+                            updated_files_map.get_mut(&normalised_file_name)
+                        {
+                            preserve_custom_metadata(file_item, new_file_item);
+                        }
+                        if let Some(sync_state) = sync_state {
+                            sync_state.record(file_path, &updated_files_map[&normalised_file_name]);
+                        }
+                    } else if !upload_failed {
+                        notify_add_error(progress, local_file_name, &processed_files);
+                    }
+                } else {
+                    // No need to update FileItem just copy the existing one
+                    updated_files_map.insert(normalised_file_name.to_string(), file_item.clone());
+
+                    if !force && !compare_file_content {
+                        let comp_str = if is_modified { "different" } else { "same" };
+                        processed_files.insert(
+                            local_file_name.to_string(),
+                            (
+                                CONTENT_ERROR_SIGN.to_string(),
+                                format!(
+                                    "File named \"{}\" with {} content already exists on target. Use the 'force' flag to replace it",
+                                    normalised_file_name, comp_str
+                                ),
+                            ),
+                        );
+                        info!("Skipping file \"{}\" since a file named \"{}\" with {} content already exists on target. You can use the 'force' flag to replace the existing file with the new one", local_file_name, normalised_file_name, comp_str);
+                    }
+                }
+
+                // let's now remove it from the current list so we now it has been processed
+                current_files_map.remove(&normalised_file_name);
+
+                // We also remove any parent directories
+                // from the current list, so they will not be deleted.
+                let mut trail = Vec::<&str>::new();
+                for part in normalised_file_name.split('/') {
+                    trail.push(part);
+                    let ancestor = if trail.len() > 1 {
+                        trail.join("/")
+                    } else {
+                        "/".to_string()
+                    };
+                    if ancestor != normalised_file_name {
+                        if let Some(fi) = current_files_map.get(&ancestor) {
+                            updated_files_map.insert(ancestor.clone(), fi.clone());
+                            current_files_map.remove(&ancestor);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Finally, unless 'delete' was set keep the files that are currently
+    // in FilesContainer but not in source location, nor those ignored by the filter
+    current_files_map.iter().for_each(|(file_name, file_item)| {
+        let is_ignored = path_filter.map_or(false, |path_filter| {
+            is_remote_path_ignored(path_filter, file_name, file_item, location, &dest_base_path)
+        });
+        if !delete || is_ignored {
+            updated_files_map.insert(file_name.to_string(), file_item.clone());
+        } else {
+            processed_files.insert(
+                file_name.to_string(),
+                (
+                    CONTENT_DELETED_SIGN.to_string(),
+                    // note: files have link property,
+                    //       dirs and symlinks do not
+                    file_item
+                        .get(PREDICATE_LINK)
+                        .unwrap_or(&String::default())
+                        .to_string(),
+                ),
+            );
+            notify_progress(progress, || FilesProgress::FileRemoved {
+                path: file_name.to_string(),
+            });
+            success_count += 1;
+        }
+    });
+
+    Ok((processed_files, updated_files_map, success_count))
+}
+
+fn version_conflict_error(expected_version: u64, latest_version: u64) -> Error {
+    Error::VersionConflict(format!(
+        "The FilesContainer was expected to be at version '{}' but it's at version '{}'",
+        expected_version, latest_version
+    ))
+}
+
+// Once an operation was cancelled, only the files which were uploaded until then are reported
+fn uploaded_files(processed_files: ProcessedFiles) -> ProcessedFiles {
+    processed_files
+        .into_iter()
+        .filter(|(_, (change, link))| {
+            (change == CONTENT_ADDED_SIGN || change == CONTENT_UPDATED_SIGN) && !link.is_empty()
+        })
+        .collect()
+}
+
+// Report to the progress observer, if any, the error found when adding a local file's FileItem
+fn notify_add_error(
+    progress: Option<&ProgressObserver>,
+    local_file_name: &str,
+    processed_files: &ProcessedFiles,
+) {
+    notify_progress(progress, || FilesProgress::FileErrored {
+        path: local_file_name.to_string(),
+        error: processed_files
+            .get(local_file_name)
+            .map(|(_, error)| error.to_string())
+            .unwrap_or_default(),
+    });
+}
+
+// Check if a path of the FilesContainer maps to a local path which is ignored by the filter,
+// i.e. if it's within the destination base path where the local location was synced to
+fn is_remote_path_ignored(
+    path_filter: &PathFilter,
+    file_name: &str,
+    file_item: &FileItem,
+    location: &str,
+    dest_base_path: &str,
+) -> bool {
+    let dest_base_path = format!(
+        "/{}",
+        normalise_path_separator(RelativePath::new(dest_base_path).normalize().as_str())
+    );
+    match Path::new(file_name).strip_prefix(&dest_base_path) {
+        Ok(relative_path) => {
+            let is_dir = file_item
+                .get(PREDICATE_TYPE)
+                .map_or(false, |file_type| FileMeta::filetype_is_dir(file_type));
+            path_filter.is_ignored(&Path::new(location).join(relative_path), is_dir)
+        }
+        Err(_) => false,
+    }
+}
+
+async fn files_map_add_link(
+    safe: &mut Safe,
+    mut files_map: FilesMap,
+    file_link: &str,
+    file_name: &str,
+    force: bool,
+) -> Result<(ProcessedFiles, FilesMap, u64)> {
+    let mut processed_files = ProcessedFiles::new();
+    let mut success_count = 0;
+    match SafeUrl::from_url(file_link) {
+        Err(err) => {
+            processed_files.insert(
+                file_link.to_string(),
+                (CONTENT_ERROR_SIGN.to_string(), format!("<{}>", err)),
+            );
+            info!("Skipping file \"{}\". {}", file_link, err);
+            Ok((processed_files, files_map, success_count))
+        }
+        Ok(safe_url) => {
+            let file_path = Path::new("");
+            let file_type = match safe_url.content_type() {
+                SafeContentType::MediaType(media_type) => media_type,
+                other => format!("{}", other),
+            };
+            let file_size = ""; // unknown
+
+            // Let's update FileItem if the link is different or it doesn't exist in the files_map
+            match files_map.get(file_name) {
+                Some(current_file_item) => {
+                    let mut file_meta = FileMeta::from_file_item(&current_file_item)?;
+                    file_meta.file_type = file_type;
+                    file_meta.file_size = file_size.to_string();
+
+                    let is_modified = if file_meta.is_file() {
+                        current_file_item.get(PREDICATE_LINK).map(String::as_str) != Some(file_link)
+                    } else {
+                        // directory: nothing to check.
+                        // symlink: TODO: check if sym-link path has changed.
+                        false
+                    };
+
+                    if is_modified {
+                        if force {
+                            if add_or_update_file_item(
+                                safe,
+                                file_name,
+                                file_name,
+                                &file_path,
+                                &file_meta,
+                                Some(file_link),
+                                None,
+                                true,
+                                true,
+                                &mut files_map,
+                                &mut processed_files,
+                            )
+                            .await
+                            {
+                                success_count += 1;
+                            }
+                        } else {
+                            processed_files.insert(file_name.to_string(), (CONTENT_ERROR_SIGN.to_string(), format!("File named \"{}\" already exists on target. Use the 'force' flag to replace it", file_name)));
+                            info!("Skipping file \"{}\" since a file with name \"{}\" already exists on target. You can use the 'force' flag to replace the existing file with the new one", file_link, file_name);
+                        }
+                    } else {
+                        processed_files.insert(
+                            file_link.to_string(),
+                            (
+                                CONTENT_ERROR_SIGN.to_string(),
+                                format!(
+                                    "File named \"{}\" already exists on target with same link",
+                                    file_name
+                                ),
+                            ),
+                        );
+                        info!("Skipping file \"{}\" since a file with name \"{}\" already exists on target with the same link", file_link, file_name);
+                    }
+                }
+                None => {
+                    if add_or_update_file_item(
+                        safe,
+                        file_name,
+                        file_name,
+                        &file_path,
+                        &FileMeta::from_type_and_size(&file_type, &file_size),
+                        Some(file_link),
+                        None,
+                        false,
+                        true,
+                        &mut files_map,
+                        &mut processed_files,
+                    )
+                    .await
+                    {
+                        success_count += 1;
+                    }
+                }
+            };
+
+            Ok((processed_files, files_map, success_count))
+        }
+    }
+}
+
+// Remove a path from the FilesMap provided
+fn files_map_remove_path(
+    dest_path: &str,
+    mut files_map: FilesMap,
+    recursive: bool,
+) -> Result<(ProcessedFiles, FilesMap, u64)> {
+    let mut processed_files = ProcessedFiles::default();
+    let (success_count, new_files_map) = if recursive {
+        let mut success_count = 0;
+        let mut new_files_map = FilesMap::default();
+        let folder_path = if !dest_path.ends_with('/') {
+            format!("{}/", dest_path)
+        } else {
+            dest_path.to_string()
+        };
+
+        files_map.iter().for_each(|(file_path, file_item)| {
+            // if the current file_path is a subfolder we remove it
+            if file_path.starts_with(&folder_path) {
+                processed_files.insert(
+                    file_path.to_string(),
+                    (
+                        CONTENT_DELETED_SIGN.to_string(),
+                        // note: files have link property,
+                        //       dirs and symlinks do not
+                        file_item
+                            .get(PREDICATE_LINK)
+                            .unwrap_or(&String::default())
+                            .to_string(),
+                    ),
+                );
+                success_count += 1;
+            } else {
+                new_files_map.insert(file_path.to_string(), file_item.clone());
+            }
+        });
+        (success_count, new_files_map)
+    } else {
+        let file_item = files_map
+            .remove(dest_path)
+            .ok_or_else(|| Error::ContentError(format!(
+                "No content found matching the \"{}\" path on the target FilesContainer. If you are trying to remove a folder rather than a file, you need to pass the 'recursive' flag",
+                dest_path
+            )))?;
+        processed_files.insert(
+            dest_path.to_string(),
+            (
+                CONTENT_DELETED_SIGN.to_string(),
+                // note: files have link property,
+                //       dirs and symlinks do not
+                file_item
+                    .get(PREDICATE_LINK)
+                    .unwrap_or(&String::default())
+                    .to_string(),
+            ),
+        );
+        (1, files_map)
+    };
+
+    Ok((processed_files, new_files_map, success_count))
+}
+
+// Add a directory FileItem to the FilesMap, and if `parents` is set also for any missing
+// parent directory. Any symlinks found in the path are resolved, and the path cannot go
+// through existing files. Paths with content under them are considered existing
+// directories even without a FileItem, as FilesContainers didn't always have them.
+fn files_map_mkdir(
+    dir_path: &str,
+    mut files_map: FilesMap,
+    parents: bool,
+) -> Result<(ProcessedFiles, FilesMap, u64)> {
+    let normalised_path = format!("/{}", dir_path.trim_matches('/'));
+    if normalised_path == "/" {
+        return Err(Error::InvalidInput(
+            "The root directory of a FilesContainer already exists".to_string(),
+        ));
+    }
+
+    let real_path = files_map.realpath(&normalised_path)?;
+    let components: Vec<&str> = real_path.split('/').filter(|c| !c.is_empty()).collect();
+    let mut processed_files = ProcessedFiles::default();
+    let mut success_count = 0;
+    let mut current_path = String::default();
+
+    for (index, component) in components.iter().enumerate() {
+        current_path.push('/');
+        current_path.push_str(component);
+        let is_last = index == components.len() - 1;
+
+        match files_map.get(&current_path) {
+            Some(file_item) => {
+                let file_type = file_item.getattr(PREDICATE_TYPE)?;
+                if !FileMeta::filetype_is_dir(file_type) {
+                    return Err(Error::InvalidInput(format!(
+                        "Cannot create directory \"{}\" since \"{}\" exists and it's not a directory",
+                        normalised_path, current_path
+                    )));
+                }
+                if is_last && !parents {
+                    return Err(Error::InvalidInput(format!(
+                        "Directory \"{}\" already exists",
+                        normalised_path
+                    )));
+                }
+            }
+            None => {
+                let has_content = files_map
+                    .keys()
+                    .any(|path| path.starts_with(&format!("{}/", current_path)));
+                if has_content {
+                    if is_last && !parents {
+                        return Err(Error::InvalidInput(format!(
+                            "Directory \"{}\" already exists",
+                            normalised_path
+                        )));
+                    }
+                } else if is_last || parents {
+                    let dir_item =
+                        FileMeta::from_type_and_size(MIMETYPE_FILESYSTEM_DIR, "0").to_file_item();
+                    files_map.insert(current_path.clone(), dir_item);
+                    processed_files.insert(
+                        current_path.clone(),
+                        (CONTENT_ADDED_SIGN.to_string(), String::default()),
+                    );
+                    success_count += 1;
+                } else {
+                    return Err(Error::ContentNotFound(format!(
+                        "Cannot create directory \"{}\" since parent \"{}\" doesn't exist. You can use the 'parents' flag to create it",
+                        normalised_path, current_path
+                    )));
+                }
+            }
+        }
+    }
+
+    Ok((processed_files, files_map, success_count))
+}
+
+// From the provided list of local files paths and corresponding files XOR-URLs,
+// create a FilesMap with file's metadata, their corresponding links and the
+// checksums of the files uploaded
+async fn files_map_create(
+    safe: &mut Safe,
+    mut content: &mut ProcessedFiles,
+    checksums: &UploadedChecksums,
+    location: &str,
+    dest_path: Option<&str>,
+    follow_links: bool,
+    dry_run: bool,
+) -> Result<FilesMap> {
+    let mut files_map = FilesMap::default();
+
+    let (location_base_path, dest_base_path) = get_base_paths(location, dest_path);
+
+    // We want to iterate over the BTreeMap and also modify it.
+    // We DON'T want to clone/dup the whole thing, might be very big.
+    // Rust doesn't allow that exactly, but we can get the keys
+    // to iterate over instead.  Cloning the keys isn't ideal
+    // either, but is much less data.  Is there a more efficient way?
+    let keys = content.keys().cloned().collect::<Vec<_>>();
+    for file_name in keys {
+        let (change, link) = &content[&file_name].clone();
+
+        if change == CONTENT_ERROR_SIGN {
+            continue;
+        }
+
+        let new_file_name = RelativePath::new(
+            &file_name
+                .to_string()
+                .replace(&location_base_path, &dest_base_path),
+        )
+        .normalize();
+
+        // Above normalize removes initial slash, and uses '\' if it's on Windows
+        // here, we trim any trailing '/', as it could be a filename.
+        let final_name = format!("/{}", normalise_path_separator(new_file_name.as_str()))
+            .trim_end_matches('/')
+            .to_string();
+
+        debug!("FileItem item name: {:?}", &file_name);
+
+        add_or_update_file_item(
+            safe,
+            &file_name,
+            &final_name,
+            &Path::new(&file_name),
+            &FileMeta::from_path(&file_name, follow_links)?,
+            if link.is_empty() { None } else { Some(&link) },
+            checksums.get(&file_name).map(String::as_str),
+            false,
+            dry_run,
+            &mut files_map,
+            &mut content,
+        )
+        .await;
+    }
+    Ok(files_map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::test_helpers::{
+            new_in_memory_safe_instance, new_safe_instance, new_temp_dir, random_nrs_name,
+        },
+        retry_loop, retry_loop_for_pattern, CancellationToken,
+    };
+    use anyhow::{anyhow, bail, Result};
+    use rand::{distributions::Alphanumeric, thread_rng, Rng};
+
+    // make some constants for these, in case entries in the
+    // testdata folder change.
+    const TESTDATA_PUT_FILEITEM_COUNT: usize = 11;
+    const TESTDATA_NO_SLASH_PUT_FILEITEM_COUNT: usize = 12;
+    const SUBFOLDER_PUT_FILEITEM_COUNT: usize = 2;
+    const SUBFOLDER_NO_SLASH_PUT_FILEITEM_COUNT: usize = 3;
+
+    #[tokio::test]
+    async fn test_files_map_create() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let mut processed_files = ProcessedFiles::new();
+        let first_xorurl = SafeUrl::from_url("safe://top_xorurl")?.to_xorurl_string();
+        let second_xorurl = SafeUrl::from_url("safe://second_xorurl")?.to_xorurl_string();
+
+        processed_files.insert(
+            "../testdata/test.md".to_string(),
+            (CONTENT_ADDED_SIGN.to_string(), first_xorurl.clone()),
+        );
+        processed_files.insert(
+            "../testdata/subfolder/subexists.md".to_string(),
+            (CONTENT_ADDED_SIGN.to_string(), second_xorurl.clone()),
+        );
+        let mut checksums = UploadedChecksums::new();
+        let _ = checksums.insert("../testdata/test.md".to_string(), "checksum".to_string());
+        let files_map = files_map_create(
+            &mut safe,
+            &mut processed_files,
+            &checksums,
+            "../testdata",
+            Some(""),
+            true,
+            false,
+        )
+        .await?;
+        assert_eq!(files_map.len(), 2);
+        let file_item1 = &files_map["/testdata/test.md"];
+        assert_eq!(file_item1[PREDICATE_LINK], first_xorurl);
+        assert_eq!(file_item1[PREDICATE_TYPE], "text/markdown");
+        assert_eq!(file_item1[PREDICATE_SIZE], "12");
+        assert_eq!(file_item1[PREDICATE_CHECKSUM], "checksum");
+
+        let file_item2 = &files_map["/testdata/subfolder/subexists.md"];
+        assert_eq!(file_item2[PREDICATE_LINK], second_xorurl);
+        assert_eq!(file_item2[PREDICATE_TYPE], "text/markdown");
+        assert_eq!(file_item2[PREDICATE_SIZE], "23");
+        assert!(!file_item2.contains_key(PREDICATE_CHECKSUM));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_create_empty() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create(None, None, false, false, false)
+            .await?;
+
+        assert!(xorurl.starts_with("safe://"));
+        assert_eq!(processed_files.len(), 0);
+        assert_eq!(files_map.len(), 0);
+
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+
+        // let's add a file
+        let (version, new_processed_files, new_files_map) = safe
+            .files_container_add("../testdata/test.md", &xorurl, false, false, false, false)
+            .await?;
+
+        assert_eq!(version, 1);
+        assert_eq!(new_processed_files.len(), 1);
+        assert_eq!(new_files_map.len(), 1);
+
+        let filename = "../testdata/test.md";
+        assert_eq!(new_processed_files[filename].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            new_processed_files[filename].1,
+            new_files_map["/test.md"][PREDICATE_LINK]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_store_pub_blob() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let random_blob_content: String =
+            thread_rng().sample_iter(&Alphanumeric).take(20).collect();
+
+        let file_xorurl = safe
+            .files_store_public_blob(random_blob_content.as_bytes(), None, false)
+            .await?;
+
+        let retrieved = retry_loop!(safe.files_get_public_blob(&file_xorurl, None));
+        assert_eq!(retrieved, random_blob_content.as_bytes());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_create_file() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let filename = "../testdata/test.md";
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create(Some(filename), None, false, false, false)
+            .await?;
+
+        assert!(xorurl.starts_with("safe://"));
+        assert_eq!(processed_files.len(), 1);
+        assert_eq!(files_map.len(), 1);
+        let file_path = "/test.md";
+        assert_eq!(processed_files[filename].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename].1,
+            files_map[file_path][PREDICATE_LINK]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_create_dry_run() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let filename = "../testdata/";
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create(Some(filename), None, true, false, true)
+            .await?;
+
+        assert!(xorurl.is_empty());
+        assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
+        assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
+
+        let filename1 = "../testdata/test.md";
+        assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
+        assert!(!processed_files[filename1].1.is_empty());
+        assert_eq!(
+            processed_files[filename1].1,
+            files_map["/test.md"][PREDICATE_LINK]
+        );
+
+        let filename2 = "../testdata/another.md";
+        assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
+        assert!(!processed_files[filename2].1.is_empty());
+        assert_eq!(
+            processed_files[filename2].1,
+            files_map["/another.md"][PREDICATE_LINK]
+        );
+
+        let filename3 = "../testdata/subfolder/subexists.md";
+        assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
+        assert!(!processed_files[filename3].1.is_empty());
+        assert_eq!(
+            processed_files[filename3].1,
+            files_map["/subfolder/subexists.md"][PREDICATE_LINK]
+        );
+
+        let filename4 = "../testdata/noextension";
+        assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
+        assert!(!processed_files[filename4].1.is_empty());
+        assert_eq!(
+            processed_files[filename4].1,
+            files_map["/noextension"][PREDICATE_LINK]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_create_folder_without_trailing_slash() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create(Some("../testdata"), None, true, true, false)
+            .await?;
+
+        assert!(xorurl.starts_with("safe://"));
+        assert_eq!(processed_files.len(), TESTDATA_NO_SLASH_PUT_FILEITEM_COUNT);
+        assert_eq!(files_map.len(), TESTDATA_NO_SLASH_PUT_FILEITEM_COUNT);
+
+        let filename1 = "../testdata/test.md";
+        assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename1].1,
+            files_map["/testdata/test.md"][PREDICATE_LINK]
+        );
+
+        let filename2 = "../testdata/another.md";
+        assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename2].1,
+            files_map["/testdata/another.md"][PREDICATE_LINK]
+        );
+
+        let filename3 = "../testdata/subfolder/subexists.md";
+        assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename3].1,
+            files_map["/testdata/subfolder/subexists.md"][PREDICATE_LINK]
+        );
+
+        let filename4 = "../testdata/noextension";
+        assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename4].1,
+            files_map["/testdata/noextension"][PREDICATE_LINK]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_create_folder_with_trailing_slash() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create(Some("../testdata/"), None, true, true, false)
+            .await?;
+
+        assert!(xorurl.starts_with("safe://"));
+        assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
+        assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
+
+        let filename1 = "../testdata/test.md";
+        assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename1].1,
+            files_map["/test.md"][PREDICATE_LINK]
+        );
+
+        let filename2 = "../testdata/another.md";
+        assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename2].1,
+            files_map["/another.md"][PREDICATE_LINK]
+        );
+
+        let filename3 = "../testdata/subfolder/subexists.md";
+        assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename3].1,
+            files_map["/subfolder/subexists.md"][PREDICATE_LINK]
+        );
+
+        let filename4 = "../testdata/noextension";
+        assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename4].1,
+            files_map["/noextension"][PREDICATE_LINK]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_create_dest_path_without_trailing_slash() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create(Some("../testdata"), Some("/myroot"), true, true, false)
+            .await?;
+
+        assert!(xorurl.starts_with("safe://"));
+        assert_eq!(processed_files.len(), TESTDATA_NO_SLASH_PUT_FILEITEM_COUNT);
+        assert_eq!(files_map.len(), TESTDATA_NO_SLASH_PUT_FILEITEM_COUNT);
+
+        let filename1 = "../testdata/test.md";
+        assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename1].1,
+            files_map["/myroot/test.md"][PREDICATE_LINK]
+        );
+
+        let filename2 = "../testdata/another.md";
+        assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename2].1,
+            files_map["/myroot/another.md"][PREDICATE_LINK]
+        );
+
+        let filename3 = "../testdata/subfolder/subexists.md";
+        assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename3].1,
+            files_map["/myroot/subfolder/subexists.md"][PREDICATE_LINK]
+        );
+
+        let filename4 = "../testdata/noextension";
+        assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename4].1,
+            files_map["/myroot/noextension"][PREDICATE_LINK]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_create_dest_path_with_trailing_slash() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create(Some("../testdata"), Some("/myroot/"), true, true, false)
+            .await?;
+
+        assert!(xorurl.starts_with("safe://"));
+        assert_eq!(processed_files.len(), TESTDATA_NO_SLASH_PUT_FILEITEM_COUNT);
+        assert_eq!(files_map.len(), TESTDATA_NO_SLASH_PUT_FILEITEM_COUNT);
+
+        let filename1 = "../testdata/test.md";
+        assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename1].1,
+            files_map["/myroot/testdata/test.md"][PREDICATE_LINK]
+        );
+
+        let filename2 = "../testdata/another.md";
+        assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename2].1,
+            files_map["/myroot/testdata/another.md"][PREDICATE_LINK]
+        );
+
+        let filename3 = "../testdata/subfolder/subexists.md";
+        assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename3].1,
+            files_map["/myroot/testdata/subfolder/subexists.md"][PREDICATE_LINK]
+        );
+
+        let filename4 = "../testdata/noextension";
+        assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename4].1,
+            files_map["/myroot/testdata/noextension"][PREDICATE_LINK]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_sync() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create(Some("../testdata/"), None, true, true, false)
+            .await?;
+
+        assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
+        assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
+
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+
+        let (version, new_processed_files, new_files_map) = safe
+            .files_container_sync(
+                "../testdata/subfolder/",
+                &xorurl,
+                true,
+                true,
+                false,
+                false,
+                false,
+            )
+            .await?;
+
+        assert_eq!(version, 1);
+        assert_eq!(new_processed_files.len(), 2);
+        assert_eq!(
+            new_files_map.len(),
+            TESTDATA_PUT_FILEITEM_COUNT + SUBFOLDER_PUT_FILEITEM_COUNT
+        );
+
+        let filename1 = "../testdata/test.md";
+        assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename1].1,
+            new_files_map["/test.md"][PREDICATE_LINK]
+        );
+
+        let filename2 = "../testdata/another.md";
+        assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename2].1,
+            new_files_map["/another.md"][PREDICATE_LINK]
         );
-        (1, files_map)
-    };
 
-    Ok((processed_files, new_files_map, success_count))
-}
+        let filename3 = "../testdata/subfolder/subexists.md";
+        assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename3].1,
+            new_files_map["/subfolder/subexists.md"][PREDICATE_LINK]
+        );
 
-// Upload a files to the Network as a Public Blob
-async fn upload_file_to_net(safe: &mut Safe, path: &Path, dry_run: bool) -> Result<XorUrl> {
-    let data = fs::read(path).map_err(|err| {
-        Error::InvalidInput(format!("Failed to read file from local location: {}", err))
-    })?;
+        let filename4 = "../testdata/noextension";
+        assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename4].1,
+            new_files_map["/noextension"][PREDICATE_LINK]
+        );
 
-    let mime_type = mime_guess::from_path(&path);
-    match safe
-        .files_store_public_blob(&data, mime_type.first_raw(), dry_run)
-        .await
-    {
-        Ok(xorurl) => Ok(xorurl),
-        Err(err) => {
-            // Let's then upload it and set media-type to be simply raw content
-            if let Error::InvalidMediaType(_) = err {
-                safe.files_store_public_blob(&data, None, dry_run).await
-            } else {
-                Err(err)
-            }
-        }
+        let filename5 = "../testdata/subfolder/subexists.md";
+        assert_eq!(new_processed_files[filename5].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            new_processed_files[filename5].1,
+            new_files_map["/subexists.md"][PREDICATE_LINK]
+        );
+
+        let filename6 = "../testdata/subfolder/sub2.md";
+        assert_eq!(new_processed_files[filename6].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            new_processed_files[filename6].1,
+            new_files_map["/sub2.md"][PREDICATE_LINK]
+        );
+
+        Ok(())
     }
-}
 
-// From the provided list of local files paths and corresponding files XOR-URLs,
-// create a FilesMap with file's metadata and their corresponding links
-async fn files_map_create(
-    safe: &mut Safe,
-    mut content: &mut ProcessedFiles,
-    location: &str,
-    dest_path: Option<&str>,
-    follow_links: bool,
-    dry_run: bool,
-) -> Result<FilesMap> {
-    let mut files_map = FilesMap::default();
+    #[tokio::test]
+    async fn test_files_container_sync_dry_run() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create(Some("../testdata/"), None, true, true, false)
+            .await?;
 
-    let (location_base_path, dest_base_path) = get_base_paths(location, dest_path);
+        assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
+        assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
 
-    // We want to iterate over the BTreeMap and also modify it.
-    // We DON'T want to clone/dup the whole thing, might be very big.
-    // Rust doesn't allow that exactly, but we can get the keys
-    // to iterate over instead.  Cloning the keys isn't ideal
-    // either, but is much less data.  Is there a more efficient way?
-    let keys = content.keys().cloned().collect::<Vec<_>>();
-    for file_name in keys {
-        let (change, link) = &content[&file_name].clone();
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
 
-        if change == CONTENT_ERROR_SIGN {
-            continue;
-        }
+        let (version, new_processed_files, new_files_map) = safe
+            .files_container_sync(
+                "../testdata/subfolder/",
+                &xorurl,
+                true,
+                true,
+                false,
+                false,
+                true, // set dry_run flag on
+            )
+            .await?;
 
-        let new_file_name = RelativePath::new(
-            &file_name
-                .to_string()
-                .replace(&location_base_path, &dest_base_path),
-        )
-        .normalize();
+        assert_eq!(version, 1);
+        assert_eq!(new_processed_files.len(), 2);
+        assert_eq!(
+            new_files_map.len(),
+            TESTDATA_PUT_FILEITEM_COUNT + SUBFOLDER_PUT_FILEITEM_COUNT
+        );
 
-        // Above normalize removes initial slash, and uses '\' if it's on Windows
-        // here, we trim any trailing '/', as it could be a filename.
-        let final_name = format!("/{}", normalise_path_separator(new_file_name.as_str()))
-            .trim_end_matches('/')
-            .to_string();
+        let filename1 = "../testdata/test.md";
+        assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename1].1,
+            new_files_map["/test.md"][PREDICATE_LINK]
+        );
 
-        debug!("FileItem item name: {:?}", &file_name);
+        let filename2 = "../testdata/another.md";
+        assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename2].1,
+            new_files_map["/another.md"][PREDICATE_LINK]
+        );
 
-        add_or_update_file_item(
-            safe,
-            &file_name,
-            &final_name,
-            &Path::new(&file_name),
-            &FileMeta::from_path(&file_name, follow_links)?,
-            if link.is_empty() { None } else { Some(&link) },
-            false,
-            dry_run,
-            &mut files_map,
-            &mut content,
-        )
-        .await;
-    }
-    Ok(files_map)
-}
+        let filename3 = "../testdata/subfolder/subexists.md";
+        assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename3].1,
+            new_files_map["/subfolder/subexists.md"][PREDICATE_LINK]
+        );
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{
-        app::test_helpers::{new_safe_instance, random_nrs_name},
-        retry_loop, retry_loop_for_pattern,
-    };
-    use anyhow::{anyhow, bail, Result};
-    use rand::{distributions::Alphanumeric, thread_rng, Rng};
+        let filename4 = "../testdata/noextension";
+        assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename4].1,
+            new_files_map["/noextension"][PREDICATE_LINK]
+        );
 
-    // make some constants for these, in case entries in the
-    // testdata folder change.
-    const TESTDATA_PUT_FILEITEM_COUNT: usize = 11;
-    const TESTDATA_NO_SLASH_PUT_FILEITEM_COUNT: usize = 12;
-    const SUBFOLDER_PUT_FILEITEM_COUNT: usize = 2;
-    const SUBFOLDER_NO_SLASH_PUT_FILEITEM_COUNT: usize = 3;
+        let filename5 = "../testdata/subfolder/subexists.md";
+        assert_eq!(new_processed_files[filename5].0, CONTENT_ADDED_SIGN);
+        assert!(!new_processed_files[filename5].1.is_empty());
+        assert_eq!(
+            new_processed_files[filename5].1,
+            new_files_map["/subexists.md"][PREDICATE_LINK]
+        );
+
+        let filename6 = "../testdata/subfolder/sub2.md";
+        assert_eq!(new_processed_files[filename6].0, CONTENT_ADDED_SIGN);
+        assert!(!new_processed_files[filename6].1.is_empty());
+        assert_eq!(
+            new_processed_files[filename6].1,
+            new_files_map["/sub2.md"][PREDICATE_LINK]
+        );
+
+        Ok(())
+    }
 
     #[tokio::test]
-    async fn test_files_map_create() -> Result<()> {
+    async fn test_files_container_sync_same_size() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let mut processed_files = ProcessedFiles::new();
-        let first_xorurl = SafeUrl::from_url("safe://top_xorurl")?.to_xorurl_string();
-        let second_xorurl = SafeUrl::from_url("safe://second_xorurl")?.to_xorurl_string();
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create(Some("../testdata/test.md"), None, false, false, false)
+            .await?;
 
-        processed_files.insert(
-            "../testdata/test.md".to_string(),
-            (CONTENT_ADDED_SIGN.to_string(), first_xorurl.clone()),
+        assert_eq!(processed_files.len(), 1);
+        assert_eq!(files_map.len(), 1);
+
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+
+        let (version, new_processed_files, new_files_map) = safe
+            .files_container_sync(
+                "../testdata/.subhidden/test.md",
+                &xorurl,
+                false,
+                false,
+                false,
+                false,
+                false,
+            )
+            .await?;
+
+        assert_eq!(version, 1);
+        assert_eq!(new_processed_files.len(), 1);
+        assert_eq!(new_files_map.len(), 1);
+
+        let filename1 = "../testdata/test.md";
+        assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            processed_files[filename1].1,
+            files_map["/test.md"][PREDICATE_LINK]
         );
-        processed_files.insert(
-            "../testdata/subfolder/subexists.md".to_string(),
-            (CONTENT_ADDED_SIGN.to_string(), second_xorurl.clone()),
+        let filename2 = "../testdata/.subhidden/test.md";
+        assert_eq!(new_processed_files[filename2].0, CONTENT_UPDATED_SIGN);
+        assert_eq!(
+            new_processed_files[filename2].1,
+            new_files_map["/test.md"][PREDICATE_LINK]
         );
-        let files_map = files_map_create(
-            &mut safe,
-            &mut processed_files,
-            "../testdata",
-            Some(""),
-            true,
-            false,
-        )
-        .await?;
-        assert_eq!(files_map.len(), 2);
-        let file_item1 = &files_map["/testdata/test.md"];
-        assert_eq!(file_item1[PREDICATE_LINK], first_xorurl);
-        assert_eq!(file_item1[PREDICATE_TYPE], "text/markdown");
-        assert_eq!(file_item1[PREDICATE_SIZE], "12");
 
-        let file_item2 = &files_map["/testdata/subfolder/subexists.md"];
-        assert_eq!(file_item2[PREDICATE_LINK], second_xorurl);
-        assert_eq!(file_item2[PREDICATE_TYPE], "text/markdown");
-        assert_eq!(file_item2[PREDICATE_SIZE], "23");
-        Ok(())
+        // check sizes are the same but links are different
+        assert_eq!(
+            files_map["/test.md"][PREDICATE_SIZE],
+            new_files_map["/test.md"][PREDICATE_SIZE]
+        );
+        assert_ne!(
+            files_map["/test.md"][PREDICATE_LINK],
+            new_files_map["/test.md"][PREDICATE_LINK]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_sync_with_versioned_target() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, _, _) = safe
+            .files_container_create(Some("../testdata/"), None, true, true, false)
+            .await?;
+
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+
+        let versioned_xorurl = format!("{}?v=5", xorurl);
+        match safe
+            .files_container_sync(
+                "../testdata/subfolder/",
+                &versioned_xorurl,
+                false,
+                false,
+                false,
+                true, // this flag requests the update-nrs
+                false,
+            )
+            .await
+        {
+            Ok(_) => Err(anyhow!("Sync was unexpectedly successful".to_string(),)),
+            Err(Error::InvalidInput(msg)) => {
+                assert_eq!(
+                    msg,
+                    format!(
+                        "The target URL cannot contain a version: {}",
+                        versioned_xorurl
+                    )
+                );
+                Ok(())
+            }
+            other => Err(anyhow!(
+                "Error returned is not the expected one: {:?}",
+                other
+            )),
+        }
     }
 
     #[tokio::test]
-    async fn test_files_container_create_empty() -> Result<()> {
+    async fn test_files_container_sync_with_delete() -> Result<()> {
         let mut safe = new_safe_instance().await?;
         let (xorurl, processed_files, files_map) = safe
-            .files_container_create(None, None, false, false, false)
+            .files_container_create(Some("../testdata/"), None, true, true, false)
             .await?;
 
-        assert!(xorurl.starts_with("safe://"));
-        assert_eq!(processed_files.len(), 0);
-        assert_eq!(files_map.len(), 0);
+        assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
+        assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
 
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
-        // let's add a file
         let (version, new_processed_files, new_files_map) = safe
-            .files_container_add("../testdata/test.md", &xorurl, false, false, false, false)
+            .files_container_sync(
+                "../testdata/subfolder/",
+                &xorurl,
+                true,
+                false,
+                true, // this sets the delete flag
+                false,
+                false,
+            )
             .await?;
 
         assert_eq!(version, 1);
-        assert_eq!(new_processed_files.len(), 1);
-        assert_eq!(new_files_map.len(), 1);
+        assert_eq!(
+            new_processed_files.len(),
+            TESTDATA_PUT_FILEITEM_COUNT + SUBFOLDER_PUT_FILEITEM_COUNT
+        );
+        assert_eq!(new_files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
 
-        let filename = "../testdata/test.md";
-        assert_eq!(new_processed_files[filename].0, CONTENT_ADDED_SIGN);
+        // first check all previous files were removed
+        let file_path1 = "/test.md";
+        assert_eq!(new_processed_files[file_path1].0, CONTENT_DELETED_SIGN);
         assert_eq!(
-            new_processed_files[filename].1,
-            new_files_map["/test.md"][PREDICATE_LINK]
+            new_processed_files[file_path1].1,
+            files_map[file_path1][PREDICATE_LINK]
+        );
+
+        let file_path2 = "/another.md";
+        assert_eq!(new_processed_files[file_path2].0, CONTENT_DELETED_SIGN);
+        assert_eq!(
+            new_processed_files[file_path2].1,
+            files_map[file_path2][PREDICATE_LINK]
+        );
+
+        let file_path3 = "/subfolder/subexists.md";
+        assert_eq!(new_processed_files[file_path3].0, CONTENT_DELETED_SIGN);
+        assert_eq!(
+            new_processed_files[file_path3].1,
+            files_map[file_path3][PREDICATE_LINK]
+        );
+
+        let file_path4 = "/noextension";
+        assert_eq!(new_processed_files[file_path4].0, CONTENT_DELETED_SIGN);
+        assert_eq!(
+            new_processed_files[file_path4].1,
+            files_map[file_path4][PREDICATE_LINK]
+        );
+
+        // and finally check the synced file was added
+        let filename5 = "../testdata/subfolder/subexists.md";
+        assert_eq!(new_processed_files[filename5].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            new_processed_files[filename5].1,
+            new_files_map["/subexists.md"][PREDICATE_LINK]
         );
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_store_pub_blob() -> Result<()> {
+    async fn test_files_container_sync_delete_without_recursive() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let random_blob_content: String =
-            thread_rng().sample_iter(&Alphanumeric).take(20).collect();
+        match safe
+            .files_container_sync(
+                "../testdata/subfolder/",
+                "some-url",
+                false, // this sets the recursive flag to off
+                false, // do not follow links
+                true,  // this sets the delete flag
+                false,
+                false,
+            )
+            .await
+        {
+            Ok(_) => Err(anyhow!("Sync was unexpectedly successful".to_string(),)),
+            Err(Error::InvalidInput(msg)) => {
+                assert_eq!(
+                    msg,
+                    "'delete' is not allowed if 'recursive' is not set".to_string()
+                );
+                Ok(())
+            }
+            other => Err(anyhow!(
+                "Error returned is not the expected one: {:?}",
+                other
+            )),
+        }
+    }
 
-        let file_xorurl = safe
-            .files_store_public_blob(random_blob_content.as_bytes(), None, false)
+    #[tokio::test]
+    async fn test_files_container_sync_update_nrs_unversioned_link() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, _, _) = safe
+            .files_container_create(Some("../testdata/"), None, true, true, false)
             .await?;
 
-        let retrieved = retry_loop!(safe.files_get_public_blob(&file_xorurl, None));
-        assert_eq!(retrieved, random_blob_content.as_bytes());
-
-        Ok(())
+        let nrsurl = random_nrs_name();
+        let mut safe_url = SafeUrl::from_url(&xorurl)?;
+        safe_url.set_content_version(None);
+        let unversioned_link = safe_url.to_string();
+        match safe
+            .nrs_map_container_create(&nrsurl, &unversioned_link, false, true, false)
+            .await
+        {
+            Ok(_) => Err(anyhow!(
+                "NRS create was unexpectedly successful".to_string(),
+            )),
+            Err(Error::InvalidInput(msg)) => {
+                assert_eq!(
+                msg,
+                format!(
+                    "The linked content (FilesContainer) is versionable, therefore NRS requires the link to specify a version: \"{}\"",
+                    unversioned_link
+                )
+            );
+                Ok(())
+            }
+            other => Err(anyhow!(
+                "Error returned is not the expected one: {:?}",
+                other
+            )),
+        }
     }
 
     #[tokio::test]
-    async fn test_files_container_create_file() -> Result<()> {
+    async fn test_files_container_sync_update_nrs_with_xorurl() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let filename = "../testdata/test.md";
-        let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some(filename), None, false, false, false)
+        let (xorurl, _, _) = safe
+            .files_container_create(Some("../testdata/"), None, true, true, false)
             .await?;
 
-        assert!(xorurl.starts_with("safe://"));
-        assert_eq!(processed_files.len(), 1);
-        assert_eq!(files_map.len(), 1);
-        let file_path = "/test.md";
-        assert_eq!(processed_files[filename].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            processed_files[filename].1,
-            files_map[file_path][PREDICATE_LINK]
-        );
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
 
-        Ok(())
+        match safe
+            .files_container_sync(
+                "../testdata/subfolder/",
+                &xorurl,
+                false,
+                false,
+                false,
+                true, // this flag requests the update-nrs
+                false,
+            )
+            .await
+        {
+            Ok(_) => Err(anyhow!("Sync was unexpectedly successful".to_string(),)),
+            Err(Error::InvalidInput(msg)) => {
+                assert_eq!(
+                    msg,
+                    "'update-nrs' is not allowed since the URL provided is not an NRS URL"
+                        .to_string()
+                );
+                Ok(())
+            }
+            other => Err(anyhow!(
+                "Error returned is not the expected one: {:?}",
+                other
+            )),
+        }
     }
 
     #[tokio::test]
-    async fn test_files_container_create_dry_run() -> Result<()> {
+    async fn test_files_container_sync_update_nrs_versioned_link() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let filename = "../testdata/";
-        let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some(filename), None, true, false, true)
+        let (xorurl, _, _) = safe
+            .files_container_create(Some("../testdata/"), None, true, true, false)
             .await?;
 
-        assert!(xorurl.is_empty());
-        assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
-        assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
 
-        let filename1 = "../testdata/test.md";
-        assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
-        assert!(!processed_files[filename1].1.is_empty());
-        assert_eq!(
-            processed_files[filename1].1,
-            files_map["/test.md"][PREDICATE_LINK]
-        );
+        let nrsurl = random_nrs_name();
+        let mut safe_url = SafeUrl::from_url(&xorurl)?;
+        safe_url.set_content_version(Some(0));
+        let (nrs_xorurl, _, _) = safe
+            .nrs_map_container_create(&nrsurl, &safe_url.to_string(), false, true, false)
+            .await?;
 
-        let filename2 = "../testdata/another.md";
-        assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
-        assert!(!processed_files[filename2].1.is_empty());
-        assert_eq!(
-            processed_files[filename2].1,
-            files_map["/another.md"][PREDICATE_LINK]
-        );
+        let _ = retry_loop!(safe.fetch(&nrs_xorurl, None));
 
-        let filename3 = "../testdata/subfolder/subexists.md";
-        assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
-        assert!(!processed_files[filename3].1.is_empty());
-        assert_eq!(
-            processed_files[filename3].1,
-            files_map["/subfolder/subexists.md"][PREDICATE_LINK]
-        );
+        let _ = safe
+            .files_container_sync(
+                "../testdata/subfolder/",
+                &nrsurl,
+                false,
+                false,
+                false,
+                true, // this flag requests the update-nrs
+                false,
+            )
+            .await?;
 
-        let filename4 = "../testdata/noextension";
-        assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
-        assert!(!processed_files[filename4].1.is_empty());
-        assert_eq!(
-            processed_files[filename4].1,
-            files_map["/noextension"][PREDICATE_LINK]
-        );
+        let mut safe_url = SafeUrl::from_url(&xorurl)?;
+        safe_url.set_content_version(Some(1));
+        let (new_link, _) = safe.parse_and_resolve_url(&nrsurl).await?;
+        assert_eq!(new_link.to_string(), safe_url.to_string());
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_create_folder_without_trailing_slash() -> Result<()> {
+    async fn test_files_container_sync_target_path_without_trailing_slash() -> Result<()> {
         let mut safe = new_safe_instance().await?;
         let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata"), None, true, true, false)
+            .files_container_create(Some("../testdata/"), None, true, true, false)
             .await?;
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
 
-        assert!(xorurl.starts_with("safe://"));
-        assert_eq!(processed_files.len(), TESTDATA_NO_SLASH_PUT_FILEITEM_COUNT);
-        assert_eq!(files_map.len(), TESTDATA_NO_SLASH_PUT_FILEITEM_COUNT);
+        assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
+        assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
+        let mut safe_url = SafeUrl::from_url(&xorurl)?;
+        safe_url.set_path("path/when/sync");
+        let (version, new_processed_files, new_files_map) = safe
+            .files_container_sync(
+                "../testdata/subfolder",
+                &safe_url.to_string(),
+                true,
+                false,
+                false,
+                false,
+                false,
+            )
+            .await?;
+
+        assert_eq!(version, 1);
+        assert_eq!(
+            new_processed_files.len(),
+            SUBFOLDER_NO_SLASH_PUT_FILEITEM_COUNT
+        );
+        assert_eq!(
+            new_files_map.len(),
+            TESTDATA_PUT_FILEITEM_COUNT + SUBFOLDER_NO_SLASH_PUT_FILEITEM_COUNT
+        );
 
         let filename1 = "../testdata/test.md";
         assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename1].1,
-            files_map["/testdata/test.md"][PREDICATE_LINK]
+            new_files_map["/test.md"][PREDICATE_LINK]
         );
 
         let filename2 = "../testdata/another.md";
         assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename2].1,
-            files_map["/testdata/another.md"][PREDICATE_LINK]
+            new_files_map["/another.md"][PREDICATE_LINK]
         );
 
         let filename3 = "../testdata/subfolder/subexists.md";
         assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename3].1,
-            files_map["/testdata/subfolder/subexists.md"][PREDICATE_LINK]
+            new_files_map["/subfolder/subexists.md"][PREDICATE_LINK]
         );
 
         let filename4 = "../testdata/noextension";
         assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename4].1,
-            files_map["/testdata/noextension"][PREDICATE_LINK]
+            new_files_map["/noextension"][PREDICATE_LINK]
+        );
+
+        // and finally check the synced file is there
+        let filename5 = "../testdata/subfolder/subexists.md";
+        assert_eq!(new_processed_files[filename5].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            new_processed_files[filename5].1,
+            new_files_map["/path/when/sync/subexists.md"][PREDICATE_LINK]
         );
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_create_folder_with_trailing_slash() -> Result<()> {
+    async fn test_files_container_sync_target_path_with_trailing_slash() -> Result<()> {
         let mut safe = new_safe_instance().await?;
         let (xorurl, processed_files, files_map) = safe
             .files_container_create(Some("../testdata/"), None, true, true, false)
             .await?;
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
 
-        assert!(xorurl.starts_with("safe://"));
         assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
         assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
+        let mut safe_url = SafeUrl::from_url(&xorurl)?;
+        safe_url.set_path("/path/when/sync/");
+        let (version, new_processed_files, new_files_map) = safe
+            .files_container_sync(
+                "../testdata/subfolder",
+                &safe_url.to_string(),
+                true,
+                false,
+                false,
+                false,
+                false,
+            )
+            .await?;
+
+        assert_eq!(version, 1);
+        assert_eq!(
+            new_processed_files.len(),
+            SUBFOLDER_NO_SLASH_PUT_FILEITEM_COUNT
+        );
+        assert_eq!(
+            new_files_map.len(),
+            TESTDATA_PUT_FILEITEM_COUNT + SUBFOLDER_NO_SLASH_PUT_FILEITEM_COUNT
+        );
 
         let filename1 = "../testdata/test.md";
         assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename1].1,
-            files_map["/test.md"][PREDICATE_LINK]
+            new_files_map["/test.md"][PREDICATE_LINK]
         );
 
         let filename2 = "../testdata/another.md";
         assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename2].1,
-            files_map["/another.md"][PREDICATE_LINK]
+            new_files_map["/another.md"][PREDICATE_LINK]
         );
 
         let filename3 = "../testdata/subfolder/subexists.md";
         assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename3].1,
-            files_map["/subfolder/subexists.md"][PREDICATE_LINK]
+            new_files_map["/subfolder/subexists.md"][PREDICATE_LINK]
         );
 
         let filename4 = "../testdata/noextension";
         assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename4].1,
-            files_map["/noextension"][PREDICATE_LINK]
+            new_files_map["/noextension"][PREDICATE_LINK]
+        );
+
+        // and finally check the synced file is there
+        let filename5 = "../testdata/subfolder/subexists.md";
+        assert_eq!(new_processed_files[filename5].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            new_processed_files[filename5].1,
+            new_files_map["/path/when/sync/subfolder/subexists.md"][PREDICATE_LINK]
         );
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_create_dest_path_without_trailing_slash() -> Result<()> {
+    async fn test_files_container_get() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata"), Some("/myroot"), true, true, false)
+        let (xorurl, _, files_map) = safe
+            .files_container_create(Some("../testdata/"), None, true, true, false)
             .await?;
 
-        assert!(xorurl.starts_with("safe://"));
-        assert_eq!(processed_files.len(), TESTDATA_NO_SLASH_PUT_FILEITEM_COUNT);
-        assert_eq!(files_map.len(), TESTDATA_NO_SLASH_PUT_FILEITEM_COUNT);
+        let (version, fetched_files_map) = retry_loop!(safe.files_container_get(&xorurl));
 
-        let filename1 = "../testdata/test.md";
-        assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
+        assert_eq!(version, 0);
+        assert_eq!(fetched_files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
+        assert_eq!(files_map.len(), fetched_files_map.len());
+        assert_eq!(files_map["/test.md"], fetched_files_map["/test.md"]);
+        assert_eq!(files_map["/another.md"], fetched_files_map["/another.md"]);
         assert_eq!(
-            processed_files[filename1].1,
-            files_map["/myroot/test.md"][PREDICATE_LINK]
+            files_map["/subfolder/subexists.md"],
+            fetched_files_map["/subfolder/subexists.md"]
         );
+        assert_eq!(files_map["/noextension"], fetched_files_map["/noextension"]);
 
-        let filename2 = "../testdata/another.md";
-        assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            processed_files[filename2].1,
-            files_map["/myroot/another.md"][PREDICATE_LINK]
-        );
+        Ok(())
+    }
 
-        let filename3 = "../testdata/subfolder/subexists.md";
-        assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            processed_files[filename3].1,
-            files_map["/myroot/subfolder/subexists.md"][PREDICATE_LINK]
-        );
+    #[tokio::test]
+    async fn test_files_container_version() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, _, _) = safe
+            .files_container_create(Some("../testdata/"), None, true, true, false)
+            .await?;
 
-        let filename4 = "../testdata/noextension";
-        assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            processed_files[filename4].1,
-            files_map["/myroot/noextension"][PREDICATE_LINK]
-        );
+        let (version, _) = retry_loop!(safe.files_container_get(&xorurl));
+        assert_eq!(version, 0);
+
+        let (version, _, _) = safe
+            .files_container_sync(
+                "../testdata/subfolder/",
+                &xorurl,
+                true,
+                false,
+                true, // this sets the delete flag,
+                false,
+                false,
+            )
+            .await?;
+        assert_eq!(version, 1);
+
+        let mut safe_url = SafeUrl::from_url(&xorurl)?;
+        safe_url.set_content_version(None);
+        let (version, _) = retry_loop_for_pattern!(safe
+            .files_container_get(&safe_url.to_string()), Ok((version, _)) if *version == 1)?;
+        assert_eq!(version, 1);
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_create_dest_path_with_trailing_slash() -> Result<()> {
+    async fn test_files_container_get_with_version() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata"), Some("/myroot/"), true, true, false)
+        let (xorurl, _processed_files, files_map) = safe
+            .files_container_create(Some("../testdata/"), None, true, true, false)
             .await?;
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
 
-        assert!(xorurl.starts_with("safe://"));
-        assert_eq!(processed_files.len(), TESTDATA_NO_SLASH_PUT_FILEITEM_COUNT);
-        assert_eq!(files_map.len(), TESTDATA_NO_SLASH_PUT_FILEITEM_COUNT);
+        // let's create a new version of the files container
+        let (_version, _new_processed_files, new_files_map) = safe
+            .files_container_sync(
+                "../testdata/subfolder/",
+                &xorurl,
+                true,
+                false,
+                true, // this sets the delete flag
+                false,
+                false,
+            )
+            .await?;
 
-        let filename1 = "../testdata/test.md";
-        assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            processed_files[filename1].1,
-            files_map["/myroot/testdata/test.md"][PREDICATE_LINK]
-        );
+        // let's fetch version 0
+        let mut safe_url = SafeUrl::from_url(&xorurl)?;
+        safe_url.set_content_version(Some(0));
+        let (version, v0_files_map) = safe.files_container_get(&safe_url.to_string()).await?;
 
-        let filename2 = "../testdata/another.md";
-        assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
+        assert_eq!(version, 0);
+        assert_eq!(files_map, v0_files_map);
+        // let's check that one of the files in v1 is still there
+        let file_path1 = "/test.md";
         assert_eq!(
-            processed_files[filename2].1,
-            files_map["/myroot/testdata/another.md"][PREDICATE_LINK]
+            files_map[file_path1][PREDICATE_LINK],
+            v0_files_map[file_path1][PREDICATE_LINK]
         );
 
-        let filename3 = "../testdata/subfolder/subexists.md";
-        assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            processed_files[filename3].1,
-            files_map["/myroot/testdata/subfolder/subexists.md"][PREDICATE_LINK]
-        );
+        // let's fetch version 1
+        safe_url.set_content_version(Some(1));
+        let (version, v1_files_map) = retry_loop_for_pattern!(safe
+                .files_container_get(&safe_url.to_string()), Ok((version, _)) if *version == 1)?;
+
+        assert_eq!(version, 1);
+        assert_eq!(new_files_map, v1_files_map);
+        // let's check that some of the files are no in v2 anymore
+        let file_path2 = "/another.md";
+        let file_path3 = "/subfolder/subexists.md";
+        let file_path4 = "/noextension";
+        assert!(v1_files_map.get(file_path1).is_none());
+        assert!(v1_files_map.get(file_path2).is_none());
+        assert!(v1_files_map.get(file_path3).is_none());
+        assert!(v1_files_map.get(file_path4).is_none());
+
+        // let's fetch version 2 (invalid)
+        safe_url.set_content_version(Some(2));
+        match safe.files_container_get(&safe_url.to_string()).await {
+            Ok(_) => Err(anyhow!(
+                "Unexpectedly retrieved verion 2 of container".to_string(),
+            )),
+            Err(Error::VersionNotFound(msg)) => {
+                assert_eq!(
+                    msg,
+                    format!(
+                        "Version '2' is invalid for FilesContainer found at \"{}\"",
+                        safe_url
+                    )
+                );
+                Ok(())
+            }
+            other => Err(anyhow!(
+                "Error returned is not the expected one: {:?}",
+                other
+            )),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_files_container_create_get_empty_folder() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, _processed_files, files_map) = safe
+            .files_container_create(Some("../testdata/"), None, true, true, false)
+            .await?;
 
-        let filename4 = "../testdata/noextension";
-        assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
+        let (_, files_map_get) = retry_loop!(safe.files_container_get(&xorurl.to_string()));
+
+        assert_eq!(files_map, files_map_get);
+        assert_eq!(files_map_get["/emptyfolder"], files_map["/emptyfolder"]);
         assert_eq!(
-            processed_files[filename4].1,
-            files_map["/myroot/testdata/noextension"][PREDICATE_LINK]
+            files_map_get["/emptyfolder"]["type"],
+            MIMETYPE_FILESYSTEM_DIR
         );
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_sync() -> Result<()> {
+    async fn test_files_container_sync_with_nrs_url() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
+        let (xorurl, _, _) = safe
+            .files_container_create(Some("../testdata/test.md"), None, false, true, false)
             .await?;
-
-        assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
-        assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
-
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
-        let (version, new_processed_files, new_files_map) = safe
+        let nrsurl = random_nrs_name();
+        let mut safe_url = SafeUrl::from_url(&xorurl)?;
+        safe_url.set_content_version(Some(0));
+        let (nrs_xorurl, _, _) = safe
+            .nrs_map_container_create(&nrsurl, &safe_url.to_string(), false, true, false)
+            .await?;
+        let _ = retry_loop!(safe.fetch(&nrs_xorurl, None));
+
+        let _ = safe
             .files_container_sync(
                 "../testdata/subfolder/",
                 &xorurl,
-                true,
-                true,
+                false,
+                false,
                 false,
                 false,
                 false,
             )
             .await?;
 
-        assert_eq!(version, 1);
-        assert_eq!(new_processed_files.len(), 2);
-        assert_eq!(
-            new_files_map.len(),
-            TESTDATA_PUT_FILEITEM_COUNT + SUBFOLDER_PUT_FILEITEM_COUNT
-        );
-
-        let filename1 = "../testdata/test.md";
-        assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            processed_files[filename1].1,
-            new_files_map["/test.md"][PREDICATE_LINK]
-        );
-
-        let filename2 = "../testdata/another.md";
-        assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            processed_files[filename2].1,
-            new_files_map["/another.md"][PREDICATE_LINK]
-        );
-
-        let filename3 = "../testdata/subfolder/subexists.md";
-        assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            processed_files[filename3].1,
-            new_files_map["/subfolder/subexists.md"][PREDICATE_LINK]
-        );
-
-        let filename4 = "../testdata/noextension";
-        assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            processed_files[filename4].1,
-            new_files_map["/noextension"][PREDICATE_LINK]
-        );
-
-        let filename5 = "../testdata/subfolder/subexists.md";
-        assert_eq!(new_processed_files[filename5].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            new_processed_files[filename5].1,
-            new_files_map["/subexists.md"][PREDICATE_LINK]
-        );
+        let _ = safe
+            .files_container_sync(
+                "../testdata/",
+                &nrsurl,
+                false,
+                false,
+                false,
+                true, // this flag requests the update-nrs
+                false,
+            )
+            .await?;
 
-        let filename6 = "../testdata/subfolder/sub2.md";
-        assert_eq!(new_processed_files[filename6].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            new_processed_files[filename6].1,
-            new_files_map["/sub2.md"][PREDICATE_LINK]
-        );
+        // now it should look like:
+        // safe://<nrs>
+        // ├── .hidden.txt
+        // ├── another.md
+        // ├── noextension
+        // ├── sub2.md
+        // ├── subexists.md
+        // └── test.md
+        //
+        // So, we have 6 items.
+        let (version, fetched_files_map) = retry_loop_for_pattern!(safe.files_container_get(&xorurl), Ok((version, _)) if *version == 2)?;
+        assert_eq!(version, 2);
+        assert_eq!(fetched_files_map.len(), 6);
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_sync_dry_run() -> Result<()> {
+    async fn test_files_container_add() -> Result<()> {
         let mut safe = new_safe_instance().await?;
         let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
+            .files_container_create(Some("../testdata/subfolder/"), None, false, true, false)
             .await?;
-
-        assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
-        assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
-
+        assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
+        assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
         let (version, new_processed_files, new_files_map) = safe
-            .files_container_sync(
-                "../testdata/subfolder/",
-                &xorurl,
-                true,
-                true,
+            .files_container_add(
+                "../testdata/test.md",
+                &format!("{}/new_filename_test.md", xorurl),
+                false,
+                false,
                 false,
                 false,
-                true, // set dry_run flag on
             )
             .await?;
 
         assert_eq!(version, 1);
-        assert_eq!(new_processed_files.len(), 2);
-        assert_eq!(
-            new_files_map.len(),
-            TESTDATA_PUT_FILEITEM_COUNT + SUBFOLDER_PUT_FILEITEM_COUNT
-        );
+        assert_eq!(new_processed_files.len(), 1);
+        assert_eq!(new_files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT + 1);
 
-        let filename1 = "../testdata/test.md";
+        let filename1 = "../testdata/subfolder/subexists.md";
         assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename1].1,
-            new_files_map["/test.md"][PREDICATE_LINK]
+            new_files_map["/subexists.md"][PREDICATE_LINK]
         );
 
-        let filename2 = "../testdata/another.md";
+        let filename2 = "../testdata/subfolder/sub2.md";
         assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename2].1,
-            new_files_map["/another.md"][PREDICATE_LINK]
-        );
-
-        let filename3 = "../testdata/subfolder/subexists.md";
-        assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            processed_files[filename3].1,
-            new_files_map["/subfolder/subexists.md"][PREDICATE_LINK]
-        );
-
-        let filename4 = "../testdata/noextension";
-        assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            processed_files[filename4].1,
-            new_files_map["/noextension"][PREDICATE_LINK]
-        );
-
-        let filename5 = "../testdata/subfolder/subexists.md";
-        assert_eq!(new_processed_files[filename5].0, CONTENT_ADDED_SIGN);
-        assert!(!new_processed_files[filename5].1.is_empty());
-        assert_eq!(
-            new_processed_files[filename5].1,
-            new_files_map["/subexists.md"][PREDICATE_LINK]
+            new_files_map["/sub2.md"][PREDICATE_LINK]
         );
 
-        let filename6 = "../testdata/subfolder/sub2.md";
-        assert_eq!(new_processed_files[filename6].0, CONTENT_ADDED_SIGN);
-        assert!(!new_processed_files[filename6].1.is_empty());
+        let filename3 = "../testdata/test.md";
+        assert_eq!(new_processed_files[filename3].0, CONTENT_ADDED_SIGN);
         assert_eq!(
-            new_processed_files[filename6].1,
-            new_files_map["/sub2.md"][PREDICATE_LINK]
+            new_processed_files[filename3].1,
+            new_files_map["/new_filename_test.md"][PREDICATE_LINK]
         );
-
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_sync_same_size() -> Result<()> {
+    async fn test_files_container_add_dry_run() -> Result<()> {
         let mut safe = new_safe_instance().await?;
         let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/test.md"), None, false, false, false)
+            .files_container_create(Some("../testdata/subfolder/"), None, false, true, false)
             .await?;
-
-        assert_eq!(processed_files.len(), 1);
-        assert_eq!(files_map.len(), 1);
-
+        assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
+        assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
         let (version, new_processed_files, new_files_map) = safe
-            .files_container_sync(
-                "../testdata/.subhidden/test.md",
-                &xorurl,
-                false,
-                false,
+            .files_container_add(
+                "../testdata/test.md",
+                &format!("{}/new_filename_test.md", xorurl),
                 false,
                 false,
                 false,
+                true, // dry run
             )
             .await?;
 
         assert_eq!(version, 1);
         assert_eq!(new_processed_files.len(), 1);
-        assert_eq!(new_files_map.len(), 1);
+        assert_eq!(new_files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT + 1);
 
-        let filename1 = "../testdata/test.md";
-        assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            processed_files[filename1].1,
-            files_map["/test.md"][PREDICATE_LINK]
-        );
-        let filename2 = "../testdata/.subhidden/test.md";
-        assert_eq!(new_processed_files[filename2].0, CONTENT_UPDATED_SIGN);
-        assert_eq!(
-            new_processed_files[filename2].1,
-            new_files_map["/test.md"][PREDICATE_LINK]
-        );
+        // a dry run again should give the exact same results
+        let (version2, new_processed_files2, new_files_map2) = safe
+            .files_container_add(
+                "../testdata/test.md",
+                &format!("{}/new_filename_test.md", xorurl),
+                false,
+                false,
+                false,
+                true, // dry run
+            )
+            .await?;
 
-        // check sizes are the same but links are different
+        assert_eq!(version, version2);
+        assert_eq!(new_processed_files.len(), new_processed_files2.len());
+        assert_eq!(new_files_map.len(), new_files_map2.len());
+
+        let filename = "../testdata/test.md";
+        assert_eq!(new_processed_files[filename].0, CONTENT_ADDED_SIGN);
+        assert_eq!(new_processed_files2[filename].0, CONTENT_ADDED_SIGN);
         assert_eq!(
-            files_map["/test.md"][PREDICATE_SIZE],
-            new_files_map["/test.md"][PREDICATE_SIZE]
+            new_processed_files[filename].1,
+            new_files_map["/new_filename_test.md"][PREDICATE_LINK]
         );
-        assert_ne!(
-            files_map["/test.md"][PREDICATE_LINK],
-            new_files_map["/test.md"][PREDICATE_LINK]
+        assert_eq!(
+            new_processed_files2[filename].1,
+            new_files_map2["/new_filename_test.md"][PREDICATE_LINK]
         );
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_sync_with_versioned_target() -> Result<()> {
+    async fn test_files_container_add_dir() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let (xorurl, _, _) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create(Some("../testdata/subfolder/"), None, false, true, false)
             .await?;
-
+        assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT); // root "/" + 2 files
+        assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
-        let versioned_xorurl = format!("{}?v=5", xorurl);
         match safe
-            .files_container_sync(
-                "../testdata/subfolder/",
-                &versioned_xorurl,
-                false,
-                false,
-                false,
-                true, // this flag requests the update-nrs
-                false,
-            )
+            .files_container_add("../testdata", &xorurl, false, false, false, false)
             .await
         {
-            Ok(_) => Err(anyhow!("Sync was unexpectedly successful".to_string(),)),
+            Ok(_) => Err(anyhow!(
+                "Unexpectedly added a folder to files container".to_string(),
+            )),
             Err(Error::InvalidInput(msg)) => {
                 assert_eq!(
                     msg,
-                    format!(
-                        "The target URL cannot contain a version: {}",
-                        versioned_xorurl
-                    )
+                    "'../testdata' is a directory, only individual files can be added. Use files sync operation for uploading folders".to_string(),
                 );
                 Ok(())
             }
@@ -1732,233 +4104,162 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_files_container_sync_with_delete() -> Result<()> {
+    async fn test_files_container_add_existing_name() -> Result<()> {
         let mut safe = new_safe_instance().await?;
         let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
+            .files_container_create(Some("../testdata/subfolder/"), None, false, true, false)
             .await?;
-
-        assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
-        assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
-
+        assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
+        assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
+        // let's try to add a file with same target name and same content, it should fail
         let (version, new_processed_files, new_files_map) = safe
-            .files_container_sync(
-                "../testdata/subfolder/",
-                &xorurl,
-                true,
+            .files_container_add(
+                "../testdata/subfolder/sub2.md",
+                &format!("{}/sub2.md", xorurl),
+                false,
                 false,
-                true, // this sets the delete flag
                 false,
                 false,
             )
             .await?;
 
-        assert_eq!(version, 1);
-        assert_eq!(
-            new_processed_files.len(),
-            TESTDATA_PUT_FILEITEM_COUNT + SUBFOLDER_PUT_FILEITEM_COUNT
-        );
+        assert_eq!(version, 0);
+        assert_eq!(new_processed_files.len(), 1);
         assert_eq!(new_files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
-
-        // first check all previous files were removed
-        let file_path1 = "/test.md";
-        assert_eq!(new_processed_files[file_path1].0, CONTENT_DELETED_SIGN);
         assert_eq!(
-            new_processed_files[file_path1].1,
-            files_map[file_path1][PREDICATE_LINK]
+            new_processed_files["../testdata/subfolder/sub2.md"].1,
+            "File named \"/sub2.md\" with same content already exists on target. Use the \'force\' flag to replace it"
         );
+        assert_eq!(files_map, new_files_map);
 
-        let file_path2 = "/another.md";
-        assert_eq!(new_processed_files[file_path2].0, CONTENT_DELETED_SIGN);
-        assert_eq!(
-            new_processed_files[file_path2].1,
-            files_map[file_path2][PREDICATE_LINK]
-        );
+        // let's try to add a file with same target name but with different content, it should still fail
+        let (version, new_processed_files, new_files_map) = safe
+            .files_container_add(
+                "../testdata/test.md",
+                &format!("{}/sub2.md", xorurl),
+                false,
+                false,
+                false,
+                false,
+            )
+            .await?;
 
-        let file_path3 = "/subfolder/subexists.md";
-        assert_eq!(new_processed_files[file_path3].0, CONTENT_DELETED_SIGN);
+        assert_eq!(version, 0);
+        assert_eq!(new_processed_files.len(), 1);
+        assert_eq!(new_files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         assert_eq!(
-            new_processed_files[file_path3].1,
-            files_map[file_path3][PREDICATE_LINK]
+            new_processed_files["../testdata/test.md"].1,
+            "File named \"/sub2.md\" with different content already exists on target. Use the \'force\' flag to replace it"
         );
+        assert_eq!(files_map, new_files_map);
 
-        let file_path4 = "/noextension";
-        assert_eq!(new_processed_files[file_path4].0, CONTENT_DELETED_SIGN);
+        // let's now force it
+        let (version, new_processed_files, new_files_map) = safe
+            .files_container_add(
+                "../testdata/test.md",
+                &format!("{}/sub2.md", xorurl),
+                true, //force it
+                false,
+                false,
+                false,
+            )
+            .await?;
+
+        assert_eq!(version, 1);
+        assert_eq!(new_processed_files.len(), 1);
+        assert_eq!(new_files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         assert_eq!(
-            new_processed_files[file_path4].1,
-            files_map[file_path4][PREDICATE_LINK]
+            new_processed_files["../testdata/test.md"].0,
+            CONTENT_UPDATED_SIGN
         );
-
-        // and finally check the synced file was added
-        let filename5 = "../testdata/subfolder/subexists.md";
-        assert_eq!(new_processed_files[filename5].0, CONTENT_ADDED_SIGN);
         assert_eq!(
-            new_processed_files[filename5].1,
-            new_files_map["/subexists.md"][PREDICATE_LINK]
+            new_processed_files["../testdata/test.md"].1,
+            new_files_map["/sub2.md"]["link"]
         );
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_sync_delete_without_recursive() -> Result<()> {
-        let mut safe = new_safe_instance().await?;
-        match safe
-            .files_container_sync(
-                "../testdata/subfolder/",
-                "some-url",
-                false, // this sets the recursive flag to off
-                false, // do not follow links
-                true,  // this sets the delete flag
-                false,
-                false,
-            )
-            .await
-        {
-            Ok(_) => Err(anyhow!("Sync was unexpectedly successful".to_string(),)),
-            Err(Error::InvalidInput(msg)) => {
-                assert_eq!(
-                    msg,
-                    "'delete' is not allowed if 'recursive' is not set".to_string()
-                );
-                Ok(())
-            }
-            other => Err(anyhow!(
-                "Error returned is not the expected one: {:?}",
-                other
-            )),
-        }
-    }
-
-    #[tokio::test]
-    async fn test_files_container_sync_update_nrs_unversioned_link() -> Result<()> {
-        let mut safe = new_safe_instance().await?;
-        let (xorurl, _, _) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
-            .await?;
-
-        let nrsurl = random_nrs_name();
-        let mut safe_url = SafeUrl::from_url(&xorurl)?;
-        safe_url.set_content_version(None);
-        let unversioned_link = safe_url.to_string();
-        match safe
-            .nrs_map_container_create(&nrsurl, &unversioned_link, false, true, false)
-            .await
-        {
-            Ok(_) => Err(anyhow!(
-                "NRS create was unexpectedly successful".to_string(),
-            )),
-            Err(Error::InvalidInput(msg)) => {
-                assert_eq!(
-                msg,
-                format!(
-                    "The linked content (FilesContainer) is versionable, therefore NRS requires the link to specify a version: \"{}\"",
-                    unversioned_link
-                )
-            );
-                Ok(())
-            }
-            other => Err(anyhow!(
-                "Error returned is not the expected one: {:?}",
-                other
-            )),
-        }
-    }
-
-    #[tokio::test]
-    async fn test_files_container_sync_update_nrs_with_xorurl() -> Result<()> {
+    async fn test_files_container_fail_add_or_sync_invalid_path() -> Result<()> {
         let mut safe = new_safe_instance().await?;
-        let (xorurl, _, _) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create(Some("../testdata/test.md"), None, false, true, false)
             .await?;
-
+        assert_eq!(processed_files.len(), 1);
+        assert_eq!(files_map.len(), 1);
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
         match safe
             .files_container_sync(
-                "../testdata/subfolder/",
+                "/non-existing-path",
                 &xorurl,
                 false,
                 false,
                 false,
-                true, // this flag requests the update-nrs
+                false,
                 false,
             )
             .await
         {
-            Ok(_) => Err(anyhow!("Sync was unexpectedly successful".to_string(),)),
-            Err(Error::InvalidInput(msg)) => {
-                assert_eq!(
-                    msg,
-                    "'update-nrs' is not allowed since the URL provided is not an NRS URL"
-                        .to_string()
-                );
-                Ok(())
+            Ok(_) => {
+                bail!("Unexpectedly added a folder to files container".to_string(),)
+            }
+            Err(Error::FileSystemError(msg)) => {
+                assert!(msg
+                    .starts_with("Couldn't read metadata from source path ('/non-existing-path')"))
+            }
+            other => {
+                bail!("Error returned is not the expected one: {:?}", other)
             }
-            other => Err(anyhow!(
-                "Error returned is not the expected one: {:?}",
-                other
-            )),
         }
-    }
-
-    #[tokio::test]
-    async fn test_files_container_sync_update_nrs_versioned_link() -> Result<()> {
-        let mut safe = new_safe_instance().await?;
-        let (xorurl, _, _) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
-            .await?;
-
-        let _ = retry_loop!(safe.fetch(&xorurl, None));
-
-        let nrsurl = random_nrs_name();
-        let mut safe_url = SafeUrl::from_url(&xorurl)?;
-        safe_url.set_content_version(Some(0));
-        let (nrs_xorurl, _, _) = safe
-            .nrs_map_container_create(&nrsurl, &safe_url.to_string(), false, true, false)
-            .await?;
-
-        let _ = retry_loop!(safe.fetch(&nrs_xorurl, None));
 
-        let _ = safe
-            .files_container_sync(
-                "../testdata/subfolder/",
-                &nrsurl,
-                false,
+        match safe
+            .files_container_add(
+                "/non-existing-path",
+                &format!("{}/test.md", xorurl),
+                true, // force it
                 false,
                 false,
-                true, // this flag requests the update-nrs
                 false,
             )
-            .await?;
-
-        let mut safe_url = SafeUrl::from_url(&xorurl)?;
-        safe_url.set_content_version(Some(1));
-        let (new_link, _) = safe.parse_and_resolve_url(&nrsurl).await?;
-        assert_eq!(new_link.to_string(), safe_url.to_string());
-
-        Ok(())
+            .await
+        {
+            Ok(_) => Err(anyhow!(
+                "Unexpectedly added a folder to files container".to_string(),
+            )),
+            Err(Error::FileSystemError(msg)) => {
+                assert!(msg
+                    .starts_with("Couldn't read metadata from source path ('/non-existing-path')"));
+                Ok(())
+            }
+            other => Err(anyhow!(
+                "Error returned is not the expected one: {:?}",
+                other
+            )),
+        }
     }
 
     #[tokio::test]
-    async fn test_files_container_sync_target_path_without_trailing_slash() -> Result<()> {
+    async fn test_files_container_add_a_url() -> Result<()> {
         let mut safe = new_safe_instance().await?;
         let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
+            .files_container_create(Some("../testdata/subfolder/"), None, false, true, false)
             .await?;
+        assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
+        assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
-        assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
-        assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
-        let mut safe_url = SafeUrl::from_url(&xorurl)?;
-        safe_url.set_path("path/when/sync");
+        let data = b"0123456789";
+        let file_xorurl = safe.files_store_public_blob(data, None, false).await?;
+        let new_filename = "/new_filename_test.md";
+
         let (version, new_processed_files, new_files_map) = safe
-            .files_container_sync(
-                "../testdata/subfolder",
-                &safe_url.to_string(),
-                true,
+            .files_container_add(
+                &file_xorurl,
+                &format!("{}{}", xorurl, new_filename),
                 false,
                 false,
                 false,
@@ -1967,72 +4268,77 @@ mod tests {
             .await?;
 
         assert_eq!(version, 1);
-        assert_eq!(
-            new_processed_files.len(),
-            SUBFOLDER_NO_SLASH_PUT_FILEITEM_COUNT
-        );
-        assert_eq!(
-            new_files_map.len(),
-            TESTDATA_PUT_FILEITEM_COUNT + SUBFOLDER_NO_SLASH_PUT_FILEITEM_COUNT
-        );
+        assert_eq!(new_processed_files.len(), 1);
+        assert_eq!(new_files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT + 1);
 
-        let filename1 = "../testdata/test.md";
+        let filename1 = "../testdata/subfolder/subexists.md";
         assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename1].1,
-            new_files_map["/test.md"][PREDICATE_LINK]
+            new_files_map["/subexists.md"][PREDICATE_LINK]
         );
 
-        let filename2 = "../testdata/another.md";
+        let filename2 = "../testdata/subfolder/sub2.md";
         assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
         assert_eq!(
             processed_files[filename2].1,
-            new_files_map["/another.md"][PREDICATE_LINK]
+            new_files_map["/sub2.md"][PREDICATE_LINK]
         );
 
-        let filename3 = "../testdata/subfolder/subexists.md";
-        assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
+        assert_eq!(new_processed_files[new_filename].0, CONTENT_ADDED_SIGN);
         assert_eq!(
-            processed_files[filename3].1,
-            new_files_map["/subfolder/subexists.md"][PREDICATE_LINK]
+            new_processed_files[new_filename].1,
+            new_files_map[new_filename][PREDICATE_LINK]
         );
+        assert_eq!(new_files_map[new_filename][PREDICATE_LINK], file_xorurl);
 
-        let filename4 = "../testdata/noextension";
-        assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
+        // let's add another file but with the same name
+        let data = b"9876543210";
+        let other_file_xorurl = safe.files_store_public_blob(data, None, false).await?;
+        let (version, new_processed_files, new_files_map) = safe
+            .files_container_add(
+                &other_file_xorurl,
+                &format!("{}{}", xorurl, new_filename),
+                true, // force to overwrite it with new link
+                false,
+                false,
+                false,
+            )
+            .await?;
+
+        assert_eq!(version, 2);
+        assert_eq!(new_processed_files.len(), 1);
+        assert_eq!(new_files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT + 1);
+        assert_eq!(new_processed_files[new_filename].0, CONTENT_UPDATED_SIGN);
         assert_eq!(
-            processed_files[filename4].1,
-            new_files_map["/noextension"][PREDICATE_LINK]
+            new_processed_files[new_filename].1,
+            new_files_map[new_filename][PREDICATE_LINK]
         );
-
-        // and finally check the synced file is there
-        let filename5 = "../testdata/subfolder/subexists.md";
-        assert_eq!(new_processed_files[filename5].0, CONTENT_ADDED_SIGN);
         assert_eq!(
-            new_processed_files[filename5].1,
-            new_files_map["/path/when/sync/subexists.md"][PREDICATE_LINK]
+            new_files_map[new_filename][PREDICATE_LINK],
+            other_file_xorurl
         );
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_sync_target_path_with_trailing_slash() -> Result<()> {
+    async fn test_files_container_add_from_raw() -> Result<()> {
         let mut safe = new_safe_instance().await?;
         let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
+            .files_container_create(Some("../testdata/subfolder/"), None, false, true, false)
             .await?;
+        assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
+        assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
-        assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
-        assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
-        let mut safe_url = SafeUrl::from_url(&xorurl)?;
-        safe_url.set_path("/path/when/sync/");
+        let data = b"0123456789";
+        let new_filename = "/new_filename_test.md";
+
         let (version, new_processed_files, new_files_map) = safe
-            .files_container_sync(
-                "../testdata/subfolder",
-                &safe_url.to_string(),
-                true,
-                false,
+            .files_container_add_from_raw(
+                data,
+                &format!("{}{}", xorurl, new_filename),
                 false,
                 false,
                 false,
@@ -2040,551 +4346,1113 @@ mod tests {
             .await?;
 
         assert_eq!(version, 1);
+        assert_eq!(new_processed_files.len(), 1);
+        assert_eq!(new_files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT + 1);
+
+        assert_eq!(new_processed_files[new_filename].0, CONTENT_ADDED_SIGN);
         assert_eq!(
-            new_processed_files.len(),
-            SUBFOLDER_NO_SLASH_PUT_FILEITEM_COUNT
-        );
-        assert_eq!(
-            new_files_map.len(),
-            TESTDATA_PUT_FILEITEM_COUNT + SUBFOLDER_NO_SLASH_PUT_FILEITEM_COUNT
+            new_processed_files[new_filename].1,
+            new_files_map[new_filename][PREDICATE_LINK]
         );
 
-        let filename1 = "../testdata/test.md";
-        assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
+        // let's add another file but with the same name
+        let data = b"9876543210";
+        let (version, new_processed_files, new_files_map) = safe
+            .files_container_add_from_raw(
+                data,
+                &format!("{}{}", xorurl, new_filename),
+                true, // force to overwrite it with new link
+                false,
+                false,
+            )
+            .await?;
+
+        assert_eq!(version, 2);
+        assert_eq!(new_processed_files.len(), 1);
+        assert_eq!(new_files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT + 1);
+        assert_eq!(new_processed_files[new_filename].0, CONTENT_UPDATED_SIGN);
         assert_eq!(
-            processed_files[filename1].1,
-            new_files_map["/test.md"][PREDICATE_LINK]
+            new_processed_files[new_filename].1,
+            new_files_map[new_filename][PREDICATE_LINK]
         );
+        Ok(())
+    }
 
-        let filename2 = "../testdata/another.md";
-        assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
+    #[tokio::test]
+    async fn test_files_container_remove_path() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create(Some("../testdata/"), None, true, true, false)
+            .await?;
+        assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
+        assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+
+        // let's remove a file first
+        let (version, new_processed_files, new_files_map) = safe
+            .files_container_remove_path(&format!("{}/test.md", xorurl), false, false, false)
+            .await?;
+
+        assert_eq!(version, 1);
+        assert_eq!(new_processed_files.len(), 1);
+        assert_eq!(new_files_map.len(), TESTDATA_PUT_FILEITEM_COUNT - 1);
+
+        let filepath = "/test.md";
+        assert_eq!(new_processed_files[filepath].0, CONTENT_DELETED_SIGN);
         assert_eq!(
-            processed_files[filename2].1,
-            new_files_map["/another.md"][PREDICATE_LINK]
+            new_processed_files[filepath].1,
+            files_map[filepath][PREDICATE_LINK]
         );
 
-        let filename3 = "../testdata/subfolder/subexists.md";
-        assert_eq!(processed_files[filename3].0, CONTENT_ADDED_SIGN);
+        // let's remove an entire folder now with recursive flag
+        let (version, new_processed_files, new_files_map) = safe
+            .files_container_remove_path(&format!("{}/subfolder", xorurl), true, false, false)
+            .await?;
+
+        assert_eq!(version, 2);
+        assert_eq!(new_processed_files.len(), 2);
         assert_eq!(
-            processed_files[filename3].1,
-            new_files_map["/subfolder/subexists.md"][PREDICATE_LINK]
+            new_files_map.len(),
+            TESTDATA_PUT_FILEITEM_COUNT - SUBFOLDER_PUT_FILEITEM_COUNT - 1
         );
 
-        let filename4 = "../testdata/noextension";
-        assert_eq!(processed_files[filename4].0, CONTENT_ADDED_SIGN);
+        let filename1 = "/subfolder/subexists.md";
+        assert_eq!(new_processed_files[filename1].0, CONTENT_DELETED_SIGN);
         assert_eq!(
-            processed_files[filename4].1,
-            new_files_map["/noextension"][PREDICATE_LINK]
+            new_processed_files[filename1].1,
+            files_map[filename1][PREDICATE_LINK]
         );
 
-        // and finally check the synced file is there
-        let filename5 = "../testdata/subfolder/subexists.md";
-        assert_eq!(new_processed_files[filename5].0, CONTENT_ADDED_SIGN);
+        let filename2 = "/subfolder/sub2.md";
+        assert_eq!(new_processed_files[filename2].0, CONTENT_DELETED_SIGN);
         assert_eq!(
-            new_processed_files[filename5].1,
-            new_files_map["/path/when/sync/subfolder/subexists.md"][PREDICATE_LINK]
+            new_processed_files[filename2].1,
+            files_map[filename2][PREDICATE_LINK]
         );
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_get() -> Result<()> {
-        let mut safe = new_safe_instance().await?;
-        let (xorurl, _, files_map) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
+    async fn test_files_container_revert() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let (xorurl, _, _) = safe
+            .files_container_create(None, None, true, false, false)
+            .await?;
+        let (_, _, files_map_v1) = safe
+            .files_container_add_from_raw(
+                b"first",
+                &format!("{}/a.txt", xorurl),
+                false,
+                false,
+                false,
+            )
+            .await?;
+        let _ = safe
+            .files_container_add_from_raw(
+                b"second",
+                &format!("{}/b.txt", xorurl),
+                false,
+                false,
+                false,
+            )
+            .await?;
+        let _ = safe
+            .files_container_add_from_raw(
+                b"updated",
+                &format!("{}/a.txt", xorurl),
+                true,
+                false,
+                false,
+            )
             .await?;
 
-        let (version, fetched_files_map) = retry_loop!(safe.files_container_get(&xorurl));
+        // a dry run doesn't append any version
+        let (version, processed_files, _) =
+            safe.files_container_revert(&xorurl, 1, false, true).await?;
+        assert_eq!(version, 4);
+        assert_eq!(processed_files.len(), 2);
+        let (version, _) = safe.files_container_get(&xorurl).await?;
+        assert_eq!(version, 3);
 
-        assert_eq!(version, 0);
-        assert_eq!(fetched_files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
-        assert_eq!(files_map.len(), fetched_files_map.len());
-        assert_eq!(files_map["/test.md"], fetched_files_map["/test.md"]);
-        assert_eq!(files_map["/another.md"], fetched_files_map["/another.md"]);
+        let (version, processed_files, files_map) = safe
+            .files_container_revert(&xorurl, 1, false, false)
+            .await?;
+        assert_eq!(version, 4);
+        assert_eq!(files_map, files_map_v1);
+        assert_eq!(processed_files["/a.txt"].0, CONTENT_UPDATED_SIGN);
         assert_eq!(
-            files_map["/subfolder/subexists.md"],
-            fetched_files_map["/subfolder/subexists.md"]
+            processed_files["/a.txt"].1,
+            files_map_v1["/a.txt"][PREDICATE_LINK]
         );
-        assert_eq!(files_map["/noextension"], fetched_files_map["/noextension"]);
+        assert_eq!(processed_files["/b.txt"].0, CONTENT_DELETED_SIGN);
+
+        // the new version links to the very same FilesMap as the target version
+        let (_, history) = safe.files_container_history(&xorurl, 1, None).await?;
+        assert_eq!(history[0].files_map_xorurl, history[3].files_map_xorurl);
+
+        // reverting to the current content doesn't create a new version
+        let (version, processed_files, _) = safe
+            .files_container_revert(&xorurl, 1, false, false)
+            .await?;
+        assert_eq!(version, 4);
+        assert!(processed_files.is_empty());
+
+        match safe.files_container_revert(&xorurl, 10, false, false).await {
+            Err(Error::VersionNotFound(_)) => Ok(()),
+            other => Err(anyhow!("Unexpected result: {:?}", other)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_files_container_sync_with_filter() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let src = new_temp_dir()?;
+        std::fs::write(src.join(SAFE_IGNORE_FILENAME), "*.log\n")?;
+        std::fs::write(src.join("index.html"), "<html/>")?;
+        std::fs::write(src.join("debug.log"), "noise")?;
+        std::fs::create_dir_all(src.join("node_modules"))?;
+        std::fs::write(src.join("node_modules").join("dep.js"), "dep")?;
+        let location = format!("{}/", src.display());
+
+        let filter = FilesFilter::default().exclude("node_modules");
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create_with_options(
+                Some(&location),
+                None,
+                true,
+                false,
+                &SyncOptions {
+                    filter: filter.clone(),
+                    ..SyncOptions::default()
+                },
+                false,
+            )
+            .await?;
+        assert!(files_map.contains_key("/index.html"));
+        assert!(files_map.contains_key(&format!("/{}", SAFE_IGNORE_FILENAME)));
+        assert!(!files_map.contains_key("/debug.log"));
+        assert!(!files_map.contains_key("/node_modules"));
+        assert!(!files_map.contains_key("/node_modules/dep.js"));
+        assert_eq!(processed_files.len(), files_map.len());
+
+        // an ignored file which was uploaded beforehand is not deleted when syncing
+        let _ = safe
+            .files_container_add_from_raw(
+                b"old",
+                &format!("{}/old.log", xorurl),
+                false,
+                false,
+                false,
+            )
+            .await?;
+        std::fs::remove_file(src.join("index.html"))?;
+
+        let options = SyncOptions {
+            filter,
+            ..SyncOptions::default()
+        };
+        let (version, processed_files, files_map) = safe
+            .files_container_sync_with_options(
+                &location, &xorurl, true, false, true, false, &options, true,
+            )
+            .await?;
+        assert_eq!(version, 2);
+        assert_eq!(processed_files.len(), 1);
+        assert_eq!(processed_files["/index.html"].0, CONTENT_DELETED_SIGN);
+        assert!(files_map.contains_key("/old.log"));
+        assert!(!files_map.contains_key("/index.html"));
+
+        // without the filter the ignore file is still honoured
+        let (_, processed_files, _) = safe
+            .files_container_sync(&location, &xorurl, true, false, true, false, true)
+            .await?;
+        assert!(!processed_files.contains_key("/old.log"));
+        assert!(processed_files.keys().any(|path| path.ends_with("dep.js")));
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_version() -> Result<()> {
-        let mut safe = new_safe_instance().await?;
+    async fn test_files_container_add_with_filter() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let src = new_temp_dir()?;
+        std::fs::write(src.join(SAFE_IGNORE_FILENAME), "*.log\n")?;
+        std::fs::write(src.join("debug.log"), "noise")?;
+        std::fs::write(src.join("index.html"), "<html/>")?;
+
         let (xorurl, _, _) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
+            .files_container_create(None, None, false, false, false)
             .await?;
 
-        let (version, _) = retry_loop!(safe.files_container_get(&xorurl));
-        assert_eq!(version, 0);
+        let debug_log = src.join("debug.log").display().to_string();
+        match safe
+            .files_container_add(
+                &debug_log,
+                &format!("{}/debug.log", xorurl),
+                false,
+                false,
+                false,
+                false,
+            )
+            .await
+        {
+            Err(Error::InvalidInput(_)) => {}
+            other => bail!("Unexpected result: {:?}", other),
+        }
 
-        let (version, _, _) = safe
-            .files_container_sync(
-                "../testdata/subfolder/",
-                &xorurl,
-                true,
+        let options = SyncOptions {
+            filter: FilesFilter::default().exclude("*.html"),
+            ..SyncOptions::default()
+        };
+        let index_html = src.join("index.html").display().to_string();
+        match safe
+            .files_container_add_with_options(
+                &index_html,
+                &format!("{}/index.html", xorurl),
+                false,
                 false,
-                true, // this sets the delete flag,
                 false,
+                &options,
                 false,
             )
-            .await?;
-        assert_eq!(version, 1);
+            .await
+        {
+            Err(Error::InvalidInput(_)) => {}
+            other => bail!("Unexpected result: {:?}", other),
+        }
 
-        let mut safe_url = SafeUrl::from_url(&xorurl)?;
-        safe_url.set_content_version(None);
-        let (version, _) = retry_loop_for_pattern!(safe
-            .files_container_get(&safe_url.to_string()), Ok((version, _)) if *version == 1)?;
+        // unless the ignore files are not honoured
+        let options = SyncOptions {
+            filter: FilesFilter::default().ignore_files(false),
+            ..SyncOptions::default()
+        };
+        let (version, _, files_map) = safe
+            .files_container_add_with_options(
+                &debug_log,
+                &format!("{}/debug.log", xorurl),
+                false,
+                false,
+                false,
+                &options,
+                false,
+            )
+            .await?;
         assert_eq!(version, 1);
-
+        assert!(files_map.contains_key("/debug.log"));
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_get_with_version() -> Result<()> {
-        let mut safe = new_safe_instance().await?;
-        let (xorurl, _processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
-            .await?;
-        let _ = retry_loop!(safe.fetch(&xorurl, None));
+    async fn test_files_container_unsupported_options() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let options = SyncOptions {
+            merge: true,
+            ..SyncOptions::default()
+        };
+        match safe
+            .files_container_create_with_options(None, None, false, false, &options, false)
+            .await
+        {
+            Err(Error::InvalidInput(msg)) => assert!(msg.ends_with(": merge")),
+            other => bail!("Unexpected result: {:?}", other),
+        }
 
-        // let's create a new version of the files container
-        let (_version, _new_processed_files, new_files_map) = safe
-            .files_container_sync(
-                "../testdata/subfolder/",
+        let (xorurl, _, _) = safe
+            .files_container_create(None, None, false, false, false)
+            .await?;
+        let options = SyncOptions {
+            sync_state: Some(std::env::temp_dir().join("unused.syncstate")),
+            concurrency: 2,
+            ..SyncOptions::default()
+        };
+        match safe
+            .files_container_remove_paths_with_options(
                 &xorurl,
-                true,
+                &["/a"],
                 false,
-                true, // this sets the delete flag
                 false,
+                &options,
                 false,
             )
+            .await
+        {
+            Err(Error::InvalidInput(msg)) => assert!(msg.ends_with(": sync_state, concurrency")),
+            other => bail!("Unexpected result: {:?}", other),
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_sync_change_detection() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let src = new_temp_dir()?;
+        let file_path = src.join("test.md");
+        std::fs::write(&file_path, "hello tests!")?;
+        let location = format!("{}/", src.display());
+        let (xorurl, _, _) = safe
+            .files_container_create(Some(&location), None, true, false, false)
             .await?;
 
-        // let's fetch version 0
-        let mut safe_url = SafeUrl::from_url(&xorurl)?;
-        safe_url.set_content_version(Some(0));
-        let (version, v0_files_map) = safe.files_container_get(&safe_url.to_string()).await?;
+        // change the content but keep the same size and modification time
+        let mtime =
+            filetime::FileTime::from_last_modification_time(&std::fs::metadata(&file_path)?);
+        std::fs::write(&file_path, "HELLO TESTS!")?;
+        filetime::set_file_mtime(&file_path, mtime)?;
 
+        let sync_state_path = src.join("..").join(format!(
+            "{}.syncstate",
+            src.file_name().and_then(|name| name.to_str()).unwrap_or("")
+        ));
+        let options = SyncOptions {
+            change_detection: ChangeDetection::Metadata,
+            sync_state: Some(sync_state_path.clone()),
+            ..SyncOptions::default()
+        };
+        let (version, processed_files, _) = safe
+            .files_container_sync_with_options(
+                &location, &xorurl, true, false, false, false, &options, false,
+            )
+            .await?;
         assert_eq!(version, 0);
-        assert_eq!(files_map, v0_files_map);
-        // let's check that one of the files in v1 is still there
-        let file_path1 = "/test.md";
-        assert_eq!(
-            files_map[file_path1][PREDICATE_LINK],
-            v0_files_map[file_path1][PREDICATE_LINK]
-        );
-
-        // let's fetch version 1
-        safe_url.set_content_version(Some(1));
-        let (version, v1_files_map) = retry_loop_for_pattern!(safe
-                .files_container_get(&safe_url.to_string()), Ok((version, _)) if *version == 1)?;
+        assert!(processed_files.is_empty());
 
+        // comparing the content detects the change, and caches the file's XOR-URL
+        let options = SyncOptions {
+            change_detection: ChangeDetection::Content,
+            ..options
+        };
+        let (version, processed_files, files_map) = safe
+            .files_container_sync_with_options(
+                &location, &xorurl, true, false, false, false, &options, false,
+            )
+            .await?;
         assert_eq!(version, 1);
-        assert_eq!(new_files_map, v1_files_map);
-        // let's check that some of the files are no in v2 anymore
-        let file_path2 = "/another.md";
-        let file_path3 = "/subfolder/subexists.md";
-        let file_path4 = "/noextension";
-        assert!(v1_files_map.get(file_path1).is_none());
-        assert!(v1_files_map.get(file_path2).is_none());
-        assert!(v1_files_map.get(file_path3).is_none());
-        assert!(v1_files_map.get(file_path4).is_none());
-
-        // let's fetch version 2 (invalid)
-        safe_url.set_content_version(Some(2));
-        match safe.files_container_get(&safe_url.to_string()).await {
-            Ok(_) => Err(anyhow!(
-                "Unexpectedly retrieved verion 2 of container".to_string(),
-            )),
-            Err(Error::VersionNotFound(msg)) => {
-                assert_eq!(
-                    msg,
-                    format!(
-                        "Version '2' is invalid for FilesContainer found at \"{}\"",
-                        safe_url
-                    )
-                );
-                Ok(())
-            }
-            other => Err(anyhow!(
-                "Error returned is not the expected one: {:?}",
-                other
-            )),
-        }
-    }
-
-    #[tokio::test]
-    async fn test_files_container_create_get_empty_folder() -> Result<()> {
-        let mut safe = new_safe_instance().await?;
-        let (xorurl, _processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
+        let local_file = normalise_path_separator(&file_path.display().to_string());
+        assert_eq!(processed_files[&local_file].0, CONTENT_UPDATED_SIGN);
+        let sync_state = std::fs::read_to_string(&sync_state_path)?;
+        assert!(sync_state.contains(&files_map["/test.md"][PREDICATE_LINK]));
+
+        // a change the cache cannot tell apart is not detected when the cache is used...
+        std::fs::write(&file_path, "hello TESTS!")?;
+        filetime::set_file_mtime(&file_path, mtime)?;
+        let (version, processed_files, _) = safe
+            .files_container_sync_with_options(
+                &location, &xorurl, true, false, false, false, &options, true,
+            )
             .await?;
+        assert_eq!(version, 1);
+        assert!(processed_files.is_empty());
 
-        let (_, files_map_get) = retry_loop!(safe.files_container_get(&xorurl.to_string()));
+        // ...but it is when the content is compared without the cache
+        let (version, _, _) = safe
+            .files_container_sync(&location, &xorurl, true, false, false, false, true)
+            .await?;
+        assert_eq!(version, 2);
 
-        assert_eq!(files_map, files_map_get);
-        assert_eq!(files_map_get["/emptyfolder"], files_map["/emptyfolder"]);
-        assert_eq!(
-            files_map_get["/emptyfolder"]["type"],
-            MIMETYPE_FILESYSTEM_DIR
-        );
+        // a change within the same second is detected with the metadata kept by the cache,
+        // even though the FileItem's modification time only has a precision of seconds
+        std::fs::write(&file_path, "HELLO tests!")?;
+        let nanos = if mtime.nanoseconds() == 500_000_000 {
+            0
+        } else {
+            500_000_000
+        };
+        filetime::set_file_mtime(
+            &file_path,
+            filetime::FileTime::from_unix_time(mtime.unix_seconds(), nanos),
+        )?;
+        let options = SyncOptions {
+            change_detection: ChangeDetection::Metadata,
+            ..options
+        };
+        let (version, processed_files, _) = safe
+            .files_container_sync_with_options(
+                &location, &xorurl, true, false, false, false, &options, false,
+            )
+            .await?;
+        assert_eq!(version, 3);
+        assert_eq!(processed_files[&local_file].0, CONTENT_UPDATED_SIGN);
 
+        std::fs::remove_file(&sync_state_path)?;
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_sync_with_nrs_url() -> Result<()> {
-        let mut safe = new_safe_instance().await?;
-        let (xorurl, _, _) = safe
-            .files_container_create(Some("../testdata/test.md"), None, false, true, false)
+    async fn test_files_container_sync_concurrency() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let src = new_temp_dir()?;
+        for i in 0..20 {
+            std::fs::write(src.join(format!("file{}.txt", i)), format!("content {}", i))?;
+        }
+        let location = format!("{}/", src.display());
+
+        let sequential = SyncOptions {
+            concurrency: 1,
+            ..SyncOptions::default()
+        };
+        let (_, _, expected_files_map) = safe
+            .files_container_create_with_options(
+                Some(&location),
+                None,
+                true,
+                false,
+                &sequential,
+                false,
+            )
             .await?;
-        let _ = retry_loop!(safe.fetch(&xorurl, None));
 
-        let nrsurl = random_nrs_name();
-        let mut safe_url = SafeUrl::from_url(&xorurl)?;
-        safe_url.set_content_version(Some(0));
-        let (nrs_xorurl, _, _) = safe
-            .nrs_map_container_create(&nrsurl, &safe_url.to_string(), false, true, false)
+        let concurrent = SyncOptions {
+            concurrency: 3,
+            ..SyncOptions::default()
+        };
+        let (xorurl, processed_files, files_map) = safe
+            .files_container_create_with_options(
+                Some(&location),
+                None,
+                true,
+                false,
+                &concurrent,
+                false,
+            )
             .await?;
-        let _ = retry_loop!(safe.fetch(&nrs_xorurl, None));
+        assert_eq!(files_map.len(), 20);
+        assert_eq!(processed_files.len(), files_map.len());
+        for (path, file_item) in files_map.iter() {
+            assert_eq!(
+                file_item[PREDICATE_LINK],
+                expected_files_map[path][PREDICATE_LINK]
+            );
+            let local_file = normalise_path_separator(&src.join(&path[1..]).display().to_string());
+            assert_eq!(processed_files[&local_file].1, file_item[PREDICATE_LINK]);
+        }
 
-        let _ = safe
-            .files_container_sync(
-                "../testdata/subfolder/",
+        for i in 0..5 {
+            std::fs::write(src.join(format!("file{}.txt", i)), format!("updated {}", i))?;
+        }
+        let (version, processed_files, files_map) = safe
+            .files_container_sync_with_options(
+                &location,
                 &xorurl,
+                true,
+                false,
                 false,
                 false,
+                &concurrent,
                 false,
+            )
+            .await?;
+        assert_eq!(version, 1);
+        assert_eq!(processed_files.len(), 5);
+        assert!(processed_files
+            .values()
+            .all(|(change, _)| change == CONTENT_UPDATED_SIGN));
+        let file_xorurl = safe
+            .files_store_public_blob(b"updated 0", None, true)
+            .await?;
+        assert_eq!(files_map["/file0.txt"][PREDICATE_LINK], file_xorurl);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_progress() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let src = new_temp_dir()?;
+        std::fs::write(src.join("a.txt"), "aaa")?;
+        std::fs::write(src.join("b.txt"), "bbbbb")?;
+        let location = format!("{}/", src.display());
+        let local_path =
+            |name: &str| normalise_path_separator(&src.join(name).display().to_string());
+
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+        let options = SyncOptions {
+            progress: Some(ProgressObserver::new(move |event| {
+                events_clone.lock().unwrap().push(event)
+            })),
+            ..SyncOptions::default()
+        };
+        let take_events = || std::mem::take(&mut *events.lock().unwrap());
+
+        let (xorurl, _, files_map) = safe
+            .files_container_create_with_options(
+                Some(&location),
+                None,
+                true,
                 false,
+                &options,
                 false,
             )
             .await?;
+        let created = take_events();
+        assert_eq!(created.len(), 7);
+        assert!(created.contains(&FilesProgress::BytesUploaded {
+            path: local_path("a.txt"),
+            bytes: 3
+        }));
+        assert!(created.contains(&FilesProgress::FileDiscovered {
+            path: local_path("b.txt"),
+            size: 5
+        }));
+        assert!(created.contains(&FilesProgress::FileUploaded {
+            path: local_path("a.txt"),
+            size: 3,
+            xorurl: files_map["/a.txt"][PREDICATE_LINK].clone(),
+        }));
+        assert_eq!(
+            created.last(),
+            Some(&FilesProgress::VersionCommitted {
+                version: 0,
+                url: xorurl.clone()
+            })
+        );
+
+        std::fs::write(src.join("b.txt"), "BBBBB")?;
+        std::fs::remove_file(src.join("a.txt"))?;
+        let (version, _, _) = safe
+            .files_container_sync_with_options(
+                &location, &xorurl, true, false, true, false, &options, false,
+            )
+            .await?;
+        let synced = take_events();
+        assert_eq!(synced.len(), 5);
+        assert!(synced.contains(&FilesProgress::FileRemoved {
+            path: "/a.txt".to_string()
+        }));
+        assert!(synced.iter().any(|event| matches!(
+            event,
+            FilesProgress::FileUploaded { path, size: 5, .. } if path == &local_path("b.txt")
+        )));
+        assert_eq!(
+            synced.last(),
+            Some(&FilesProgress::VersionCommitted {
+                version,
+                url: xorurl.clone()
+            })
+        );
 
+        // adding an existing file without 'force' skips it, and nothing is committed
         let _ = safe
-            .files_container_sync(
-                "../testdata/",
-                &nrsurl,
+            .files_container_add_with_options(
+                &local_path("b.txt"),
+                &format!("{}/b.txt", xorurl),
                 false,
                 false,
                 false,
-                true, // this flag requests the update-nrs
+                &options,
                 false,
             )
             .await?;
+        let added = take_events();
+        assert_eq!(added.len(), 2);
+        assert!(matches!(added[0], FilesProgress::FileDiscovered { .. }));
+        assert!(matches!(added[1], FilesProgress::FileSkipped { .. }));
 
-        // now it should look like:
-        // safe://<nrs>
-        // ├── .hidden.txt
-        // ├── another.md
-        // ├── noextension
-        // ├── sub2.md
-        // ├── subexists.md
-        // └── test.md
-        //
-        // So, we have 6 items.
-        let (version, fetched_files_map) = retry_loop_for_pattern!(safe.files_container_get(&xorurl), Ok((version, _)) if *version == 2)?;
-        assert_eq!(version, 2);
-        assert_eq!(fetched_files_map.len(), 6);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_sync_cancelled() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let src = new_temp_dir()?;
+        for i in 0..10 {
+            std::fs::write(src.join(format!("file{}.txt", i)), format!("content {}", i))?;
+        }
+        let location = format!("{}/", src.display());
+        let (xorurl, _, original_files_map) = safe
+            .files_container_create(Some(&location), None, true, false, false)
+            .await?;
+
+        // cancel the sync once the third file was uploaded
+        for i in 0..10 {
+            std::fs::write(src.join(format!("file{}.txt", i)), format!("updated {}", i))?;
+        }
+        let cancel = CancellationToken::new();
+        let uploaded = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let (cancel_clone, uploaded_clone) = (cancel.clone(), uploaded.clone());
+        let options = SyncOptions {
+            concurrency: 1,
+            cancel: Some(cancel.clone()),
+            progress: Some(ProgressObserver::new(move |event| {
+                if let FilesProgress::FileUploaded { .. } = event {
+                    if uploaded_clone.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 2 {
+                        cancel_clone.cancel();
+                    }
+                }
+            })),
+            ..SyncOptions::default()
+        };
+        let (version, processed_files, files_map) = safe
+            .files_container_sync_with_options(
+                &location, &xorurl, true, false, false, false, &options, false,
+            )
+            .await?;
+        assert_eq!(version, 0);
+        assert_eq!(files_map, original_files_map);
+        assert_eq!(processed_files.len(), 3);
+        assert!(processed_files
+            .values()
+            .all(|(change, _)| change == CONTENT_UPDATED_SIGN));
+
+        let (version, current_files_map) = safe.files_container_get(&xorurl).await?;
+        assert_eq!(version, 0);
+        assert_eq!(current_files_map, original_files_map);
+
+        // nothing is created if it was cancelled beforehand
+        let (new_xorurl, processed_files, files_map) = safe
+            .files_container_create_with_options(
+                Some(&location),
+                None,
+                true,
+                false,
+                &options,
+                false,
+            )
+            .await?;
+        assert!(new_xorurl.is_empty());
+        assert!(processed_files.is_empty());
+        assert!(files_map.is_empty());
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_add() -> Result<()> {
-        let mut safe = new_safe_instance().await?;
-        let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/subfolder/"), None, false, true, false)
+    async fn test_files_container_expected_version() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let (xorurl, _, _) = safe
+            .files_container_create(None, None, true, false, false)
+            .await?;
+        let (base_version, _, _) = safe
+            .files_container_add_from_raw(b"a", &format!("{}/a.txt", xorurl), false, false, false)
             .await?;
-        assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
-        assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
-        let _ = retry_loop!(safe.fetch(&xorurl, None));
 
-        let (version, new_processed_files, new_files_map) = safe
-            .files_container_add(
-                "../testdata/test.md",
-                &format!("{}/new_filename_test.md", xorurl),
+        // another writer adds a file in the meantime
+        let (their_version, _, _) = safe
+            .files_container_add_from_raw(b"b", &format!("{}/b.txt", xorurl), false, false, false)
+            .await?;
+
+        let options = SyncOptions {
+            expected_version: Some(base_version),
+            ..SyncOptions::default()
+        };
+        match safe
+            .files_container_remove_path_with_options(
+                &format!("{}/a.txt", xorurl),
+                false,
                 false,
+                &options,
+                false,
+            )
+            .await
+        {
+            Err(Error::VersionConflict(_)) => {}
+            other => bail!("Unexpected result: {:?}", other),
+        }
+        let (version, _) = safe.files_container_get(&xorurl).await?;
+        assert_eq!(version, their_version);
+
+        // the changes don't overlap so they can be merged
+        let options = SyncOptions {
+            merge: true,
+            ..options
+        };
+        let (version, processed_files, files_map) = safe
+            .files_container_remove_path_with_options(
+                &format!("{}/a.txt", xorurl),
                 false,
                 false,
+                &options,
                 false,
             )
             .await?;
+        assert_eq!(version, their_version + 1);
+        assert_eq!(processed_files["/a.txt"].0, CONTENT_DELETED_SIGN);
+        assert_eq!(files_map.len(), 1);
+        assert!(files_map.contains_key("/b.txt"));
 
-        assert_eq!(version, 1);
-        assert_eq!(new_processed_files.len(), 1);
-        assert_eq!(new_files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT + 1);
-
-        let filename1 = "../testdata/subfolder/subexists.md";
-        assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            processed_files[filename1].1,
-            new_files_map["/subexists.md"][PREDICATE_LINK]
-        );
-
-        let filename2 = "../testdata/subfolder/sub2.md";
-        assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            processed_files[filename2].1,
-            new_files_map["/sub2.md"][PREDICATE_LINK]
-        );
+        // but they cannot be if both change the same path
+        let src = new_temp_dir()?;
+        let local_file = src.join("b.txt");
+        std::fs::write(&local_file, "ours")?;
+        match safe
+            .files_container_add_with_options(
+                &local_file.display().to_string(),
+                &format!("{}/b.txt", xorurl),
+                true,
+                false,
+                false,
+                &options,
+                false,
+            )
+            .await
+        {
+            Err(Error::VersionConflict(msg)) => assert!(msg.contains("/b.txt")),
+            other => bail!("Unexpected result: {:?}", other),
+        }
 
-        let filename3 = "../testdata/test.md";
-        assert_eq!(new_processed_files[filename3].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            new_processed_files[filename3].1,
-            new_files_map["/new_filename_test.md"][PREDICATE_LINK]
-        );
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_add_dry_run() -> Result<()> {
-        let mut safe = new_safe_instance().await?;
-        let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/subfolder/"), None, false, true, false)
+    async fn test_files_container_sync_two_way() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let src = new_temp_dir()?;
+        std::fs::write(src.join("a.txt"), "a")?;
+        std::fs::write(src.join("b.txt"), "b")?;
+        std::fs::write(src.join("c.txt"), "c")?;
+        let location = format!("{}/", src.display());
+        let (xorurl, _, _) = safe
+            .files_container_create(Some(&location), None, true, false, false)
             .await?;
-        assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
-        assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
-        let _ = retry_loop!(safe.fetch(&xorurl, None));
 
-        let (version, new_processed_files, new_files_map) = safe
-            .files_container_add(
-                "../testdata/test.md",
-                &format!("{}/new_filename_test.md", xorurl),
-                false,
+        // changes made remotely, by another writer
+        let _ = safe
+            .files_container_add_from_raw(b"r", &format!("{}/r.txt", xorurl), false, false, false)
+            .await?;
+        let (remote_version, _, _) = safe
+            .files_container_add_from_raw(
+                b"remote b",
+                &format!("{}/b.txt", xorurl),
+                true,
                 false,
                 false,
-                true, // dry run
             )
             .await?;
 
-        assert_eq!(version, 1);
-        assert_eq!(new_processed_files.len(), 1);
-        assert_eq!(new_files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT + 1);
+        // changes made locally
+        std::fs::write(src.join("a.txt"), "local a")?;
+        std::fs::write(src.join("b.txt"), "local b")?;
+        std::fs::remove_file(src.join("c.txt"))?;
+        let local_path =
+            |name: &str| normalise_path_separator(&src.join(name).display().to_string());
 
-        // a dry run again should give the exact same results
-        let (version2, new_processed_files2, new_files_map2) = safe
-            .files_container_add(
-                "../testdata/test.md",
-                &format!("{}/new_filename_test.md", xorurl),
+        let result = safe
+            .files_container_sync_two_way(
+                &location,
+                &xorurl,
+                0,
                 false,
+                SyncConflictResolution::Report,
+                &SyncOptions::default(),
+                true,
+            )
+            .await?;
+        assert_eq!(result.version, remote_version + 1);
+        assert_eq!(
+            result.conflicts,
+            vec!["/b.txt".to_string()].into_iter().collect()
+        );
+        assert_eq!(result.pulled.len(), 1);
+        assert_eq!(result.pulled[&local_path("r.txt")].0, CONTENT_ADDED_SIGN);
+        assert_eq!(result.pushed.len(), 2);
+        assert_eq!(result.pushed[&local_path("a.txt")].0, CONTENT_UPDATED_SIGN);
+        assert_eq!(result.pushed["/c.txt"].0, CONTENT_DELETED_SIGN);
+        assert!(!src.join("r.txt").exists());
+
+        let result = safe
+            .files_container_sync_two_way(
+                &location,
+                &xorurl,
+                0,
                 false,
+                SyncConflictResolution::KeepBoth,
+                &SyncOptions::default(),
                 false,
-                true, // dry run
             )
             .await?;
-
-        assert_eq!(version, version2);
-        assert_eq!(new_processed_files.len(), new_processed_files2.len());
-        assert_eq!(new_files_map.len(), new_files_map2.len());
-
-        let filename = "../testdata/test.md";
-        assert_eq!(new_processed_files[filename].0, CONTENT_ADDED_SIGN);
-        assert_eq!(new_processed_files2[filename].0, CONTENT_ADDED_SIGN);
+        assert_eq!(result.version, remote_version + 1);
+        assert_eq!(std::fs::read_to_string(src.join("r.txt"))?, "r");
+        assert_eq!(std::fs::read_to_string(src.join("b.txt"))?, "local b");
+        let copy_name = format!("b.remote-v{}.txt", remote_version);
+        assert_eq!(std::fs::read_to_string(src.join(&copy_name))?, "remote b");
+
+        let (version, files_map) = safe.files_container_get(&xorurl).await?;
+        assert_eq!(version, result.version);
+        assert_eq!(files_map, result.files_map);
+        assert!(!files_map.contains_key("/c.txt"));
+        let local_a = safe.files_store_public_blob(b"local a", None, true).await?;
+        let local_b = safe.files_store_public_blob(b"local b", None, true).await?;
+        let remote_b = safe
+            .files_store_public_blob(b"remote b", None, true)
+            .await?;
+        let xorname = |link: &str| SafeUrl::from_url(link).map(|url| url.xorname());
         assert_eq!(
-            new_processed_files[filename].1,
-            new_files_map["/new_filename_test.md"][PREDICATE_LINK]
+            xorname(&files_map["/a.txt"][PREDICATE_LINK])?,
+            xorname(&local_a)?
         );
         assert_eq!(
-            new_processed_files2[filename].1,
-            new_files_map2["/new_filename_test.md"][PREDICATE_LINK]
+            xorname(&files_map["/b.txt"][PREDICATE_LINK])?,
+            xorname(&local_b)?
+        );
+        assert_eq!(
+            xorname(&files_map[&format!("/{}", copy_name)][PREDICATE_LINK])?,
+            xorname(&remote_b)?
         );
 
-        Ok(())
-    }
-
-    #[tokio::test]
-    async fn test_files_container_add_dir() -> Result<()> {
-        let mut safe = new_safe_instance().await?;
-        let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/subfolder/"), None, false, true, false)
+        // nothing changed since the last sync
+        let result = safe
+            .files_container_sync_two_way(
+                &location,
+                &xorurl,
+                result.version,
+                false,
+                SyncConflictResolution::Report,
+                &SyncOptions::default(),
+                false,
+            )
             .await?;
-        assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT); // root "/" + 2 files
-        assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
-        let _ = retry_loop!(safe.fetch(&xorurl, None));
+        assert_eq!(result.version, version);
+        assert!(result.pulled.is_empty());
+        assert!(result.pushed.is_empty());
+        assert!(result.conflicts.is_empty());
 
-        match safe
-            .files_container_add("../testdata", &xorurl, false, false, false, false)
-            .await
-        {
-            Ok(_) => Err(anyhow!(
-                "Unexpectedly added a folder to files container".to_string(),
-            )),
-            Err(Error::InvalidInput(msg)) => {
-                assert_eq!(
-                    msg,
-                    "'../testdata' is a directory, only individual files can be added. Use files sync operation for uploading folders".to_string(),
-                );
-                Ok(())
-            }
-            other => Err(anyhow!(
-                "Error returned is not the expected one: {:?}",
-                other
-            )),
-        }
+        Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_add_existing_name() -> Result<()> {
-        let mut safe = new_safe_instance().await?;
-        let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/subfolder/"), None, false, true, false)
+    async fn test_files_container_move_and_copy() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let (xorurl, _, _) = safe
+            .files_container_create(None, None, true, false, false)
             .await?;
-        assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
-        assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
-        let _ = retry_loop!(safe.fetch(&xorurl, None));
-
-        // let's try to add a file with same target name and same content, it should fail
-        let (version, new_processed_files, new_files_map) = safe
-            .files_container_add(
-                "../testdata/subfolder/sub2.md",
-                &format!("{}/sub2.md", xorurl),
+        let _ = safe
+            .files_container_add_from_raw(
+                b"a",
+                &format!("{}/dir/a.md", xorurl),
+                false,
+                false,
                 false,
+            )
+            .await?;
+        let (version, _, files_map) = safe
+            .files_container_add_from_raw(
+                b"b",
+                &format!("{}/dir/b.md", xorurl),
                 false,
                 false,
                 false,
             )
             .await?;
+        assert_eq!(version, 2);
 
-        assert_eq!(version, 0);
-        assert_eq!(new_processed_files.len(), 1);
-        assert_eq!(new_files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
+        let (version, processed_files, new_files_map) = safe
+            .files_container_move(&format!("{}/dir", xorurl), "/moved", false, false, false)
+            .await?;
+        assert_eq!(version, 3);
+        assert_eq!(processed_files["/dir/a.md"].0, CONTENT_DELETED_SIGN);
+        assert_eq!(processed_files["/moved/a.md"].0, CONTENT_ADDED_SIGN);
         assert_eq!(
-            new_processed_files["../testdata/subfolder/sub2.md"].1,
-            "File named \"/sub2.md\" with same content already exists on target. Use the \'force\' flag to replace it"
+            new_files_map["/moved/b.md"][PREDICATE_LINK],
+            files_map["/dir/b.md"][PREDICATE_LINK]
         );
-        assert_eq!(files_map, new_files_map);
+        assert!(new_files_map.keys().all(|path| !path.starts_with("/dir/")));
 
-        // let's try to add a file with same target name but with different content, it should still fail
-        let (version, new_processed_files, new_files_map) = safe
-            .files_container_add(
-                "../testdata/test.md",
-                &format!("{}/sub2.md", xorurl),
-                false,
-                false,
+        let (version, _, new_files_map) = safe
+            .files_container_copy(
+                &format!("{}/moved/a.md", xorurl),
+                "/moved/b.md",
+                true,
                 false,
                 false,
             )
             .await?;
-
-        assert_eq!(version, 0);
-        assert_eq!(new_processed_files.len(), 1);
-        assert_eq!(new_files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
+        assert_eq!(version, 4);
         assert_eq!(
-            new_processed_files["../testdata/test.md"].1,
-            "File named \"/sub2.md\" with different content already exists on target. Use the \'force\' flag to replace it"
+            new_files_map["/moved/b.md"][PREDICATE_LINK],
+            new_files_map["/moved/a.md"][PREDICATE_LINK]
         );
-        assert_eq!(files_map, new_files_map);
 
-        // let's now force it
-        let (version, new_processed_files, new_files_map) = safe
-            .files_container_add(
-                "../testdata/test.md",
-                &format!("{}/sub2.md", xorurl),
-                true, //force it
+        let (_, fetched_files_map) = safe.files_container_get(&xorurl).await?;
+        assert_eq!(fetched_files_map, new_files_map);
+
+        // nothing is published if nothing could be relocated
+        let (version, processed_files, _) = safe
+            .files_container_copy(
+                &format!("{}/moved/a.md", xorurl),
+                "/moved/b.md",
                 false,
                 false,
                 false,
             )
             .await?;
-
-        assert_eq!(version, 1);
-        assert_eq!(new_processed_files.len(), 1);
-        assert_eq!(new_files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
-        assert_eq!(
-            new_processed_files["../testdata/test.md"].0,
-            CONTENT_UPDATED_SIGN
-        );
-        assert_eq!(
-            new_processed_files["../testdata/test.md"].1,
-            new_files_map["/sub2.md"]["link"]
-        );
+        assert_eq!(version, 4);
+        assert_eq!(processed_files["/moved/a.md"].0, CONTENT_ERROR_SIGN);
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_fail_add_or_sync_invalid_path() -> Result<()> {
-        let mut safe = new_safe_instance().await?;
-        let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/test.md"), None, false, true, false)
+    async fn test_files_container_mkdir() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let (xorurl, _, _) = safe
+            .files_container_create(None, None, true, false, false)
             .await?;
-        assert_eq!(processed_files.len(), 1);
-        assert_eq!(files_map.len(), 1);
-        let _ = retry_loop!(safe.fetch(&xorurl, None));
-
-        match safe
-            .files_container_sync(
-                "/non-existing-path",
-                &xorurl,
-                false,
-                false,
+        let _ = safe
+            .files_container_add_from_raw(
+                b"a",
+                &format!("{}/dir/a.md", xorurl),
                 false,
                 false,
                 false,
             )
+            .await?;
+
+        // parents must exist unless 'parents' is set
+        match safe
+            .files_container_mkdir(&format!("{}/new/sub", xorurl), false, false, false)
             .await
         {
-            Ok(_) => {
-                bail!("Unexpectedly added a folder to files container".to_string(),)
-            }
-            Err(Error::FileSystemError(msg)) => {
-                assert!(msg
-                    .starts_with("Couldn't read metadata from source path ('/non-existing-path')"))
-            }
-            other => {
-                bail!("Error returned is not the expected one: {:?}", other)
-            }
+            Err(Error::ContentNotFound(_)) => {}
+            other => bail!("Unexpected result: {:?}", other),
+        }
+
+        let (version, processed_files, files_map) = safe
+            .files_container_mkdir(&format!("{}/new/sub/", xorurl), true, false, false)
+            .await?;
+        assert_eq!(version, 2);
+        assert_eq!(processed_files.len(), 2);
+        assert_eq!(processed_files["/new"].0, CONTENT_ADDED_SIGN);
+        assert_eq!(
+            files_map["/new/sub"][PREDICATE_TYPE],
+            MIMETYPE_FILESYSTEM_DIR
+        );
+
+        // an existing directory is fine only with 'parents', even
+        // if it's only implied by the files it contains
+        let (version, processed_files, _) = safe
+            .files_container_mkdir(&format!("{}/dir", xorurl), true, false, false)
+            .await?;
+        assert_eq!(version, 2);
+        assert!(processed_files.is_empty());
+        assert!(safe
+            .files_container_mkdir(&format!("{}/new/sub", xorurl), false, false, false)
+            .await
+            .is_err());
+
+        let (version, processed_files, _) = safe
+            .files_container_mkdir(&format!("{}/dir/empty", xorurl), false, false, false)
+            .await?;
+        assert_eq!(version, 3);
+        assert_eq!(processed_files.len(), 1);
+
+        // it cannot go through files
+        match safe
+            .files_container_mkdir(&format!("{}/dir/a.md/sub", xorurl), true, false, false)
+            .await
+        {
+            Err(Error::InvalidInput(_)) => Ok(()),
+            other => Err(anyhow!("Unexpected result: {:?}", other)),
         }
+    }
+
+    #[tokio::test]
+    async fn test_files_container_custom_metadata() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let src = new_temp_dir()?;
+        std::fs::write(src.join("a.txt"), "a")?;
+        std::fs::write(src.join("b.txt"), "b")?;
+        let location = format!("{}/", src.display());
+        let (xorurl, _, _) = safe
+            .files_container_create(Some(&location), None, true, false, false)
+            .await?;
 
-        match safe
+        let a_url = format!("{}/a.txt", xorurl);
+        let b_url = format!("{}/b.txt", xorurl);
+        let (version, processed_files, files_map) = safe
+            .files_container_set_metadata(&a_url, "author", "maidsafe", false, false)
+            .await?;
+        assert_eq!(version, 1);
+        assert_eq!(processed_files["/a.txt"].0, CONTENT_UPDATED_SIGN);
+        assert_eq!(files_map["/a.txt"]["custom:author"], "maidsafe");
+        let (version, _, _) = safe
+            .files_container_set_metadata(&b_url, "cache-control", "no-cache", false, false)
+            .await?;
+        assert_eq!(version, 2);
+
+        // custom metadata can be read back from specific versions too
+        let metadata = safe.files_container_get_metadata(&a_url).await?;
+        assert_eq!(metadata.len(), 1);
+        assert_eq!(metadata["author"], "maidsafe");
+        let metadata = safe
+            .files_container_get_metadata(&format!("{}?v=0", a_url))
+            .await?;
+        assert!(metadata.is_empty());
+
+        // it's kept for unchanged content only
+        std::fs::write(src.join("b.txt"), "new b")?;
+        let (version, _, files_map) = safe
+            .files_container_sync(&location, &xorurl, true, false, false, false, false)
+            .await?;
+        assert_eq!(version, 3);
+        assert_eq!(files_map["/a.txt"]["custom:author"], "maidsafe");
+        assert!(!files_map["/b.txt"].contains_key("custom:cache-control"));
+
+        let (version, _, files_map) = safe
             .files_container_add(
-                "/non-existing-path",
-                &format!("{}/test.md", xorurl),
-                true, // force it
+                &src.join("a.txt").display().to_string(),
+                &a_url,
+                true,
                 false,
                 false,
                 false,
             )
+            .await?;
+        assert_eq!(version, 4);
+        assert_eq!(files_map["/a.txt"]["custom:author"], "maidsafe");
+
+        let (version, _, files_map) = safe
+            .files_container_remove_metadata(&a_url, "author", false, false)
+            .await?;
+        assert_eq!(version, 5);
+        assert!(!files_map["/a.txt"].contains_key("custom:author"));
+
+        // nothing to remove, thus no new version
+        let (version, processed_files, _) = safe
+            .files_container_remove_metadata(&a_url, "author", false, false)
+            .await?;
+        assert_eq!(version, 5);
+        assert!(processed_files.is_empty());
+
+        match safe
+            .files_container_set_metadata(&format!("{}/c.txt", xorurl), "a", "b", false, false)
             .await
         {
-            Ok(_) => Err(anyhow!(
-                "Unexpectedly added a folder to files container".to_string(),
-            )),
-            Err(Error::FileSystemError(msg)) => {
-                assert!(msg
-                    .starts_with("Couldn't read metadata from source path ('/non-existing-path')"));
-                Ok(())
-            }
-            other => Err(anyhow!(
-                "Error returned is not the expected one: {:?}",
-                other
-            )),
+            Err(Error::ContentNotFound(_)) => Ok(()),
+            other => Err(anyhow!("Unexpected result: {:?}", other)),
         }
     }
 
     #[tokio::test]
-    async fn test_files_container_add_a_url() -> Result<()> {
-        let mut safe = new_safe_instance().await?;
-        let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/subfolder/"), None, false, true, false)
+    async fn test_files_container_verify() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let src = new_temp_dir()?;
+        std::fs::create_dir(src.join("dir"))?;
+        std::fs::write(src.join("dir/a.txt"), "a")?;
+        std::fs::write(src.join("b.txt"), "b")?;
+        let location = format!("{}/", src.display());
+        let (xorurl, _, files_map) = safe
+            .files_container_create(Some(&location), None, true, false, false)
             .await?;
-        assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
-        assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
-        let _ = retry_loop!(safe.fetch(&xorurl, None));
+        assert_eq!(files_map["/b.txt"][PREDICATE_CHECKSUM], data_checksum(b"b"));
 
-        let data = b"0123456789";
-        let file_xorurl = safe.files_store_public_blob(data, None, false).await?;
-        let new_filename = "/new_filename_test.md";
+        // content added from raw data has its size and checksum recorded as well
+        let _ = safe
+            .files_container_add_from_raw(
+                b"raw",
+                &format!("{}/raw.txt", xorurl),
+                false,
+                false,
+                false,
+            )
+            .await?;
 
-        let (version, new_processed_files, new_files_map) = safe
+        // content added by linking to it has neither
+        let link = safe.files_store_public_blob(b"linked", None, false).await?;
+        let (version, _, _) = safe
             .files_container_add(
-                &file_xorurl,
-                &format!("{}{}", xorurl, new_filename),
+                &link,
+                &format!("{}/linked", xorurl),
                 false,
                 false,
                 false,
@@ -2592,169 +5460,297 @@ mod tests {
             )
             .await?;
 
-        assert_eq!(version, 1);
-        assert_eq!(new_processed_files.len(), 1);
-        assert_eq!(new_files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT + 1);
+        let (verified_version, files_integrity) = safe.files_container_verify(&xorurl).await?;
+        assert_eq!(verified_version, version);
+        assert_eq!(files_integrity.len(), 4);
+        assert_eq!(files_integrity["/dir/a.txt"], FileIntegrity::Valid);
+        assert_eq!(files_integrity["/b.txt"], FileIntegrity::Valid);
+        assert_eq!(files_integrity["/raw.txt"], FileIntegrity::Valid);
+        assert_eq!(files_integrity["/linked"], FileIntegrity::Unverified);
+
+        // only the files within the path are verified
+        let (_, files_integrity) = safe
+            .files_container_verify(&format!("{}/dir", xorurl))
+            .await?;
+        assert_eq!(files_integrity.len(), 1);
+        assert!(files_integrity.contains_key("/dir/a.txt"));
 
-        let filename1 = "../testdata/subfolder/subexists.md";
-        assert_eq!(processed_files[filename1].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            processed_files[filename1].1,
-            new_files_map["/subexists.md"][PREDICATE_LINK]
-        );
+        // content which doesn't match what was recorded is reported
+        let mut corrupted_map = files_map.clone();
+        if let Some(file_item) = corrupted_map.get_mut("/b.txt") {
+            file_item.insert(PREDICATE_SIZE.to_string(), "2".to_string());
+        }
+        if let Some(file_item) = corrupted_map.get_mut("/dir/a.txt") {
+            file_item.insert(PREDICATE_CHECKSUM.to_string(), data_checksum(b"x"));
+        }
+        let missing_link = safe.files_store_public_blob(b"missing", None, true).await?;
+        let mut missing_item = files_map["/b.txt"].clone();
+        missing_item.insert(PREDICATE_LINK.to_string(), missing_link);
+        corrupted_map.insert("/missing.txt".to_string(), missing_item);
+        let mut malformed_item = files_map["/b.txt"].clone();
+        malformed_item.remove(PREDICATE_MODIFIED);
+        corrupted_map.insert("/malformed.txt".to_string(), malformed_item);
+        let files_integrity = files_map_verify(&safe, &corrupted_map, "").await?;
+        assert!(matches!(
+            files_integrity["/b.txt"],
+            FileIntegrity::Corrupted(_)
+        ));
+        assert!(matches!(
+            files_integrity["/dir/a.txt"],
+            FileIntegrity::Corrupted(_)
+        ));
+        assert!(matches!(
+            files_integrity["/missing.txt"],
+            FileIntegrity::Missing(_)
+        ));
+        assert!(matches!(
+            files_integrity["/malformed.txt"],
+            FileIntegrity::Corrupted(_)
+        ));
+        assert_eq!(files_integrity["/raw.txt"], FileIntegrity::Valid);
 
-        let filename2 = "../testdata/subfolder/sub2.md";
-        assert_eq!(processed_files[filename2].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            processed_files[filename2].1,
-            new_files_map["/sub2.md"][PREDICATE_LINK]
-        );
+        match safe
+            .files_container_verify(&format!("{}/nothing", xorurl))
+            .await
+        {
+            Err(Error::ContentNotFound(_)) => Ok(()),
+            other => Err(anyhow!("Unexpected result: {:?}", other)),
+        }
+    }
 
-        assert_eq!(new_processed_files[new_filename].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            new_processed_files[new_filename].1,
-            new_files_map[new_filename][PREDICATE_LINK]
-        );
-        assert_eq!(new_files_map[new_filename][PREDICATE_LINK], file_xorurl);
+    #[tokio::test]
+    async fn test_files_container_ls() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let src = new_temp_dir()?;
+        std::fs::create_dir(src.join("dir"))?;
+        std::fs::write(src.join("dir/a.txt"), "a")?;
+        std::fs::write(src.join("dir/b.html"), "bb")?;
+        std::fs::write(src.join("dir/c.txt"), "ccc")?;
+        let location = format!("{}/", src.display());
+        let (xorurl, _, _) = safe
+            .files_container_create(Some(&location), None, true, false, false)
+            .await?;
 
-        // let's add another file but with the same name
-        let data = b"9876543210";
-        let other_file_xorurl = safe.files_store_public_blob(data, None, false).await?;
-        let (version, new_processed_files, new_files_map) = safe
-            .files_container_add(
-                &other_file_xorurl,
-                &format!("{}{}", xorurl, new_filename),
-                true, // force to overwrite it with new link
+        let result = safe
+            .files_container_ls(&xorurl, &LsOptions::default())
+            .await?;
+        assert_eq!(result.version, 0);
+        assert_eq!(result.entries.len(), 1);
+        assert_eq!(result.entries[0].path, "/dir");
+        assert_eq!(result.entries[0].media_type(), MIMETYPE_FILESYSTEM_DIR);
+
+        let dir_url = format!("{}/dir", xorurl);
+        let options = LsOptions {
+            sort_by: LsSortKey::Size,
+            descending: true,
+            media_types: vec!["text/*".to_string()],
+            limit: Some(1),
+            cursor: None,
+        };
+        let first_page = safe.files_container_ls(&dir_url, &options).await?;
+        assert_eq!(first_page.entries.len(), 1);
+        assert_eq!(first_page.entries[0].name, "c.txt");
+        assert_eq!(first_page.entries[0].size, Some(3));
+
+        // the next pages are taken from the version the first one was
+        let _ = safe
+            .files_container_add_from_raw(
+                b"dddd",
+                &format!("{}/d.txt", dir_url),
                 false,
                 false,
                 false,
             )
             .await?;
+        let options = LsOptions {
+            cursor: first_page.next_cursor,
+            ..options
+        };
+        let second_page = safe.files_container_ls(&dir_url, &options).await?;
+        assert_eq!(second_page.version, 0);
+        assert_eq!(second_page.entries[0].name, "b.html");
+        assert!(second_page.next_cursor.is_some());
 
-        assert_eq!(version, 2);
-        assert_eq!(new_processed_files.len(), 1);
-        assert_eq!(new_files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT + 1);
-        assert_eq!(new_processed_files[new_filename].0, CONTENT_UPDATED_SIGN);
-        assert_eq!(
-            new_processed_files[new_filename].1,
-            new_files_map[new_filename][PREDICATE_LINK]
-        );
-        assert_eq!(
-            new_files_map[new_filename][PREDICATE_LINK],
-            other_file_xorurl
-        );
+        let result = safe
+            .files_container_ls(&dir_url, &LsOptions::default())
+            .await?;
+        assert_eq!(result.version, 1);
+        assert_eq!(result.entries.len(), 4);
 
-        Ok(())
+        match safe
+            .files_container_ls(&format!("{}?v=1", dir_url), &options)
+            .await
+        {
+            Err(Error::InvalidInput(_)) => Ok(()),
+            other => Err(anyhow!("Unexpected result: {:?}", other)),
+        }
     }
 
     #[tokio::test]
-    async fn test_files_container_add_from_raw() -> Result<()> {
-        let mut safe = new_safe_instance().await?;
-        let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/subfolder/"), None, false, true, false)
+    async fn test_files_container_search() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let (xorurl, _, _) = safe
+            .files_container_create(None, None, true, false, false)
             .await?;
-        assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
-        assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
-        let _ = retry_loop!(safe.fetch(&xorurl, None));
-
-        let data = b"0123456789";
-        let new_filename = "/new_filename_test.md";
-
-        let (version, new_processed_files, new_files_map) = safe
+        let _ = safe
             .files_container_add_from_raw(
-                data,
-                &format!("{}{}", xorurl, new_filename),
+                b"small",
+                &format!("{}/a/small.txt", xorurl),
                 false,
                 false,
                 false,
             )
             .await?;
-
-        assert_eq!(version, 1);
-        assert_eq!(new_processed_files.len(), 1);
-        assert_eq!(new_files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT + 1);
-
-        assert_eq!(new_processed_files[new_filename].0, CONTENT_ADDED_SIGN);
-        assert_eq!(
-            new_processed_files[new_filename].1,
-            new_files_map[new_filename][PREDICATE_LINK]
-        );
-
-        // let's add another file but with the same name
-        let data = b"9876543210";
-        let (version, new_processed_files, new_files_map) = safe
+        let (version, _, _) = safe
             .files_container_add_from_raw(
-                data,
-                &format!("{}{}", xorurl, new_filename),
-                true, // force to overwrite it with new link
+                b"larger content",
+                &format!("{}/b/large.txt", xorurl),
+                false,
+                false,
+                false,
+            )
+            .await?;
+        let _ = safe
+            .files_container_set_metadata(
+                &format!("{}/a/small.txt", xorurl),
+                "tags",
+                "x",
                 false,
                 false,
             )
             .await?;
 
-        assert_eq!(version, 2);
-        assert_eq!(new_processed_files.len(), 1);
-        assert_eq!(new_files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT + 1);
-        assert_eq!(new_processed_files[new_filename].0, CONTENT_UPDATED_SIGN);
-        assert_eq!(
-            new_processed_files[new_filename].1,
-            new_files_map[new_filename][PREDICATE_LINK]
-        );
+        let query = FilesQuery::SizeAtLeast(10);
+        let (found_version, found) = safe.files_container_search(&xorurl, &query).await?;
+        assert_eq!(found_version, version + 1);
+        assert_eq!(found.keys().collect::<Vec<_>>(), ["/b/large.txt"]);
+
+        // searching a previous version
+        let query = FilesQuery::Metadata {
+            name: "tags".to_string(),
+            value: None,
+        };
+        let (_, found) = safe.files_container_search(&xorurl, &query).await?;
+        assert_eq!(found.len(), 1);
+        let (_, found) = safe
+            .files_container_search(&format!("{}?v={}", xorurl, version), &query)
+            .await?;
+        assert!(found.is_empty());
+
+        // searching within a path
+        let query = FilesQuery::PathGlob("*.txt".to_string());
+        let (_, found) = safe
+            .files_container_search(&format!("{}/a", xorurl), &query)
+            .await?;
+        assert_eq!(found.keys().collect::<Vec<_>>(), ["/a/small.txt"]);
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_files_container_remove_path() -> Result<()> {
-        let mut safe = new_safe_instance().await?;
-        let (xorurl, processed_files, files_map) = safe
-            .files_container_create(Some("../testdata/"), None, true, true, false)
+    async fn test_files_container_remove_paths() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let (xorurl, _, _) = safe
+            .files_container_create(None, None, true, false, false)
             .await?;
-        assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
-        assert_eq!(files_map.len(), TESTDATA_PUT_FILEITEM_COUNT);
-        let _ = retry_loop!(safe.fetch(&xorurl, None));
+        for path in &[
+            "/a.txt",
+            "/logs/1.tmp",
+            "/logs/old/2.tmp",
+            "/logs/old/2.log",
+        ] {
+            let _ = safe
+                .files_container_add_from_raw(
+                    path.as_bytes(),
+                    &format!("{}{}", xorurl, path),
+                    false,
+                    false,
+                    false,
+                )
+                .await?;
+        }
 
-        // let's remove a file first
-        let (version, new_processed_files, new_files_map) = safe
-            .files_container_remove_path(&format!("{}/test.md", xorurl), false, false, false)
+        let paths = ["/a.txt", "/logs/**/*.tmp"];
+        let (version, processed_files, files_map) = safe
+            .files_container_remove_paths(&xorurl, &paths, false, false, true)
             .await?;
-
-        assert_eq!(version, 1);
-        assert_eq!(new_processed_files.len(), 1);
-        assert_eq!(new_files_map.len(), TESTDATA_PUT_FILEITEM_COUNT - 1);
-
-        let filepath = "/test.md";
-        assert_eq!(new_processed_files[filepath].0, CONTENT_DELETED_SIGN);
-        assert_eq!(
-            new_processed_files[filepath].1,
-            files_map[filepath][PREDICATE_LINK]
-        );
-
-        // let's remove an entire folder now with recursive flag
-        let (version, new_processed_files, new_files_map) = safe
-            .files_container_remove_path(&format!("{}/subfolder", xorurl), true, false, false)
+        assert_eq!(version, 5);
+        assert_eq!(processed_files.len(), 3);
+        assert_eq!(files_map.keys().collect::<Vec<_>>(), ["/logs/old/2.log"]);
+        let (version, _) = safe.files_container_get(&xorurl).await?;
+        assert_eq!(version, 4);
+
+        // all the paths are removed in a single version
+        let (version, processed_files, _) = safe
+            .files_container_remove_paths(&xorurl, &paths, false, false, false)
             .await?;
+        assert_eq!(version, 5);
+        assert!(processed_files
+            .values()
+            .all(|(change, _)| change == CONTENT_DELETED_SIGN));
+
+        // paths are relative to the path of the URL, and nothing matching isn't a change
+        let (version, processed_files, _) = safe
+            .files_container_remove_paths(
+                &format!("{}/logs", xorurl),
+                &["*.tmp"],
+                false,
+                false,
+                false,
+            )
+            .await?;
+        assert_eq!(version, 5);
+        assert!(processed_files.is_empty());
+        let (version, _, files_map) = safe
+            .files_container_remove_paths(
+                &format!("{}/logs", xorurl),
+                &["old/*"],
+                false,
+                false,
+                false,
+            )
+            .await?;
+        assert_eq!(version, 6);
+        assert!(files_map.is_empty());
 
-        assert_eq!(version, 2);
-        assert_eq!(new_processed_files.len(), 2);
-        assert_eq!(
-            new_files_map.len(),
-            TESTDATA_PUT_FILEITEM_COUNT - SUBFOLDER_PUT_FILEITEM_COUNT - 1
-        );
-
-        let filename1 = "/subfolder/subexists.md";
-        assert_eq!(new_processed_files[filename1].0, CONTENT_DELETED_SIGN);
-        assert_eq!(
-            new_processed_files[filename1].1,
-            files_map[filename1][PREDICATE_LINK]
-        );
+        assert!(safe
+            .files_container_remove_paths(&xorurl, &[], false, false, false)
+            .await
+            .is_err());
+        Ok(())
+    }
+    #[tokio::test]
+    async fn test_files_container_remove_path_with_glob_characters() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let (xorurl, _, _) = safe
+            .files_container_create(None, None, true, false, false)
+            .await?;
+        for path in &["/photos/%5B2021%5D%20trip.jpg", "/photos/2%20trip.jpg"] {
+            let _ = safe
+                .files_container_add_from_raw(
+                    b"photo",
+                    &format!("{}{}", xorurl, path),
+                    false,
+                    false,
+                    false,
+                )
+                .await?;
+        }
 
-        let filename2 = "/subfolder/sub2.md";
-        assert_eq!(new_processed_files[filename2].0, CONTENT_DELETED_SIGN);
+        // the path of the URL is taken literally rather than as a pattern
+        let (version, processed_files, files_map) = safe
+            .files_container_remove_path(
+                &format!("{}/photos/%5B2021%5D%20trip.jpg", xorurl),
+                false,
+                false,
+                false,
+            )
+            .await?;
+        assert_eq!(version, 3);
         assert_eq!(
-            new_processed_files[filename2].1,
-            files_map[filename2][PREDICATE_LINK]
+            processed_files.keys().collect::<Vec<_>>(),
+            ["/photos/[2021] trip.jpg"]
         );
-
+        assert!(files_map.contains_key("/photos/2 trip.jpg"));
         Ok(())
     }
 }
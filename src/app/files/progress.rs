@@ -0,0 +1,120 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::XorUrl;
+use std::{fmt, sync::Arc};
+
+/// Event reported to a `ProgressObserver` whilst creating, syncing or adding files to
+/// a FilesContainer. Paths are the local paths, as reported in the `ProcessedFiles` list,
+/// except for removed paths which are paths of the FilesContainer.
+///
+/// Every local file discovered is eventually reported as either uploaded, skipped or
+/// errored, which allows calculating the overall progress of the operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilesProgress {
+    /// A local file to be processed was found
+    FileDiscovered {
+        /// Local path of the file
+        path: String,
+        /// Size of the file in bytes
+        size: u64,
+    },
+    /// A piece of a local file was uploaded, or only hashed if it's a dry run. Files larger
+    /// than a piece are uploaded in several of them, thus reporting progress within a file.
+    BytesUploaded {
+        /// Local path of the file
+        path: String,
+        /// Number of bytes of the piece uploaded
+        bytes: u64,
+    },
+    /// A local file was uploaded, or only its XOR-URL calculated if it's a dry run
+    FileUploaded {
+        /// Local path of the file
+        path: String,
+        /// Number of bytes uploaded
+        size: u64,
+        /// XOR-URL of the file uploaded
+        xorurl: XorUrl,
+    },
+    /// A local file was not uploaded, since it's unchanged or it already exists on the target
+    FileSkipped {
+        /// Local path of the file
+        path: String,
+        /// Reason the file was skipped
+        reason: String,
+    },
+    /// A local file couldn't be uploaded or added to the FilesContainer
+    FileErrored {
+        /// Local path of the file
+        path: String,
+        /// Description of the error
+        error: String,
+    },
+    /// A path was removed from the FilesContainer
+    FileRemoved {
+        /// Path in the FilesContainer
+        path: String,
+    },
+    /// A new version of the FilesContainer was committed
+    VersionCommitted {
+        /// The version committed
+        version: u64,
+        /// URL of the FilesContainer
+        url: String,
+    },
+}
+
+/// Observer notified of the progress of long-running files operations.
+///
+/// It can be used to update a progress bar, or to forward the events through a channel.
+/// It may be notified from concurrent uploads, thus it should return quickly.
+///
+/// ## Example
+///
+/// ```rust
+/// # use sn_api::files::{FilesProgress, ProgressObserver};
+/// # use std::sync::{mpsc, Mutex};
+/// let (sender, receiver) = mpsc::channel();
+/// let sender = Mutex::new(sender);
+/// let observer = ProgressObserver::new(move |event: FilesProgress| {
+///     let _ = sender.lock().map(|sender| sender.send(event));
+/// });
+/// ```
+#[derive(Clone)]
+pub struct ProgressObserver(Arc<dyn Fn(FilesProgress) + Send + Sync>);
+
+impl ProgressObserver {
+    /// Create an observer which calls the provided function with each event
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(FilesProgress) + Send + Sync + 'static,
+    {
+        Self(Arc::new(callback))
+    }
+
+    pub(crate) fn notify(&self, event: FilesProgress) {
+        (self.0)(event)
+    }
+}
+
+impl fmt::Debug for ProgressObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ProgressObserver")
+    }
+}
+
+// Notify the observer, if any, building the event only if it's needed
+pub(crate) fn notify_progress<F>(progress: Option<&ProgressObserver>, event: F)
+where
+    F: FnOnce() -> FilesProgress,
+{
+    if let Some(progress) = progress {
+        progress.notify(event());
+    }
+}
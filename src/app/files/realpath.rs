@@ -8,7 +8,7 @@
 // Software.
 
 use super::{
-    files_map::{FilesMap, GetAttr},
+    files_map::{FileItem, FilesMap, GetAttr},
     metadata::FileMeta,
 };
 use crate::{Error, Result};
@@ -17,7 +17,19 @@ pub(crate) trait RealPath {
     fn realpath(&self, fpath: &str) -> Result<String>;
 }
 
-impl RealPath for FilesMap {
+// Lookup of the FileItem found at a path, which is all resolving a real path needs,
+// so it can be done on FilesMaps which are only partially loaded
+pub(crate) trait FileItemLookup {
+    fn lookup(&self, path: &str) -> Option<&FileItem>;
+}
+
+impl FileItemLookup for FilesMap {
+    fn lookup(&self, path: &str) -> Option<&FileItem> {
+        self.get(path)
+    }
+}
+
+impl<T: FileItemLookup> RealPath for T {
     // Realpath returns the real path of a given file in the filecontainer
     // after resolving instances of "../", "./", and any relative symlinks.
     //
@@ -57,9 +69,9 @@ impl RealPath for FilesMap {
                     newpath.push(cur);
                     let tmppath = newpath.join("/");
 
-                    match &self.get(&tmppath) {
+                    match &self.lookup(&tmppath) {
                         Some(fileitem) => {
-                            let meta = FileMeta::from_file_item(&fileitem);
+                            let meta = FileMeta::from_file_item(&fileitem)?;
 
                             if meta.is_symlink() {
                                 nlinks += 1;
@@ -0,0 +1,331 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{realpath::RealPath, FileItem, FileMeta, FilesMap, ProcessedFiles};
+use crate::{app::consts::*, Error, Result};
+use log::info;
+
+// Move, or copy if `keep_source` is set, a path of a FilesMap onto a new path. The path can be
+// a file, a symlink or a directory, in which case all its content is relocated as well.
+// Symlinks found in the parents of both paths are resolved. If the destination is an existing
+// directory, or a symlink to one, the source is relocated into it. Nothing is relocated if
+// any of the entries cannot be, so a directory is never left half way relocated.
+// The root directory can be the destination, but it cannot be relocated itself.
+pub(crate) fn files_map_relocate(
+    src_path: &str,
+    dest_path: &str,
+    mut files_map: FilesMap,
+    force: bool,
+    keep_source: bool,
+) -> Result<(ProcessedFiles, FilesMap, u64)> {
+    let src_path = resolve_parents(&files_map, &normalise_path(src_path))?;
+    if src_path == "/" {
+        return Err(Error::InvalidInput(
+            "The root path of a FilesContainer cannot be relocated".to_string(),
+        ));
+    }
+    let mut dest_path = resolve_parents(&files_map, &normalise_path(dest_path))?;
+
+    if is_same_or_descendant(&dest_path, &src_path) {
+        return Err(Error::InvalidInput(format!(
+            "Cannot relocate \"{}\" onto itself or any of its sub-folders: \"{}\"",
+            src_path, dest_path
+        )));
+    }
+
+    let entries: Vec<(String, FileItem)> = files_map
+        .iter()
+        .filter(|(path, _)| is_same_or_descendant(path, &src_path))
+        .map(|(path, item)| (path.to_string(), item.clone()))
+        .collect();
+    if entries.is_empty() {
+        return Err(Error::ContentNotFound(format!(
+            "No content found matching the \"{}\" path on the target FilesContainer",
+            src_path
+        )));
+    }
+
+    // The root directory has no entry of its own in the FilesMap
+    let real_dest_path = normalise_path(&files_map.realpath(&dest_path)?);
+    if real_dest_path == "/" || files_map.get(&real_dest_path).map_or(false, is_dir) {
+        let src_name = src_path.rsplit('/').next().unwrap_or_default();
+        dest_path = format!("{}/{}", real_dest_path.trim_end_matches('/'), src_name);
+        if is_same_or_descendant(&dest_path, &src_path) {
+            return Err(Error::InvalidInput(format!(
+                "Cannot relocate \"{}\" onto itself",
+                src_path
+            )));
+        }
+    }
+    check_dest_parents(&files_map, &dest_path)?;
+
+    // All the entries are checked before relocating any of them
+    let mut processed_files = ProcessedFiles::new();
+    let mut relocations = Vec::with_capacity(entries.len());
+    for (path, file_item) in entries.into_iter() {
+        let new_path = format!("{}{}", dest_path, &path[src_path.len()..]);
+        let change = match files_map.get(&new_path) {
+            None => CONTENT_ADDED_SIGN,
+            // Directories are simply merged
+            Some(existing) if is_dir(existing) && is_dir(&file_item) => CONTENT_UPDATED_SIGN,
+            Some(existing) if is_dir(existing) => {
+                let msg = format!(
+                    "Cannot overwrite directory \"{}\" with non-directory \"{}\"",
+                    new_path, path
+                );
+                info!("Skipping \"{}\": {}", path, msg);
+                processed_files.insert(path, (CONTENT_ERROR_SIGN.to_string(), msg));
+                continue;
+            }
+            Some(_) if force => CONTENT_UPDATED_SIGN,
+            Some(_) => {
+                let msg = format!(
+                    "\"{}\" already exists on target. Use the 'force' flag to replace it",
+                    new_path
+                );
+                info!("Skipping \"{}\": {}", path, msg);
+                processed_files.insert(path, (CONTENT_ERROR_SIGN.to_string(), msg));
+                continue;
+            }
+        };
+        relocations.push((path, new_path, file_item, change));
+    }
+
+    if !processed_files.is_empty() {
+        info!(
+            "Nothing relocated from \"{}\" since {} of its entries cannot be",
+            src_path,
+            processed_files.len()
+        );
+        return Ok((processed_files, files_map, 0));
+    }
+
+    let mut success_count = 0;
+    for (path, new_path, file_item, change) in relocations.into_iter() {
+        let link = file_item.get(PREDICATE_LINK).cloned().unwrap_or_default();
+        if !keep_source {
+            files_map.remove(&path);
+            processed_files.insert(path, (CONTENT_DELETED_SIGN.to_string(), link.clone()));
+        }
+        files_map.insert(new_path.clone(), file_item);
+        processed_files.insert(new_path, (change.to_string(), link));
+        success_count += 1;
+    }
+
+    Ok((processed_files, files_map, success_count))
+}
+
+// Resolve the symlinks found in the parents of a path, but not in its last component,
+// so a symlink is relocated itself rather than what it links to
+pub(crate) fn resolve_parents(files_map: &FilesMap, path: &str) -> Result<String> {
+    let (parent, name) = path.split_at(path.rfind('/').unwrap_or(0));
+    let real_path = if name == "/." || name == "/.." {
+        files_map.realpath(path)?
+    } else if parent.is_empty() {
+        path.to_string()
+    } else {
+        format!(
+            "{}{}",
+            files_map.realpath(parent)?.trim_end_matches('/'),
+            name
+        )
+    };
+
+    // Resolving '..' components may lead to the root path
+    Ok(normalise_path(&real_path))
+}
+
+// The parents of the destination may not exist, since FilesContainers didn't
+// always have directories, but none of them can be a file or a symlink
+fn check_dest_parents(files_map: &FilesMap, dest_path: &str) -> Result<()> {
+    let mut parent = String::default();
+    let components: Vec<&str> = dest_path.split('/').filter(|c| !c.is_empty()).collect();
+    for component in components.iter().take(components.len().saturating_sub(1)) {
+        parent.push('/');
+        parent.push_str(component);
+        match files_map.get(&parent) {
+            Some(file_item) if !is_dir(file_item) => {
+                return Err(Error::InvalidInput(format!(
+                    "Cannot relocate onto \"{}\" since \"{}\" exists and it's not a directory",
+                    dest_path, parent
+                )))
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+// Paths in a FilesMap are absolute and have no trailing '/', but the root one which is "/"
+fn normalise_path(path: &str) -> String {
+    format!("/{}", path.trim_matches('/'))
+}
+
+fn is_same_or_descendant(path: &str, ancestor: &str) -> bool {
+    path == ancestor || path.starts_with(&format!("{}/", ancestor))
+}
+
+fn is_dir(file_item: &FileItem) -> bool {
+    file_item
+        .get(PREDICATE_TYPE)
+        .map_or(false, |file_type| FileMeta::filetype_is_dir(file_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{
+        files::files_map::SYMLINK_TARGET,
+        test_helpers::{self, FileItemBuilder},
+    };
+
+    fn file_item(link: &str) -> FileItem {
+        FileItemBuilder::file("text/markdown", link).build()
+    }
+
+    fn test_files_map() -> FilesMap {
+        test_helpers::files_map(vec![
+            ("/a.md", file_item("safe://a")),
+            ("/dir", FileItemBuilder::dir().build()),
+            ("/dir/b.md", file_item("safe://b")),
+            ("/dir/sub", FileItemBuilder::dir().build()),
+            ("/dir/sub/c.md", file_item("safe://c")),
+            ("/other", FileItemBuilder::dir().build()),
+        ])
+    }
+
+    #[test]
+    fn test_files_map_relocate_file() -> Result<()> {
+        let (processed_files, files_map, count) =
+            files_map_relocate("/a.md", "/renamed.md", test_files_map(), false, false)?;
+        assert_eq!(count, 1);
+        assert!(!files_map.contains_key("/a.md"));
+        assert_eq!(files_map["/renamed.md"][PREDICATE_LINK], "safe://a");
+        assert_eq!(processed_files["/a.md"].0, CONTENT_DELETED_SIGN);
+        assert_eq!(
+            processed_files["/renamed.md"],
+            (CONTENT_ADDED_SIGN.to_string(), "safe://a".to_string())
+        );
+
+        // into an existing directory
+        let (_, files_map, _) =
+            files_map_relocate("/a.md", "/other/", test_files_map(), false, true)?;
+        assert!(files_map.contains_key("/a.md"));
+        assert_eq!(files_map["/other/a.md"][PREDICATE_LINK], "safe://a");
+
+        // overwriting an existing file requires 'force'
+        let (processed_files, files_map, count) =
+            files_map_relocate("/a.md", "/dir/b.md", test_files_map(), false, false)?;
+        assert_eq!(count, 0);
+        assert_eq!(processed_files["/a.md"].0, CONTENT_ERROR_SIGN);
+        assert_eq!(files_map, test_files_map());
+
+        let (processed_files, files_map, count) =
+            files_map_relocate("/a.md", "/dir/b.md", test_files_map(), true, false)?;
+        assert_eq!(count, 1);
+        assert_eq!(processed_files["/dir/b.md"].0, CONTENT_UPDATED_SIGN);
+        assert_eq!(files_map["/dir/b.md"][PREDICATE_LINK], "safe://a");
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_map_relocate_dir() -> Result<()> {
+        let (processed_files, files_map, count) =
+            files_map_relocate("/dir", "/moved", test_files_map(), false, false)?;
+        assert_eq!(count, 4);
+        assert_eq!(processed_files.len(), 8);
+        assert!(files_map.keys().all(|path| !path.starts_with("/dir")));
+        assert!(files_map.contains_key("/moved"));
+        assert_eq!(files_map["/moved/sub/c.md"][PREDICATE_LINK], "safe://c");
+
+        let (_, files_map, count) =
+            files_map_relocate("/dir/", "/other", test_files_map(), false, true)?;
+        assert_eq!(count, 4);
+        assert!(files_map.contains_key("/dir/sub/c.md"));
+        assert!(files_map.contains_key("/other/dir/sub/c.md"));
+
+        assert!(files_map_relocate("/dir", "/dir/sub/x", test_files_map(), false, false).is_err());
+        assert!(files_map_relocate("/missing", "/x", test_files_map(), false, false).is_err());
+        assert!(files_map_relocate("/", "/x", test_files_map(), false, false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_map_relocate_into_root() -> Result<()> {
+        let (processed_files, files_map, count) =
+            files_map_relocate("/dir/b.md", "/", test_files_map(), false, false)?;
+        assert_eq!(count, 1);
+        assert!(!files_map.contains_key("/dir/b.md"));
+        assert_eq!(files_map["/b.md"][PREDICATE_LINK], "safe://b");
+        assert_eq!(processed_files["/b.md"].0, CONTENT_ADDED_SIGN);
+
+        let (_, files_map, count) =
+            files_map_relocate("/dir/sub", "/", test_files_map(), false, true)?;
+        assert_eq!(count, 2);
+        assert!(files_map.contains_key("/dir/sub/c.md"));
+        assert_eq!(files_map["/sub/c.md"][PREDICATE_LINK], "safe://c");
+
+        // what is already in the root cannot be relocated onto itself
+        assert!(files_map_relocate("/a.md", "/", test_files_map(), false, false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_map_relocate_all_or_nothing() -> Result<()> {
+        let mut files_map = test_files_map();
+        files_map.insert("/other/dir".to_string(), FileItemBuilder::dir().build());
+        files_map.insert("/other/dir/sub".to_string(), file_item("safe://x"));
+
+        // '/dir/sub' cannot overwrite the file, thus none of its siblings is moved either
+        let (processed_files, new_files_map, count) =
+            files_map_relocate("/dir", "/other", files_map.clone(), false, false)?;
+        assert_eq!(count, 0);
+        assert_eq!(processed_files.len(), 1);
+        assert_eq!(processed_files["/dir/sub"].0, CONTENT_ERROR_SIGN);
+        assert_eq!(new_files_map, files_map);
+
+        // nor onto a path going through an existing file
+        assert!(files_map_relocate("/dir", "/a.md/x", test_files_map(), false, false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_map_relocate_through_symlinks() -> Result<()> {
+        let mut files_map = test_files_map();
+        files_map.insert(
+            "/link".to_string(),
+            FileItemBuilder::new(MIMETYPE_FILESYSTEM_SYMLINK)
+                .property(SYMLINK_TARGET, "dir/sub")
+                .build(),
+        );
+
+        // symlinks in the parents are resolved
+        let (_, new_files_map, count) =
+            files_map_relocate("/link/c.md", "/link/d.md", files_map.clone(), false, false)?;
+        assert_eq!(count, 1);
+        assert!(!new_files_map.contains_key("/dir/sub/c.md"));
+        assert_eq!(new_files_map["/dir/sub/d.md"][PREDICATE_LINK], "safe://c");
+        assert!(!new_files_map.contains_key("/link/d.md"));
+
+        // a symlink to a directory as the destination is relocated into
+        let (_, new_files_map, _) =
+            files_map_relocate("/a.md", "/link", files_map.clone(), false, false)?;
+        assert_eq!(new_files_map["/dir/sub/a.md"][PREDICATE_LINK], "safe://a");
+        assert!(new_files_map.contains_key("/link"));
+
+        // whilst a symlink as the source is relocated itself
+        let (_, new_files_map, _) =
+            files_map_relocate("/link", "/other/link", files_map, false, false)?;
+        assert!(!new_files_map.contains_key("/link"));
+        assert_eq!(new_files_map["/other/link"][SYMLINK_TARGET], "dir/sub");
+        assert!(new_files_map.contains_key("/dir/sub/c.md"));
+        Ok(())
+    }
+}
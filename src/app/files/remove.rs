@@ -0,0 +1,196 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{files_map_remove_path, search::path_glob, FilesMap, ProcessedFiles};
+use crate::{app::consts::*, Result};
+
+// Remove several paths, relative to `base_path`, from the FilesMap provided. Paths containing
+// any glob special character are patterns which remove every entry matching them, whilst
+// the base path is always taken literally.
+pub(crate) fn files_map_remove_paths(
+    base_path: &str,
+    paths: &[&str],
+    mut files_map: FilesMap,
+    recursive: bool,
+) -> Result<(ProcessedFiles, FilesMap, u64)> {
+    let mut processed_files = ProcessedFiles::new();
+    for path in paths.iter() {
+        let (processed, new_files_map, _) = if is_glob(path) {
+            files_map_remove_matching(base_path, path, files_map, recursive)?
+        } else if processed_files.contains_key(&join_path(base_path, path)) {
+            // it was already removed as it matched a previous pattern
+            continue;
+        } else {
+            files_map_remove_path(&join_path(base_path, path), files_map, recursive)?
+        };
+        files_map = new_files_map;
+        processed_files.extend(processed);
+    }
+
+    let success_count = processed_files.len() as u64;
+    Ok((processed_files, files_map, success_count))
+}
+
+// Remove every entry within `base_path` whose path relative to it matches a glob pattern.
+// Directories are only removed, along with all their content, if `recursive` is set.
+fn files_map_remove_matching(
+    base_path: &str,
+    pattern: &str,
+    mut files_map: FilesMap,
+    recursive: bool,
+) -> Result<(ProcessedFiles, FilesMap, u64)> {
+    let glob = path_glob(pattern)?;
+    let prefix = format!("{}/", base_path.trim_end_matches('/'));
+    let matching: Vec<String> = files_map
+        .keys()
+        .filter(|path| {
+            path.strip_prefix(&prefix)
+                .map_or(false, |relative_path| glob.is_match(relative_path))
+        })
+        .cloned()
+        .collect();
+
+    let mut processed_files = ProcessedFiles::new();
+    for path in matching {
+        let is_dir = match files_map.get(&path) {
+            Some(file_item) => {
+                file_item.get(PREDICATE_TYPE).map(String::as_str) == Some(MIMETYPE_FILESYSTEM_DIR)
+            }
+            // it was removed along with a directory matched before
+            None => continue,
+        };
+
+        if is_dir {
+            if !recursive {
+                continue;
+            }
+            let (processed, new_files_map, _) = files_map_remove_path(&path, files_map, true)?;
+            files_map = new_files_map;
+            processed_files.extend(processed);
+        }
+
+        let (processed, new_files_map, _) = files_map_remove_path(&path, files_map, false)?;
+        files_map = new_files_map;
+        processed_files.extend(processed);
+    }
+
+    let success_count = processed_files.len() as u64;
+    Ok((processed_files, files_map, success_count))
+}
+
+// Join a path onto the base path, an empty path referring to the base path itself
+fn join_path(base_path: &str, path: &str) -> String {
+    let base_path = base_path.trim_end_matches('/');
+    let path = path.trim_start_matches('/');
+    if path.is_empty() {
+        base_path.to_string()
+    } else {
+        format!("{}/{}", base_path, path)
+    }
+}
+
+fn is_glob(path: &str) -> bool {
+    path.contains(|c| matches!(c, '*' | '?' | '[' | '{' | '\\'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_helpers::{self, FileItemBuilder};
+
+    // FilesMap with a directory for each path ending with '/', and a file for each other one
+    fn files_map(paths: &[&str]) -> FilesMap {
+        test_helpers::files_map(paths.iter().map(|path| {
+            let item = if path.ends_with('/') {
+                FileItemBuilder::dir()
+            } else {
+                FileItemBuilder::file("text/plain", &format!("safe://{}", path))
+            };
+            (path.trim_end_matches('/'), item.build())
+        }))
+    }
+
+    #[test]
+    fn test_files_map_remove_paths() -> Result<()> {
+        let files_map = files_map(&[
+            "/a.txt",
+            "/b.txt",
+            "/logs/",
+            "/logs/1.tmp",
+            "/logs/old/",
+            "/logs/old/2.tmp",
+            "/logs/old/2.log",
+        ]);
+
+        let (processed_files, new_files_map, count) =
+            files_map_remove_paths("", &["/a.txt", "/logs/**/*.tmp"], files_map.clone(), false)?;
+        assert_eq!(count, 3);
+        assert_eq!(
+            processed_files.keys().collect::<Vec<_>>(),
+            ["/a.txt", "/logs/1.tmp", "/logs/old/2.tmp"]
+        );
+        assert!(processed_files
+            .values()
+            .all(|(change, _)| change == CONTENT_DELETED_SIGN));
+        assert_eq!(
+            processed_files["/logs/1.tmp"].1,
+            "safe:///logs/1.tmp".to_string()
+        );
+        assert_eq!(new_files_map.len(), 4);
+
+        // directories matched are only removed if recursive
+        let (_, new_files_map, count) =
+            files_map_remove_paths("/logs", &["*"], files_map.clone(), false)?;
+        assert_eq!(count, 1);
+        assert!(new_files_map.contains_key("/logs/old"));
+
+        let (_, new_files_map, count) =
+            files_map_remove_paths("/logs", &["*", "old/2.log"], files_map.clone(), true)?;
+        assert_eq!(count, 4);
+        assert_eq!(
+            new_files_map.keys().collect::<Vec<_>>(),
+            ["/a.txt", "/b.txt", "/logs"]
+        );
+
+        // patterns not matching any entry are not an error, unlike paths not found
+        let (_, _, count) = files_map_remove_paths("", &["/*.md"], files_map.clone(), false)?;
+        assert_eq!(count, 0);
+        assert!(files_map_remove_paths("", &["/c.txt"], files_map.clone(), false).is_err());
+        assert!(files_map_remove_paths("", &["/[a"], files_map, false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_map_remove_paths_literal_base_path() -> Result<()> {
+        let files_map = files_map(&[
+            "/photos/",
+            "/photos/[2021] trip.jpg",
+            "/photos/2 trip.jpg",
+            "/photos/[2021]/",
+            "/photos/[2021]/a.jpg",
+            "/photos/2/",
+            "/photos/2/b.jpg",
+        ]);
+
+        // the base path is never a pattern, even if it contains glob special characters
+        let (processed_files, new_files_map, count) =
+            files_map_remove_paths("/photos/[2021] trip.jpg", &[""], files_map.clone(), false)?;
+        assert_eq!(count, 1);
+        assert!(processed_files.contains_key("/photos/[2021] trip.jpg"));
+        assert!(new_files_map.contains_key("/photos/2 trip.jpg"));
+
+        // only the paths relative to it are
+        let (processed_files, new_files_map, count) =
+            files_map_remove_paths("/photos/[2021]", &["*.jpg"], files_map, false)?;
+        assert_eq!(count, 1);
+        assert!(processed_files.contains_key("/photos/[2021]/a.jpg"));
+        assert!(new_files_map.contains_key("/photos/2/b.jpg"));
+        Ok(())
+    }
+}
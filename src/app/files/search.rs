@@ -0,0 +1,293 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{ls::matches_media_types, FileEntry, FilesMap};
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use globset::{GlobBuilder, GlobMatcher};
+use log::warn;
+use regex::Regex;
+use std::collections::BTreeMap;
+
+/// Predicate the entries of a FilesContainer are searched with, see
+/// `Safe::files_container_search`. Predicates can be composed with `All`, `Any` and `Not`.
+///
+/// ## Example
+///
+/// ```rust
+/// # use sn_api::files::FilesQuery;
+/// # use chrono::{Duration, Utc};
+/// // images over 1 MB modified during the last week
+/// let query = FilesQuery::All(vec![
+///     FilesQuery::MediaType("image/*".to_string()),
+///     FilesQuery::SizeAtLeast(1024 * 1024),
+///     FilesQuery::ModifiedAfter(Utc::now() - Duration::weeks(1)),
+/// ]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilesQuery {
+    /// The path matches the glob pattern. Patterns without a `/` are matched against the
+    /// name of the entries, otherwise against their whole path, e.g. `/photos/**/*.jpg`.
+    PathGlob(String),
+    /// The path matches the regular expression
+    PathRegex(String),
+    /// The media type matches, which can end with `/*` to match any of its subtypes.
+    /// Directories and symlinks have `inode/directory` and `inode/symlink` types respectively.
+    MediaType(String),
+    /// The size is known and it's at least this number of bytes
+    SizeAtLeast(u64),
+    /// The size is known and it's at most this number of bytes
+    SizeAtMost(u64),
+    /// The entry was last updated on the FilesContainer at, or after, this time
+    ModifiedAfter(DateTime<Utc>),
+    /// The entry was last updated on the FilesContainer before this time
+    ModifiedBefore(DateTime<Utc>),
+    /// The file was last modified on its original filesystem at, or after, this time
+    OriginalModifiedAfter(DateTime<Utc>),
+    /// The file was last modified on its original filesystem before this time
+    OriginalModifiedBefore(DateTime<Utc>),
+    /// The entry has the custom metadata, with the value provided if any
+    Metadata {
+        /// Name of the custom metadata
+        name: String,
+        /// Value the custom metadata must have, any value matches if None
+        value: Option<String>,
+    },
+    /// All of the predicates match, which is the case if there is none
+    All(Vec<FilesQuery>),
+    /// Any of the predicates matches, which is never the case if there is none
+    Any(Vec<FilesQuery>),
+    /// The predicate doesn't match
+    Not(Box<FilesQuery>),
+}
+
+// A FilesQuery with its patterns compiled, so they are only compiled once per search
+enum Matcher<'a> {
+    Glob(GlobMatcher, bool),
+    Regex(Regex),
+    Predicate(&'a FilesQuery),
+    All(Vec<Matcher<'a>>),
+    Any(Vec<Matcher<'a>>),
+    Not(Box<Matcher<'a>>),
+}
+
+impl<'a> Matcher<'a> {
+    fn compile(query: &'a FilesQuery) -> Result<Self> {
+        let matcher = match query {
+            FilesQuery::PathGlob(pattern) => Self::Glob(path_glob(pattern)?, pattern.contains('/')),
+            FilesQuery::PathRegex(pattern) => Self::Regex(Regex::new(pattern).map_err(|err| {
+                Error::InvalidInput(format!("Invalid regular expression '{}': {}", pattern, err))
+            })?),
+            FilesQuery::All(queries) => Self::All(Self::compile_all(queries)?),
+            FilesQuery::Any(queries) => Self::Any(Self::compile_all(queries)?),
+            FilesQuery::Not(query) => Self::Not(Box::new(Self::compile(query)?)),
+            predicate => Self::Predicate(predicate),
+        };
+
+        Ok(matcher)
+    }
+
+    fn compile_all(queries: &'a [FilesQuery]) -> Result<Vec<Self>> {
+        queries.iter().map(Self::compile).collect()
+    }
+
+    fn matches(&self, path: &str, entry: &FileEntry) -> bool {
+        match self {
+            Self::Glob(glob, true) => glob.is_match(path.trim_start_matches('/')),
+            Self::Glob(glob, false) => glob.is_match(path.rsplit('/').next().unwrap_or_default()),
+            Self::Regex(regex) => regex.is_match(path),
+            Self::Predicate(predicate) => predicate_matches(predicate, entry),
+            Self::All(matchers) => matchers.iter().all(|matcher| matcher.matches(path, entry)),
+            Self::Any(matchers) => matchers.iter().any(|matcher| matcher.matches(path, entry)),
+            Self::Not(matcher) => !matcher.matches(path, entry),
+        }
+    }
+}
+
+// Compile a glob pattern matched against paths of a FilesMap without their leading '/'
+pub(crate) fn path_glob(pattern: &str) -> Result<GlobMatcher> {
+    let glob = GlobBuilder::new(pattern.trim_start_matches('/'))
+        .literal_separator(true)
+        .build()
+        .map_err(|err| {
+            Error::InvalidInput(format!("Invalid glob pattern '{}': {}", pattern, err))
+        })?;
+
+    Ok(glob.compile_matcher())
+}
+
+fn predicate_matches(predicate: &FilesQuery, entry: &FileEntry) -> bool {
+    match predicate {
+        FilesQuery::MediaType(media_type) => {
+            matches_media_types(entry.media_type(), &[media_type.clone()])
+        }
+        FilesQuery::SizeAtLeast(size) => entry.size.map_or(false, |s| s >= *size),
+        FilesQuery::SizeAtMost(size) => entry.size.map_or(false, |s| s <= *size),
+        FilesQuery::ModifiedAfter(time) => entry.modified >= *time,
+        FilesQuery::ModifiedBefore(time) => entry.modified < *time,
+        FilesQuery::OriginalModifiedAfter(time) => {
+            entry.original_modified.map_or(false, |t| t >= *time)
+        }
+        FilesQuery::OriginalModifiedBefore(time) => {
+            entry.original_modified.map_or(false, |t| t < *time)
+        }
+        FilesQuery::Metadata { name, value } => match (entry.custom.get(name), value) {
+            (Some(current), Some(value)) => current == value,
+            (current, None) => current.is_some(),
+            (None, Some(_)) => false,
+        },
+        // Patterns and compositions are compiled into their own matchers
+        FilesQuery::PathGlob(_)
+        | FilesQuery::PathRegex(_)
+        | FilesQuery::All(_)
+        | FilesQuery::Any(_)
+        | FilesQuery::Not(_) => false,
+    }
+}
+
+// Search the entries of a FilesMap found at, or within, `base_path` which match the query.
+// Entries which cannot be parsed cannot match any query, thus they are skipped.
+pub(crate) fn files_map_search(
+    files_map: &FilesMap,
+    base_path: &str,
+    query: &FilesQuery,
+) -> Result<BTreeMap<String, FileEntry>> {
+    let matcher = Matcher::compile(query)?;
+    let base_path = base_path.trim_end_matches('/');
+    let prefix = format!("{}/", base_path);
+
+    let mut found = BTreeMap::new();
+    for (path, file_item) in files_map.iter() {
+        if path != base_path && !path.starts_with(&prefix) {
+            continue;
+        }
+        let entry = match FileEntry::from_file_item(file_item) {
+            Ok(entry) => entry,
+            Err(err) => {
+                warn!("Skipping \"{}\" from the search: {}", path, err);
+                continue;
+            }
+        };
+        if matcher.matches(path, &entry) {
+            let _ = found.insert(path.to_string(), entry);
+        }
+    }
+
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_helpers::{self, FileItemBuilder};
+    use chrono::TimeZone;
+
+    fn file(file_type: &str, size: &str, modified: &str) -> FileItemBuilder {
+        FileItemBuilder::file(file_type, "safe://link")
+            .size(size)
+            .timestamps(modified)
+    }
+
+    fn files_map() -> FilesMap {
+        test_helpers::files_map(vec![
+            (
+                "/photos/big.png",
+                file("image/png", "2000000", "2021-03-10T10:00:00Z").build(),
+            ),
+            (
+                "/photos/old.jpg",
+                file("image/jpeg", "3000000", "2021-01-01T10:00:00Z").build(),
+            ),
+            (
+                "/photos/small.png",
+                file("image/png", "1000", "2021-03-10T10:00:00Z").build(),
+            ),
+            (
+                "/docs/README.md",
+                file("text/markdown", "100", "2021-03-10T10:00:00Z")
+                    .property("custom:author", "maidsafe")
+                    .build(),
+            ),
+        ])
+    }
+
+    fn paths(found: &BTreeMap<String, FileEntry>) -> Vec<&str> {
+        found.keys().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn test_files_map_search() -> Result<()> {
+        let files_map = files_map();
+
+        let query = FilesQuery::All(vec![
+            FilesQuery::MediaType("image/*".to_string()),
+            FilesQuery::SizeAtLeast(1024 * 1024),
+            FilesQuery::ModifiedAfter(Utc.ymd(2021, 3, 3).and_hms(0, 0, 0)),
+        ]);
+        let found = files_map_search(&files_map, "", &query)?;
+        assert_eq!(paths(&found), ["/photos/big.png"]);
+        assert_eq!(found["/photos/big.png"].size, Some(2_000_000));
+
+        let query = FilesQuery::PathGlob("*.png".to_string());
+        let found = files_map_search(&files_map, "", &query)?;
+        assert_eq!(paths(&found), ["/photos/big.png", "/photos/small.png"]);
+
+        let query = FilesQuery::PathGlob("/docs/*".to_string());
+        assert_eq!(
+            paths(&files_map_search(&files_map, "", &query)?),
+            ["/docs/README.md"]
+        );
+
+        let query = FilesQuery::Any(vec![
+            FilesQuery::PathRegex("^/photos/.*\\.jpg$".to_string()),
+            FilesQuery::Metadata {
+                name: "author".to_string(),
+                value: Some("maidsafe".to_string()),
+            },
+        ]);
+        let found = files_map_search(&files_map, "", &query)?;
+        assert_eq!(paths(&found), ["/docs/README.md", "/photos/old.jpg"]);
+
+        // searching within a path only
+        let query = FilesQuery::Not(Box::new(FilesQuery::MediaType("image/png".to_string())));
+        let found = files_map_search(&files_map, "/photos/", &query)?;
+        assert_eq!(paths(&found), ["/photos/old.jpg"]);
+
+        assert!(files_map_search(&files_map, "", &FilesQuery::Any(vec![]))?.is_empty());
+        assert_eq!(
+            files_map_search(&files_map, "", &FilesQuery::All(vec![]))?.len(),
+            4
+        );
+
+        // entries which cannot be parsed don't fail the search
+        let mut files_map = files_map;
+        files_map.insert(
+            "/photos/malformed.png".to_string(),
+            FileItemBuilder::new("image/png").build(),
+        );
+        let query = FilesQuery::PathGlob("*.png".to_string());
+        let found = files_map_search(&files_map, "", &query)?;
+        assert_eq!(paths(&found), ["/photos/big.png", "/photos/small.png"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_files_map_search_invalid_patterns() {
+        let files_map = files_map();
+        for query in &[
+            FilesQuery::PathGlob("[a".to_string()),
+            FilesQuery::Not(Box::new(FilesQuery::PathRegex("(a".to_string()))),
+        ] {
+            assert!(matches!(
+                files_map_search(&files_map, "", query),
+                Err(Error::InvalidInput(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,564 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    files_map::{validate_files_map, FileItem, FilesMap},
+    realpath::{FileItemLookup, RealPath},
+};
+use crate::{
+    app::serialisation::{deserialise_from_envelope, serialise_to_envelope},
+    Error, Result, Safe, SafeUrl, XorUrl,
+};
+use futures::stream::{self, StreamExt};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, BTreeSet},
+};
+
+// Subtrees of a FilesMap with at least this many entries are stored in a shard of their own
+const SHARD_MIN_ENTRIES: usize = 512;
+
+// Maximum number of shards fetched at a time
+const SHARD_FETCH_CONCURRENCY: usize = 8;
+
+// The subtree of a directory of a FilesMap, with the entries keyed by their path relative
+// to the directory. Subtrees of it large enough are stored in shards of their own, which
+// are referenced by their XOR-URL, keyed by the path of their directory relative to this one.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FilesMapShard {
+    entries: FilesMap,
+    shards: BTreeMap<String, XorUrl>,
+}
+
+// The Blob a FilesContainer version links to holds either the root shard of the FilesMap,
+// or the entire FilesMap when none of its subtrees is large enough to be sharded
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredFilesMap {
+    Sharded(FilesMapShard),
+    Flat(FilesMap),
+}
+
+// A shard serialised and ready to be stored, along with the XOR-URL it gets once stored
+struct PreparedShard {
+    data: Vec<u8>,
+    xorurl: XorUrl,
+    shards: BTreeMap<String, XorUrl>,
+}
+
+// Store a FilesMap returning the XOR-URL of the Blob a FilesContainer version is to link to.
+// Shards which are the same as in the FilesMap stored at `previous_xorurl`, if any, are
+// already on the network, thus only the shards of the subtrees which changed are uploaded.
+pub(crate) async fn store_files_map(
+    safe: &Safe,
+    files_map: &FilesMap,
+    previous_xorurl: Option<XorUrl>,
+) -> Result<XorUrl> {
+    store_files_map_shards(safe, files_map, previous_xorurl, SHARD_MIN_ENTRIES).await
+}
+
+async fn store_files_map_shards(
+    safe: &Safe,
+    files_map: &FilesMap,
+    previous_xorurl: Option<XorUrl>,
+    min_entries: usize,
+) -> Result<XorUrl> {
+    // paths which are not absolute cannot be split in subtrees
+    let shards = if files_map.keys().all(|path| path.starts_with('/')) {
+        split_files_map(files_map, min_entries)
+    } else {
+        BTreeMap::new()
+    };
+    if shards.len() <= 1 {
+        let serialised_files_map = serialise_to_envelope(files_map, "FilesMap")?;
+        return safe
+            .files_store_public_blob(&serialised_files_map, None, false)
+            .await;
+    }
+
+    let prepared_shards = prepare_shards(safe, shards).await?;
+
+    // Walk down the tree of shards along with the previous one, a shard with the same
+    // XOR-URL as before is the root of a subtree which is already stored as it is
+    let mut pending = vec![(String::new(), previous_xorurl)];
+    while let Some((dir, previous_xorurl)) = pending.pop() {
+        let shard = &prepared_shards[&dir];
+        if previous_xorurl.as_ref() == Some(&shard.xorurl) {
+            continue;
+        }
+
+        let _ = safe
+            .files_store_public_blob(&shard.data, None, false)
+            .await?;
+        debug!("FilesMap shard of \"{}\" stored at {}", dir, shard.xorurl);
+
+        let previous_shards = match previous_xorurl {
+            Some(xorurl) => fetch_shard(safe, &xorurl).await?.shards,
+            None => BTreeMap::new(),
+        };
+        for sub_dir in shard.shards.keys() {
+            pending.push((
+                format!("{}{}", dir, sub_dir),
+                previous_shards.get(sub_dir).cloned(),
+            ));
+        }
+    }
+
+    Ok(prepared_shards[""].xorurl.clone())
+}
+
+// Split a FilesMap into the shards it's stored in, keyed by the path of the directory they
+// hold the subtree of, the root one being "". Only the references to other shards are missing.
+// Since the subtree of a directory is at least as large as any within it, the parent of a
+// sharded directory is always sharded too, thus each shard is referenced by a single name.
+fn split_files_map(files_map: &FilesMap, min_entries: usize) -> BTreeMap<String, FilesMapShard> {
+    let mut subtree_sizes = BTreeMap::<&str, usize>::new();
+    for path in files_map.keys() {
+        for (index, _) in path.match_indices('/').filter(|(index, _)| *index > 0) {
+            *subtree_sizes.entry(&path[..index]).or_default() += 1;
+        }
+    }
+    let sharded_dirs: BTreeSet<&str> = subtree_sizes
+        .into_iter()
+        // directories with no proper name, nor those within them, can't be referenced by name
+        .filter(|(dir, size)| *size >= min_entries && dir.split('/').skip(1).all(is_dir_name))
+        .map(|(dir, _)| dir)
+        .collect();
+
+    let mut shards = BTreeMap::new();
+    let _ = shards.insert(String::new(), FilesMapShard::default());
+    for dir in sharded_dirs.iter() {
+        let _ = shards.insert(dir.to_string(), FilesMapShard::default());
+    }
+    for (path, file_item) in files_map.iter() {
+        let dir = sharded_parent_dir(path, &sharded_dirs);
+        if let Some(shard) = shards.get_mut(dir) {
+            let _ = shard
+                .entries
+                .insert(path[dir.len()..].to_string(), file_item.clone());
+        }
+    }
+
+    shards
+}
+
+// The closest directory containing the path which is stored in a shard of its own
+fn sharded_parent_dir<'a>(path: &'a str, sharded_dirs: &BTreeSet<&str>) -> &'a str {
+    path.rmatch_indices('/')
+        .map(|(index, _)| &path[..index])
+        .find(|dir| !dir.is_empty() && sharded_dirs.contains(dir))
+        .unwrap_or("")
+}
+
+// Serialise the shards, the deepest ones first so the XOR-URLs they
+// get are known by the time the shards referencing them are serialised
+async fn prepare_shards(
+    safe: &Safe,
+    mut shards: BTreeMap<String, FilesMapShard>,
+) -> Result<BTreeMap<String, PreparedShard>> {
+    let mut dirs: Vec<String> = shards.keys().cloned().collect();
+    dirs.sort_by_key(|dir| std::cmp::Reverse(dir.len()));
+    let sharded_dirs: BTreeSet<&str> = dirs.iter().map(String::as_str).collect();
+
+    let mut prepared_shards = BTreeMap::new();
+    for dir in dirs.iter() {
+        let shard = match shards.remove(dir) {
+            Some(shard) => shard,
+            None => continue,
+        };
+        let data = serialise_to_envelope(&shard, "FilesMap shard")?;
+        let xorurl = safe.files_store_public_blob(&data, None, true).await?;
+
+        if !dir.is_empty() {
+            let parent_dir = sharded_parent_dir(dir, &sharded_dirs);
+            if let Some(parent) = shards.get_mut(parent_dir) {
+                let _ = parent
+                    .shards
+                    .insert(dir[parent_dir.len()..].to_string(), xorurl.clone());
+            }
+        }
+
+        let _ = prepared_shards.insert(
+            dir.to_string(),
+            PreparedShard {
+                data,
+                xorurl,
+                shards: shard.shards,
+            },
+        );
+    }
+
+    Ok(prepared_shards)
+}
+
+// Fetch a shard, a FilesMap stored as a whole is a shard with no references to others
+async fn fetch_shard(safe: &Safe, xorurl: &str) -> Result<FilesMapShard> {
+    let safe_url = SafeUrl::from_url(xorurl)?;
+    let serialised_shard = safe.fetch_public_blob(&safe_url, None).await?;
+    match deserialise_from_envelope(&serialised_shard, "FilesMap")? {
+        StoredFilesMap::Sharded(shard) => Ok(shard),
+        StoredFilesMap::Flat(files_map) => Ok(FilesMapShard {
+            entries: files_map,
+            shards: BTreeMap::new(),
+        }),
+    }
+}
+
+// A FilesMap of which only some of the shards are loaded, they are
+// fetched as they are needed to resolve paths or list subtrees
+#[derive(Default)]
+pub(crate) struct PartialFilesMap {
+    files_map: FilesMap,
+    // shards not loaded yet, keyed by the path of the directory they hold the subtree of
+    unloaded_shards: BTreeMap<String, XorUrl>,
+    // directories each of the shards loaded holds the subtree of, keyed by their XOR-URL
+    loaded_shards: BTreeMap<XorUrl, Vec<String>>,
+    // shard a lookup couldn't be answered without, the last one found missing
+    missing_shard: Cell<Option<String>>,
+}
+
+impl PartialFilesMap {
+    // Fetch the FilesMap stored at the XOR-URL provided, loading only its root shard
+    pub(crate) async fn fetch(safe: &Safe, xorurl: &str) -> Result<Self> {
+        let mut partial_files_map = Self::default();
+        let root_shard = fetch_shard(safe, xorurl).await?;
+        partial_files_map.add_shard("", xorurl, root_shard)?;
+        Ok(partial_files_map)
+    }
+
+    // The entries loaded so far
+    pub(crate) fn files_map(&self) -> &FilesMap {
+        &self.files_map
+    }
+
+    pub(crate) fn into_files_map(self) -> FilesMap {
+        self.files_map
+    }
+
+    // Load every shard, returning the entire FilesMap
+    pub(crate) async fn load_all(mut self, safe: &Safe) -> Result<FilesMap> {
+        self.load_subtree(safe, "").await?;
+        Ok(self.files_map)
+    }
+
+    // Load the shards needed to have all the entries within the directory provided
+    pub(crate) async fn load_subtree(&mut self, safe: &Safe, dir: &str) -> Result<()> {
+        let dir = dir.trim_end_matches('/');
+        loop {
+            // the shards the ones within the directory are referenced from are needed too
+            let dirs: Vec<String> = self
+                .unloaded_shards
+                .keys()
+                .filter(|shard_dir| is_within(shard_dir, dir) || is_within(dir, shard_dir))
+                .cloned()
+                .collect();
+            if dirs.is_empty() {
+                return Ok(());
+            }
+            self.load_shards(safe, dirs).await?;
+        }
+    }
+
+    // Resolve the real path of a path loading only the shards the resolution goes through
+    pub(crate) async fn resolve_realpath(&mut self, safe: &Safe, path: &str) -> Result<String> {
+        loop {
+            let realpath = self.realpath(path);
+            let missing_shard = self.missing_shard.take();
+            match missing_shard {
+                Some(dir) => self.load_shards(safe, vec![dir]).await?,
+                None => return realpath,
+            }
+        }
+    }
+
+    pub(crate) fn get(&self, path: &str) -> Option<&FileItem> {
+        self.files_map.get(path)
+    }
+
+    async fn load_shards(&mut self, safe: &Safe, dirs: Vec<String>) -> Result<()> {
+        let shards_to_load: Vec<(String, XorUrl)> = dirs
+            .into_iter()
+            .filter_map(|dir| {
+                self.unloaded_shards
+                    .remove(&dir)
+                    .map(|xorurl| (dir, xorurl))
+            })
+            .collect();
+
+        let loaded_shards: Vec<Result<FilesMapShard>> = stream::iter(shards_to_load.iter())
+            .map(|(_, xorurl)| fetch_shard(safe, xorurl))
+            .buffered(SHARD_FETCH_CONCURRENCY)
+            .collect()
+            .await;
+
+        for ((dir, xorurl), shard) in shards_to_load.iter().zip(loaded_shards) {
+            self.add_shard(dir, xorurl, shard?)?;
+        }
+        Ok(())
+    }
+
+    fn add_shard(&mut self, dir: &str, xorurl: &str, shard: FilesMapShard) -> Result<()> {
+        let invalid_shard = |msg: String| {
+            Error::ContentError(format!(
+                "FilesMap shard of \"{}\" stored at {} is invalid: {}",
+                dir, xorurl, msg
+            ))
+        };
+
+        // a FilesMap stored as a whole can have any path, but not the shards of one
+        let is_sharded = !dir.is_empty() || !shard.shards.is_empty();
+        if let Some(path) = shard
+            .entries
+            .keys()
+            .find(|path| is_sharded && !path.starts_with('/'))
+        {
+            return Err(invalid_shard(format!("path \"{}\" is not absolute", path)));
+        }
+
+        self.loaded_shards
+            .entry(xorurl.to_string())
+            .or_default()
+            .push(dir.to_string());
+        for (sub_dir, sub_xorurl) in shard.shards.iter() {
+            let name = sub_dir.strip_prefix('/').unwrap_or("");
+            if name.contains('/') || !is_dir_name(name) {
+                return Err(invalid_shard(format!(
+                    "\"{}\" is not the name of a directory",
+                    sub_dir
+                )));
+            }
+
+            // a shard within one it's loaded at would be loaded again, and again
+            let shard_dir = format!("{}{}", dir, sub_dir);
+            let is_loop = self.loaded_shards.get(sub_xorurl).map_or(false, |dirs| {
+                dirs.iter().any(|dir| is_within(&shard_dir, dir))
+            });
+            if is_loop {
+                return Err(invalid_shard(format!(
+                    "the shard of \"{}\" references itself",
+                    shard_dir
+                )));
+            }
+        }
+
+        let entries: FilesMap = shard
+            .entries
+            .into_iter()
+            .map(|(path, file_item)| (format!("{}{}", dir, path), file_item))
+            .collect();
+        validate_files_map(&entries)?;
+
+        self.files_map.extend(entries);
+        for (sub_dir, xorurl) in shard.shards {
+            let _ = self
+                .unloaded_shards
+                .insert(format!("{}{}", dir, sub_dir), xorurl);
+        }
+        Ok(())
+    }
+}
+
+impl FileItemLookup for PartialFilesMap {
+    fn lookup(&self, path: &str) -> Option<&FileItem> {
+        let unloaded_dir = path
+            .match_indices('/')
+            .map(|(index, _)| &path[..index])
+            .find(|dir| self.unloaded_shards.contains_key(*dir));
+        match unloaded_dir {
+            Some(dir) => {
+                self.missing_shard.set(Some(dir.to_string()));
+                None
+            }
+            None => self.files_map.get(path),
+        }
+    }
+}
+
+// Whether a name can be the one of a directory stored in a shard of its own
+fn is_dir_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".."
+}
+
+// Whether the path is the directory provided or within it, everything is within ""
+fn is_within(path: &str, dir: &str) -> bool {
+    dir.is_empty() || path == dir || path.starts_with(&format!("{}/", dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_helpers::{new_in_memory_safe_instance, FileItemBuilder};
+    use anyhow::Result;
+
+    fn file_item(builder: FileItemBuilder) -> FileItem {
+        builder.size("0").timestamps("2021-03-10T10:00:00Z").build()
+    }
+
+    fn files_map(link: &str) -> FilesMap {
+        let mut files_map = FilesMap::new();
+        for dir in &["/a", "/a/b", "/c"] {
+            files_map.insert(dir.to_string(), file_item(FileItemBuilder::dir()));
+            for i in 0..3 {
+                files_map.insert(
+                    format!("{}/file-{}.txt", dir, i),
+                    file_item(FileItemBuilder::file("text/plain", link)),
+                );
+            }
+        }
+        files_map.insert(
+            "/top.txt".to_string(),
+            file_item(FileItemBuilder::file("text/plain", link)),
+        );
+        files_map
+    }
+
+    #[test]
+    fn test_split_files_map() {
+        let files_map = files_map("safe://hbyyyyx");
+        let shards = split_files_map(&files_map, 4);
+        assert_eq!(shards.keys().collect::<Vec<_>>(), vec!["", "/a"],);
+        assert!(shards[""].entries.contains_key("/a"));
+        assert!(shards[""].entries.contains_key("/c/file-0.txt"));
+        assert!(shards["/a"].entries.contains_key("/b"));
+        assert!(shards["/a"].entries.contains_key("/b/file-2.txt"));
+        assert_eq!(shards["/a"].entries.len(), 7);
+
+        assert_eq!(split_files_map(&files_map, 3).len(), 4);
+        assert_eq!(split_files_map(&files_map, 100).len(), 1);
+        assert!(is_within("/a/b", "/a"));
+        assert!(!is_within("/ab", "/a"));
+
+        // directories with no proper name are stored along with their parent
+        let mut files_map = files_map;
+        for i in 0..5 {
+            files_map.insert(
+                format!("/c//file-{}.txt", i),
+                file_item(FileItemBuilder::file("text/plain", "safe://hbyyyyx")),
+            );
+        }
+        let shards = split_files_map(&files_map, 4);
+        assert_eq!(shards.keys().collect::<Vec<_>>(), vec!["", "/a", "/c"],);
+        assert!(shards["/c"].entries.contains_key("//file-4.txt"));
+    }
+
+    #[test]
+    fn test_files_map_shards_validation() {
+        let shard = |entries: &[&str], shards: &[(&str, &str)]| FilesMapShard {
+            entries: entries
+                .iter()
+                .map(|path| (path.to_string(), file_item(FileItemBuilder::dir())))
+                .collect(),
+            shards: shards
+                .iter()
+                .map(|(dir, xorurl)| (dir.to_string(), xorurl.to_string()))
+                .collect(),
+        };
+
+        // only a FilesMap stored as a whole can have paths which are not absolute
+        let mut partial_files_map = PartialFilesMap::default();
+        assert!(partial_files_map
+            .add_shard("", "safe://root", shard(&["a.txt"], &[]))
+            .is_ok());
+        let mut partial_files_map = PartialFilesMap::default();
+        assert!(partial_files_map
+            .add_shard("", "safe://root", shard(&["a.txt"], &[("/b", "safe://b")]))
+            .is_err());
+
+        for sub_dir in &["", "/", "b", "/b/c", "/.", "/.."] {
+            let mut partial_files_map = PartialFilesMap::default();
+            assert!(partial_files_map
+                .add_shard("", "safe://root", shard(&["/a"], &[(*sub_dir, "safe://b")]))
+                .is_err());
+        }
+
+        // shards can be shared by siblings, but can't reference themselves
+        let mut partial_files_map = PartialFilesMap::default();
+        assert!(partial_files_map
+            .add_shard(
+                "",
+                "safe://root",
+                shard(&["/a"], &[("/b", "safe://b"), ("/c", "safe://b")])
+            )
+            .is_ok());
+        assert!(partial_files_map
+            .add_shard("/b", "safe://b", shard(&["/d"], &[]))
+            .is_ok());
+        assert!(partial_files_map
+            .add_shard("/c", "safe://b", shard(&["/d"], &[]))
+            .is_ok());
+        assert!(partial_files_map
+            .add_shard("/b", "safe://b", shard(&["/d"], &[("/e", "safe://b")]))
+            .is_err());
+        assert!(partial_files_map
+            .add_shard("/c", "safe://b", shard(&["/d"], &[("/e", "safe://root")]))
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_files_map_shards_store_and_fetch() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let link = safe
+            .files_store_public_blob(b"content", Some("text/plain"), false)
+            .await?;
+        let mut files_map = files_map(&link);
+
+        let xorurl = store_files_map_shards(&safe, &files_map, None, 3).await?;
+        let root_shard = fetch_shard(&safe, &xorurl).await?;
+        assert_eq!(root_shard.shards.len(), 2);
+        let partial_files_map = PartialFilesMap::fetch(&safe, &xorurl).await?;
+        assert_eq!(partial_files_map.load_all(&safe).await?, files_map);
+
+        // resolving a path only loads the shards it goes through
+        let mut partial_files_map = PartialFilesMap::fetch(&safe, &xorurl).await?;
+        let realpath = partial_files_map
+            .resolve_realpath(&safe, "/a/./b/../b/file-1.txt")
+            .await?;
+        assert_eq!(realpath, "/a/b/file-1.txt");
+        assert!(partial_files_map.get(&realpath).is_some());
+        assert!(!partial_files_map.files_map().contains_key("/c/file-1.txt"));
+
+        partial_files_map.load_subtree(&safe, "/c").await?;
+        assert!(partial_files_map.files_map().contains_key("/c/file-1.txt"));
+
+        // the shards of the subtrees which didn't change are reused
+        files_map.insert(
+            "/a/b/new.txt".to_string(),
+            file_item(FileItemBuilder::file("text/plain", &link)),
+        );
+        let new_xorurl = store_files_map_shards(&safe, &files_map, Some(xorurl.clone()), 3).await?;
+        assert_ne!(new_xorurl, xorurl);
+        let new_root_shard = fetch_shard(&safe, &new_xorurl).await?;
+        assert_eq!(new_root_shard.shards["/c"], root_shard.shards["/c"]);
+        assert_ne!(new_root_shard.shards["/a"], root_shard.shards["/a"]);
+        let partial_files_map = PartialFilesMap::fetch(&safe, &new_xorurl).await?;
+        assert_eq!(partial_files_map.load_all(&safe).await?, files_map);
+
+        // small FilesMaps are stored as a whole
+        let xorurl = store_files_map(&safe, &files_map, None).await?;
+        let serialised_files_map = safe.files_get_public_blob(&xorurl, None).await?;
+        let stored_files_map: FilesMap =
+            deserialise_from_envelope(&serialised_files_map, "FilesMap")?;
+        assert_eq!(stored_files_map, files_map);
+
+        // as are the ones with paths which are not absolute
+        files_map.insert(
+            "file.txt".to_string(),
+            file_item(FileItemBuilder::file("text/plain", &link)),
+        );
+        let xorurl = store_files_map_shards(&safe, &files_map, None, 3).await?;
+        let serialised_files_map = safe.files_get_public_blob(&xorurl, None).await?;
+        let stored_files_map: FilesMap =
+            deserialise_from_envelope(&serialised_files_map, "FilesMap")?;
+        assert_eq!(stored_files_map, files_map);
+        Ok(())
+    }
+}
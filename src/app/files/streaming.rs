@@ -0,0 +1,465 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::checksum::ChecksumHasher;
+use crate::{
+    app::fetch::Range, Error, Result, Safe, SafeContentType, SafeDataType, SafeUrl, XorUrl,
+};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use xor_name::XorName;
+
+// Maximum size of each of the pieces a content read from a stream is uploaded in
+pub(crate) const BLOB_PIECE_SIZE: usize = 4 * 1024 * 1024;
+
+// Type tag set in the XOR-URL of a Public Blob which contains a manifest rather than the
+// actual content. Blobs otherwise have no type tag, thus a manifest is told apart by its
+// link alone, without having to look into the content of the Blob.
+pub(crate) const BLOB_MANIFEST_TYPE_TAG: u64 = 1_300;
+
+// When content is larger than a single piece, each of its pieces is stored as a separate
+// Public Blob, and the list of them is stored in yet another Public Blob as a manifest.
+// The XOR-URL of the manifest is then used as the link to the content.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct BlobManifest {
+    pub(crate) size: u64,
+    pub(crate) piece_size: u64,
+    pub(crate) pieces: Vec<XorName>,
+}
+
+impl BlobManifest {
+    // Parse a manifest from the content of a Blob linked as a manifest
+    pub(crate) fn from_blob(data: &[u8]) -> Result<Self> {
+        rmp_serde::from_slice(data)
+            .map_err(|err| Error::ContentError(format!("Couldn't parse Blob manifest: {:?}", err)))
+    }
+
+    fn serialise(&self) -> Result<Vec<u8>> {
+        rmp_serde::to_vec_named(self).map_err(|err| {
+            Error::Serialisation(format!("Couldn't serialise the Blob manifest: {:?}", err))
+        })
+    }
+
+    // Absolute start and end (exclusive) positions of the content for the range provided
+    pub(crate) fn range_bounds(&self, range: Range) -> (u64, u64) {
+        match range {
+            Some((start, end)) => {
+                let end = end.unwrap_or(self.size).min(self.size);
+                let start = start.unwrap_or(0).min(end);
+                (start, end)
+            }
+            None => (0, self.size),
+        }
+    }
+
+    // List of pieces, with the range within each of them, holding the content
+    // found between the start and end (exclusive) positions provided
+    pub(crate) fn pieces_for(&self, start: u64, end: u64) -> Vec<(XorName, Range)> {
+        let mut pieces = vec![];
+        for (index, piece) in self.pieces.iter().enumerate() {
+            let piece_start = index as u64 * self.piece_size;
+            let piece_end = (piece_start + self.piece_size).min(self.size);
+            if piece_end <= start || piece_start >= end {
+                continue;
+            }
+
+            let range = if start <= piece_start && end >= piece_end {
+                None
+            } else {
+                let from = start.max(piece_start) - piece_start;
+                let to = end.min(piece_end) - piece_start;
+                Some((Some(from), Some(to)))
+            };
+            pieces.push((*piece, range));
+        }
+
+        pieces
+    }
+}
+
+// Upload the content read from the stream as a Public Blob, in pieces of bounded size,
+// calling `on_piece` with each of them once uploaded. If the content fits in a single piece
+// it's stored as a regular Public Blob, thus generating the same XOR-URL as if it was
+// uploaded with `files_store_public_blob`. No more pieces are uploaded once `on_piece`
+// returns an error, e.g. when the operation was cancelled, and the error is returned.
+pub(crate) async fn store_blob_from_reader<R, F>(
+    safe: &Safe,
+    mut reader: R,
+    content_type: SafeContentType,
+    dry_run: bool,
+    mut on_piece: F,
+) -> Result<XorUrl>
+where
+    R: AsyncRead + Unpin + Send,
+    F: FnMut(&[u8]) -> Result<()> + Send,
+{
+    let first_piece = read_piece(&mut reader).await?;
+    let mut next_piece = read_piece(&mut reader).await?;
+
+    if next_piece.is_empty() {
+        let xorname = safe
+            .safe_client
+            .store_public_blob(&first_piece, dry_run)
+            .await?;
+        on_piece(&first_piece)?;
+        SafeUrl::encode_blob(xorname, content_type, safe.xorurl_base)
+    } else {
+        let mut size = first_piece.len() as u64;
+        let mut pieces = vec![
+            safe.safe_client
+                .store_public_blob(&first_piece, dry_run)
+                .await?,
+        ];
+        on_piece(&first_piece)?;
+        drop(first_piece);
+
+        while !next_piece.is_empty() {
+            size += next_piece.len() as u64;
+            pieces.push(
+                safe.safe_client
+                    .store_public_blob(&next_piece, dry_run)
+                    .await?,
+            );
+            on_piece(&next_piece)?;
+            next_piece = read_piece(&mut reader).await?;
+        }
+
+        debug!(
+            "Content of {} bytes uploaded in {} pieces",
+            size,
+            pieces.len()
+        );
+        let manifest = BlobManifest {
+            size,
+            piece_size: BLOB_PIECE_SIZE as u64,
+            pieces,
+        };
+        let xorname = safe
+            .safe_client
+            .store_public_blob(&manifest.serialise()?, dry_run)
+            .await?;
+        SafeUrl::encode(
+            xorname,
+            None,
+            BLOB_MANIFEST_TYPE_TAG,
+            SafeDataType::PublicBlob,
+            content_type,
+            None,
+            None,
+            None,
+            None,
+            None,
+            safe.xorurl_base,
+        )
+    }
+}
+
+// Whether the URL links to the manifest of a content stored in pieces
+pub(crate) fn is_manifest_url(safe_url: &SafeUrl) -> bool {
+    safe_url.data_type() == SafeDataType::PublicBlob
+        && safe_url.type_tag() == BLOB_MANIFEST_TYPE_TAG
+}
+
+// Content of a Public Blob, which is either the actual
+// content or the manifest of the pieces it was stored in
+enum BlobContent {
+    Data(Vec<u8>),
+    Manifest(BlobManifest),
+}
+
+// Fetch the content of a Public Blob, putting its pieces together if it's a manifest
+pub(crate) async fn fetch_blob(safe: &Safe, safe_url: &SafeUrl, range: Range) -> Result<Vec<u8>> {
+    match fetch_blob_content(safe, safe_url, range).await? {
+        BlobContent::Data(data) => Ok(data),
+        BlobContent::Manifest(manifest) => {
+            let (start, end) = manifest.range_bounds(range);
+            let mut data = Vec::with_capacity((end - start) as usize);
+            for (piece, piece_range) in manifest.pieces_for(start, end) {
+                data.extend(safe.safe_client.get_public_blob(piece, piece_range).await?);
+            }
+
+            Ok(data)
+        }
+    }
+}
+
+// Write the content of a Public Blob into the writer provided, one piece at a time
+// if it's a manifest, returning the total number of bytes written
+pub(crate) async fn write_blob<W>(
+    safe: &Safe,
+    safe_url: &SafeUrl,
+    range: Range,
+    writer: &mut W,
+) -> Result<u64>
+where
+    W: AsyncWrite + Unpin + Send,
+{
+    let mut written = 0;
+    match fetch_blob_content(safe, safe_url, range).await? {
+        BlobContent::Data(data) => {
+            write_piece(writer, &data).await?;
+            written += data.len() as u64;
+        }
+        BlobContent::Manifest(manifest) => {
+            let (start, end) = manifest.range_bounds(range);
+            for (piece, piece_range) in manifest.pieces_for(start, end) {
+                let data = safe.safe_client.get_public_blob(piece, piece_range).await?;
+                write_piece(writer, &data).await?;
+                written += data.len() as u64;
+            }
+        }
+    }
+
+    writer
+        .flush()
+        .await
+        .map_err(|err| Error::FileSystemError(format!("Failed to write content: {}", err)))?;
+
+    debug!(
+        "{} bytes of Blob at {} written",
+        written,
+        safe_url.xorname()
+    );
+    Ok(written)
+}
+
+// Calculate the size and checksum of the content of a Public Blob, fetching one piece at a time
+pub(crate) async fn blob_checksum(safe: &Safe, safe_url: &SafeUrl) -> Result<(u64, String)> {
+    let mut hasher = ChecksumHasher::new();
+    let mut size = 0;
+    match fetch_blob_content(safe, safe_url, None).await? {
+        BlobContent::Data(data) => {
+            hasher.update(&data);
+            size += data.len() as u64;
+        }
+        BlobContent::Manifest(manifest) => {
+            for (piece, piece_range) in manifest.pieces_for(0, manifest.size) {
+                let data = safe.safe_client.get_public_blob(piece, piece_range).await?;
+                hasher.update(&data);
+                size += data.len() as u64;
+            }
+        }
+    }
+
+    Ok((size, hasher.finalize()))
+}
+
+// Fetch either the content (with the range provided) or the manifest of a Public Blob
+async fn fetch_blob_content(safe: &Safe, safe_url: &SafeUrl, range: Range) -> Result<BlobContent> {
+    if is_manifest_url(safe_url) {
+        let data = safe
+            .safe_client
+            .get_public_blob(safe_url.xorname(), None)
+            .await?;
+        Ok(BlobContent::Manifest(BlobManifest::from_blob(&data)?))
+    } else {
+        let data = safe
+            .safe_client
+            .get_public_blob(safe_url.xorname(), range)
+            .await?;
+        Ok(BlobContent::Data(data))
+    }
+}
+
+async fn write_piece<W>(writer: &mut W, data: &[u8]) -> Result<()>
+where
+    W: AsyncWrite + Unpin + Send,
+{
+    writer
+        .write_all(data)
+        .await
+        .map_err(|err| Error::FileSystemError(format!("Failed to write content: {}", err)))
+}
+
+// Read from the stream until a whole piece is read or the stream is exhausted
+async fn read_piece<R>(reader: &mut R) -> Result<Vec<u8>>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    let mut piece = Vec::with_capacity(BLOB_PIECE_SIZE);
+    let _ = reader
+        .take(BLOB_PIECE_SIZE as u64)
+        .read_to_end(&mut piece)
+        .await
+        .map_err(|err| Error::InvalidInput(format!("Failed to read content: {}", err)))?;
+
+    Ok(piece)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_helpers::new_in_memory_safe_instance;
+    use anyhow::Result;
+    use std::io::Cursor;
+
+    #[tokio::test]
+    async fn test_store_blob_from_reader_single_piece() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let data = b"Something super good";
+
+        let xorurl = safe
+            .files_store_public_blob_from_reader(Cursor::new(data), Some("text/plain"), false)
+            .await?;
+        let same_xorurl = safe
+            .files_store_public_blob(data, Some("text/plain"), true)
+            .await?;
+        assert_eq!(xorurl, same_xorurl);
+
+        let received_data = safe.files_get_public_blob(&xorurl, None).await?;
+        assert_eq!(received_data, data);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_store_blob_from_reader_many_pieces() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let data: Vec<u8> = (0..BLOB_PIECE_SIZE * 2 + 10)
+            .map(|i| (i % 251) as u8)
+            .collect();
+
+        let dry_run_xorurl = safe
+            .files_store_public_blob_from_reader(Cursor::new(&data), None, true)
+            .await?;
+        let mut uploaded = vec![];
+        let xorurl = store_blob_from_reader(
+            &safe,
+            Cursor::new(&data),
+            SafeContentType::Raw,
+            false,
+            |piece: &[u8]| {
+                uploaded.push(piece.len());
+                Ok(())
+            },
+        )
+        .await?;
+        assert_eq!(xorurl, dry_run_xorurl);
+        assert_eq!(uploaded, vec![BLOB_PIECE_SIZE, BLOB_PIECE_SIZE, 10]);
+
+        // no more pieces are uploaded once the hook fails
+        let mut uploaded = 0;
+        let result = store_blob_from_reader(
+            &safe,
+            Cursor::new(&data),
+            SafeContentType::Raw,
+            true,
+            |_: &[u8]| {
+                uploaded += 1;
+                Err(Error::Cancelled("The operation was cancelled".to_string()))
+            },
+        )
+        .await;
+        assert!(matches!(result, Err(Error::Cancelled(_))));
+        assert_eq!(uploaded, 1);
+        assert!(is_manifest_url(&SafeUrl::from_url(&xorurl)?));
+
+        let received_data = safe.files_get_public_blob(&xorurl, None).await?;
+        assert_eq!(received_data, data);
+
+        // a range which spans over the three pieces
+        let start = BLOB_PIECE_SIZE - 5;
+        let end = BLOB_PIECE_SIZE * 2 + 5;
+        let range = Some((Some(start as u64), Some(end as u64)));
+        let received_data = safe.files_get_public_blob(&xorurl, range).await?;
+        assert_eq!(received_data, data[start..end].to_vec());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_store_blob_which_looks_like_a_manifest() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let manifest = BlobManifest {
+            size: 10,
+            piece_size: 5,
+            pieces: vec![rand::random(), rand::random()],
+        };
+        let data = manifest.serialise()?;
+
+        // content is never taken as a manifest unless its XOR-URL says so
+        let xorurl = safe
+            .files_store_public_blob_from_reader(Cursor::new(&data), None, false)
+            .await?;
+        assert!(!is_manifest_url(&SafeUrl::from_url(&xorurl)?));
+
+        let received_data = safe.files_get_public_blob(&xorurl, None).await?;
+        assert_eq!(received_data, data);
+
+        let range = Some((Some(1), Some(4)));
+        let received_data = safe.files_get_public_blob(&xorurl, range).await?;
+        assert_eq!(received_data, data[1..4].to_vec());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_get_public_blob_to_writer() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let data: Vec<u8> = (0..BLOB_PIECE_SIZE * 2 + 10)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let file_xorurl = safe
+            .files_store_public_blob_from_reader(Cursor::new(&data), Some("text/plain"), false)
+            .await?;
+
+        let (xorurl, _, _) = safe
+            .files_container_create(None, None, false, false, false)
+            .await?;
+        let file_url = format!("{}/big.txt", xorurl);
+        let _ = safe
+            .files_container_add(&file_xorurl, &file_url, false, false, false, false)
+            .await?;
+
+        let mut output = Vec::new();
+        let written = safe
+            .files_get_public_blob_to_writer(&file_url, None, &mut output)
+            .await?;
+        assert_eq!(written, data.len() as u64);
+        assert_eq!(output, data);
+
+        let start = BLOB_PIECE_SIZE - 5;
+        let end = BLOB_PIECE_SIZE + 5;
+        let range = Some((Some(start as u64), Some(end as u64)));
+        let mut output = Vec::new();
+        let written = safe
+            .files_get_public_blob_to_writer(&file_url, range, &mut output)
+            .await?;
+        assert_eq!(written, 10);
+        assert_eq!(output, data[start..end].to_vec());
+
+        // a FilesContainer itself cannot be written out
+        let mut output = Vec::new();
+        assert!(safe
+            .files_get_public_blob_to_writer(&xorurl, None, &mut output)
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_blob_manifest_pieces_for() {
+        let manifest = BlobManifest {
+            size: 25,
+            piece_size: 10,
+            pieces: vec![rand::random(), rand::random(), rand::random()],
+        };
+
+        let pieces = manifest.pieces_for(0, 25);
+        assert_eq!(pieces.len(), 3);
+        assert!(pieces.iter().all(|(_, range)| range.is_none()));
+
+        let pieces = manifest.pieces_for(12, 22);
+        assert_eq!(
+            pieces,
+            vec![
+                (manifest.pieces[1], Some((Some(2), Some(10)))),
+                (manifest.pieces[2], Some((Some(0), Some(2)))),
+            ]
+        );
+    }
+}
@@ -0,0 +1,435 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    file_system::{upload_file_to_net, upload_local_file, UploadedFile},
+    progress::{notify_progress, FilesProgress, ProgressObserver},
+    FileItem, FileMeta, FilesFilter, ProcessedFiles,
+};
+use crate::{
+    app::{cancellation::check_cancelled, consts::*, helpers::systemtime_to_rfc3339},
+    CancellationToken, Error, Result, Safe, SafeUrl, XorUrl,
+};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::UNIX_EPOCH,
+};
+
+/// How local files are compared with the files in a FilesContainer to detect changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeDetection {
+    /// Compare the content of every local file, which requires hashing all of them
+    Content,
+    /// Only compare the content of the local files whose size or modification time
+    /// differ from those recorded in the FilesContainer. The modification time is compared
+    /// with sub-second precision against the sync state cache for the files found in it,
+    /// otherwise against the FilesContainer's, which only has a precision of seconds, hence
+    /// a change keeping the same size made within the same second would go unnoticed.
+    Metadata,
+}
+
+impl Default for ChangeDetection {
+    fn default() -> Self {
+        Self::Content
+    }
+}
+
+/// Default maximum number of files uploaded, or hashed, concurrently
+pub const DEFAULT_UPLOAD_CONCURRENCY: usize = 8;
+
+/// Options to upload, or sync up, a local folder to a FilesContainer
+#[derive(Debug, Clone)]
+pub struct SyncOptions {
+    /// Filter for the local files to be synced
+    pub filter: FilesFilter,
+    /// How to detect the changes made to local files
+    pub change_detection: ChangeDetection,
+    /// Local file where to cache the XOR-URLs of the synced files along with their size
+    /// and modification time, so unchanged files are not hashed again in subsequent syncs
+    pub sync_state: Option<PathBuf>,
+    /// Maximum number of files uploaded, or hashed, concurrently
+    pub concurrency: usize,
+    /// Observer to be notified of the progress of the operation
+    pub progress: Option<ProgressObserver>,
+    /// Token to cancel the operation, in which case no new version is committed and the
+    /// files uploaded until then are reported
+    pub cancel: Option<CancellationToken>,
+    /// Version of the FilesContainer the changes are expected to be applied on. If the
+    /// FilesContainer has moved on from it, the operation fails with a `VersionConflict` error,
+    /// unless `merge` is set.
+    /// The Network cannot append versions conditionally, hence when another writer appends a
+    /// version right as the new one is being appended, the conflict is only detected afterwards:
+    /// the `VersionConflict` error is returned but the new version was appended after theirs,
+    /// overwriting their changes until the FilesContainer is synced again.
+    pub expected_version: Option<u64>,
+    /// Merge the changes with those made concurrently by other writers, as long as they
+    /// don't change the same paths, rather than failing with a `VersionConflict` error
+    pub merge: bool,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        Self {
+            filter: FilesFilter::default(),
+            change_detection: ChangeDetection::default(),
+            sync_state: None,
+            concurrency: DEFAULT_UPLOAD_CONCURRENCY,
+            progress: None,
+            cancel: None,
+            expected_version: None,
+            merge: false,
+        }
+    }
+}
+
+impl SyncOptions {
+    // Whether the changes made by concurrent writers must never be overwritten, in which case
+    // the new version is only appended if the FilesContainer wasn't updated in the meantime
+    pub(crate) fn checks_version(&self) -> bool {
+        self.expected_version.is_some() || self.merge
+    }
+
+    // Fail if any of the options set, i.e. those differing from their defaults, is not
+    // supported by the operation, rather than silently ignoring it
+    pub(crate) fn check_supported(&self, operation: &str, supported: &[&str]) -> Result<()> {
+        let default = Self::default();
+        let options_set = [
+            ("filter", self.filter != default.filter),
+            (
+                "change_detection",
+                self.change_detection != default.change_detection,
+            ),
+            ("sync_state", self.sync_state.is_some()),
+            ("concurrency", self.concurrency != default.concurrency),
+            ("progress", self.progress.is_some()),
+            ("cancel", self.cancel.is_some()),
+            ("expected_version", self.expected_version.is_some()),
+            ("merge", self.merge),
+        ];
+
+        let unsupported: Vec<&str> = options_set
+            .iter()
+            .filter(|(name, is_set)| *is_set && !supported.contains(name))
+            .map(|(name, _)| *name)
+            .collect();
+        if unsupported.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidInput(format!(
+                "The following options are not supported when {}: {}",
+                operation,
+                unsupported.join(", ")
+            )))
+        }
+    }
+}
+
+// Local cache of the XOR-URLs calculated for the local files, which is valid as long
+// as the size and modification time of the files remain the same. It can be shared
+// by the tasks hashing and uploading files concurrently.
+#[derive(Debug, Default)]
+pub(crate) struct SyncState {
+    cache: Mutex<SyncStateCache>,
+}
+
+#[derive(Debug, Default)]
+struct SyncStateCache {
+    files: BTreeMap<String, SyncedFile>,
+    // Files looked up or recorded since the cache was loaded, only those are saved back
+    seen: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SyncedFile {
+    size: u64,
+    modified: (u64, u32),
+    xorurl: XorUrl,
+}
+
+impl SyncState {
+    // Load the cache from a local file, starting with an empty one if the file doesn't exist
+    // or it cannot be read, the cache is just an optimisation thus it can always be rebuilt
+    pub(crate) fn load(path: &Path) -> Self {
+        let files = match fs::read(path) {
+            Ok(serialised) => serde_json::from_slice(&serialised).unwrap_or_else(|err| {
+                warn!(
+                    "Ignoring sync state cache found at \"{}\" since it's invalid: {}",
+                    path.display(),
+                    err
+                );
+                BTreeMap::default()
+            }),
+            Err(_) => BTreeMap::default(),
+        };
+
+        Self {
+            cache: Mutex::new(SyncStateCache {
+                files,
+                seen: BTreeSet::default(),
+            }),
+        }
+    }
+
+    pub(crate) fn save(&self, path: &Path) -> Result<()> {
+        let cache = self.cache();
+        let files: BTreeMap<&String, &SyncedFile> = cache
+            .files
+            .iter()
+            .filter(|(key, _)| cache.seen.contains(*key))
+            .collect();
+        let serialised = serde_json::to_vec(&files).map_err(|err| {
+            Error::Serialisation(format!(
+                "Couldn't serialise the sync state cache: {:?}",
+                err
+            ))
+        })?;
+        fs::write(path, serialised).map_err(|err| {
+            Error::FileSystemError(format!(
+                "Couldn't write the sync state cache to \"{}\": {}",
+                path.display(),
+                err
+            ))
+        })
+    }
+
+    // Get the cached XOR-URL of a local file if it hasn't changed since it was cached
+    pub(crate) fn get(&self, path: &Path, metadata: &fs::Metadata) -> Option<XorUrl> {
+        let key = cache_key(path);
+        let mut cache = self.cache();
+        let synced_file = cache.files.get(&key)?.clone();
+        cache.seen.insert(key);
+        if synced_file.size == metadata.len()
+            && Some(synced_file.modified) == modified_time(metadata)
+        {
+            Some(synced_file.xorurl)
+        } else {
+            None
+        }
+    }
+
+    // Check if a local file is in the cache, regardless of whether it changed since then
+    pub(crate) fn contains(&self, path: &Path) -> bool {
+        self.cache().files.contains_key(&cache_key(path))
+    }
+
+    // Record the XOR-URL a local file was uploaded to, as per the FileItem generated for it
+    pub(crate) fn record(&self, path: &Path, file_item: &FileItem) {
+        if let Some(xorurl) = file_item.get(PREDICATE_LINK) {
+            self.record_file(path, xorurl);
+        }
+    }
+
+    // Record the XOR-URLs of the local files which were uploaded as per the list provided
+    pub(crate) fn record_uploaded(&self, processed_files: &ProcessedFiles) {
+        processed_files
+            .iter()
+            .filter(|(_, (change, link))| change == CONTENT_ADDED_SIGN && !link.is_empty())
+            .for_each(|(path, (_, xorurl))| self.record_file(Path::new(path), xorurl));
+    }
+
+    pub(crate) fn insert(&self, path: &Path, metadata: &fs::Metadata, xorurl: &str) {
+        if let Some(modified) = modified_time(metadata) {
+            let key = cache_key(path);
+            let synced_file = SyncedFile {
+                size: metadata.len(),
+                modified,
+                xorurl: xorurl.to_string(),
+            };
+            let mut cache = self.cache();
+            cache.seen.insert(key.clone());
+            let _ = cache.files.insert(key, synced_file);
+        }
+    }
+
+    fn record_file(&self, path: &Path, xorurl: &str) {
+        if let Ok(metadata) = fs::metadata(path) {
+            if metadata.is_file() {
+                self.insert(path, &metadata, xorurl);
+            }
+        }
+    }
+
+    // The cache is just an optimisation, so it's still usable even
+    // if a task panicked whilst holding the lock
+    fn cache(&self) -> MutexGuard<SyncStateCache> {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+// Check if a local file differs from the file the FileItem describes
+pub(crate) async fn is_file_item_modified(
+    safe: &Safe,
+    local_filename: &Path,
+    file_item: &FileItem,
+    change_detection: ChangeDetection,
+    sync_state: Option<&SyncState>,
+) -> bool {
+    let file_type = file_item.get(PREDICATE_TYPE).map_or("", String::as_str);
+    if FileMeta::filetype_is_file(file_type) {
+        let metadata = fs::metadata(local_filename).ok();
+        if change_detection == ChangeDetection::Metadata {
+            // The cache keeps the modification time with sub-second precision, thus it
+            // decides for the files found in it, when the XOR-URL is looked up below
+            let is_cached =
+                sync_state.map_or(false, |sync_state| sync_state.contains(local_filename));
+            if let (Some(metadata), false) = (&metadata, is_cached) {
+                if is_same_metadata(metadata, file_item) {
+                    debug!(
+                        "Local file \"{}\" assumed unchanged since its size and modification time are the same",
+                        local_filename.display()
+                    );
+                    return false;
+                }
+            }
+        }
+
+        match local_file_xorurl(safe, local_filename, metadata.as_ref(), sync_state).await {
+            Ok(local_xorurl) => file_item
+                .get(PREDICATE_LINK)
+                .map_or(true, |link| !is_same_blob(link, &local_xorurl)),
+            Err(_err) => false,
+        }
+    } else {
+        // for now, we just return false if a symlink or directory.
+        // In the future, should check if symlink has been modified.
+        false
+    }
+}
+
+// Outcome of checking a local file against the FilesContainer, and of uploading it if needed
+#[derive(Debug, Default)]
+pub(crate) struct PreparedFile {
+    // Whether the content of the local file differs from the existing FileItem's
+    pub(crate) is_modified: bool,
+    // Result of uploading the local file, unless it didn't need to be uploaded
+    pub(crate) upload: Option<Result<UploadedFile>>,
+}
+
+// Check a local file against the FileItem it currently has in the FilesContainer, if any,
+// uploading it if it's new or if it's been modified. This is the expensive part of syncing
+// a local file, hence it's meant to be run for many files concurrently.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn prepare_local_file(
+    safe: &Safe,
+    local_file_name: &str,
+    current_file_item: Option<&FileItem>,
+    force: bool,
+    compare_file_content: bool,
+    options: &SyncOptions,
+    sync_state: Option<&SyncState>,
+    follow_links: bool,
+    dry_run: bool,
+) -> PreparedFile {
+    // Don't even check the file if the operation was cancelled
+    if let Err(err) = check_cancelled(options.cancel.as_ref()) {
+        return PreparedFile {
+            is_modified: false,
+            upload: Some(Err(err)),
+        };
+    }
+
+    // Only files are uploaded, metadata errors will be reported when generating the FileItem
+    let is_file = FileMeta::from_path(local_file_name, follow_links)
+        .map(|file_meta| file_meta.is_file())
+        .unwrap_or(false);
+    if !is_file {
+        return PreparedFile::default();
+    }
+
+    match current_file_item {
+        None => PreparedFile {
+            is_modified: false,
+            upload: Some(upload_local_file(safe, local_file_name, options, dry_run).await),
+        },
+        Some(file_item) => {
+            let path = Path::new(local_file_name);
+            let is_modified =
+                is_file_item_modified(safe, path, file_item, options.change_detection, sync_state)
+                    .await;
+            let upload = if force || (compare_file_content && is_modified) {
+                Some(upload_local_file(safe, local_file_name, options, dry_run).await)
+            } else {
+                notify_progress(options.progress.as_ref(), || FilesProgress::FileSkipped {
+                    path: local_file_name.to_string(),
+                    reason: if compare_file_content {
+                        "unchanged".to_string()
+                    } else {
+                        "already exists on target".to_string()
+                    },
+                });
+                None
+            };
+            PreparedFile {
+                is_modified,
+                upload,
+            }
+        }
+    }
+}
+
+// Calculate the XOR-URL of a local file, unless it's found in the cache
+async fn local_file_xorurl(
+    safe: &Safe,
+    path: &Path,
+    metadata: Option<&fs::Metadata>,
+    sync_state: Option<&SyncState>,
+) -> Result<XorUrl> {
+    match (sync_state, metadata) {
+        (Some(sync_state), Some(metadata)) => match sync_state.get(path, metadata) {
+            Some(xorurl) => Ok(xorurl),
+            None => {
+                let xorurl = upload_file_to_net(safe, path, None, None, true /* dry-run */)
+                    .await?
+                    .xorurl;
+                sync_state.insert(path, metadata, &xorurl);
+                Ok(xorurl)
+            }
+        },
+        _ => upload_file_to_net(safe, path, None, None, true /* dry-run */)
+            .await
+            .map(|uploaded| uploaded.xorurl),
+    }
+}
+
+// Blobs are the same if their content is, regardless of the media type set in their XOR-URLs
+pub(crate) fn is_same_blob(xorurl: &str, other_xorurl: &str) -> bool {
+    match (SafeUrl::from_url(xorurl), SafeUrl::from_url(other_xorurl)) {
+        (Ok(url), Ok(other_url)) => url.xorname() == other_url.xorname(),
+        _ => xorurl == other_xorurl,
+    }
+}
+
+fn is_same_metadata(metadata: &fs::Metadata, file_item: &FileItem) -> bool {
+    let local_modified = metadata
+        .modified()
+        .ok()
+        .map(|time| systemtime_to_rfc3339(&time));
+
+    file_item.get(PREDICATE_SIZE) == Some(&metadata.len().to_string())
+        && local_modified.is_some()
+        && file_item.get(PREDICATE_ORIGINAL_MODIFIED) == local_modified.as_ref()
+}
+
+fn modified_time(metadata: &fs::Metadata) -> Option<(u64, u32)> {
+    let modified = metadata.modified().ok()?;
+    let since_epoch = modified.duration_since(UNIX_EPOCH).ok()?;
+    Some((since_epoch.as_secs(), since_epoch.subsec_nanos()))
+}
+
+fn cache_key(path: &Path) -> String {
+    fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .display()
+        .to_string()
+}
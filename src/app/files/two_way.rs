@@ -0,0 +1,670 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    custom_metadata::preserve_custom_metadata,
+    download::{download_file_item, LocalDest},
+    file_system::{
+        file_system_dir_list, normalise_path_separator, upload_files_to_net, UploadedFile,
+    },
+    files_map::{add_or_update_file_item, SYMLINK_TARGET},
+    filter::PathFilter,
+    sync::{is_file_item_modified, is_same_blob, SyncState},
+    FileItem, FileMeta, FilesMap, ProcessedFiles, SyncOptions,
+};
+use crate::{
+    app::{cancellation::is_cancelled, consts::*},
+    Error, Result, Safe,
+};
+use log::{debug, info};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// How a two-way sync resolves the paths changed both locally and on the FilesContainer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncConflictResolution {
+    /// Leave both the local and the remote path untouched, only reporting the conflict.
+    /// The conflict is to be resolved before the next sync, since the version returned
+    /// becomes its base and the path would then be seen as changed on one end only.
+    Report,
+    /// Keep the local version in place and the remote one alongside it, with a
+    /// `.remote-v<version>` suffix added to its name, both locally and remotely
+    KeepBoth,
+}
+
+impl Default for SyncConflictResolution {
+    fn default() -> Self {
+        Self::Report
+    }
+}
+
+/// Outcome of a two-way sync between a local folder and a FilesContainer
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TwoWaySyncResult {
+    /// Version of the FilesContainer after the sync, to be used as the base version of the next one
+    pub version: u64,
+    /// Local paths written or removed with the changes pulled from the FilesContainer
+    pub pulled: ProcessedFiles,
+    /// Local changes pushed to the FilesContainer
+    pub pushed: ProcessedFiles,
+    /// Paths of the FilesContainer changed both locally and remotely since the base version
+    pub conflicts: BTreeSet<String>,
+    /// The FilesMap of the FilesContainer after the sync
+    pub files_map: FilesMap,
+}
+
+// Paths to be pushed to the FilesContainer, keyed by their path in the FilesContainer
+type PathsToPush = BTreeMap<String, PathBuf>;
+
+// Reconcile a local folder with the content of a FilesContainer found at `dest_base_path`,
+// using the FilesMap of the version they were last synced at as the common base. Remote-only
+// changes are written onto the local folder, and the local-only changes are applied onto the
+// FilesMap returned, along with the number of changes applied onto it.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn files_map_two_way_sync(
+    safe: &mut Safe,
+    location: &Path,
+    dest_base_path: &str,
+    base_files_map: &FilesMap,
+    remote_version: u64,
+    remote_files_map: FilesMap,
+    conflict_resolution: SyncConflictResolution,
+    options: &SyncOptions,
+    sync_state: Option<&SyncState>,
+    dry_run: bool,
+) -> Result<(TwoWaySyncResult, u64)> {
+    let dest_base_path = dest_base_path.trim_end_matches('/');
+    let mut path_filter = PathFilter::new(&options.filter, location)?;
+
+    // The ignore files are loaded whilst listing the local folder,
+    // so the remote paths can only be filtered afterwards
+    let (local_entries, _) = file_system_dir_list(
+        &location.display().to_string(),
+        true,
+        false,
+        &mut path_filter,
+        options.progress.as_ref(),
+    )?;
+    let mut relative_paths: BTreeSet<String> = local_entries
+        .keys()
+        .filter_map(|local_path| Path::new(local_path).strip_prefix(location).ok())
+        .map(|relative_path| normalise_path_separator(&relative_path.display().to_string()))
+        .filter(|relative_path| !relative_path.is_empty())
+        .collect();
+    for (path, file_item) in base_files_map.iter().chain(remote_files_map.iter()) {
+        if let Some(relative_path) = path.strip_prefix(&format!("{}/", dest_base_path)) {
+            if !path_filter.is_ignored(&location.join(relative_path), is_dir(Some(file_item))) {
+                let _ = relative_paths.insert(relative_path.to_string());
+            }
+        }
+    }
+
+    let mut result = TwoWaySyncResult {
+        version: remote_version,
+        files_map: remote_files_map.clone(),
+        ..TwoWaySyncResult::default()
+    };
+    let mut to_push = PathsToPush::new();
+    let mut to_remove_locally = Vec::<String>::new();
+    let mut success_count = 0;
+
+    // The paths found in the FilesMaps are never used to read, write
+    // or remove anything outside of the local folder
+    let mut local_dest = LocalDest::new(location);
+    for relative_path in relative_paths.iter() {
+        let remote_path = format!("{}/{}", dest_base_path, relative_path);
+        let local_path = match local_dest.local_path(relative_path) {
+            Ok(local_path) => local_path,
+            Err(err) => {
+                report_pull_error(&location.join(relative_path), &err, &mut result.pulled);
+                continue;
+            }
+        };
+        let base_item = base_files_map.get(&remote_path);
+        let remote_item = remote_files_map.get(&remote_path);
+
+        let remote_changed = !is_same_content(base_item, remote_item);
+        let local_changed = !local_matches(safe, &local_path, base_item, options, sync_state).await;
+        match (local_changed, remote_changed) {
+            (false, false) => {}
+            (false, true) => {
+                pull(
+                    safe,
+                    &mut local_dest,
+                    relative_path,
+                    remote_item,
+                    sync_state,
+                    dry_run,
+                    &mut result.pulled,
+                    &mut to_remove_locally,
+                )
+                .await
+            }
+            (true, false) => {
+                let _ = to_push.insert(remote_path, local_path);
+            }
+            (true, true) => {
+                if local_matches(safe, &local_path, remote_item, options, sync_state).await {
+                    debug!("Path \"{}\" changed the same way on both ends", remote_path);
+                    continue;
+                }
+
+                info!("Path \"{}\" changed both locally and remotely", remote_path);
+                let _ = result.conflicts.insert(remote_path.clone());
+                if conflict_resolution == SyncConflictResolution::Report {
+                    continue;
+                }
+
+                let local_exists = fs::symlink_metadata(&local_path).is_ok();
+                match remote_item {
+                    Some(remote_item) if local_exists => {
+                        // Type changes involving folders are only reported
+                        if is_dir(Some(remote_item)) || local_path.is_dir() {
+                            continue;
+                        }
+                        let copy_path = unused_conflict_copy_path(
+                            location,
+                            dest_base_path,
+                            relative_path,
+                            remote_version,
+                            &result.files_map,
+                        );
+                        pull(
+                            safe,
+                            &mut local_dest,
+                            &copy_path,
+                            Some(remote_item),
+                            sync_state,
+                            dry_run,
+                            &mut result.pulled,
+                            &mut to_remove_locally,
+                        )
+                        .await;
+                        let copy_remote_path = format!("{}/{}", dest_base_path, copy_path);
+                        result.pushed.insert(
+                            copy_remote_path.clone(),
+                            (
+                                CONTENT_ADDED_SIGN.to_string(),
+                                remote_item.get(PREDICATE_LINK).cloned().unwrap_or_default(),
+                            ),
+                        );
+                        result
+                            .files_map
+                            .insert(copy_remote_path, remote_item.clone());
+                        success_count += 1;
+                        let _ = to_push.insert(remote_path, local_path);
+                    }
+                    // Removed remotely, the local version is restored remotely
+                    None if local_exists => {
+                        let _ = to_push.insert(remote_path, local_path);
+                    }
+                    // Removed locally, the remote version is restored locally
+                    remote_item => {
+                        pull(
+                            safe,
+                            &mut local_dest,
+                            relative_path,
+                            remote_item,
+                            sync_state,
+                            dry_run,
+                            &mut result.pulled,
+                            &mut to_remove_locally,
+                        )
+                        .await
+                    }
+                }
+            }
+        }
+    }
+
+    // Folders are removed after their content
+    for relative_path in to_remove_locally.iter().rev() {
+        remove_local_path(&local_dest, relative_path, dry_run, &mut result.pulled);
+    }
+
+    if !is_cancelled(options.cancel.as_ref()) {
+        success_count += push(
+            safe,
+            &to_push,
+            options,
+            sync_state,
+            dry_run,
+            &mut result.files_map,
+            &mut result.pushed,
+        )
+        .await;
+    }
+
+    Ok((result, success_count))
+}
+
+// Write a remote FileItem onto the local path, or schedule the local path
+// to be removed if there is no FileItem for it in the FilesContainer
+#[allow(clippy::too_many_arguments)]
+async fn pull(
+    safe: &Safe,
+    local_dest: &mut LocalDest,
+    relative_path: &str,
+    remote_item: Option<&FileItem>,
+    sync_state: Option<&SyncState>,
+    dry_run: bool,
+    pulled: &mut ProcessedFiles,
+    to_remove_locally: &mut Vec<String>,
+) {
+    let remote_item = match remote_item {
+        Some(remote_item) => remote_item,
+        None => {
+            to_remove_locally.push(relative_path.to_string());
+            return;
+        }
+    };
+
+    let link = remote_item.get(PREDICATE_LINK).cloned().unwrap_or_default();
+    let result = match local_dest.local_path(relative_path) {
+        Ok(local_path) => download_file_item(safe, &local_path, remote_item, true, dry_run)
+            .await
+            .map(|change| (local_path, change)),
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok((_, None)) => {}
+        Ok((local_path, Some(change))) => {
+            let is_symlink = remote_item
+                .get(PREDICATE_TYPE)
+                .map_or(false, |file_type| FileMeta::filetype_is_symlink(file_type));
+            if let (Some(sync_state), false) = (sync_state, dry_run) {
+                sync_state.record(&local_path, remote_item);
+            }
+            let local_path_str = normalise_path_separator(&local_path.display().to_string());
+            pulled.insert(local_path_str, (change.to_string(), link));
+            if is_symlink {
+                local_dest.symlink_created(local_path);
+            }
+        }
+        Err(err) => report_pull_error(&local_dest.root().join(relative_path), &err, pulled),
+    }
+}
+
+fn remove_local_path(
+    local_dest: &LocalDest,
+    relative_path: &str,
+    dry_run: bool,
+    pulled: &mut ProcessedFiles,
+) {
+    let local_path = match local_dest.local_path(relative_path) {
+        Ok(local_path) => local_path,
+        Err(err) => {
+            report_pull_error(&local_dest.root().join(relative_path), &err, pulled);
+            return;
+        }
+    };
+    let local_path_str = normalise_path_separator(&local_path.display().to_string());
+    let result = match fs::symlink_metadata(&local_path) {
+        Err(_) => return,
+        Ok(_) if dry_run => Ok(()),
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir(&local_path),
+        Ok(_) => fs::remove_file(&local_path),
+    };
+
+    match result {
+        Ok(()) => {
+            pulled.insert(
+                local_path_str,
+                (CONTENT_DELETED_SIGN.to_string(), String::default()),
+            );
+        }
+        Err(err) => {
+            info!("Skipping removing \"{}\": {}", local_path_str, err);
+            pulled.insert(
+                local_path_str,
+                (
+                    CONTENT_ERROR_SIGN.to_string(),
+                    format!("<Couldn't remove local path: {}>", err),
+                ),
+            );
+        }
+    }
+}
+
+fn report_pull_error(local_path: &Path, err: &Error, pulled: &mut ProcessedFiles) {
+    let local_path_str = normalise_path_separator(&local_path.display().to_string());
+    info!("Skipping pulling \"{}\": {}", local_path_str, err);
+    pulled.insert(
+        local_path_str,
+        (CONTENT_ERROR_SIGN.to_string(), format!("<{}>", err)),
+    );
+}
+
+// Apply the local changes onto the FilesMap, uploading the files concurrently,
+// and return the number of changes successfully applied
+async fn push(
+    safe: &mut Safe,
+    to_push: &PathsToPush,
+    options: &SyncOptions,
+    sync_state: Option<&SyncState>,
+    dry_run: bool,
+    files_map: &mut FilesMap,
+    pushed: &mut ProcessedFiles,
+) -> u64 {
+    let files: Vec<String> = to_push
+        .values()
+        .filter(|local_path| local_path.is_file())
+        .map(|local_path| normalise_path_separator(&local_path.display().to_string()))
+        .collect();
+    let mut uploads: BTreeMap<String, Result<UploadedFile>> = files
+        .iter()
+        .cloned()
+        .zip(upload_files_to_net(safe, &files, options, dry_run).await)
+        .collect();
+
+    let mut success_count = 0;
+    for (remote_path, local_path) in to_push.iter() {
+        let local_path_str = normalise_path_separator(&local_path.display().to_string());
+        let file_meta = match FileMeta::from_path(&local_path_str, false) {
+            Ok(file_meta) => file_meta,
+            Err(_) => {
+                // Removed locally
+                if let Some(file_item) = files_map.remove(remote_path) {
+                    pushed.insert(
+                        remote_path.to_string(),
+                        (
+                            CONTENT_DELETED_SIGN.to_string(),
+                            file_item.get(PREDICATE_LINK).cloned().unwrap_or_default(),
+                        ),
+                    );
+                    success_count += 1;
+                }
+                continue;
+            }
+        };
+
+        let uploaded = match uploads.remove(&local_path_str) {
+            Some(Ok(uploaded)) => Some(uploaded),
+            Some(Err(err)) => {
+                pushed.insert(
+                    local_path_str,
+                    (CONTENT_ERROR_SIGN.to_string(), format!("<{}>", err)),
+                );
+                continue;
+            }
+            None => None,
+        };
+
+        let current_file_item = files_map.get(remote_path).cloned();
+        if add_or_update_file_item(
+            safe,
+            &local_path_str,
+            remote_path,
+            local_path,
+            &file_meta,
+            uploaded.as_ref().map(|uploaded| uploaded.xorurl.as_str()),
+            uploaded
+                .as_ref()
+                .and_then(|uploaded| uploaded.checksum.as_deref()),
+            current_file_item.is_some(),
+            dry_run,
+            files_map,
+            pushed,
+        )
+        .await
+        {
+            if let (Some(file_item), Some(new_file_item)) =
+                (&current_file_item, files_map.get_mut(remote_path))
+            {
+                preserve_custom_metadata(file_item, new_file_item);
+            }
+            if let Some(sync_state) = sync_state {
+                sync_state.record(local_path, &files_map[remote_path]);
+            }
+            success_count += 1;
+        }
+    }
+
+    success_count
+}
+
+// Check if the local path is in the state the FileItem describes, where None means it doesn't exist
+async fn local_matches(
+    safe: &Safe,
+    local_path: &Path,
+    file_item: Option<&FileItem>,
+    options: &SyncOptions,
+    sync_state: Option<&SyncState>,
+) -> bool {
+    let metadata = fs::symlink_metadata(local_path).ok();
+    let (metadata, file_item) = match (metadata, file_item) {
+        (None, None) => return true,
+        (Some(metadata), Some(file_item)) => (metadata, file_item),
+        _ => return false,
+    };
+
+    let file_type = file_item
+        .get(PREDICATE_TYPE)
+        .map(String::as_str)
+        .unwrap_or_default();
+    if FileMeta::filetype_is_dir(file_type) {
+        metadata.is_dir()
+    } else if FileMeta::filetype_is_symlink(file_type) {
+        metadata.file_type().is_symlink()
+            && fs::read_link(local_path)
+                .ok()
+                .map(|target| normalise_path_separator(&target.display().to_string()))
+                == file_item.get(SYMLINK_TARGET).cloned()
+    } else {
+        metadata.is_file()
+            && !is_file_item_modified(
+                safe,
+                local_path,
+                file_item,
+                options.change_detection,
+                sync_state,
+            )
+            .await
+    }
+}
+
+// Check if two versions of a path have the same type and content, regardless of their metadata,
+// including the media type set in their links
+fn is_same_content(item: Option<&FileItem>, other_item: Option<&FileItem>) -> bool {
+    match (item, other_item) {
+        (None, None) => true,
+        (Some(item), Some(other_item)) => {
+            let same_link = match (item.get(PREDICATE_LINK), other_item.get(PREDICATE_LINK)) {
+                (Some(link), Some(other_link)) => is_same_blob(link, other_link),
+                (link, other_link) => link == other_link,
+            };
+            same_link
+                && [PREDICATE_TYPE, SYMLINK_TARGET]
+                    .iter()
+                    .all(|key| item.get(*key) == other_item.get(*key))
+        }
+        _ => false,
+    }
+}
+
+fn is_dir(file_item: Option<&FileItem>) -> bool {
+    file_item
+        .and_then(|file_item| file_item.get(PREDICATE_TYPE))
+        .map_or(false, |file_type| FileMeta::filetype_is_dir(file_type))
+}
+
+// Name for the copy of a conflicting remote file which is not taken neither locally nor in the
+// FilesMap, e.g. `dir/index.remote-v3.html`, or `dir/index.remote-v3-1.html` if that one is
+fn unused_conflict_copy_path(
+    location: &Path,
+    dest_base_path: &str,
+    relative_path: &str,
+    version: u64,
+    files_map: &FilesMap,
+) -> String {
+    (0..)
+        .map(|attempt| conflict_copy_path(relative_path, version, attempt))
+        .find(|copy_path| {
+            !files_map.contains_key(&format!("{}/{}", dest_base_path, copy_path))
+                && fs::symlink_metadata(location.join(copy_path)).is_err()
+        })
+        .unwrap_or_default()
+}
+
+// Name for the copy of a conflicting remote file, e.g. `dir/index.remote-v3.html`,
+// with a counter added to the suffix for any attempt other than the first one
+fn conflict_copy_path(relative_path: &str, version: u64, attempt: u64) -> String {
+    let (parent, name) = match relative_path.rfind('/') {
+        Some(idx) => relative_path.split_at(idx + 1),
+        None => ("", relative_path),
+    };
+    let suffix = if attempt == 0 {
+        format!(".remote-v{}", version)
+    } else {
+        format!(".remote-v{}-{}", version, attempt)
+    };
+    match name.rfind('.') {
+        Some(idx) if idx > 0 => format!("{}{}{}{}", parent, &name[..idx], suffix, &name[idx..]),
+        _ => format!("{}{}{}", parent, name, suffix),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::test_helpers::{new_in_memory_safe_instance, new_temp_dir, FileItemBuilder},
+        SafeContentType, SafeUrl,
+    };
+    use anyhow::Result;
+
+    #[test]
+    fn test_conflict_copy_path() {
+        assert_eq!(
+            conflict_copy_path("index.html", 3, 0),
+            "index.remote-v3.html"
+        );
+        assert_eq!(
+            conflict_copy_path("dir/a.tar.gz", 1, 0),
+            "dir/a.tar.remote-v1.gz"
+        );
+        assert_eq!(
+            conflict_copy_path("dir/README", 2, 0),
+            "dir/README.remote-v2"
+        );
+        assert_eq!(
+            conflict_copy_path(".safeignore", 4, 0),
+            ".safeignore.remote-v4"
+        );
+        assert_eq!(
+            conflict_copy_path("index.html", 3, 2),
+            "index.remote-v3-2.html"
+        );
+    }
+
+    #[test]
+    fn test_unused_conflict_copy_path() -> Result<()> {
+        let location = new_temp_dir()?;
+        let mut files_map = FilesMap::new();
+        assert_eq!(
+            unused_conflict_copy_path(&location, "", "index.html", 3, &files_map),
+            "index.remote-v3.html"
+        );
+
+        // taken locally, then remotely too
+        fs::write(location.join("index.remote-v3.html"), "local")?;
+        assert_eq!(
+            unused_conflict_copy_path(&location, "", "index.html", 3, &files_map),
+            "index.remote-v3-1.html"
+        );
+        let _ = files_map.insert("/index.remote-v3-1.html".to_string(), FileItem::new());
+        assert_eq!(
+            unused_conflict_copy_path(&location, "", "index.html", 3, &files_map),
+            "index.remote-v3-2.html"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_same_content() -> Result<()> {
+        let xorname = rand::random();
+        let link = SafeUrl::encode_blob(
+            xorname,
+            SafeContentType::MediaType("text/plain".to_string()),
+            DEFAULT_XORURL_BASE,
+        )?;
+        let item = FileItemBuilder::file("text/plain", &link).build();
+        assert!(is_same_content(Some(&item), Some(&item)));
+
+        // the same Blob linked with a different media type in its XOR-URL
+        let raw_link = SafeUrl::encode_blob(xorname, SafeContentType::Raw, DEFAULT_XORURL_BASE)?;
+        let other_item = FileItemBuilder::file("text/plain", &raw_link).build();
+        assert_ne!(item[PREDICATE_LINK], other_item[PREDICATE_LINK]);
+        assert!(is_same_content(Some(&item), Some(&other_item)));
+
+        let other_item = FileItemBuilder::file("text/html", &raw_link).build();
+        assert!(!is_same_content(Some(&item), Some(&other_item)));
+        assert!(!is_same_content(Some(&item), None));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_map_two_way_sync_paths_outside_location() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let link = safe
+            .files_store_public_blob(b"malicious", Some("text/plain"), false)
+            .await?;
+        let file_item = FileItemBuilder::file("text/plain", &link).size("9").build();
+
+        let outside = new_temp_dir()?;
+        let outside_name = outside
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        fs::write(outside.join("victim.txt"), "victim")?;
+        let location = new_temp_dir()?;
+
+        // a file added remotely outside of the folder, and another one removed from there
+        let mut base_files_map = FilesMap::new();
+        let _ = base_files_map.insert(
+            format!("/../{}/victim.txt", outside_name),
+            file_item.clone(),
+        );
+        let mut remote_files_map = FilesMap::new();
+        let _ = remote_files_map.insert(
+            format!("/../{}/escaped.txt", outside_name),
+            file_item.clone(),
+        );
+        let _ = remote_files_map.insert("/legit.txt".to_string(), file_item);
+
+        let (result, _) = files_map_two_way_sync(
+            &mut safe,
+            &location,
+            "",
+            &base_files_map,
+            1,
+            remote_files_map,
+            SyncConflictResolution::KeepBoth,
+            &SyncOptions::default(),
+            None,
+            false,
+        )
+        .await?;
+        assert_eq!(fs::read_to_string(location.join("legit.txt"))?, "malicious");
+        assert!(!outside.join("escaped.txt").exists());
+        assert_eq!(fs::read_to_string(outside.join("victim.txt"))?, "victim");
+        assert_eq!(
+            result
+                .pulled
+                .values()
+                .filter(|(change, _)| change == CONTENT_ERROR_SIGN)
+                .count(),
+            2
+        );
+        Ok(())
+    }
+}
@@ -8,15 +8,18 @@
 // Software.
 
 mod auth;
+mod cancellation;
 mod consts;
 mod helpers;
 mod keys;
 mod safe_client;
 mod sequence;
+mod serialisation;
 #[cfg(test)]
 mod test_helpers;
 
 use super::{common, constants, Result};
+use backend::StorageBackend;
 use rand::rngs::OsRng;
 use safe_client::SafeAppClient;
 use sn_data_types::Keypair;
@@ -26,12 +29,15 @@ static DEFAULT_TIMEOUT_SECS: u64 = 20;
 
 // The following is what's meant to be the public API
 
+pub mod backend;
 pub mod fetch;
 pub mod files;
 pub mod multimap;
 pub mod nrs;
+pub mod rdf;
 pub mod register;
 pub mod wallet;
+pub use cancellation::CancellationToken;
 pub use consts::DEFAULT_XORURL_BASE;
 pub use helpers::parse_coins_amount;
 pub use sn_url::*;
@@ -39,7 +45,7 @@ pub use xor_name::{XorName, XOR_NAME_LEN};
 
 #[derive(Clone)]
 pub struct Safe {
-    safe_client: SafeAppClient,
+    safe_client: Box<dyn StorageBackend>,
     pub xorurl_base: XorUrlBase,
     #[allow(dead_code)]
     timeout: Duration,
@@ -56,8 +62,18 @@ impl Default for Safe {
 
 impl Safe {
     pub fn new(xorurl_base: Option<XorUrlBase>, timeout: Duration) -> Self {
+        Self::with_backend(SafeAppClient::new(), xorurl_base, timeout)
+    }
+
+    /// Instantiate a Safe which stores and retrieves all data using the backend provided,
+    /// e.g. an `InMemoryBackend` to use the API without connecting to the network
+    pub fn with_backend(
+        backend: impl StorageBackend + 'static,
+        xorurl_base: Option<XorUrlBase>,
+        timeout: Duration,
+    ) -> Self {
         Self {
-            safe_client: SafeAppClient::new(),
+            safe_client: Box::new(backend),
             xorurl_base: xorurl_base.unwrap_or(DEFAULT_XORURL_BASE),
             timeout,
         }
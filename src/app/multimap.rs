@@ -195,7 +195,10 @@ impl Safe {
 
 #[cfg(test)]
 mod tests {
-    use crate::{app::test_helpers::new_safe_instance, retry_loop, retry_loop_for_pattern};
+    use crate::{
+        app::test_helpers::{new_in_memory_safe_instance, new_safe_instance},
+        retry_loop, retry_loop_for_pattern,
+    };
     use anyhow::Result;
     use std::collections::BTreeSet;
 
@@ -380,4 +383,44 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_multimap_in_memory() -> Result<()> {
+        let safe = new_in_memory_safe_instance();
+        let key = b"key".to_vec();
+        let key_val = (key.clone(), b"value".to_vec());
+        let key_val2 = (key.clone(), b"value2".to_vec());
+
+        let xorurl = safe.multimap_create(None, 25_000, false).await?;
+        assert_eq!(
+            safe.multimap_get_by_key(&xorurl, &key).await?,
+            Default::default()
+        );
+
+        let hash = safe
+            .multimap_insert(&xorurl, key_val.clone(), BTreeSet::new())
+            .await?;
+        assert_eq!(
+            safe.multimap_get_by_hash(&xorurl, hash).await?,
+            Some(key_val)
+        );
+
+        let hash2 = safe
+            .multimap_insert(&xorurl, key_val2.clone(), vec![hash].into_iter().collect())
+            .await?;
+        assert_eq!(
+            safe.multimap_get_by_key(&xorurl, &key).await?,
+            vec![(hash2, key_val2)].into_iter().collect()
+        );
+
+        let _ = safe
+            .multimap_remove(&xorurl, vec![hash2].into_iter().collect())
+            .await?;
+        assert_eq!(
+            safe.multimap_get_by_key(&xorurl, &key).await?,
+            Default::default()
+        );
+
+        Ok(())
+    }
 }
@@ -0,0 +1,402 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::nrs_map::{DefaultRdf, NrsMap, SubNameRdf};
+use crate::{
+    app::rdf::{
+        self, map_from_properties, properties_from_map, vocab_iri, RdfFormat, RdfNode, RdfObject,
+    },
+    Error, Result,
+};
+use std::collections::BTreeMap;
+
+// Classes and properties of the vocabulary used to describe an NrsMap
+const NRS_MAP_CLASS: &str = "NrsMap";
+const SUB_NAME_CLASS: &str = "SubName";
+const DEFINITION_CLASS: &str = "Definition";
+const SUB_NAME: &str = "subName";
+const NAME: &str = "name";
+const DEFINITION: &str = "definition";
+const NRS_MAP: &str = "nrsMap";
+const DEFAULT: &str = "default";
+const DEFAULT_SUB_NAME: &str = "defaultSubName";
+
+impl NrsMap {
+    /// # Export the NrsMap as linked data.
+    /// The NrsMap is described as the resource identified by `base_url`, which is expected
+    /// to be the URL of the NrsMapContainer it belongs to, with each of its sub names, and
+    /// their definitions, described as nested resources.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::{rdf::RdfFormat, Safe};
+    /// # use rand::distributions::Alphanumeric;
+    /// # use rand::{thread_rng, Rng};
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let rand_string: String = thread_rng().sample_iter(&Alphanumeric).take(15).collect();
+    ///     let file_xorurl = safe.files_store_public_blob(&vec![], Some("text/plain"), false).await.unwrap();
+    ///     let (xorurl, _processed_entries, nrs_map) = safe.nrs_map_container_create(&rand_string, &file_xorurl, true, false, false).await.unwrap();
+    ///     let json_ld = nrs_map.to_rdf(&xorurl, RdfFormat::JsonLd).unwrap();
+    ///     println!("NrsMap as JSON-LD: {}", json_ld);
+    /// # });
+    /// ```
+    pub fn to_rdf(&self, base_url: &str, format: RdfFormat) -> Result<String> {
+        let mut node = nrs_map_node(self);
+        node.id = Some(String::default());
+        rdf::serialise(&[node], base_url, format)
+    }
+
+    /// # Import an NrsMap from linked data.
+    /// The first resource described as an NrsMap in the document is imported,
+    /// as exported by `NrsMap::to_rdf`. Any other resource or property is ignored.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use sn_api::{nrs::NrsMap, rdf::RdfFormat, Safe};
+    /// # use rand::distributions::Alphanumeric;
+    /// # use rand::{thread_rng, Rng};
+    /// # let mut safe = Safe::default();
+    /// # async_std::task::block_on(async {
+    /// #   safe.connect("", Some("fake-credentials")).await.unwrap();
+    ///     let rand_string: String = thread_rng().sample_iter(&Alphanumeric).take(15).collect();
+    ///     let file_xorurl = safe.files_store_public_blob(&vec![], Some("text/plain"), false).await.unwrap();
+    ///     let (xorurl, _processed_entries, nrs_map) = safe.nrs_map_container_create(&rand_string, &file_xorurl, true, false, false).await.unwrap();
+    ///     let turtle = nrs_map.to_rdf(&xorurl, RdfFormat::Turtle).unwrap();
+    ///     let imported_nrs_map = NrsMap::from_rdf(&turtle, RdfFormat::Turtle).unwrap();
+    ///     assert_eq!(imported_nrs_map, nrs_map);
+    /// # });
+    /// ```
+    pub fn from_rdf(data: &str, format: RdfFormat) -> Result<Self> {
+        let nodes = rdf::parse(data, format)?;
+        let node = nodes
+            .iter()
+            .find(|node| node.has_type(NRS_MAP_CLASS))
+            .ok_or_else(|| {
+                Error::InvalidInput("No NrsMap is described by the document".to_string())
+            })?;
+
+        nrs_map_from_node(node)
+    }
+}
+
+fn nrs_map_node(nrs_map: &NrsMap) -> RdfNode {
+    let mut properties = vec![];
+    match &nrs_map.default {
+        DefaultRdf::NotSet => {}
+        DefaultRdf::ExistingRdf(sub_name) => properties.push((
+            vocab_iri(DEFAULT_SUB_NAME),
+            RdfObject::Literal {
+                value: sub_name.to_string(),
+                datatype: None,
+            },
+        )),
+        DefaultRdf::OtherRdf(definition) => {
+            properties.push((vocab_iri(DEFAULT), definition_object(definition)))
+        }
+    }
+
+    for (name, sub_name) in nrs_map.sub_names_map.iter() {
+        let value = match sub_name {
+            SubNameRdf::Definition(definition) => {
+                (vocab_iri(DEFINITION), definition_object(definition))
+            }
+            SubNameRdf::SubName(nrs_map) => {
+                (vocab_iri(NRS_MAP), RdfObject::Node(nrs_map_node(nrs_map)))
+            }
+        };
+        let name = (
+            vocab_iri(NAME),
+            RdfObject::Literal {
+                value: name.to_string(),
+                datatype: None,
+            },
+        );
+        properties.push((
+            vocab_iri(SUB_NAME),
+            RdfObject::Node(RdfNode {
+                id: None,
+                types: vec![vocab_iri(SUB_NAME_CLASS)],
+                properties: vec![name, value],
+            }),
+        ));
+    }
+
+    RdfNode {
+        id: None,
+        types: vec![vocab_iri(NRS_MAP_CLASS)],
+        properties,
+    }
+}
+
+fn definition_object(definition: &BTreeMap<String, String>) -> RdfObject {
+    RdfObject::Node(RdfNode {
+        id: None,
+        types: vec![vocab_iri(DEFINITION_CLASS)],
+        properties: properties_from_map(definition),
+    })
+}
+
+fn nrs_map_from_node(node: &RdfNode) -> Result<NrsMap> {
+    let mut nrs_map = NrsMap::default();
+    for object in node.objects(SUB_NAME) {
+        let sub_name_node = as_node(SUB_NAME, object)?;
+        let name = match single_object(sub_name_node, NAME)? {
+            Some(RdfObject::Literal { value, .. }) => value.to_string(),
+            _ => {
+                return Err(Error::InvalidInput(
+                    "Every sub name of an NrsMap must have a name".to_string(),
+                ))
+            }
+        };
+
+        let sub_name = match (
+            single_object(sub_name_node, DEFINITION)?,
+            single_object(sub_name_node, NRS_MAP)?,
+        ) {
+            (Some(definition), None) => SubNameRdf::Definition(map_from_properties(
+                &as_node(DEFINITION, definition)?.properties,
+            )?),
+            (None, Some(sub_nrs_map)) => {
+                SubNameRdf::SubName(nrs_map_from_node(as_node(NRS_MAP, sub_nrs_map)?)?)
+            }
+            _ => {
+                return Err(Error::InvalidInput(format!(
+                    "Sub name \"{}\" must have either a definition or a nested NrsMap",
+                    name
+                )))
+            }
+        };
+
+        if nrs_map
+            .sub_names_map
+            .insert(name.clone(), sub_name)
+            .is_some()
+        {
+            return Err(Error::InvalidInput(format!(
+                "Sub name \"{}\" is described more than once",
+                name
+            )));
+        }
+    }
+
+    nrs_map.default = match (
+        single_object(node, DEFAULT)?,
+        single_object(node, DEFAULT_SUB_NAME)?,
+    ) {
+        (None, None) => DefaultRdf::NotSet,
+        (Some(definition), None) => DefaultRdf::OtherRdf(map_from_properties(
+            &as_node(DEFAULT, definition)?.properties,
+        )?),
+        (None, Some(RdfObject::Literal { value, .. })) => {
+            DefaultRdf::ExistingRdf(value.to_string())
+        }
+        _ => {
+            return Err(Error::InvalidInput(
+                "The default of an NrsMap must be either a definition or a sub name".to_string(),
+            ))
+        }
+    };
+
+    Ok(nrs_map)
+}
+
+// The value of a property which can only have one
+fn single_object<'a>(node: &'a RdfNode, term: &str) -> Result<Option<&'a RdfObject>> {
+    let mut objects = node.objects(term);
+    let object = objects.next();
+    if objects.next().is_some() {
+        return Err(Error::InvalidInput(format!(
+            "Property \"{}\" is expected to have a single value",
+            term
+        )));
+    }
+    Ok(object)
+}
+
+fn as_node<'a>(term: &str, object: &'a RdfObject) -> Result<&'a RdfNode> {
+    match object {
+        RdfObject::Node(node) => Ok(node),
+        _ => Err(Error::InvalidInput(format!(
+            "The value of property \"{}\" is expected to be a nested resource",
+            term
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::consts::*;
+    use anyhow::Result;
+
+    fn definition(link: &str) -> BTreeMap<String, String> {
+        let mut definition = BTreeMap::new();
+        definition.insert(PREDICATE_LINK.to_string(), link.to_string());
+        definition.insert(
+            PREDICATE_CREATED.to_string(),
+            "2021-03-10T10:00:00Z".to_string(),
+        );
+        definition.insert(
+            PREDICATE_MODIFIED.to_string(),
+            "2021-03-10T10:00:00Z".to_string(),
+        );
+        definition
+    }
+
+    fn test_nrs_map() -> NrsMap {
+        let mut blog = NrsMap {
+            default: DefaultRdf::OtherRdf(definition("safe://hbyyyyblog?v=0")),
+            ..NrsMap::default()
+        };
+        blog.sub_names_map.insert(
+            "old".to_string(),
+            SubNameRdf::Definition(definition("safe://hbyyyyold?v=3")),
+        );
+
+        let mut nrs_map = NrsMap {
+            default: DefaultRdf::ExistingRdf("www".to_string()),
+            ..NrsMap::default()
+        };
+        nrs_map.sub_names_map.insert(
+            "www".to_string(),
+            SubNameRdf::Definition(definition("safe://hbyyyywww?v=1")),
+        );
+        nrs_map
+            .sub_names_map
+            .insert("blog".to_string(), SubNameRdf::SubName(blog));
+        nrs_map
+    }
+
+    #[test]
+    fn test_nrs_map_rdf_roundtrip() -> Result<()> {
+        let nrs_map = test_nrs_map();
+        for format in &[RdfFormat::JsonLd, RdfFormat::Turtle] {
+            let data = nrs_map.to_rdf("safe://hnyynyss", *format)?;
+            assert_eq!(NrsMap::from_rdf(&data, *format)?, nrs_map);
+        }
+
+        let empty_nrs_map = NrsMap::default();
+        let data = empty_nrs_map.to_rdf("safe://hnyynyss", RdfFormat::Turtle)?;
+        assert_eq!(NrsMap::from_rdf(&data, RdfFormat::Turtle)?, empty_nrs_map);
+
+        let turtle = nrs_map.to_rdf("safe://hnyynyss", RdfFormat::Turtle)?;
+        assert!(turtle.contains("safe:defaultSubName \"www\""));
+        assert!(NrsMap::from_rdf("<a> <b> <c> .", RdfFormat::Turtle).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_nrs_map_from_rdf_with_labelled_blank_nodes() -> Result<()> {
+        // as serialised by Jena, with the flat Turtle writer
+        let turtle = r#"@prefix safe:  <https://safenetwork.tech/vocab/v1#> .
+@prefix xsd:   <http://www.w3.org/2001/XMLSchema#> .
+
+<safe://hnyynyss>  a          safe:NrsMap ;
+        safe:defaultSubName  "www" ;
+        safe:subName         _:b0 , _:b1 .
+
+_:b0    a                safe:SubName ;
+        safe:definition  _:b2 ;
+        safe:name        "www" .
+
+_:b2    a              safe:Definition ;
+        safe:created   "2021-03-10T10:00:00Z"^^xsd:dateTime ;
+        safe:link      <safe://hbyyyywww?v=1> ;
+        safe:modified  "2021-03-10T10:00:00Z"^^xsd:dateTime .
+
+_:b1    a            safe:SubName ;
+        safe:name    "blog" ;
+        safe:nrsMap  _:b3 .
+
+_:b3    a             safe:NrsMap ;
+        safe:default  _:b4 ;
+        safe:subName  [ a                safe:SubName ;
+                        safe:definition  _:b5 ;
+                        safe:name        "old"
+                      ] .
+
+_:b4    a              safe:Definition ;
+        safe:created   "2021-03-10T10:00:00Z"^^xsd:dateTime ;
+        safe:link      <safe://hbyyyyblog?v=0> ;
+        safe:modified  "2021-03-10T10:00:00Z"^^xsd:dateTime .
+
+_:b5    a              safe:Definition ;
+        safe:created   "2021-03-10T10:00:00Z"^^xsd:dateTime ;
+        safe:link      <safe://hbyyyyold?v=3> ;
+        safe:modified  "2021-03-10T10:00:00Z"^^xsd:dateTime .
+"#;
+        assert_eq!(NrsMap::from_rdf(turtle, RdfFormat::Turtle)?, test_nrs_map());
+
+        // as serialised by rdflib, in expanded form with a node per blank node
+        let definition = |id: &str, link: &str| {
+            format!(
+                r#"{{
+                    "@id": "{}",
+                    "@type": ["https://safenetwork.tech/vocab/v1#Definition"],
+                    "https://safenetwork.tech/vocab/v1#created": [
+                        {{ "@type": "http://www.w3.org/2001/XMLSchema#dateTime", "@value": "2021-03-10T10:00:00Z" }}
+                    ],
+                    "https://safenetwork.tech/vocab/v1#link": [{{ "@id": "{}" }}],
+                    "https://safenetwork.tech/vocab/v1#modified": [
+                        {{ "@type": "http://www.w3.org/2001/XMLSchema#dateTime", "@value": "2021-03-10T10:00:00Z" }}
+                    ]
+                }}"#,
+                id, link
+            )
+        };
+        let json_ld = format!(
+            r#"[
+                {{
+                    "@id": "_:N1",
+                    "@type": ["https://safenetwork.tech/vocab/v1#SubName"],
+                    "https://safenetwork.tech/vocab/v1#name": [{{ "@value": "blog" }}],
+                    "https://safenetwork.tech/vocab/v1#nrsMap": [{{ "@id": "_:N3" }}]
+                }},
+                {},
+                {{
+                    "@id": "safe://hnyynyss",
+                    "@type": ["https://safenetwork.tech/vocab/v1#NrsMap"],
+                    "https://safenetwork.tech/vocab/v1#defaultSubName": [{{ "@value": "www" }}],
+                    "https://safenetwork.tech/vocab/v1#subName": [{{ "@id": "_:N0" }}, {{ "@id": "_:N1" }}]
+                }},
+                {{
+                    "@context": {{ "safe": "https://safenetwork.tech/vocab/v1#" }},
+                    "@id": "_:N3",
+                    "@type": "safe:NrsMap",
+                    "safe:default": {{ "@id": "_:N4" }},
+                    "safe:subName": {{
+                        "@type": "safe:SubName",
+                        "safe:name": "old",
+                        "safe:definition": {{ "@id": "_:N5" }}
+                    }}
+                }},
+                {},
+                {},
+                {{
+                    "@id": "_:N0",
+                    "@type": ["https://safenetwork.tech/vocab/v1#SubName"],
+                    "https://safenetwork.tech/vocab/v1#definition": [{{ "@id": "_:N2" }}],
+                    "https://safenetwork.tech/vocab/v1#name": [{{ "@value": "www" }}]
+                }}
+            ]"#,
+            definition("_:N2", "safe://hbyyyywww?v=1"),
+            definition("_:N4", "safe://hbyyyyblog?v=0"),
+            definition("_:N5", "safe://hbyyyyold?v=3"),
+        );
+        assert_eq!(
+            NrsMap::from_rdf(&json_ld, RdfFormat::JsonLd)?,
+            test_nrs_map()
+        );
+        Ok(())
+    }
+}
@@ -7,13 +7,16 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
+mod linked_data;
 mod nrs_map;
 
 pub use nrs_map::{DefaultRdf, NrsMap};
 
 use crate::{
     app::{
+        cancellation::{check_cancelled, CancellationToken},
         consts::{CONTENT_ADDED_SIGN, CONTENT_DELETED_SIGN},
+        serialisation::{deserialise_from_envelope, serialise_to_envelope},
         Safe,
     },
     Error, Result, SafeContentType, SafeUrl, XorUrl,
@@ -53,6 +56,7 @@ impl Safe {
                 false,
                 None,
                 false, // don't resolve the URL's path
+                false, // only the XOR-URLs of the resolution chain are used
             )
             .await?;
 
@@ -82,6 +86,23 @@ impl Safe {
         default: bool,
         hard_link: bool,
         dry_run: bool,
+    ) -> Result<(u64, XorUrl, ProcessedEntries, NrsMap)> {
+        self.nrs_map_container_add_with_cancel(name, link, default, hard_link, None, dry_run)
+            .await
+    }
+
+    /// # Add a name to a NrsMapContainer, unless the operation is cancelled.
+    /// The token is checked before the new NrsMap is stored and before it's appended to the
+    /// NrsMapContainer, and a `Cancelled` error is returned if it was cancelled, in which case no
+    /// new version of the NrsMapContainer is appended.
+    pub async fn nrs_map_container_add_with_cancel(
+        &self,
+        name: &str,
+        link: &str,
+        default: bool,
+        hard_link: bool,
+        cancel: Option<&CancellationToken>,
+        dry_run: bool,
     ) -> Result<(u64, XorUrl, ProcessedEntries, NrsMap)> {
         info!("Adding to NRS map...");
         // GET current NRS map from name's TLD
@@ -97,13 +118,16 @@ impl Safe {
         debug!("The new NRS Map: {:?}", nrs_map);
         if !dry_run {
             // Append new version of the NrsMap in the Public Sequence (NRS Map Container)
+            check_cancelled(cancel)?;
             let nrs_map_xorurl = self.store_nrs_map(&nrs_map).await?;
+            check_cancelled(cancel)?;
             self.safe_client
                 .append_to_sequence(
                     nrs_map_xorurl.as_bytes(),
                     safe_url.xorname(),
                     safe_url.type_tag(),
                     false,
+                    None,
                 )
                 .await?;
         }
@@ -135,6 +159,23 @@ impl Safe {
         default: bool,
         hard_link: bool,
         dry_run: bool,
+    ) -> Result<(XorUrl, ProcessedEntries, NrsMap)> {
+        self.nrs_map_container_create_with_cancel(name, link, default, hard_link, None, dry_run)
+            .await
+    }
+
+    /// # Create a NrsMapContainer, unless the operation is cancelled.
+    /// The token is checked before the NrsMap is stored and before the NrsMapContainer is
+    /// created, and a `Cancelled` error is returned if it was cancelled, in which case no
+    /// NrsMapContainer is created.
+    pub async fn nrs_map_container_create_with_cancel(
+        &mut self,
+        name: &str,
+        link: &str,
+        default: bool,
+        hard_link: bool,
+        cancel: Option<&CancellationToken>,
+        dry_run: bool,
     ) -> Result<(XorUrl, ProcessedEntries, NrsMap)> {
         info!("Creating an NRS map");
         let (_, nrs_url) = validate_nrs_name(name)?;
@@ -157,7 +198,9 @@ impl Safe {
                 debug!("XorName for \"{:?}\" is \"{:?}\"", &nrs_url, &nrs_xorname);
 
                 // Store the serialised NrsMap in a Public Blob
+                check_cancelled(cancel)?;
                 let nrs_map_xorurl = self.store_nrs_map(&nrs_map).await?;
+                check_cancelled(cancel)?;
 
                 // Store the NrsMapContainer in a Public Sequence, putting the
                 // serialised NrsMap XOR-URL as the first entry value
@@ -189,6 +232,20 @@ impl Safe {
         &self,
         name: &str,
         dry_run: bool,
+    ) -> Result<(u64, XorUrl, ProcessedEntries, NrsMap)> {
+        self.nrs_map_container_remove_with_cancel(name, None, dry_run)
+            .await
+    }
+
+    /// # Remove a name from a NrsMapContainer, unless the operation is cancelled.
+    /// The token is checked before the new NrsMap is stored and before it's appended to the
+    /// NrsMapContainer, and a `Cancelled` error is returned if it was cancelled, in which case no
+    /// new version of the NrsMapContainer is appended.
+    pub async fn nrs_map_container_remove_with_cancel(
+        &self,
+        name: &str,
+        cancel: Option<&CancellationToken>,
+        dry_run: bool,
     ) -> Result<(u64, XorUrl, ProcessedEntries, NrsMap)> {
         info!("Removing from NRS map...");
         // GET current NRS map from &name TLD
@@ -207,13 +264,16 @@ impl Safe {
         debug!("The new NRS Map: {:?}", nrs_map);
         if !dry_run {
             // Append new version of the NrsMap in the Public Sequence (NRS Map Container)
+            check_cancelled(cancel)?;
             let nrs_map_xorurl = self.store_nrs_map(&nrs_map).await?;
+            check_cancelled(cancel)?;
             self.safe_client
                 .append_to_sequence(
                     nrs_map_xorurl.as_bytes(),
                     safe_url.xorname(),
                     safe_url.type_tag(),
                     false,
+                    None,
                 )
                 .await?;
         }
@@ -288,14 +348,7 @@ impl Safe {
                 let serialised_nrs_map = self.fetch_public_blob(&nrs_map_xorurl, None).await?;
 
                 debug!("Nrs map v{} retrieved: {:?} ", version, &serialised_nrs_map);
-                let nrs_map =
-                    serde_json::from_str(&String::from_utf8_lossy(&serialised_nrs_map.as_slice()))
-                        .map_err(|err| {
-                            Error::ContentError(format!(
-                                "Couldn't deserialise the NrsMap stored in the NrsContainer: {:?}",
-                                err
-                            ))
-                        })?;
+                let nrs_map = deserialise_from_envelope(&serialised_nrs_map, "NrsMap")?;
 
                 Ok((version, nrs_map))
             }
@@ -318,16 +371,10 @@ impl Safe {
     async fn store_nrs_map(&self, nrs_map: &NrsMap) -> Result<String> {
         // The NrsMapContainer is a Sequence where each NRS Map version is
         // an entry containing the XOR-URL of the Blob that contains the serialised NrsMap.
-        // TODO: use RDF format
-        let serialised_nrs_map = serde_json::to_string(nrs_map).map_err(|err| {
-            Error::Serialisation(format!(
-                "Couldn't serialise the NrsMap generated: {:?}",
-                err
-            ))
-        })?;
+        let serialised_nrs_map = serialise_to_envelope(nrs_map, "NrsMap")?;
 
         let nrs_map_xorurl = self
-            .files_store_public_blob(serialised_nrs_map.as_bytes(), None, false)
+            .files_store_public_blob(&serialised_nrs_map, None, false)
             .await?;
 
         Ok(nrs_map_xorurl)
@@ -364,7 +411,7 @@ mod tests {
     use crate::{
         app::{
             consts::PREDICATE_LINK,
-            test_helpers::{new_safe_instance, random_nrs_name},
+            test_helpers::{new_in_memory_safe_instance, new_safe_instance, random_nrs_name},
         },
         retry_loop, retry_loop_for_pattern,
     };
@@ -441,6 +488,71 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_nrs_map_container_cancelled() -> Result<()> {
+        let site_name = random_nrs_name();
+        let mut safe = new_in_memory_safe_instance();
+        let (link, _, _) = safe
+            .files_container_create(None, None, true, true, false)
+            .await?;
+        let link_v0 = format!("{}?v=0", link);
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let name = format!("a.{}", site_name);
+        match safe
+            .nrs_map_container_create_with_cancel(
+                &name,
+                &link_v0,
+                true,
+                false,
+                Some(&cancel),
+                false,
+            )
+            .await
+        {
+            Err(Error::Cancelled(_)) => {}
+            other => bail!("Cancelled error was expected, got: {:?}", other),
+        }
+
+        // it can be created afterwards since nothing was created when cancelled
+        let (xorurl, _, _) = safe
+            .nrs_map_container_create(&name, &link_v0, true, false, false)
+            .await?;
+        let _ = retry_loop!(safe.fetch(&xorurl, None));
+
+        let other_name = format!("b.{}", site_name);
+        match safe
+            .nrs_map_container_add_with_cancel(
+                &other_name,
+                &link_v0,
+                false,
+                false,
+                Some(&cancel),
+                false,
+            )
+            .await
+        {
+            Err(Error::Cancelled(_)) => {}
+            other => bail!("Cancelled error was expected, got: {:?}", other),
+        }
+        match safe
+            .nrs_map_container_remove_with_cancel(&name, Some(&cancel), false)
+            .await
+        {
+            Err(Error::Cancelled(_)) => {}
+            other => bail!("Cancelled error was expected, got: {:?}", other),
+        }
+
+        // no new version was appended
+        let (version, nrs_map) = safe.nrs_map_container_get(&xorurl).await?;
+        assert_eq!(version, 0);
+        assert_eq!(nrs_map.sub_names_map.len(), 1);
+        assert_eq!(nrs_map.get_default_link()?, link_v0);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_nrs_map_container_add_or_remove_with_versioned_target() -> Result<()> {
         let site_name = random_nrs_name();
@@ -648,4 +760,39 @@ mod tests {
             Err(err) => Err(anyhow!("Error returned is not the expected one: {}", err)),
         }
     }
+
+    #[tokio::test]
+    async fn test_nrs_map_container_in_memory() -> Result<()> {
+        let site_name = random_nrs_name();
+        let mut safe = new_in_memory_safe_instance();
+        let (link, _, _) = safe
+            .files_container_create(None, None, true, true, false)
+            .await?;
+        let link_v0 = format!("{}?v=0", link);
+        let link_v1 = format!("{}?v=1", link);
+
+        let (xorurl, _, nrs_map) = safe
+            .nrs_map_container_create(&format!("a.b.{}", site_name), &link_v0, true, false, false)
+            .await?;
+        assert_eq!(nrs_map.sub_names_map.len(), 1);
+        assert_eq!(nrs_map.get_default_link()?, link_v0);
+
+        let (version, _, _, nrs_map) = safe
+            .nrs_map_container_add(&format!("a2.b.{}", site_name), &link_v1, true, false, false)
+            .await?;
+        assert_eq!(version, 1);
+        assert_eq!(nrs_map.get_default_link()?, link_v1);
+
+        let (version, _, _, nrs_map) = safe
+            .nrs_map_container_remove(&format!("a.b.{}", site_name), false)
+            .await?;
+        assert_eq!(version, 2);
+        assert_eq!(nrs_map.sub_names_map.len(), 1);
+
+        let (version, fetched_nrs_map) = safe.nrs_map_container_get(&xorurl).await?;
+        assert_eq!(version, 2);
+        assert_eq!(fetched_nrs_map, nrs_map);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,463 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{embed_blank_nodes, RdfNode, RdfObject, PREFIXES, RDF_TYPE_IRI, XSD_IRI};
+use crate::{Error, Result};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+pub(super) fn serialise(nodes: &[RdfNode], base_url: &str) -> Result<String> {
+    let mut context = Map::new();
+    if !base_url.is_empty() {
+        let _ = context.insert("@base".to_string(), json!(base_url));
+    }
+    for (prefix, iri) in PREFIXES.iter() {
+        let _ = context.insert(prefix.to_string(), json!(iri));
+    }
+
+    let document = json!({
+        "@context": context,
+        "@graph": nodes.iter().map(node_to_json).collect::<Vec<_>>(),
+    });
+
+    serde_json::to_string_pretty(&document).map_err(|err| {
+        Error::Serialisation(format!("Couldn't serialise the JSON-LD document: {}", err))
+    })
+}
+
+fn node_to_json(node: &RdfNode) -> Value {
+    let mut object = Map::new();
+    if let Some(id) = &node.id {
+        let _ = object.insert("@id".to_string(), json!(id));
+    }
+    match node.types.as_slice() {
+        [] => {}
+        [single] => {
+            let _ = object.insert("@type".to_string(), json!(compact_iri(single)));
+        }
+        types => {
+            let types: Vec<String> = types.iter().map(|iri| compact_iri(iri)).collect();
+            let _ = object.insert("@type".to_string(), json!(types));
+        }
+    }
+
+    // properties with several values are grouped in an array
+    let mut properties: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+    for (predicate, value) in node.properties.iter() {
+        let value = match value {
+            RdfObject::Iri(iri) => json!({ "@id": iri }),
+            RdfObject::Literal {
+                value,
+                datatype: None,
+            } => json!(value),
+            RdfObject::Literal {
+                value,
+                datatype: Some(datatype),
+            } => json!({ "@value": value, "@type": compact_iri(datatype) }),
+            RdfObject::Node(node) => node_to_json(node),
+        };
+        properties
+            .entry(compact_iri(predicate))
+            .or_default()
+            .push(value);
+    }
+    for (predicate, mut values) in properties {
+        let value = if values.len() == 1 {
+            values.remove(0)
+        } else {
+            Value::Array(values)
+        };
+        let _ = object.insert(predicate, value);
+    }
+
+    Value::Object(object)
+}
+
+fn compact_iri(iri: &str) -> String {
+    for (prefix, namespace) in PREFIXES.iter() {
+        if let Some(name) = iri.strip_prefix(namespace) {
+            if !name.is_empty() {
+                return format!("{}:{}", prefix, name);
+            }
+        }
+    }
+    iri.to_string()
+}
+
+// Definition of a term in the context of a document
+#[derive(Debug, Clone)]
+struct TermDefinition {
+    iri: String,
+    // the '@type' the values of the term are coerced to, if any
+    coercion: Option<String>,
+}
+
+// Parse a JSON-LD document, which can either be a node, an array of nodes, or a
+// graph of them. Only local contexts are supported since they are never fetched, though
+// any node can have its own, and labelled blank nodes are nested where they are referenced.
+pub(super) fn parse(data: &str) -> Result<Vec<RdfNode>> {
+    let document: Value = serde_json::from_str(data)
+        .map_err(|err| Error::InvalidInput(format!("Invalid JSON-LD document: {}", err)))?;
+
+    // our prefixes are known even if the context doesn't define them
+    let mut terms = BTreeMap::new();
+    for (prefix, iri) in PREFIXES.iter() {
+        let _ = terms.insert(
+            prefix.to_string(),
+            TermDefinition {
+                iri: iri.to_string(),
+                coercion: None,
+            },
+        );
+    }
+
+    let nodes = match document {
+        Value::Array(nodes) => nodes,
+        Value::Object(mut object) => {
+            if let Some(context) = object.remove("@context") {
+                parse_context(&context, &mut terms)?;
+            }
+            match object.remove("@graph") {
+                Some(Value::Array(nodes)) => nodes,
+                Some(node) => vec![node],
+                None => vec![Value::Object(object)],
+            }
+        }
+        _ => {
+            return Err(invalid_document(
+                "a JSON-LD document must be an object or an array",
+            ))
+        }
+    };
+
+    let nodes = nodes
+        .iter()
+        .map(|node| parse_node(node, &terms))
+        .collect::<Result<Vec<_>>>()?;
+    embed_blank_nodes(nodes)
+}
+
+fn parse_context(context: &Value, terms: &mut BTreeMap<String, TermDefinition>) -> Result<()> {
+    let definitions = match context {
+        Value::Object(definitions) => definitions,
+        Value::Array(contexts) => {
+            for context in contexts.iter() {
+                parse_context(context, terms)?;
+            }
+            return Ok(());
+        }
+        Value::Null => return Ok(()),
+        _ => return Err(invalid_document("remote contexts are not supported")),
+    };
+
+    // prefixes are defined first since other terms can be compact IRIs using them,
+    // regardless of the order they are found in the context
+    let (prefixes, others): (Vec<_>, Vec<_>) = definitions
+        .iter()
+        .filter(|(term, _)| !term.starts_with('@'))
+        .partition(|(_, definition)| definition.is_string());
+
+    for (term, definition) in prefixes.into_iter().chain(others) {
+        let (iri, coercion) = match definition {
+            Value::String(iri) => (iri.to_string(), None),
+            Value::Object(definition) => match definition.get("@id") {
+                Some(Value::String(iri)) => (
+                    iri.to_string(),
+                    definition
+                        .get("@type")
+                        .and_then(Value::as_str)
+                        .map(String::from),
+                ),
+                _ => continue,
+            },
+            _ => continue,
+        };
+        let iri = expand_iri(&iri, terms)?;
+        let coercion = match coercion {
+            Some(coercion) if coercion.starts_with('@') => Some(coercion),
+            Some(coercion) => Some(expand_iri(&coercion, terms)?),
+            None => None,
+        };
+        let _ = terms.insert(term.to_string(), TermDefinition { iri, coercion });
+    }
+
+    Ok(())
+}
+
+fn parse_node(value: &Value, terms: &BTreeMap<String, TermDefinition>) -> Result<RdfNode> {
+    let object = value
+        .as_object()
+        .ok_or_else(|| invalid_document("nodes must be JSON objects"))?;
+
+    // an embedded context applies to the node and to the ones nested in it
+    let embedded_terms = match object.get("@context") {
+        Some(context) => {
+            let mut embedded_terms = terms.clone();
+            parse_context(context, &mut embedded_terms)?;
+            Some(embedded_terms)
+        }
+        None => None,
+    };
+    let terms = embedded_terms.as_ref().unwrap_or(terms);
+
+    let mut node = RdfNode::default();
+    for (key, value) in object.iter() {
+        match key.as_str() {
+            "@id" => {
+                let id = value
+                    .as_str()
+                    .ok_or_else(|| invalid_document("'@id' must be a string"))?;
+                node.id = Some(id.to_string());
+            }
+            "@type" => {
+                for iri in as_array(value).iter() {
+                    let iri = iri
+                        .as_str()
+                        .ok_or_else(|| invalid_document("'@type' must be a string"))?;
+                    node.types.push(expand_iri(iri, terms)?);
+                }
+            }
+            key if key.starts_with('@') => {}
+            key => {
+                let (predicate, coercion) = match terms.get(key) {
+                    Some(definition) => (definition.iri.clone(), definition.coercion.as_deref()),
+                    None => (expand_iri(key, terms)?, None),
+                };
+                for value in as_array(value).iter() {
+                    let object = parse_object(value, coercion, terms)?;
+                    if predicate == RDF_TYPE_IRI {
+                        if let RdfObject::Iri(iri) = object {
+                            node.types.push(iri);
+                        }
+                    } else {
+                        node.properties.push((predicate.clone(), object));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(node)
+}
+
+fn parse_object(
+    value: &Value,
+    coercion: Option<&str>,
+    terms: &BTreeMap<String, TermDefinition>,
+) -> Result<RdfObject> {
+    let (value, datatype) = match value {
+        Value::String(value) => match coercion {
+            Some("@id") | Some("@vocab") => return Ok(RdfObject::Iri(value.to_string())),
+            Some(datatype) if !datatype.starts_with('@') => {
+                (value.to_string(), Some(datatype.to_string()))
+            }
+            _ => (value.to_string(), None),
+        },
+        Value::Bool(value) => (value.to_string(), Some(format!("{}boolean", XSD_IRI))),
+        Value::Number(number) => {
+            let datatype = if number.is_f64() { "double" } else { "integer" };
+            (number.to_string(), Some(format!("{}{}", XSD_IRI, datatype)))
+        }
+        Value::Object(object) if object.contains_key("@value") => {
+            let value = match &object["@value"] {
+                Value::String(value) => value.to_string(),
+                Value::Null => return Err(invalid_document("'@value' cannot be null")),
+                value => value.to_string(),
+            };
+            let datatype = match object.get("@type").and_then(Value::as_str) {
+                Some(datatype) => Some(expand_iri(datatype, terms)?),
+                None => None,
+            };
+            (value, datatype)
+        }
+        Value::Object(object) if object.contains_key("@list") || object.contains_key("@set") => {
+            return Err(invalid_document("lists and sets are not supported"))
+        }
+        Value::Object(object) if object.len() == 1 && object.contains_key("@id") => {
+            let iri = object["@id"]
+                .as_str()
+                .ok_or_else(|| invalid_document("'@id' must be a string"))?;
+            return Ok(RdfObject::Iri(iri.to_string()));
+        }
+        Value::Object(_) => return Ok(RdfObject::Node(parse_node(value, terms)?)),
+        _ => return Err(invalid_document("unsupported value")),
+    };
+
+    Ok(RdfObject::Literal { value, datatype })
+}
+
+// Expand a term or a compact IRI, anything else is taken as an absolute IRI
+fn expand_iri(iri: &str, terms: &BTreeMap<String, TermDefinition>) -> Result<String> {
+    if let Some(definition) = terms.get(iri) {
+        return Ok(definition.iri.clone());
+    }
+    match iri.find(':') {
+        Some(index) if !iri[index + 1..].starts_with("//") => {
+            let (prefix, name) = (&iri[..index], &iri[index + 1..]);
+            match terms.get(prefix) {
+                Some(definition) => Ok(format!("{}{}", definition.iri, name)),
+                // it's an absolute IRI with a scheme, e.g. 'urn:'
+                None => Ok(iri.to_string()),
+            }
+        }
+        Some(_) => Ok(iri.to_string()),
+        None => Err(invalid_document(&format!(
+            "'{}' is neither a term, a compact IRI, nor an absolute IRI",
+            iri
+        ))),
+    }
+}
+
+fn as_array(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(values) => values.iter().collect(),
+        value => vec![value],
+    }
+}
+
+fn invalid_document(msg: &str) -> Error {
+    Error::InvalidInput(format!("Invalid JSON-LD document: {}", msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::rdf::{vocab_iri, SAFE_KEYS_IRI};
+    use anyhow::Result;
+
+    #[test]
+    fn test_json_ld_roundtrip() -> Result<()> {
+        let nodes = vec![RdfNode {
+            id: Some("/a%20b.txt".to_string()),
+            types: vec![vocab_iri("File")],
+            properties: vec![
+                (
+                    vocab_iri("link"),
+                    RdfObject::Iri("safe://hbyyyyx".to_string()),
+                ),
+                (
+                    vocab_iri("size"),
+                    RdfObject::Literal {
+                        value: "10".to_string(),
+                        datatype: Some(format!("{}integer", XSD_IRI)),
+                    },
+                ),
+                (
+                    format!("{}custom:tag", SAFE_KEYS_IRI),
+                    RdfObject::Node(RdfNode {
+                        id: None,
+                        types: vec![],
+                        properties: vec![(
+                            vocab_iri("name"),
+                            RdfObject::Literal {
+                                value: "nested".to_string(),
+                                datatype: None,
+                            },
+                        )],
+                    }),
+                ),
+            ],
+        }];
+
+        let document = serialise(&nodes, "safe://hnyynyss")?;
+        let value: Value = serde_json::from_str(&document)?;
+        assert_eq!(value["@context"]["@base"], "safe://hnyynyss");
+        assert_eq!(value["@graph"][0]["@type"], "safe:File");
+        assert_eq!(value["@graph"][0]["safe:link"]["@id"], "safe://hbyyyyx");
+
+        assert_eq!(parse(&document)?, nodes);
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_ld_parse_terms() -> Result<()> {
+        let document = r#"{
+            "@context": {
+                "s": "https://safenetwork.tech/vocab/v1#",
+                "size": { "@id": "s:size", "@type": "http://www.w3.org/2001/XMLSchema#integer" },
+                "link": { "@id": "s:link", "@type": "@id" }
+            },
+            "@id": "safe://hnyynyss/a.txt",
+            "@type": "s:File",
+            "link": "safe://hbyyyyx",
+            "size": "10",
+            "s:readonly": true
+        }"#;
+
+        let nodes = parse(document)?;
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].id.as_deref(), Some("safe://hnyynyss/a.txt"));
+        assert!(nodes[0].has_type("File"));
+        assert_eq!(
+            nodes[0].objects("link").collect::<Vec<_>>(),
+            [&RdfObject::Iri("safe://hbyyyyx".to_string())]
+        );
+        assert_eq!(
+            nodes[0].objects("readonly").collect::<Vec<_>>(),
+            [&RdfObject::Literal {
+                value: "true".to_string(),
+                datatype: Some(format!("{}boolean", XSD_IRI))
+            }]
+        );
+
+        assert!(parse("\"text\"").is_err());
+        assert!(parse(r#"{ "@context": "https://schema.org", "@id": "a" }"#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_ld_parse_embedded_contexts_and_blank_nodes() -> Result<()> {
+        let document = r#"{
+            "@context": { "s": "https://safenetwork.tech/vocab/v1#" },
+            "@graph": [
+                {
+                    "@id": "safe://hnyynyss",
+                    "s:subName": [{ "@id": "_:b0" }, { "@id": "_:b1", "s:name": "labelled" }]
+                },
+                {
+                    "@context": { "name": { "@id": "s:name" } },
+                    "@id": "_:b0",
+                    "name": "embedded",
+                    "s:definition": { "name": "nested" }
+                },
+                { "@id": "_:b1", "@type": "s:SubName" }
+            ]
+        }"#;
+
+        let nodes = parse(document)?;
+        assert_eq!(nodes.len(), 1);
+        let sub_names: Vec<&RdfObject> = nodes[0].objects("subName").collect();
+        let name = |object: &RdfObject| match object {
+            RdfObject::Node(node) => node.objects("name").cloned().collect::<Vec<_>>(),
+            _ => vec![],
+        };
+        let literal = |value: &str| RdfObject::Literal {
+            value: value.to_string(),
+            datatype: None,
+        };
+        assert_eq!(name(sub_names[0]), [literal("embedded")]);
+        assert_eq!(name(sub_names[1]), [literal("labelled")]);
+        assert!(matches!(sub_names[1], RdfObject::Node(node) if node.has_type("SubName")));
+
+        // the embedded context applies to the nested nodes too
+        let definition = match sub_names[0] {
+            RdfObject::Node(node) => node.objects("definition").next().cloned(),
+            _ => None,
+        };
+        assert_eq!(definition.as_ref().map(name), Some(vec![literal("nested")]));
+
+        // but not to the other nodes
+        let document = r#"[
+            { "@context": { "name": "https://safenetwork.tech/vocab/v1#name" }, "@id": "a" },
+            { "@id": "b", "name": "c" }
+        ]"#;
+        assert!(parse(document).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,416 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+mod json_ld;
+mod turtle;
+
+use crate::{
+    app::{
+        consts::*,
+        files::{SYMLINK_TARGET, SYMLINK_TARGET_TYPE},
+    },
+    Error, Result,
+};
+use chrono::DateTime;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// IRI of the vocabulary used to describe FilesMaps and NrsMaps as linked data
+pub const SAFE_VOCAB_IRI: &str = "https://safenetwork.tech/vocab/v1#";
+
+/// IRI of the namespace any property not part of the vocabulary, e.g. custom metadata,
+/// is mapped onto. The name of the property is appended to it percent-encoded.
+pub const SAFE_KEYS_IRI: &str = "https://safenetwork.tech/vocab/v1/keys#";
+
+pub(crate) const RDF_TYPE_IRI: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+pub(crate) const XSD_IRI: &str = "http://www.w3.org/2001/XMLSchema#";
+
+// Prefix of the identifiers of labelled blank nodes, e.g. `_:b0`
+const BLANK_NODE_PREFIX: &str = "_:";
+
+// Prefixes used by the documents generated
+const PREFIXES: &[(&str, &str)] = &[
+    ("safe", SAFE_VOCAB_IRI),
+    ("safekey", SAFE_KEYS_IRI),
+    ("xsd", XSD_IRI),
+];
+
+// Terms of the vocabulary each of the well-known properties is mapped onto
+const PROPERTY_TERMS: &[(&str, &str)] = &[
+    (PREDICATE_LINK, "link"),
+    (PREDICATE_TYPE, "mediaType"),
+    (PREDICATE_SIZE, "size"),
+    (PREDICATE_CREATED, "created"),
+    (PREDICATE_MODIFIED, "modified"),
+    (PREDICATE_ORIGINAL_CREATED, "originalCreated"),
+    (PREDICATE_ORIGINAL_MODIFIED, "originalModified"),
+    (PREDICATE_READONLY, "readonly"),
+    (PREDICATE_MODE_BITS, "modeBits"),
+    (PREDICATE_CHECKSUM, "checksum"),
+    (SYMLINK_TARGET, "symlinkTarget"),
+    (SYMLINK_TARGET_TYPE, "symlinkTargetType"),
+];
+
+/// Linked data formats FilesMaps and NrsMaps can be exported to and imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RdfFormat {
+    /// JSON-LD, see https://www.w3.org/TR/json-ld11/
+    JsonLd,
+    /// Turtle, see https://www.w3.org/TR/turtle/
+    Turtle,
+}
+
+// A resource described by a set of properties, which is either identified by an
+// IRI (relative to the base of the document) or a blank node nested in another one
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct RdfNode {
+    pub(crate) id: Option<String>,
+    pub(crate) types: Vec<String>,
+    pub(crate) properties: Vec<(String, RdfObject)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum RdfObject {
+    Iri(String),
+    Literal {
+        value: String,
+        datatype: Option<String>,
+    },
+    Node(RdfNode),
+}
+
+impl RdfNode {
+    pub(crate) fn has_type(&self, term: &str) -> bool {
+        self.types.iter().any(|iri| *iri == vocab_iri(term))
+    }
+
+    // Objects of the properties which are the vocabulary term provided
+    pub(crate) fn objects<'a>(&'a self, term: &str) -> impl Iterator<Item = &'a RdfObject> {
+        let predicate = vocab_iri(term);
+        self.properties
+            .iter()
+            .filter(move |(iri, _)| *iri == predicate)
+            .map(|(_, object)| object)
+    }
+}
+
+// Serialise the nodes in the format requested, their IRIs are relative to `base_url`
+pub(crate) fn serialise(nodes: &[RdfNode], base_url: &str, format: RdfFormat) -> Result<String> {
+    match format {
+        RdfFormat::JsonLd => json_ld::serialise(nodes, base_url),
+        RdfFormat::Turtle => Ok(turtle::serialise(nodes, base_url)),
+    }
+}
+
+// Parse the nodes described by a document in the format provided
+pub(crate) fn parse(data: &str, format: RdfFormat) -> Result<Vec<RdfNode>> {
+    match format {
+        RdfFormat::JsonLd => json_ld::parse(data),
+        RdfFormat::Turtle => turtle::parse(data),
+    }
+}
+
+pub(crate) fn is_blank_node_id(id: &str) -> bool {
+    id.starts_with(BLANK_NODE_PREFIX)
+}
+
+// Nest the labelled blank nodes, e.g. `_:b0`, where they are referenced from, either
+// as an IRI or as a node with that identifier, as other tools like rdflib or Jena describe
+// nested resources with them. Labelled blank nodes which are not referenced from any other
+// node are kept as top-level anonymous nodes.
+pub(crate) fn embed_blank_nodes(nodes: Vec<RdfNode>) -> Result<Vec<RdfNode>> {
+    // statements about each labelled blank node, merged if there are several
+    let mut labelled = BTreeMap::<String, RdfNode>::new();
+    let mut top_level = vec![];
+    for node in nodes {
+        match node.id.clone().filter(|id| is_blank_node_id(id)) {
+            Some(label) => match labelled.get_mut(&label) {
+                Some(existing) => {
+                    existing.types.extend(node.types);
+                    existing.properties.extend(node.properties);
+                }
+                None => {
+                    top_level.push(RdfNode {
+                        id: Some(label.clone()),
+                        ..RdfNode::default()
+                    });
+                    let _ = labelled.insert(label, node);
+                }
+            },
+            None => top_level.push(node),
+        }
+    }
+
+    let mut referenced = BTreeSet::new();
+    for node in labelled.values() {
+        collect_blank_node_references(node, &mut referenced);
+    }
+    for node in top_level.iter() {
+        collect_blank_node_references(node, &mut referenced);
+    }
+    top_level.retain(|node| node.id.as_ref().map_or(true, |id| !referenced.contains(id)));
+
+    let mut embedded = BTreeSet::new();
+    let nodes = top_level
+        .into_iter()
+        .map(|node| embed_blank_node(node, &labelled, &mut vec![], &mut embedded))
+        .collect::<Result<Vec<_>>>()?;
+
+    // the nodes only referenced from each other are not reachable from any top-level one
+    if labelled.keys().any(|label| !embedded.contains(label)) {
+        return Err(Error::InvalidInput(
+            "Blank nodes cannot reference themselves, neither directly nor indirectly".to_string(),
+        ));
+    }
+
+    Ok(nodes)
+}
+
+// Labels of the blank nodes referenced from the properties of a node, or of its nested ones
+fn collect_blank_node_references(node: &RdfNode, referenced: &mut BTreeSet<String>) {
+    for (_, object) in node.properties.iter() {
+        match object {
+            RdfObject::Iri(id) if is_blank_node_id(id) => {
+                let _ = referenced.insert(id.to_string());
+            }
+            RdfObject::Node(nested) => {
+                if let Some(id) = nested.id.as_ref().filter(|id| is_blank_node_id(id)) {
+                    let _ = referenced.insert(id.to_string());
+                }
+                collect_blank_node_references(nested, referenced);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn embed_blank_node(
+    mut node: RdfNode,
+    labelled: &BTreeMap<String, RdfNode>,
+    ancestors: &mut Vec<String>,
+    embedded: &mut BTreeSet<String>,
+) -> Result<RdfNode> {
+    let label = node.id.clone().filter(|id| is_blank_node_id(id));
+    if let Some(label) = &label {
+        if ancestors.contains(label) {
+            return Err(Error::InvalidInput(format!(
+                "Blank node '{}' cannot reference itself, neither directly nor indirectly",
+                label
+            )));
+        }
+        if let Some(statements) = labelled.get(label) {
+            node.types.extend(statements.types.iter().cloned());
+            node.properties
+                .extend(statements.properties.iter().cloned());
+        }
+        let _ = embedded.insert(label.to_string());
+        ancestors.push(label.to_string());
+        node.id = None;
+    }
+
+    let mut properties = Vec::with_capacity(node.properties.len());
+    for (predicate, object) in node.properties {
+        let object = match object {
+            RdfObject::Iri(id) if is_blank_node_id(&id) => {
+                let reference = RdfNode {
+                    id: Some(id),
+                    ..RdfNode::default()
+                };
+                RdfObject::Node(embed_blank_node(reference, labelled, ancestors, embedded)?)
+            }
+            RdfObject::Node(nested) => {
+                RdfObject::Node(embed_blank_node(nested, labelled, ancestors, embedded)?)
+            }
+            object => object,
+        };
+        properties.push((predicate, object));
+    }
+    node.properties = properties;
+
+    if label.is_some() {
+        let _ = ancestors.pop();
+    }
+    Ok(node)
+}
+
+pub(crate) fn vocab_iri(term: &str) -> String {
+    format!("{}{}", SAFE_VOCAB_IRI, term)
+}
+
+// Properties describing the key/value pairs of a FileItem or an NRS definition
+pub(crate) fn properties_from_map(map: &BTreeMap<String, String>) -> Vec<(String, RdfObject)> {
+    map.iter()
+        .map(|(key, value)| {
+            let predicate = match PROPERTY_TERMS.iter().find(|(k, _)| k == key) {
+                Some((_, term)) => vocab_iri(term),
+                None => format!("{}{}", SAFE_KEYS_IRI, encode_iri_chars(key)),
+            };
+            (predicate, object_from_value(key, value))
+        })
+        .collect()
+}
+
+// Key/value pairs of a FileItem or an NRS definition, any property
+// which doesn't belong to our vocabulary, e.g. added by other tools, is ignored
+pub(crate) fn map_from_properties(
+    properties: &[(String, RdfObject)],
+) -> Result<BTreeMap<String, String>> {
+    let mut map = BTreeMap::new();
+    for (predicate, object) in properties.iter() {
+        let key = if let Some(term) = predicate.strip_prefix(SAFE_VOCAB_IRI) {
+            match PROPERTY_TERMS.iter().find(|(_, t)| *t == term) {
+                Some((key, _)) => key.to_string(),
+                None => continue,
+            }
+        } else if let Some(name) = predicate.strip_prefix(SAFE_KEYS_IRI) {
+            decode_iri_chars(name)?
+        } else {
+            continue;
+        };
+
+        let value = match object {
+            RdfObject::Iri(value) | RdfObject::Literal { value, .. } => value.to_string(),
+            RdfObject::Node(_) => {
+                return Err(Error::InvalidInput(format!(
+                    "The value of property <{}> is expected to be a literal or an IRI",
+                    predicate
+                )))
+            }
+        };
+        if map.insert(key, value).is_some() {
+            return Err(Error::InvalidInput(format!(
+                "Property <{}> is expected to have a single value",
+                predicate
+            )));
+        }
+    }
+
+    Ok(map)
+}
+
+// Values are typed as long as they are valid for the datatype of the property,
+// otherwise they are plain strings so they are never lost nor altered
+fn object_from_value(key: &str, value: &str) -> RdfObject {
+    let datatype = match key {
+        PREDICATE_LINK if value.starts_with("safe://") && !value.contains(char::is_whitespace) => {
+            return RdfObject::Iri(value.to_string())
+        }
+        PREDICATE_SIZE | PREDICATE_MODE_BITS if value.parse::<u64>().is_ok() => Some("integer"),
+        PREDICATE_READONLY if value == "true" || value == "false" => Some("boolean"),
+        PREDICATE_CREATED
+        | PREDICATE_MODIFIED
+        | PREDICATE_ORIGINAL_CREATED
+        | PREDICATE_ORIGINAL_MODIFIED
+            if DateTime::parse_from_rfc3339(value).is_ok() =>
+        {
+            Some("dateTime")
+        }
+        _ => None,
+    };
+
+    RdfObject::Literal {
+        value: value.to_string(),
+        datatype: datatype.map(|datatype| format!("{}{}", XSD_IRI, datatype)),
+    }
+}
+
+// Percent-encode the characters which are not allowed in IRIs, as well as those
+// with a special meaning in them, e.g. '#', so any path or name can be made an IRI
+pub(crate) fn encode_iri_chars(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for c in text.chars() {
+        let is_allowed = c.is_ascii_alphanumeric()
+            || "-._~/!$&'()*+,;=:@".contains(c)
+            || (!c.is_ascii() && !c.is_control() && !c.is_whitespace());
+        if is_allowed {
+            encoded.push(c);
+        } else {
+            let mut buffer = [0; 4];
+            for byte in c.encode_utf8(&mut buffer).bytes() {
+                encoded.push_str(&format!("%{:02X}", byte));
+            }
+        }
+    }
+
+    encoded
+}
+
+pub(crate) fn decode_iri_chars(text: &str) -> Result<String> {
+    urlencoding::decode(text)
+        .map(|decoded| decoded.to_string())
+        .map_err(|err| {
+            Error::InvalidInput(format!("Invalid percent-encoding in '{}': {}", text, err))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_properties_roundtrip() -> Result<()> {
+        let mut map = BTreeMap::new();
+        map.insert(PREDICATE_LINK.to_string(), "safe://hbyyyyx".to_string());
+        map.insert(PREDICATE_SIZE.to_string(), "".to_string());
+        map.insert(PREDICATE_MODE_BITS.to_string(), "420".to_string());
+        map.insert(
+            PREDICATE_CREATED.to_string(),
+            "2021-03-10T10:00:00Z".to_string(),
+        );
+        map.insert("custom:tags #1".to_string(), "a, b".to_string());
+
+        let properties = properties_from_map(&map);
+        assert!(properties.contains(&(
+            vocab_iri("link"),
+            RdfObject::Iri("safe://hbyyyyx".to_string())
+        )));
+        assert!(properties.contains(&(
+            vocab_iri("size"),
+            RdfObject::Literal {
+                value: "".to_string(),
+                datatype: None
+            }
+        )));
+        assert!(properties.contains(&(
+            vocab_iri("modeBits"),
+            RdfObject::Literal {
+                value: "420".to_string(),
+                datatype: Some(format!("{}integer", XSD_IRI))
+            }
+        )));
+        assert!(properties
+            .iter()
+            .any(|(iri, _)| iri == &format!("{}custom:tags%20%231", SAFE_KEYS_IRI)));
+        assert_eq!(map_from_properties(&properties)?, map);
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_from_properties_ignores_foreign_properties() -> Result<()> {
+        let properties = vec![
+            (
+                "http://purl.org/dc/terms/title".to_string(),
+                RdfObject::Literal {
+                    value: "title".to_string(),
+                    datatype: None,
+                },
+            ),
+            (
+                vocab_iri("checksum"),
+                RdfObject::Literal {
+                    value: "sha3-256:00".to_string(),
+                    datatype: None,
+                },
+            ),
+        ];
+        let map = map_from_properties(&properties)?;
+        assert_eq!(map.len(), 1);
+        assert_eq!(map[PREDICATE_CHECKSUM], "sha3-256:00");
+        Ok(())
+    }
+}
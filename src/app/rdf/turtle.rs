@@ -0,0 +1,742 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    embed_blank_nodes, RdfNode, RdfObject, BLANK_NODE_PREFIX, PREFIXES, RDF_TYPE_IRI, XSD_IRI,
+};
+use crate::{Error, Result};
+use std::collections::BTreeMap;
+
+const INDENT: &str = "    ";
+
+pub(super) fn serialise(nodes: &[RdfNode], base_url: &str) -> String {
+    let mut turtle = String::new();
+    if !base_url.is_empty() {
+        turtle.push_str(&format!("@base <{}> .\n", escape_iri(base_url)));
+    }
+    for (prefix, iri) in PREFIXES.iter() {
+        turtle.push_str(&format!("@prefix {}: <{}> .\n", prefix, iri));
+    }
+
+    for node in nodes.iter() {
+        let statements = node_statements(node, 1);
+        let subject = match &node.id {
+            Some(id) => format!("<{}>", escape_iri(id)),
+            None => "[]".to_string(),
+        };
+        if statements.is_empty() && node.id.is_some() {
+            // there is nothing to say about it
+            continue;
+        }
+        turtle.push_str(&format!(
+            "\n{} {} .\n",
+            subject,
+            statements.join(&format!(" ;\n{}", INDENT))
+        ));
+    }
+
+    turtle
+}
+
+// Predicate/object pairs describing a node, nested nodes are indented by `depth`
+fn node_statements(node: &RdfNode, depth: usize) -> Vec<String> {
+    let mut statements = vec![];
+    if !node.types.is_empty() {
+        let types: Vec<String> = node.types.iter().map(|iri| format_iri(iri)).collect();
+        statements.push(format!("a {}", types.join(", ")));
+    }
+
+    for (predicate, object) in node.properties.iter() {
+        let object = match object {
+            RdfObject::Iri(iri) => format!("<{}>", escape_iri(iri)),
+            RdfObject::Literal { value, datatype } => {
+                let literal = format!("\"{}\"", escape_string(value));
+                match datatype {
+                    Some(datatype) => format!("{}^^{}", literal, format_iri(datatype)),
+                    None => literal,
+                }
+            }
+            RdfObject::Node(node) => {
+                let statements = node_statements(node, depth + 1);
+                if statements.is_empty() {
+                    "[]".to_string()
+                } else {
+                    let indent = INDENT.repeat(depth + 1);
+                    format!(
+                        "[\n{}{}\n{}]",
+                        indent,
+                        statements.join(&format!(" ;\n{}", indent)),
+                        INDENT.repeat(depth)
+                    )
+                }
+            }
+        };
+        statements.push(format!("{} {}", format_iri(predicate), object));
+    }
+
+    statements
+}
+
+// Write an IRI as a prefixed name whenever possible
+fn format_iri(iri: &str) -> String {
+    for (prefix, namespace) in PREFIXES.iter() {
+        if let Some(name) = iri.strip_prefix(namespace) {
+            let is_simple_name = name.starts_with(|c: char| c.is_ascii_alphabetic())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if is_simple_name {
+                return format!("{}:{}", prefix, name);
+            }
+        }
+    }
+    format!("<{}>", escape_iri(iri))
+}
+
+fn escape_iri(iri: &str) -> String {
+    iri.chars()
+        .map(|c| {
+            if c <= ' ' || "<>\"{}|^`\\".contains(c) {
+                format!("\\u{:04X}", c as u32)
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
+fn escape_string(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\\' => "\\\\".to_string(),
+            '"' => "\\\"".to_string(),
+            '\n' => "\\n".to_string(),
+            '\r' => "\\r".to_string(),
+            '\t' => "\\t".to_string(),
+            c if c.is_control() => format!("\\u{:04X}", c as u32),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+// Parse a Turtle document. Nested resources can be described either with blank node
+// property lists, i.e. `[ ... ]`, or with labelled blank nodes, e.g. `_:b0`, whilst
+// collections are not supported.
+pub(super) fn parse(data: &str) -> Result<Vec<RdfNode>> {
+    let mut parser = Parser {
+        chars: data.chars().collect(),
+        pos: 0,
+        prefixes: BTreeMap::new(),
+    };
+
+    let mut nodes: Vec<RdfNode> = vec![];
+    // position of the nodes with an IRI, so all statements about them are merged
+    let mut nodes_index: BTreeMap<String, usize> = BTreeMap::new();
+    loop {
+        parser.skip_whitespace();
+        if parser.peek().is_none() {
+            break;
+        }
+        if parser.parse_directive()? {
+            continue;
+        }
+
+        let node = parser.parse_triples()?;
+        match node.id.as_ref().and_then(|id| nodes_index.get(id)).copied() {
+            Some(index) => {
+                let existing = &mut nodes[index];
+                existing.types.extend(node.types);
+                existing.properties.extend(node.properties);
+            }
+            None => {
+                if let Some(id) = &node.id {
+                    let _ = nodes_index.insert(id.to_string(), nodes.len());
+                }
+                nodes.push(node);
+            }
+        }
+    }
+
+    embed_blank_nodes(nodes)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    prefixes: BTreeMap<String, String>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.pos += 1;
+        c
+    }
+
+    // Check if the next characters are the keyword provided, case-insensitively,
+    // followed by a whitespace, consuming them if so
+    fn consume_keyword(&mut self, keyword: &str) -> bool {
+        let len = keyword.chars().count();
+        let is_keyword = self.chars.len() > self.pos + len
+            && self.chars[self.pos..self.pos + len]
+                .iter()
+                .collect::<String>()
+                .eq_ignore_ascii_case(keyword)
+            && self.chars[self.pos + len].is_whitespace();
+        if is_keyword {
+            self.pos += len;
+        }
+        is_keyword
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        self.skip_whitespace();
+        match self.bump() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(self.error(&format!("expected '{}' but found '{}'", expected, c))),
+            None => Err(self.error(&format!("expected '{}'", expected))),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == '#' {
+                while !matches!(self.bump(), Some('\n') | None) {}
+            } else if c.is_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn error(&self, msg: &str) -> Error {
+        let pos = self.pos.min(self.chars.len());
+        let line = self.chars[..pos].iter().filter(|c| **c == '\n').count() + 1;
+        Error::InvalidInput(format!("Invalid Turtle document, line {}: {}", line, msg))
+    }
+
+    // Parse a prefix or base directive, if there is one next. The base is not kept
+    // since relative IRIs are resolved by the caller, which knows what they refer to.
+    fn parse_directive(&mut self) -> Result<bool> {
+        let (is_prefix, is_sparql) = if self.consume_keyword("@prefix") {
+            (true, false)
+        } else if self.consume_keyword("@base") {
+            (false, false)
+        } else if self.consume_keyword("PREFIX") {
+            (true, true)
+        } else if self.consume_keyword("BASE") {
+            (false, true)
+        } else {
+            return Ok(false);
+        };
+
+        self.skip_whitespace();
+        if is_prefix {
+            let mut prefix = String::new();
+            while let Some(c) = self.peek() {
+                if c == ':' {
+                    break;
+                } else if c.is_alphanumeric() || c == '_' || c == '-' || c == '.' {
+                    prefix.push(c);
+                    self.pos += 1;
+                } else {
+                    return Err(self.error(&format!("invalid prefix name '{}{}'", prefix, c)));
+                }
+            }
+            self.expect(':')?;
+            self.skip_whitespace();
+            let iri = self.parse_iriref()?;
+            let _ = self.prefixes.insert(prefix, iri);
+        } else {
+            let _ = self.parse_iriref()?;
+        }
+
+        if !is_sparql {
+            self.expect('.')?;
+        }
+        Ok(true)
+    }
+
+    fn parse_triples(&mut self) -> Result<RdfNode> {
+        let mut node = RdfNode::default();
+        match self.peek() {
+            Some('[') => {
+                node = self.parse_blank_node()?;
+                self.skip_whitespace();
+                if self.peek() != Some('.') {
+                    self.parse_predicate_object_list(&mut node)?;
+                }
+            }
+            Some('_') if self.peek_at(1) == Some(':') => {
+                node.id = Some(self.parse_blank_node_label()?);
+                self.parse_predicate_object_list(&mut node)?;
+            }
+            _ => {
+                node.id = Some(self.parse_iri()?);
+                self.parse_predicate_object_list(&mut node)?;
+            }
+        }
+
+        self.expect('.')?;
+        Ok(node)
+    }
+
+    fn parse_predicate_object_list(&mut self, node: &mut RdfNode) -> Result<()> {
+        loop {
+            self.skip_whitespace();
+            let is_type = self.peek() == Some('a')
+                && self.peek_at(1).map_or(false, |c| {
+                    c.is_whitespace() || c == '<' || c == '[' || c == '"'
+                });
+            let predicate = if is_type {
+                self.pos += 1;
+                RDF_TYPE_IRI.to_string()
+            } else {
+                self.parse_iri()?
+            };
+
+            loop {
+                self.skip_whitespace();
+                let object = self.parse_object()?;
+                match object {
+                    RdfObject::Iri(iri) if predicate == RDF_TYPE_IRI => node.types.push(iri),
+                    object => node.properties.push((predicate.clone(), object)),
+                }
+                self.skip_whitespace();
+                if self.peek() == Some(',') {
+                    self.pos += 1;
+                } else {
+                    break;
+                }
+            }
+
+            if self.peek() != Some(';') {
+                return Ok(());
+            }
+            while self.peek() == Some(';') {
+                self.pos += 1;
+                self.skip_whitespace();
+            }
+            if matches!(self.peek(), Some('.') | Some(']') | None) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<RdfObject> {
+        for value in &["true", "false"] {
+            if self.consume_boolean(value) {
+                return Ok(RdfObject::Literal {
+                    value: value.to_string(),
+                    datatype: Some(format!("{}boolean", XSD_IRI)),
+                });
+            }
+        }
+
+        match self.peek() {
+            Some('[') => Ok(RdfObject::Node(self.parse_blank_node()?)),
+            // it's embedded where it's referenced from once the whole document is parsed
+            Some('_') if self.peek_at(1) == Some(':') => {
+                Ok(RdfObject::Iri(self.parse_blank_node_label()?))
+            }
+            Some('"') | Some('\'') => {
+                let value = self.parse_string()?;
+                let datatype = match self.peek() {
+                    Some('^') if self.peek_at(1) == Some('^') => {
+                        self.pos += 2;
+                        Some(self.parse_iri()?)
+                    }
+                    Some('@') => {
+                        // language tags are not relevant to us
+                        self.pos += 1;
+                        while self
+                            .peek()
+                            .map_or(false, |c| c.is_ascii_alphanumeric() || c == '-')
+                        {
+                            self.pos += 1;
+                        }
+                        None
+                    }
+                    _ => None,
+                };
+                Ok(RdfObject::Literal { value, datatype })
+            }
+            Some(c) if c.is_ascii_digit() || c == '+' || c == '-' || c == '.' => {
+                self.parse_number()
+            }
+            _ => Ok(RdfObject::Iri(self.parse_iri()?)),
+        }
+    }
+
+    fn consume_boolean(&mut self, word: &str) -> bool {
+        let len = word.len();
+        let is_word = self.chars.len() >= self.pos + len
+            && self.chars[self.pos..self.pos + len]
+                .iter()
+                .collect::<String>()
+                == word
+            && !self
+                .chars
+                .get(self.pos + len)
+                .map_or(false, |c| c.is_alphanumeric() || *c == ':' || *c == '_');
+        if is_word {
+            self.pos += len;
+        }
+        is_word
+    }
+
+    fn parse_blank_node(&mut self) -> Result<RdfNode> {
+        self.expect('[')?;
+        let mut node = RdfNode::default();
+        self.skip_whitespace();
+        if self.peek() != Some(']') {
+            self.parse_predicate_object_list(&mut node)?;
+        }
+        self.expect(']')?;
+        Ok(node)
+    }
+
+    // Parse the label of a blank node, e.g. `_:b0`, returning it along with its prefix
+    fn parse_blank_node_label(&mut self) -> Result<String> {
+        self.pos += 2;
+        let mut label = BLANK_NODE_PREFIX.to_string();
+        while let Some(c) = self.peek() {
+            if c.is_alphanumeric() || c == '_' || c == '-' || c == '.' {
+                label.push(c);
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        // a trailing dot ends the statement rather than being part of the label
+        while label.ends_with('.') {
+            let _ = label.pop();
+            self.pos -= 1;
+        }
+
+        if label.len() == BLANK_NODE_PREFIX.len() {
+            Err(self.error("expected a blank node label"))
+        } else {
+            Ok(label)
+        }
+    }
+
+    // Parse either an IRI reference or a prefixed name, returning the IRI
+    fn parse_iri(&mut self) -> Result<String> {
+        match self.peek() {
+            Some('<') => self.parse_iriref(),
+            Some('_') if self.peek_at(1) == Some(':') => {
+                Err(self.error("blank nodes can only be subjects or objects"))
+            }
+            Some('(') => Err(self.error("collections are not supported")),
+            Some(_) => self.parse_prefixed_name(),
+            None => Err(self.error("unexpected end of document")),
+        }
+    }
+
+    fn parse_iriref(&mut self) -> Result<String> {
+        self.expect('<')?;
+        let mut iri = String::new();
+        loop {
+            match self.bump() {
+                Some('>') => return Ok(iri),
+                Some('\\') => iri.push(self.parse_unicode_escape()?),
+                Some(c) if c.is_whitespace() => {
+                    return Err(self.error("IRIs cannot contain whitespaces"))
+                }
+                Some(c) => iri.push(c),
+                None => return Err(self.error("unterminated IRI")),
+            }
+        }
+    }
+
+    fn parse_prefixed_name(&mut self) -> Result<String> {
+        let start = self.pos;
+        let mut prefix = String::new();
+        while let Some(c) = self.peek() {
+            if c == ':' {
+                break;
+            } else if c.is_alphanumeric() || c == '_' || c == '-' || c == '.' {
+                prefix.push(c);
+                self.pos += 1;
+            } else {
+                return Err(self.error(&format!("unexpected character '{}'", c)));
+            }
+        }
+        if self.bump() != Some(':') {
+            self.pos = start;
+            return Err(self.error("expected an IRI or a prefixed name"));
+        }
+
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_alphanumeric() || "_-:.%".contains(c) {
+                name.push(c);
+                self.pos += 1;
+            } else if c == '\\' {
+                // escaped reserved character
+                self.pos += 1;
+                name.push(self.bump().ok_or_else(|| self.error("unterminated name"))?);
+            } else {
+                break;
+            }
+        }
+        // a trailing dot ends the statement rather than being part of the name
+        while name.ends_with('.') {
+            let _ = name.pop();
+            self.pos -= 1;
+        }
+
+        match self.prefixes.get(&prefix) {
+            Some(namespace) => Ok(format!("{}{}", namespace, name)),
+            None => Err(self.error(&format!("undefined prefix '{}'", prefix))),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String> {
+        let quote = self.bump().ok_or_else(|| self.error("expected a string"))?;
+        let is_long = self.peek() == Some(quote) && self.peek_at(1) == Some(quote);
+        if is_long {
+            self.pos += 2;
+        }
+
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                Some(c) if c == quote => {
+                    if !is_long {
+                        return Ok(value);
+                    }
+                    if self.peek() == Some(quote) && self.peek_at(1) == Some(quote) {
+                        self.pos += 2;
+                        return Ok(value);
+                    }
+                    value.push(c);
+                }
+                Some('\\') => {
+                    let escaped = match self.peek() {
+                        Some('t') => '\t',
+                        Some('b') => '\u{8}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('f') => '\u{c}',
+                        Some(c @ '"') | Some(c @ '\'') | Some(c @ '\\') => c,
+                        _ => {
+                            value.push(self.parse_unicode_escape()?);
+                            continue;
+                        }
+                    };
+                    self.pos += 1;
+                    value.push(escaped);
+                }
+                Some('\n') | Some('\r') if !is_long => {
+                    return Err(self.error("unterminated string"))
+                }
+                Some(c) => value.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    // Parse the \uXXXX or \UXXXXXXXX escape sequence following a backslash
+    fn parse_unicode_escape(&mut self) -> Result<char> {
+        let len = match self.bump() {
+            Some('u') => 4,
+            Some('U') => 8,
+            _ => return Err(self.error("invalid escape sequence")),
+        };
+        let hex: String = (0..len).filter_map(|_| self.bump()).collect();
+        u32::from_str_radix(&hex, 16)
+            .ok()
+            .and_then(std::char::from_u32)
+            .ok_or_else(|| self.error(&format!("invalid escape sequence '{}'", hex)))
+    }
+
+    fn parse_number(&mut self) -> Result<RdfObject> {
+        let mut number = String::new();
+        let mut datatype = "integer";
+        if let Some(sign @ '+') | Some(sign @ '-') = self.peek() {
+            number.push(sign);
+            self.pos += 1;
+        }
+        while let Some(c) = self.peek() {
+            let is_decimal_point = c == '.'
+                && datatype == "integer"
+                && self.peek_at(1).map_or(false, |c| c.is_ascii_digit());
+            if c.is_ascii_digit() {
+                number.push(c);
+            } else if is_decimal_point {
+                number.push(c);
+                datatype = "decimal";
+            } else if (c == 'e' || c == 'E') && datatype != "double" {
+                number.push(c);
+                datatype = "double";
+                if let Some(sign @ '+') | Some(sign @ '-') = self.peek_at(1) {
+                    number.push(sign);
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+            self.pos += 1;
+        }
+
+        if !number.chars().any(|c| c.is_ascii_digit()) {
+            return Err(self.error(&format!("invalid number '{}'", number)));
+        }
+        Ok(RdfObject::Literal {
+            value: number,
+            datatype: Some(format!("{}{}", XSD_IRI, datatype)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::rdf::{vocab_iri, SAFE_VOCAB_IRI};
+    use anyhow::Result;
+
+    fn literal(value: &str, datatype: Option<&str>) -> RdfObject {
+        RdfObject::Literal {
+            value: value.to_string(),
+            datatype: datatype.map(|datatype| format!("{}{}", XSD_IRI, datatype)),
+        }
+    }
+
+    #[test]
+    fn test_turtle_roundtrip() -> Result<()> {
+        let nodes = vec![
+            RdfNode {
+                id: Some("/a%20b.txt".to_string()),
+                types: vec![vocab_iri("File")],
+                properties: vec![
+                    (
+                        vocab_iri("link"),
+                        RdfObject::Iri("safe://hbyyyyx".to_string()),
+                    ),
+                    (vocab_iri("size"), literal("10", Some("integer"))),
+                    (
+                        vocab_iri("checksum"),
+                        literal("quotes \" and\nnew lines", None),
+                    ),
+                ],
+            },
+            RdfNode {
+                id: None,
+                types: vec![vocab_iri("NrsMap")],
+                properties: vec![(
+                    vocab_iri("subName"),
+                    RdfObject::Node(RdfNode {
+                        id: None,
+                        types: vec![],
+                        properties: vec![(vocab_iri("name"), literal("www", None))],
+                    }),
+                )],
+            },
+        ];
+
+        let turtle = serialise(&nodes, "safe://hnyynyss");
+        assert!(turtle.starts_with("@base <safe://hnyynyss> .\n"));
+        assert!(turtle.contains("</a%20b.txt> a safe:File ;\n    safe:link <safe://hbyyyyx> ;"));
+        assert!(turtle.contains("safe:size \"10\"^^xsd:integer"));
+
+        assert_eq!(parse(&turtle)?, nodes);
+        Ok(())
+    }
+
+    #[test]
+    fn test_turtle_parse() -> Result<()> {
+        let turtle = format!(
+            r#"# a comment
+            PREFIX s: <{}>
+            @prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+
+            <safe://hnyynyss/a.txt> a s:File;
+                s:size 10 ; s:readonly true ;
+                s:mediaType """text/plain""" , 'text/x' .
+            <safe://hnyynyss/a.txt> s:modeBits "420"^^xsd:integer ; s:checksum "x"@en .
+            [ s:name "www" ] a s:SubName ."#,
+            SAFE_VOCAB_IRI
+        );
+
+        let nodes = parse(&turtle)?;
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].id.as_deref(), Some("safe://hnyynyss/a.txt"));
+        assert!(nodes[0].has_type("File"));
+        assert_eq!(
+            nodes[0].properties,
+            [
+                (vocab_iri("size"), literal("10", Some("integer"))),
+                (vocab_iri("readonly"), literal("true", Some("boolean"))),
+                (vocab_iri("mediaType"), literal("text/plain", None)),
+                (vocab_iri("mediaType"), literal("text/x", None)),
+                (vocab_iri("modeBits"), literal("420", Some("integer"))),
+                (vocab_iri("checksum"), literal("x", None)),
+            ]
+        );
+        assert_eq!(nodes[1].id, None);
+        assert!(nodes[1].has_type("SubName"));
+
+        assert!(parse("<a> undefined:b \"c\" .").is_err());
+        assert!(parse("<a> _:b \"c\" .").is_err());
+        assert!(parse("<a> <p> ( 1 2 ) .").is_err());
+        assert!(parse("<a> <p> \"unterminated .").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_turtle_parse_labelled_blank_nodes() -> Result<()> {
+        let turtle = format!(
+            r#"@prefix s: <{}> .
+            _:b1 s:name "nested" .
+            <safe://hnyynyss> s:subName _:b0 , [ s:name "inline" ] .
+            _:b0 a s:SubName ; s:definition _:b1.
+            _:b2 s:name "unreferenced" ."#,
+            SAFE_VOCAB_IRI
+        );
+
+        let nodes = parse(&turtle)?;
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].id.as_deref(), Some("safe://hnyynyss"));
+        let sub_names: Vec<&RdfObject> = nodes[0].objects("subName").collect();
+        assert_eq!(sub_names.len(), 2);
+        let sub_name = match sub_names[0] {
+            RdfObject::Node(node) => node,
+            other => return Err(anyhow::anyhow!("Unexpected object: {:?}", other)),
+        };
+        assert_eq!(sub_name.id, None);
+        assert!(sub_name.has_type("SubName"));
+        assert_eq!(
+            sub_name.objects("definition").collect::<Vec<_>>(),
+            [&RdfObject::Node(RdfNode {
+                id: None,
+                types: vec![],
+                properties: vec![(vocab_iri("name"), literal("nested", None))],
+            })]
+        );
+        assert_eq!(nodes[1].id, None);
+        assert_eq!(
+            nodes[1].properties,
+            [(vocab_iri("name"), literal("unreferenced", None))]
+        );
+
+        // blank nodes cannot be nested within themselves
+        assert!(parse("_:a <p> _:b . _:b <p> _:a .").is_err());
+        assert!(parse("<a> <p> _:b . _:b <p> _:b .").is_err());
+        Ok(())
+    }
+}
@@ -126,7 +126,10 @@ impl Safe {
 
 #[cfg(test)]
 mod tests {
-    use crate::{app::test_helpers::new_safe_instance, retry_loop};
+    use crate::{
+        app::test_helpers::{new_in_memory_safe_instance, new_safe_instance},
+        retry_loop,
+    };
     use anyhow::Result;
 
     #[tokio::test]
@@ -158,4 +161,23 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_register_in_memory() -> Result<()> {
+        let safe = new_in_memory_safe_instance();
+
+        let xorurl = safe.register_create(None, 25_000, false).await?;
+        assert!(safe.register_read(&xorurl).await?.is_empty());
+
+        let initial_data = b"initial data";
+        let hash = safe
+            .write_to_register(&xorurl, initial_data.to_vec(), Default::default())
+            .await?;
+        assert_eq!(
+            safe.register_read_entry(&xorurl, hash).await?,
+            initial_data.to_vec()
+        );
+
+        Ok(())
+    }
 }
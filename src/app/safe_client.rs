@@ -7,8 +7,12 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use super::fetch::Range;
+use super::{
+    backend::{sequence_index_conflict_error, StorageBackend},
+    fetch::Range,
+};
 use crate::{ipc::BootstrapConfig, Error, Result};
+use async_trait::async_trait;
 use hex::encode;
 use log::{debug, info};
 use sn_client::{Client, Error as ClientError, ErrorMessage, TransfersError};
@@ -51,9 +55,49 @@ impl SafeAppClient {
         }
     }
 
+    #[allow(dead_code)]
+    pub async fn get_map(&self, name: XorName, tag: u64) -> Result<Map> {
+        let client = self.get_safe_client()?;
+        let address = MapAddress::Seq { name, tag };
+
+        client
+            .get_map(address)
+            .await
+            .map_err(|e| Error::NetDataError(format!("Failed to get SeqMap: {:?}", e)))
+    }
+
+    async fn edit_map_entries(
+        &self,
+        name: XorName,
+        tag: u64,
+        entry_actions: MapSeqEntryActions,
+        error_msg: &str,
+    ) -> Result<()> {
+        let client = self.get_safe_client()?;
+        let message = error_msg.to_string();
+        let address = MapAddress::Seq { name, tag };
+        client
+            .edit_map_entries(address, MapEntryActions::Seq(entry_actions))
+            .await
+            .map_err(|err| {
+                if let ClientError::NetworkDataError(SafeNdError::InvalidEntryActions(_)) = err {
+                    Error::EntryExists(format!("{}: {}", message, err))
+                } else {
+                    Error::NetDataError(format!("{}: {}", message, err))
+                }
+            })
+    }
+}
+
+#[async_trait]
+impl StorageBackend for SafeAppClient {
+    fn clone_box(&self) -> Box<dyn StorageBackend> {
+        Box::new(self.clone())
+    }
+
     // Connect to the SAFE Network using the keypair if provided. Contacts list
     // are overriden if a 'bootstrap_config' is provided.
-    pub async fn connect(
+    async fn connect(
         &mut self,
         app_keypair: Option<Keypair>,
         config_path: Option<&Path>,
@@ -90,13 +134,13 @@ impl SafeAppClient {
         Ok(())
     }
 
-    pub fn keypair(&self) -> Result<Keypair> {
+    fn keypair(&self) -> Result<Keypair> {
         let client = self.get_safe_client()?;
         Ok(client.keypair())
     }
 
     // === Token operations ===
-    pub async fn read_balance_from_keypair(&self, id: Keypair) -> Result<Token> {
+    async fn read_balance_from_keypair(&self, id: Keypair) -> Result<Token> {
         let temp_client = Client::new(
             Some(id),
             self.config_path.as_deref(),
@@ -111,7 +155,7 @@ impl SafeAppClient {
     }
 
     #[cfg(feature = "simulated-payouts")]
-    pub async fn trigger_simulated_farming_payout(
+    async fn trigger_simulated_farming_payout(
         &self,
         amount: Token,
         id: Option<Keypair>,
@@ -132,7 +176,7 @@ impl SafeAppClient {
         Ok(())
     }
 
-    pub async fn safecoin_transfer_to_xorname(
+    async fn safecoin_transfer_to_xorname(
         &self,
         from_id: Option<Keypair>,
         to_xorname: XorName,
@@ -152,7 +196,7 @@ impl SafeAppClient {
             .await
     }
 
-    pub async fn safecoin_transfer_to_pk(
+    async fn safecoin_transfer_to_pk(
         &self,
         from_id: Option<Keypair>,
         to_pk: PublicKey,
@@ -198,7 +242,7 @@ impl SafeAppClient {
     }
 
     // // === Blob operations ===
-    pub async fn store_public_blob(&self, data: &[u8], dry_run: bool) -> Result<XorName> {
+    async fn store_public_blob(&self, data: &[u8], dry_run: bool) -> Result<XorName> {
         let address = if dry_run {
             let (_, address) = Client::blob_data_map(data.to_vec(), None).await?;
             address
@@ -213,7 +257,7 @@ impl SafeAppClient {
         Ok(*address.name())
     }
 
-    pub async fn get_public_blob(&self, xorname: XorName, range: Range) -> Result<Vec<u8>> {
+    async fn get_public_blob(&self, xorname: XorName, range: Range) -> Result<Vec<u8>> {
         debug!("Fetching immutable data: {:?}", &xorname);
 
         let client = self.get_safe_client()?;
@@ -241,7 +285,7 @@ impl SafeAppClient {
     }
 
     // === Map operations ===
-    pub async fn store_map(
+    async fn store_map(
         &self,
         name: Option<XorName>,
         tag: u64,
@@ -279,31 +323,14 @@ impl SafeAppClient {
         Ok(xorname)
     }
 
-    #[allow(dead_code)]
-    pub async fn get_map(&self, name: XorName, tag: u64) -> Result<Map> {
-        let client = self.get_safe_client()?;
-        let address = MapAddress::Seq { name, tag };
-
-        client
-            .get_map(address)
-            .await
-            .map_err(|e| Error::NetDataError(format!("Failed to get SeqMap: {:?}", e)))
-    }
-
-    pub async fn map_insert(
-        &self,
-        name: XorName,
-        tag: u64,
-        key: &[u8],
-        value: &[u8],
-    ) -> Result<()> {
+    async fn map_insert(&self, name: XorName, tag: u64, key: &[u8], value: &[u8]) -> Result<()> {
         let entry_actions = MapSeqEntryActions::new();
         let entry_actions = entry_actions.ins(key.to_vec(), value.to_vec(), 0);
         self.edit_map_entries(name, tag, entry_actions, "Failed to insert to SeqMap")
             .await
     }
 
-    pub async fn map_get_value(&self, name: XorName, tag: u64, key: &[u8]) -> Result<MapValue> {
+    async fn map_get_value(&self, name: XorName, tag: u64, key: &[u8]) -> Result<MapValue> {
         let client = self.get_safe_client()?;
         let key_vec = key.to_vec();
         let address = MapAddress::Seq { name, tag };
@@ -337,7 +364,7 @@ impl SafeAppClient {
             })
     }
 
-    pub async fn list_map_entries(
+    async fn list_map_entries(
         &self,
         name: XorName,
         tag: u64,
@@ -373,29 +400,7 @@ impl SafeAppClient {
             })
     }
 
-    async fn edit_map_entries(
-        &self,
-        name: XorName,
-        tag: u64,
-        entry_actions: MapSeqEntryActions,
-        error_msg: &str,
-    ) -> Result<()> {
-        let client = self.get_safe_client()?;
-        let message = error_msg.to_string();
-        let address = MapAddress::Seq { name, tag };
-        client
-            .edit_map_entries(address, MapEntryActions::Seq(entry_actions))
-            .await
-            .map_err(|err| {
-                if let ClientError::NetworkDataError(SafeNdError::InvalidEntryActions(_)) = err {
-                    Error::EntryExists(format!("{}: {}", message, err))
-                } else {
-                    Error::NetDataError(format!("{}: {}", message, err))
-                }
-            })
-    }
-
-    pub async fn update_map(
+    async fn update_map(
         &self,
         name: XorName,
         tag: u64,
@@ -410,7 +415,7 @@ impl SafeAppClient {
     }
 
     // === Sequence data operations ===
-    pub async fn store_sequence(
+    async fn store_sequence(
         &self,
         data: &[u8],
         name: Option<XorName>,
@@ -461,7 +466,7 @@ impl SafeAppClient {
         Ok(xorname)
     }
 
-    pub async fn sequence_get_last_entry(
+    async fn sequence_get_last_entry(
         &self,
         name: XorName,
         tag: u64,
@@ -497,7 +502,7 @@ impl SafeAppClient {
             })
     }
 
-    pub async fn sequence_get_entry(
+    async fn sequence_get_entry(
         &self,
         name: XorName,
         tag: u64,
@@ -539,12 +544,13 @@ impl SafeAppClient {
         Ok(entry.to_vec())
     }
 
-    pub async fn append_to_sequence(
+    async fn append_to_sequence(
         &self,
         data: &[u8],
         name: XorName,
         tag: u64,
         private: bool,
+        expected_index: Option<u64>,
     ) -> Result<()> {
         debug!(
             "Appending to {} Sequence data w/ type: {:?}, xorname: {:?}",
@@ -553,6 +559,20 @@ impl SafeAppClient {
             name
         );
 
+        // Sequences don't support conditional appends on the Network, thus checking the index
+        // right before appending only narrows the window for a concurrent append to sneak in,
+        // which is then detected by reading the entry found at the index once appended
+        if let Some(expected_index) = expected_index {
+            let next_index = match self.sequence_get_last_entry(name, tag, private).await {
+                Ok((index, _)) => index + 1,
+                Err(Error::EmptyContent(_)) => 0,
+                Err(err) => return Err(err),
+            };
+            if next_index != expected_index {
+                return Err(sequence_index_conflict_error(expected_index, next_index));
+            }
+        }
+
         let client = self.get_safe_client()?;
 
         let sequence_address = if private {
@@ -564,11 +584,25 @@ impl SafeAppClient {
         client
             .append_to_sequence(sequence_address, data.to_vec())
             .await
-            .map_err(|e| Error::NetDataError(format!("Failed to append to Sequence: {:?}", e)))
+            .map_err(|e| Error::NetDataError(format!("Failed to append to Sequence: {:?}", e)))?;
+
+        if let Some(expected_index) = expected_index {
+            let entry = self
+                .sequence_get_entry(name, tag, expected_index, private)
+                .await?;
+            if entry != data {
+                return Err(Error::VersionConflict(format!(
+                    "Failed to append to Sequence at index '{}': another entry was appended concurrently, thus the entry was appended after it",
+                    expected_index
+                )));
+            }
+        }
+
+        Ok(())
     }
 
     // === Register data operations ===
-    pub async fn store_register(
+    async fn store_register(
         &self,
         name: Option<XorName>,
         tag: u64,
@@ -618,7 +652,7 @@ impl SafeAppClient {
         Ok(xorname)
     }
 
-    pub async fn read_register(&self, address: Address) -> Result<BTreeSet<(EntryHash, Entry)>> {
+    async fn read_register(&self, address: Address) -> Result<BTreeSet<(EntryHash, Entry)>> {
         debug!("Fetching Register data at {:?}", address);
 
         let client = self.get_safe_client()?;
@@ -635,7 +669,7 @@ impl SafeAppClient {
         })
     }
 
-    pub async fn get_register_entry(&self, address: Address, hash: EntryHash) -> Result<Entry> {
+    async fn get_register_entry(&self, address: Address, hash: EntryHash) -> Result<Entry> {
         debug!("Fetching Register hash {:?} at {:?}", hash, address);
 
         let client = self.get_safe_client()?;
@@ -657,7 +691,7 @@ impl SafeAppClient {
         Ok(entry.to_vec())
     }
 
-    pub async fn write_to_register(
+    async fn write_to_register(
         &self,
         address: Address,
         data: Vec<u8>,
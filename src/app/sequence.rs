@@ -146,7 +146,7 @@ impl Safe {
 
         // append to the data the data
         self.safe_client
-            .append_to_sequence(data, xorname, type_tag, is_private)
+            .append_to_sequence(data, xorname, type_tag, is_private, None)
             .await
     }
 }
@@ -0,0 +1,196 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{Error, Result};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use serde::{de::DeserializeOwned, Serialize};
+use std::io::{Read, Write};
+
+// Prefix of the envelope FilesMaps and NrsMaps are stored in, followed by a byte with the
+// encoding of the payload. Content without it was stored as JSON by previous versions.
+const ENVELOPE_MAGIC: &[u8] = b"sn_api:envelope:v1\n";
+
+// Encodings of the payload of an envelope
+const ENCODING_MSGPACK: u8 = 1;
+const ENCODING_MSGPACK_DEFLATE: u8 = 2;
+
+// Payloads smaller than this are not worth compressing
+const MIN_COMPRESSION_SIZE: usize = 1024;
+
+// Maximum size of a payload once decompressed, so content crafted to inflate
+// to a huge size cannot exhaust the memory available
+const MAX_PAYLOAD_SIZE: u64 = 128 * 1024 * 1024;
+
+// Serialise a value with MessagePack into an envelope, compressing it if that makes it smaller
+pub(crate) fn serialise_to_envelope<T: Serialize>(value: &T, name: &str) -> Result<Vec<u8>> {
+    let payload = rmp_serde::to_vec_named(value).map_err(|err| {
+        Error::Serialisation(format!("Couldn't serialise the {}: {:?}", name, err))
+    })?;
+
+    let compressed = if payload.len() >= MIN_COMPRESSION_SIZE {
+        Some(deflate(&payload).map_err(|err| {
+            Error::Serialisation(format!("Couldn't compress the {}: {:?}", name, err))
+        })?)
+    } else {
+        None
+    };
+
+    let mut data = ENVELOPE_MAGIC.to_vec();
+    match compressed {
+        Some(compressed) if compressed.len() < payload.len() => {
+            data.push(ENCODING_MSGPACK_DEFLATE);
+            data.extend(compressed);
+        }
+        _ => {
+            data.push(ENCODING_MSGPACK);
+            data.extend(payload);
+        }
+    }
+
+    Ok(data)
+}
+
+// Deserialise a value stored in an envelope, or as JSON if it was stored by a previous version
+pub(crate) fn deserialise_from_envelope<T: DeserializeOwned>(data: &[u8], name: &str) -> Result<T> {
+    let deserialisation_error = |err: &dyn std::fmt::Debug| {
+        Error::ContentError(format!("Couldn't deserialise the {}: {:?}", name, err))
+    };
+
+    if !data.starts_with(ENVELOPE_MAGIC) {
+        return serde_json::from_slice(data).map_err(|err| deserialisation_error(&err));
+    }
+
+    let data = &data[ENVELOPE_MAGIC.len()..];
+    match data.split_first() {
+        Some((&ENCODING_MSGPACK, payload)) => {
+            rmp_serde::from_slice(payload).map_err(|err| deserialisation_error(&err))
+        }
+        Some((&ENCODING_MSGPACK_DEFLATE, compressed)) => {
+            let payload = inflate(compressed, MAX_PAYLOAD_SIZE)
+                .map_err(|err| deserialisation_error(&err))?
+                .ok_or_else(|| {
+                    Error::ContentError(format!(
+                        "The {} is bigger than the maximum of {} bytes once decompressed",
+                        name, MAX_PAYLOAD_SIZE
+                    ))
+                })?;
+            rmp_serde::from_slice(&payload).map_err(|err| deserialisation_error(&err))
+        }
+        Some((encoding, _)) => Err(Error::ContentError(format!(
+            "The {} is encoded in an unsupported format ({}), it may have been stored by a newer version",
+            name, encoding
+        ))),
+        None => Err(Error::ContentError(format!(
+            "The {} stored is empty",
+            name
+        ))),
+    }
+}
+
+fn deflate(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+// Decompress some data, unless it's bigger than `max_size` once decompressed
+fn inflate(data: &[u8], max_size: u64) -> std::io::Result<Option<Vec<u8>>> {
+    let mut decoded = Vec::new();
+    let read = DeflateDecoder::new(data)
+        .take(max_size + 1)
+        .read_to_end(&mut decoded)?;
+    if read as u64 > max_size {
+        Ok(None)
+    } else {
+        Ok(Some(decoded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app::consts::DEFAULT_XORURL_BASE, nrs::NrsMap, SafeContentType, SafeUrl};
+    use anyhow::Result;
+    use std::collections::BTreeMap;
+
+    fn map(entries: usize) -> BTreeMap<String, BTreeMap<String, String>> {
+        (0..entries)
+            .map(|i| {
+                let mut item = BTreeMap::new();
+                item.insert("type".to_string(), "text/plain".to_string());
+                item.insert("size".to_string(), i.to_string());
+                (format!("/folder/file-{}.txt", i), item)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_envelope_roundtrip() -> Result<()> {
+        for map in &[map(0), map(1), map(1000)] {
+            let data = serialise_to_envelope(map, "map")?;
+            assert!(data.starts_with(ENVELOPE_MAGIC));
+            let deserialised: BTreeMap<String, BTreeMap<String, String>> =
+                deserialise_from_envelope(&data, "map")?;
+            assert_eq!(&deserialised, map);
+        }
+
+        // big payloads are compressed, making them much smaller than JSON
+        let map = map(1000);
+        let data = serialise_to_envelope(&map, "map")?;
+        assert_eq!(data[ENVELOPE_MAGIC.len()], ENCODING_MSGPACK_DEFLATE);
+        assert!(data.len() * 4 < serde_json::to_vec(&map)?.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_envelope_nrs_map_roundtrip() -> Result<()> {
+        let mut nrs_map = NrsMap::default();
+        let mut links = Vec::new();
+        for i in 0..100 {
+            let link = SafeUrl::encode_blob(
+                rand::random(),
+                SafeContentType::MediaType("text/html".to_string()),
+                DEFAULT_XORURL_BASE,
+            )?;
+            let _ = nrs_map.update(&format!("site-{}.name", i), &link, i == 0, false)?;
+            links.push(link);
+        }
+
+        let data = serialise_to_envelope(&nrs_map, "NrsMap")?;
+        assert_eq!(data[ENVELOPE_MAGIC.len()], ENCODING_MSGPACK_DEFLATE);
+        let deserialised: NrsMap = deserialise_from_envelope(&data, "NrsMap")?;
+        assert_eq!(deserialised, nrs_map);
+        assert_eq!(deserialised.get_default_link()?, links[0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_envelope_inflate_limit() -> Result<()> {
+        let compressed = deflate(&[0; 1000])?;
+        assert_eq!(inflate(&compressed, 1000)?, Some(vec![0; 1000]));
+        assert_eq!(inflate(&compressed, 999)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_envelope_json_fallback() -> Result<()> {
+        let map = map(3);
+        let json = serde_json::to_vec(&map)?;
+        let deserialised: BTreeMap<String, BTreeMap<String, String>> =
+            deserialise_from_envelope(&json, "map")?;
+        assert_eq!(deserialised, map);
+
+        let mut unsupported = ENVELOPE_MAGIC.to_vec();
+        unsupported.push(99);
+        let result: crate::Result<BTreeMap<String, String>> =
+            deserialise_from_envelope(&unsupported, "map");
+        assert!(matches!(result, Err(Error::ContentError(_))));
+        Ok(())
+    }
+}
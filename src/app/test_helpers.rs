@@ -7,10 +7,26 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use crate::Safe;
+use crate::{
+    app::{
+        consts::*,
+        files::{FileItem, FilesMap},
+    },
+    backend::InMemoryBackend,
+    Safe,
+};
 use anyhow::{Context, Result};
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
-use std::{collections::HashSet, env::var, net::SocketAddr, sync::Once};
+use std::{
+    collections::HashSet,
+    env::var,
+    fs,
+    net::SocketAddr,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::Once,
+    time::Duration,
+};
 use tracing_subscriber::{fmt, EnvFilter};
 
 // Environment variable which can be set with the auth credentials
@@ -58,6 +74,12 @@ pub async fn new_safe_instance() -> Result<Safe> {
     Ok(safe)
 }
 
+// Instantiate a Safe instance which keeps all data in memory
+pub fn new_in_memory_safe_instance() -> Safe {
+    init_logger();
+    Safe::with_backend(InMemoryBackend::new(), None, Duration::from_secs(20))
+}
+
 pub async fn new_read_only_safe_instance() -> Result<Safe> {
     init_logger();
     let mut safe = Safe::default();
@@ -71,6 +93,87 @@ pub fn random_nrs_name() -> String {
     thread_rng().sample_iter(&Alphanumeric).take(15).collect()
 }
 
+// Folder in the system's temp folder which is removed along with its content when dropped
+pub struct TempDir(PathBuf);
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+// Create a new and empty folder with a random name in the system's temp folder
+pub fn new_temp_dir() -> Result<TempDir> {
+    let name: String = thread_rng().sample_iter(&Alphanumeric).take(15).collect();
+    let path = std::env::temp_dir().join(format!("sn_api_test_{}", name));
+    fs::create_dir_all(&path)
+        .with_context(|| format!("Failed to create temp folder at {}", path.display()))?;
+    Ok(TempDir(path))
+}
+
+// Builder of the FileItems the FilesMaps used as test fixtures are made of
+pub struct FileItemBuilder(FileItem);
+
+impl FileItemBuilder {
+    // FileItem of the given type, without any other property
+    pub fn new(file_type: &str) -> Self {
+        let mut file_item = FileItem::new();
+        file_item.insert(PREDICATE_TYPE.to_string(), file_type.to_string());
+        Self(file_item)
+    }
+
+    // FileItem of a file of the given type linking to its content
+    pub fn file(file_type: &str, link: &str) -> Self {
+        Self::new(file_type).property(PREDICATE_LINK, link)
+    }
+
+    // FileItem of a directory
+    pub fn dir() -> Self {
+        Self::new(MIMETYPE_FILESYSTEM_DIR)
+    }
+
+    pub fn size(self, size: &str) -> Self {
+        self.property(PREDICATE_SIZE, size)
+    }
+
+    // Set both the creation and the modification timestamps
+    pub fn timestamps(self, timestamp: &str) -> Self {
+        self.property(PREDICATE_CREATED, timestamp)
+            .property(PREDICATE_MODIFIED, timestamp)
+    }
+
+    pub fn property(mut self, key: &str, value: &str) -> Self {
+        self.0.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn build(self) -> FileItem {
+        self.0
+    }
+}
+
+// FilesMap made of the given paths and FileItems
+pub fn files_map<'a>(file_items: impl IntoIterator<Item = (&'a str, FileItem)>) -> FilesMap {
+    file_items
+        .into_iter()
+        .map(|(path, file_item)| (path.to_string(), file_item))
+        .collect()
+}
+
 fn get_bootstrap_contacts() -> Result<HashSet<SocketAddr>> {
     let contacts = match var(TEST_BOOTSTRAPPING_PEERS) {
         Ok(val) => serde_json::from_str(&val).with_context(|| {
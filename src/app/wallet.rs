@@ -519,7 +519,10 @@ async fn resolve_wallet_url(
 mod tests {
     use super::*;
     use crate::{
-        app::test_helpers::{new_read_only_safe_instance, new_safe_instance, random_nrs_name},
+        app::test_helpers::{
+            new_in_memory_safe_instance, new_read_only_safe_instance, new_safe_instance,
+            random_nrs_name,
+        },
         common::sk_to_hex,
         retry_loop, retry_loop_for_pattern,
     };
@@ -1280,4 +1283,40 @@ mod tests {
             Ok(_) => Err(anyhow!("Transfer succeeded unexpectedly".to_string(),)),
         }
     }
+
+    #[tokio::test]
+    async fn test_wallet_in_memory() -> Result<()> {
+        let mut safe = new_in_memory_safe_instance();
+        let wallet_xorurl = safe.wallet_create().await?;
+        assert_eq!(safe.wallet_balance(&wallet_xorurl).await?, "0.000000000");
+
+        let (_, keypair1) = safe.keys_create_preload_test_coins("12.23").await?;
+        let sk1_hex = sk_to_hex(keypair1.secret_key()?);
+        let (_, keypair2) = safe.keys_create_preload_test_coins("1.53").await?;
+        let sk2_hex = sk_to_hex(keypair2.secret_key()?);
+        safe.wallet_insert(&wallet_xorurl, Some("my-first-balance"), true, &sk1_hex)
+            .await?;
+        safe.wallet_insert(&wallet_xorurl, Some("my-second-balance"), false, &sk2_hex)
+            .await?;
+        assert_eq!(safe.wallet_get(&wallet_xorurl).await?.len(), 2);
+        assert_eq!(
+            safe.wallet_balance(&wallet_xorurl).await?,
+            "13.760000000" /* 12.23 + 1.53 */
+        );
+
+        let (key_xorurl, keypair3) = safe.keys_create_preload_test_coins("10.0").await?;
+        let _ = safe
+            .wallet_transfer("2.23", &wallet_xorurl, &key_xorurl)
+            .await?;
+        assert_eq!(
+            safe.wallet_balance(&wallet_xorurl).await?,
+            "11.530000000" /* 13.76 - 2.23 */
+        );
+        assert_eq!(
+            safe.keys_balance_from_sk(keypair3.secret_key()?).await?,
+            "12.230000000"
+        );
+
+        Ok(())
+    }
 }
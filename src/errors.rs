@@ -95,4 +95,10 @@ pub enum Error {
     /// SafeUrlError
     #[error("SafeUrlError: {0}")]
     SafeUrlError(#[from] SafeUrlError),
+    /// Cancelled
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+    /// VersionConflict
+    #[error("VersionConflict: {0}")]
+    VersionConflict(String),
 }